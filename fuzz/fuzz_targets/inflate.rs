@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (u16, &[u8])| {
+    let (max_size, data) = input;
+    if let Some(inflated_size) = jls::fuzz_inflate(data, max_size as usize) {
+        assert!(inflated_size <= max_size as usize);
+    }
+});
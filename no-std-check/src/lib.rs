@@ -0,0 +1,154 @@
+#![no_std]
+
+// Built on its own (`cargo build -p jls-no-std-check`) jls is compiled without its `std` feature;
+// in a workspace-wide build feature unification turns it back on.
+
+use chrono::{DateTime, Utc};
+use jls::verification::{LicenseVerificationError, LicenseVerifier};
+use jls::VerifiedLicense;
+
+// The same license signed by an issuer of each key type
+pub struct Fixture {
+    pub issuer_public_key: &'static str,
+    pub verifiable_license: &'static str,
+}
+
+pub const RS512: Fixture = Fixture {
+    issuer_public_key: include_str!("../fixtures/rs512/issuer_public_key.json"),
+    verifiable_license: include_str!("../fixtures/rs512/verifiable_license.json"),
+};
+
+pub const ES256: Fixture = Fixture {
+    issuer_public_key: include_str!("../fixtures/es256/issuer_public_key.json"),
+    verifiable_license: include_str!("../fixtures/es256/verifiable_license.json"),
+};
+
+pub const EDDSA: Fixture = Fixture {
+    issuer_public_key: include_str!("../fixtures/eddsa/issuer_public_key.json"),
+    verifiable_license: include_str!("../fixtures/eddsa/verifiable_license.json"),
+};
+
+pub fn verify_embedded_license(
+    fixture: &Fixture,
+    now: DateTime<Utc>,
+) -> Result<VerifiedLicense, LicenseVerificationError> {
+    let verifiable_license = serde_json::from_str(fixture.verifiable_license)
+        .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
+    verify_license(fixture, verifiable_license, now)
+}
+
+pub fn verify_license(
+    fixture: &Fixture,
+    verifiable_license: serde_json::Value,
+    now: DateTime<Utc>,
+) -> Result<VerifiedLicense, LicenseVerificationError> {
+    let issuer_public_key = serde_json::from_str(fixture.issuer_public_key)
+        .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
+    let verifier = LicenseVerifier::new(issuer_public_key)
+        .map_err(|_| LicenseVerificationError::VerificationFailure)?;
+    let verified_license = verifier.verify_at(verifiable_license, now)?;
+    // There are no policies without `std`, so the expiration date is checked here
+    if now >= verified_license.expiration_date {
+        return Err(LicenseVerificationError::LicenseExpired);
+    }
+    Ok(verified_license)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jls::id::LicenseId;
+    use jls::verification::LicenseVerifierError;
+
+    extern crate alloc;
+    use alloc::string::String;
+
+    fn at(date: &str) -> DateTime<Utc> {
+        date.parse().unwrap()
+    }
+
+    // Fixtures of the backends compiled in, with the `alg` they are signed with
+    fn enabled_fixtures() -> impl Iterator<Item = (&'static Fixture, &'static str)> {
+        [
+            #[cfg(feature = "rsa")]
+            (&RS512, "RS512"),
+            #[cfg(feature = "ecdsa")]
+            (&ES256, "ES256"),
+            #[cfg(feature = "eddsa")]
+            (&EDDSA, "EdDSA"),
+        ]
+        .into_iter()
+    }
+
+    fn disabled_fixtures() -> impl Iterator<Item = &'static Fixture> {
+        [
+            #[cfg(not(feature = "rsa"))]
+            &RS512,
+            #[cfg(not(feature = "ecdsa"))]
+            &ES256,
+            #[cfg(not(feature = "eddsa"))]
+            &EDDSA,
+        ]
+        .into_iter()
+    }
+
+    #[test]
+    fn embedded_license_is_valid_before_expiration() {
+        for (fixture, alg) in enabled_fixtures() {
+            let verified_license = verify_embedded_license(fixture, at("2024-06-01T00:00:00Z"))
+                .expect("Verification should succeed");
+
+            assert_eq!(
+                verified_license.id,
+                LicenseId::from("0b5b88f5-a264-4f90-8406-50b01d9515c8")
+            );
+            assert_eq!(verified_license.alg(), alg);
+            assert_eq!(verified_license.verified_at(), at("2024-06-01T00:00:00Z"));
+        }
+    }
+
+    #[test]
+    fn embedded_license_expires() {
+        for (fixture, _) in enabled_fixtures() {
+            let result = verify_embedded_license(fixture, at("2024-10-01T00:00:00Z"));
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::LicenseExpired);
+        }
+    }
+
+    #[test]
+    fn embedded_license_with_tampered_signature() {
+        for (fixture, _) in enabled_fixtures() {
+            let mut verifiable_license: serde_json::Value =
+                serde_json::from_str(fixture.verifiable_license).unwrap();
+            let signature = verifiable_license["licenseValidation"]["signature"]
+                .as_str()
+                .unwrap();
+            // Swapping two characters keeps the length and the base64 alphabet
+            let mut tampered_signature = signature.as_bytes().to_vec();
+            tampered_signature.swap(0, 1);
+            verifiable_license["licenseValidation"]["signature"] =
+                serde_json::Value::String(String::from_utf8(tampered_signature).unwrap());
+
+            let result = verify_license(fixture, verifiable_license, at("2024-06-01T00:00:00Z"));
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::VerificationFailure);
+        }
+    }
+
+    #[test]
+    fn keys_of_disabled_backends_are_rejected() {
+        for fixture in disabled_fixtures() {
+            let issuer_public_key = serde_json::from_str(fixture.issuer_public_key).unwrap();
+            let result = LicenseVerifier::new(issuer_public_key);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerifierError::KeyTypeNotSupported);
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::jws;
+use crate::store::{LicenseStore, LicenseStoreError};
+use crate::VerifiableLicense;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+
+const SEALED_MAGIC: &[u8; 4] = b"JLS1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AtRestError {
+    InvalidFormat,
+    AuthenticationFailure,
+}
+
+pub fn seal_to_device(license: &VerifiableLicense, device_secret: &[u8]) -> Vec<u8> {
+    let plaintext = serde_json::to_vec(license).expect("Verifiable licenses are serializable");
+    seal(&plaintext, device_secret)
+}
+
+pub fn unseal_from_device(
+    sealed: &[u8],
+    device_secret: &[u8],
+) -> Result<VerifiableLicense, AtRestError> {
+    let plaintext = unseal(sealed, device_secret)?;
+    serde_json::from_slice(&plaintext).map_err(|_| AtRestError::InvalidFormat)
+}
+
+pub struct DeviceSealedStore<S: LicenseStore> {
+    inner: S,
+    device_secret: Vec<u8>,
+}
+
+impl<S: LicenseStore> DeviceSealedStore<S> {
+    pub fn new(inner: S, device_secret: &[u8]) -> Self {
+        Self {
+            inner,
+            device_secret: device_secret.to_vec(),
+        }
+    }
+}
+
+impl<S: LicenseStore> LicenseStore for DeviceSealedStore<S> {
+    fn load(&self) -> Result<serde_json::Value, LicenseStoreError> {
+        let contents = self.inner.load()?;
+        let sealed = contents
+            .get("sealed")
+            .and_then(|sealed| sealed.as_str())
+            .and_then(jws::decode)
+            .ok_or(LicenseStoreError::InvalidContents)?;
+        let plaintext =
+            unseal(&sealed, &self.device_secret).map_err(|_| LicenseStoreError::InvalidContents)?;
+        serde_json::from_slice(&plaintext).map_err(|_| LicenseStoreError::InvalidContents)
+    }
+
+    fn save(&self, contents: &serde_json::Value) -> Result<(), LicenseStoreError> {
+        let plaintext =
+            serde_json::to_vec(contents).map_err(|_| LicenseStoreError::InvalidContents)?;
+        let sealed = seal(&plaintext, &self.device_secret);
+        self.inner
+            .save(&serde_json::json!({ "sealed": jws::encode(&sealed) }))
+    }
+}
+
+pub(crate) fn seal(plaintext: &[u8], device_secret: &[u8]) -> Vec<u8> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = cipher(device_secret, &salt)
+        .encrypt(
+            &XNonce::from(nonce),
+            Payload {
+                msg: plaintext,
+                aad: SEALED_MAGIC,
+            },
+        )
+        .expect("Encryption with a fresh key cannot fail");
+
+    [SEALED_MAGIC.as_slice(), &salt, &nonce, &ciphertext].concat()
+}
+
+pub(crate) fn unseal(sealed: &[u8], device_secret: &[u8]) -> Result<Vec<u8>, AtRestError> {
+    let sealed = sealed
+        .strip_prefix(SEALED_MAGIC)
+        .ok_or(AtRestError::InvalidFormat)?;
+    if sealed.len() < SALT_LEN + NONCE_LEN {
+        return Err(AtRestError::InvalidFormat);
+    }
+    let (salt, sealed) = sealed.split_at(SALT_LEN);
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce = <[u8; NONCE_LEN]>::try_from(nonce).map_err(|_| AtRestError::InvalidFormat)?;
+
+    cipher(device_secret, salt)
+        .decrypt(
+            &XNonce::from(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: SEALED_MAGIC,
+            },
+        )
+        .map_err(|_| AtRestError::AuthenticationFailure)
+}
+
+fn cipher(device_secret: &[u8], salt: &[u8]) -> XChaCha20Poly1305 {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(salt).expect("HMAC accepts keys of any length");
+    mac.update(b"jls-at-rest");
+    mac.update(device_secret);
+    XChaCha20Poly1305::new(&mac.finalize().into_bytes())
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::signing::LicenseSigner;
+    use crate::store::FileLicenseStore;
+    use crate::test_keys::ISSUER_PRIVATE_KEY_JWK_JSON;
+    use crate::License;
+
+    fn verifiable_license() -> VerifiableLicense {
+        let license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "2024-10-01T00:00:00Z",
+            "customData": {
+                "owner": "John Doe"
+            }
+        }))
+        .unwrap();
+        let verifiable_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license)
+            .unwrap();
+        serde_json::from_value(verifiable_license).unwrap()
+    }
+
+    #[test]
+    fn sealed_license_round_trip() {
+        let verifiable_license = verifiable_license();
+
+        let sealed = seal_to_device(&verifiable_license, b"machine-a-secret");
+        let unsealed =
+            unseal_from_device(&sealed, b"machine-a-secret").expect("Unsealing should succeed");
+
+        assert_eq!(
+            serde_json::to_value(unsealed).unwrap(),
+            serde_json::to_value(verifiable_license).unwrap()
+        );
+    }
+
+    #[test]
+    fn sealed_license_with_other_device_secret() {
+        let sealed = seal_to_device(&verifiable_license(), b"machine-a-secret");
+
+        let result = unseal_from_device(&sealed, b"machine-b-secret");
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, AtRestError::AuthenticationFailure);
+    }
+
+    #[test]
+    fn sealed_license_with_flipped_byte() {
+        let mut sealed = seal_to_device(&verifiable_license(), b"machine-a-secret");
+        let last = sealed.len() - 1;
+        sealed[last] ^= 0x01;
+
+        let result = unseal_from_device(&sealed, b"machine-a-secret");
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, AtRestError::AuthenticationFailure);
+    }
+
+    #[test]
+    fn sealed_license_with_invalid_format() {
+        let result = unseal_from_device(b"{\"license\": {}}", b"machine-a-secret");
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, AtRestError::InvalidFormat);
+    }
+
+    #[test]
+    fn device_sealed_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("license.json");
+        let store = DeviceSealedStore::new(FileLicenseStore::new(&path), b"machine-a-secret");
+        let contents = serde_json::to_value(verifiable_license()).unwrap();
+
+        store.save(&contents).expect("Saving should work");
+
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("John Doe"));
+        assert_eq!(store.load(), Ok(contents));
+        let other_store = DeviceSealedStore::new(FileLicenseStore::new(&path), b"machine-b-secret");
+        assert_eq!(other_store.load(), Err(LicenseStoreError::InvalidContents));
+    }
+}
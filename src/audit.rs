@@ -0,0 +1,33 @@
+use crate::verification::LicenseVerificationError;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+// Receives the detailed cause of every failed verification, even when the verifier only reports
+// an opaque error to its caller.
+pub trait AuditSink: Send + Sync {
+    fn verification_failed(&self, error: &LicenseVerificationError);
+}
+
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct MemoryAuditSink {
+    failures: Mutex<Vec<LicenseVerificationError>>,
+}
+
+#[cfg(feature = "std")]
+impl MemoryAuditSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn failures(&self) -> Vec<LicenseVerificationError> {
+        self.failures.lock().unwrap().clone()
+    }
+}
+
+#[cfg(feature = "std")]
+impl AuditSink for MemoryAuditSink {
+    fn verification_failed(&self, error: &LicenseVerificationError) {
+        self.failures.lock().unwrap().push(error.clone());
+    }
+}
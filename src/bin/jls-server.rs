@@ -0,0 +1,51 @@
+use jls::server::LicenseServer;
+use std::path::Path;
+use std::process::ExitCode;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    tracing_subscriber::fmt().with_target(false).init();
+
+    let mut args = std::env::args().skip(1);
+    let Some(config_path) = args.next() else {
+        eprintln!("usage: jls-server <config file> [listen address]");
+        return ExitCode::FAILURE;
+    };
+    let address = args.next().unwrap_or_else(|| "127.0.0.1:8080".to_owned());
+
+    let server = match LicenseServer::from_config_file(Path::new(&config_path)) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!(
+                "jls-server: invalid configuration {}: {:?}",
+                config_path, error
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    let listener = match TcpListener::bind(address.as_str()).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!(
+                "jls-server: cannot listen on {}: {:?}",
+                address,
+                error.kind()
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Ok(local_addr) = listener.local_addr() {
+        tracing::info!("listening on {}", local_addr);
+    }
+    let shutdown = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    match server.serve(listener, shutdown).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("jls-server: {:?}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use crate::clock::{perpetual_expiration_date, saturating_add};
+use crate::datetime::{self, DateTime, Duration};
+use crate::feature::Feature;
+use crate::id::LicenseId;
+use crate::License;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseTemplateError {
+    Io(std::io::ErrorKind),
+    InvalidTemplate(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LicenseTemplate {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub product: Option<String>,
+    #[serde(
+        rename = "validityDays",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub validity_days: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub features: Vec<Feature>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub limits: BTreeMap<String, u64>,
+    #[serde(rename = "customData", default = "empty_object")]
+    pub custom_data: serde_json::Value,
+}
+
+impl LicenseTemplate {
+    pub fn load(path: &Path) -> Result<Self, LicenseTemplateError> {
+        let contents = std::fs::read(path).map_err(|e| LicenseTemplateError::Io(e.kind()))?;
+        Self::from_json(&contents)
+    }
+
+    pub fn from_json(contents: &[u8]) -> Result<Self, LicenseTemplateError> {
+        serde_json::from_slice(contents)
+            .map_err(|e| LicenseTemplateError::InvalidTemplate(e.to_string()))
+    }
+}
+
+pub struct LicenseBuilder {
+    id: Option<LicenseId>,
+    product: Option<String>,
+    kind: Option<String>,
+    issuer: Option<String>,
+    issued_at: Option<DateTime>,
+    validity: Option<Duration>,
+    custom_data: serde_json::Value,
+    features: Vec<Feature>,
+    max_seats: Option<u32>,
+    maintenance_until: Option<DateTime>,
+}
+
+impl LicenseBuilder {
+    pub fn new() -> Self {
+        Self {
+            id: None,
+            product: None,
+            kind: None,
+            issuer: None,
+            issued_at: None,
+            validity: None,
+            custom_data: empty_object(),
+            features: Vec::new(),
+            max_seats: None,
+            maintenance_until: None,
+        }
+    }
+
+    pub fn from_template(template: &LicenseTemplate) -> Self {
+        let mut builder = Self::new();
+        builder.kind = template.kind.clone();
+        builder.product = template.product.clone();
+        builder.validity = template
+            .validity_days
+            .map(|days| Duration::days(days.into()));
+        builder.custom_data = template.custom_data.clone();
+        builder.features = template.features.clone();
+        for (name, &limit) in &template.limits {
+            builder = builder.feature_limit(name, limit);
+        }
+        builder
+    }
+
+    pub fn id(mut self, id: impl Into<LicenseId>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn product(mut self, product: &str) -> Self {
+        self.product = Some(product.to_owned());
+        self
+    }
+
+    pub fn kind(mut self, kind: &str) -> Self {
+        self.kind = Some(kind.to_owned());
+        self
+    }
+
+    pub fn issuer(mut self, issuer: &str) -> Self {
+        self.issuer = Some(issuer.to_owned());
+        self
+    }
+
+    pub fn issued_at(mut self, issued_at: DateTime) -> Self {
+        self.issued_at = Some(issued_at);
+        self
+    }
+
+    pub fn validity(mut self, validity: Duration) -> Self {
+        self.validity = Some(validity);
+        self
+    }
+
+    pub fn perpetual(mut self) -> Self {
+        self.validity = None;
+        self
+    }
+
+    pub fn custom_field(mut self, name: &str, value: serde_json::Value) -> Self {
+        if !self.custom_data.is_object() {
+            self.custom_data = empty_object();
+        }
+        self.custom_data[name] = value;
+        self
+    }
+
+    pub fn feature(mut self, feature: Feature) -> Self {
+        self.features.retain(|f| f.name != feature.name);
+        self.features.push(feature);
+        self
+    }
+
+    pub fn feature_limit(mut self, name: &str, limit: u64) -> Self {
+        match self.features.iter_mut().find(|f| f.name == name) {
+            Some(feature) => feature.limit = Some(limit),
+            None => self.features.push(Feature::limited(name, limit)),
+        }
+        self
+    }
+
+    pub fn max_seats(mut self, max_seats: u32) -> Self {
+        self.max_seats = Some(max_seats);
+        self
+    }
+
+    pub fn maintenance_until(mut self, maintenance_until: DateTime) -> Self {
+        self.maintenance_until = Some(maintenance_until);
+        self
+    }
+
+    pub fn build(self) -> License {
+        let issued_at = self.issued_at.unwrap_or_else(datetime::now);
+        License {
+            id: self.id.unwrap_or_else(|| LicenseId::Uuid(Uuid::new_v4())),
+            product: self.product,
+            kind: self.kind,
+            issuer: self.issuer,
+            issued_at: Some(issued_at),
+            expiration_date: self
+                .validity
+                .map_or_else(perpetual_expiration_date, |validity| {
+                    saturating_add(issued_at, validity)
+                }),
+            custom_data: self.custom_data,
+            device_key: None,
+            features: self.features,
+            max_seats: self.max_seats,
+            delegated_key: None,
+            activation_secret: None,
+            allowed_hosts: Vec::new(),
+            allowed_networks: Vec::new(),
+            upgrades_from: None,
+            state_required: false,
+            offline_validity_days: None,
+            maintenance_until: self.maintenance_until,
+            watermark: None,
+        }
+    }
+}
+
+impl Default for LicenseBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn empty_object() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "sign")]
+    use crate::feature::QuantityCheck;
+    #[cfg(feature = "sign")]
+    use crate::signing::LicenseSigner;
+    #[cfg(feature = "sign")]
+    use crate::test_keys::{ISSUER_PRIVATE_KEY_JWK_JSON, ISSUER_PUBLIC_KEY_JWK_JSON};
+    #[cfg(feature = "sign")]
+    use crate::verification::LicenseVerifier;
+
+    const ENTERPRISE_TEMPLATE: &str = r#"{
+        "kind": "enterprise",
+        "product": "jls-pro",
+        "validityDays": 365,
+        "features": ["editor", { "name": "premium-support" }],
+        "limits": { "projects": 100 },
+        "customData": { "tier": "enterprise", "owner": null }
+    }"#;
+
+    #[test]
+    fn license_with_overflowing_validity() {
+        let issued_at = datetime::parse("2024-10-01T00:00:00Z").unwrap();
+        let license = LicenseBuilder::new()
+            .issued_at(issued_at)
+            .validity(Duration::MAX)
+            .build();
+        assert_eq!(license.expiration_date, datetime::max());
+
+        let license = LicenseBuilder::new()
+            .issued_at(issued_at)
+            .validity(Duration::MIN)
+            .build();
+        assert_eq!(license.expiration_date, datetime::min());
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_from_template_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("enterprise.json");
+        std::fs::write(&path, ENTERPRISE_TEMPLATE).unwrap();
+        let template = LicenseTemplate::load(&path).expect("Template should load");
+        let issued_at = datetime::parse("2024-10-01T00:00:00Z").unwrap();
+
+        let license = LicenseBuilder::from_template(&template)
+            .issued_at(issued_at)
+            .custom_field("owner", serde_json::json!("ACME Corp"))
+            .build();
+        let verifiable_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license)
+            .unwrap();
+        let verified_license = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .unwrap()
+            .verify(verifiable_license)
+            .expect("Verification should succeed");
+
+        assert_eq!(verified_license.kind.as_deref(), Some("enterprise"));
+        assert_eq!(
+            verified_license.expiration_date,
+            issued_at + Duration::days(365)
+        );
+        assert_eq!(
+            verified_license.custom_data,
+            serde_json::json!({ "tier": "enterprise", "owner": "ACME Corp" })
+        );
+        assert!(verified_license.has_feature("premium-support"));
+        assert_eq!(
+            verified_license.allows_quantity("projects", 100),
+            QuantityCheck::AtLimit
+        );
+    }
+
+    #[test]
+    fn template_override() {
+        let template = LicenseTemplate::from_json(ENTERPRISE_TEMPLATE.as_bytes()).unwrap();
+        let issued_at = datetime::parse("2024-10-01T00:00:00Z").unwrap();
+
+        let license = LicenseBuilder::from_template(&template)
+            .issued_at(issued_at)
+            .validity(Duration::days(30))
+            .build();
+
+        assert_eq!(license.expiration_date, issued_at + Duration::days(30));
+        assert_eq!(template.validity_days, Some(365));
+    }
+
+    #[test]
+    fn template_with_unknown_field() {
+        let result = LicenseTemplate::from_json(br#"{ "kind": "trial", "validityDayz": 14 }"#);
+        let Err(LicenseTemplateError::InvalidTemplate(message)) = result else {
+            panic!("An error was expected")
+        };
+        assert!(message.contains("validityDayz"));
+    }
+}
@@ -0,0 +1,298 @@
+use crate::datetime::DateTime;
+use crate::device::{DeviceKey, DeviceKeyError, DevicePublicKey};
+use crate::id::LicenseId;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::License;
+use serde_derive::{Deserialize, Serialize};
+
+const CAPABILITY_TOKEN_TYP: &str = "jls-capability+jwt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CapabilityError {
+    LicenseWithoutDelegatedKey,
+    InvalidDelegatedKey,
+    InvalidCapability,
+    VerificationFailure,
+    ParentLicenseMismatch,
+    FeatureNotLicensed(String),
+    SeatsExceeded,
+    ExpiresAfterParent,
+    CapabilityExpired,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CapabilityToken {
+    #[serde(rename = "parentLicenseId")]
+    pub parent_license_id: LicenseId,
+    pub subject: String,
+    pub features: Vec<String>,
+    pub seats: u32,
+    #[serde(rename = "expiresAt", with = "crate::datetime::rfc3339")]
+    pub expires_at: DateTime,
+}
+
+impl CapabilityToken {
+    pub fn new(parent_license: &License, subject: &str, expires_at: DateTime) -> Self {
+        Self {
+            parent_license_id: parent_license.id.clone(),
+            subject: subject.to_owned(),
+            features: Vec::new(),
+            seats: 1,
+            expires_at,
+        }
+    }
+
+    pub fn feature(mut self, feature: &str) -> Self {
+        self.features.push(feature.to_owned());
+        self
+    }
+
+    pub fn seats(mut self, seats: u32) -> Self {
+        self.seats = seats;
+        self
+    }
+
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+
+    pub fn sign(&self, delegated_key: &DeviceKey) -> Result<String, DeviceKeyError> {
+        delegated_key.sign_token(CAPABILITY_TOKEN_TYP, self)
+    }
+}
+
+pub fn verify_capability(
+    verifier: &LicenseVerifier,
+    token: &str,
+    parent_license: &License,
+) -> Result<CapabilityToken, CapabilityError> {
+    verify_capability_at(verifier, token, parent_license, verifier.now())
+}
+
+pub fn verify_capability_at(
+    verifier: &LicenseVerifier,
+    token: &str,
+    parent_license: &License,
+    now: DateTime,
+) -> Result<CapabilityToken, CapabilityError> {
+    let delegated_key = parent_license
+        .delegated_key
+        .as_ref()
+        .ok_or(CapabilityError::LicenseWithoutDelegatedKey)?;
+    let delegated_key = DevicePublicKey::from_jwk(delegated_key)
+        .map_err(|_| CapabilityError::InvalidDelegatedKey)?;
+
+    let capability: CapabilityToken = delegated_key
+        .verify_token_reported(verifier, token, CAPABILITY_TOKEN_TYP)
+        .map_err(|error| match error {
+            LicenseVerificationError::VerificationFailure => CapabilityError::VerificationFailure,
+            _ => CapabilityError::InvalidCapability,
+        })?;
+
+    if capability.parent_license_id != parent_license.id {
+        return Err(CapabilityError::ParentLicenseMismatch);
+    }
+    if let Some(feature) = capability
+        .features
+        .iter()
+        .find(|f| !parent_license.has_feature(f))
+    {
+        return Err(CapabilityError::FeatureNotLicensed(feature.clone()));
+    }
+    if capability.seats > parent_license.max_seats.unwrap_or(1) {
+        return Err(CapabilityError::SeatsExceeded);
+    }
+    if capability.expires_at > parent_license.expiration_date {
+        return Err(CapabilityError::ExpiresAfterParent);
+    }
+    if now >= capability.expires_at {
+        return Err(CapabilityError::CapabilityExpired);
+    }
+
+    Ok(capability)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::datetime;
+    use crate::datetime::Duration;
+    use crate::test_keys::{verifier, ISSUER_PUBLIC_KEY_JWK_JSON};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn parent_license(delegated_key: &DeviceKey) -> License {
+        License {
+            id: Uuid::parse_str("0b5b88f5-a264-4f90-8406-50b01d9515c8")
+                .unwrap()
+                .into(),
+            product: None,
+            kind: None,
+            issuer: None,
+            issued_at: None,
+            expiration_date: datetime::now() + Duration::days(30),
+            custom_data: serde_json::json!({ "owner": "ACME Corp" }),
+            device_key: None,
+            features: vec!["export".into(), "render".into()],
+            max_seats: Some(25),
+            delegated_key: Some(delegated_key.public_jwk()),
+            activation_secret: None,
+            allowed_hosts: Vec::new(),
+            allowed_networks: Vec::new(),
+            upgrades_from: None,
+            state_required: false,
+            offline_validity_days: None,
+            maintenance_until: None,
+            watermark: None,
+        }
+    }
+
+    fn assert_capability_error(token: &str, license: &License, expected: CapabilityError) {
+        let result = verify_capability(&verifier(), token, license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn capability_with_valid_attenuation() {
+        let delegated_key = DeviceKey::generate();
+        let license = parent_license(&delegated_key);
+        let capability =
+            CapabilityToken::new(&license, "alice", datetime::now() + Duration::days(1))
+                .feature("render");
+
+        let verified_capability = verify_capability(
+            &verifier(),
+            &capability.sign(&delegated_key).unwrap(),
+            &license,
+        )
+        .expect("Verification should succeed");
+        assert_eq!(verified_capability, capability);
+        assert!(verified_capability.has_feature("render"));
+    }
+
+    #[test]
+    fn capability_signed_by_undelegated_key() {
+        let delegated_key = DeviceKey::generate();
+        let license = parent_license(&delegated_key);
+        let token = CapabilityToken::new(&license, "alice", datetime::now() + Duration::days(1))
+            .sign(&DeviceKey::generate())
+            .unwrap();
+
+        assert_capability_error(&token, &license, CapabilityError::VerificationFailure);
+    }
+
+    #[test]
+    fn capability_signed_by_undelegated_key_in_opaque_mode() {
+        let delegated_key = DeviceKey::generate();
+        let license = parent_license(&delegated_key);
+        let token = CapabilityToken::new(&license, "alice", datetime::now() + Duration::days(1))
+            .sign(&DeviceKey::generate())
+            .unwrap();
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let result = verify_capability(&verifier, &token, &license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, CapabilityError::InvalidCapability);
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::VerificationFailure]
+        );
+    }
+
+    #[test]
+    fn capability_for_other_license() {
+        let delegated_key = DeviceKey::generate();
+        let license = parent_license(&delegated_key);
+        let mut other_license = license.clone();
+        other_license.id = Uuid::new_v4().into();
+        let token =
+            CapabilityToken::new(&other_license, "alice", datetime::now() + Duration::days(1))
+                .sign(&delegated_key)
+                .unwrap();
+
+        assert_capability_error(&token, &license, CapabilityError::ParentLicenseMismatch);
+    }
+
+    #[test]
+    fn capability_with_unlicensed_feature() {
+        let delegated_key = DeviceKey::generate();
+        let license = parent_license(&delegated_key);
+        let token = CapabilityToken::new(&license, "alice", datetime::now() + Duration::days(1))
+            .feature("render")
+            .feature("admin")
+            .sign(&delegated_key)
+            .unwrap();
+
+        assert_capability_error(
+            &token,
+            &license,
+            CapabilityError::FeatureNotLicensed("admin".to_owned()),
+        );
+    }
+
+    #[test]
+    fn capability_with_more_seats_than_parent() {
+        let delegated_key = DeviceKey::generate();
+        let license = parent_license(&delegated_key);
+        let token = CapabilityToken::new(&license, "alice", datetime::now() + Duration::days(1))
+            .seats(26)
+            .sign(&delegated_key)
+            .unwrap();
+
+        assert_capability_error(&token, &license, CapabilityError::SeatsExceeded);
+    }
+
+    #[test]
+    fn capability_outliving_parent() {
+        let delegated_key = DeviceKey::generate();
+        let license = parent_license(&delegated_key);
+        let token = CapabilityToken::new(
+            &license,
+            "alice",
+            license.expiration_date + Duration::days(1),
+        )
+        .sign(&delegated_key)
+        .unwrap();
+
+        assert_capability_error(&token, &license, CapabilityError::ExpiresAfterParent);
+    }
+
+    #[test]
+    fn capability_expired() {
+        let delegated_key = DeviceKey::generate();
+        let license = parent_license(&delegated_key);
+        let token = CapabilityToken::new(&license, "alice", datetime::now() - Duration::minutes(1))
+            .sign(&delegated_key)
+            .unwrap();
+
+        assert_capability_error(&token, &license, CapabilityError::CapabilityExpired);
+    }
+
+    #[test]
+    fn capability_for_license_without_delegated_key() {
+        let delegated_key = DeviceKey::generate();
+        let mut license = parent_license(&delegated_key);
+        license.delegated_key = None;
+        let token = CapabilityToken::new(&license, "alice", datetime::now() + Duration::days(1))
+            .sign(&delegated_key)
+            .unwrap();
+
+        assert_capability_error(
+            &token,
+            &license,
+            CapabilityError::LicenseWithoutDelegatedKey,
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use crate::datetime::DateTime;
+#[cfg(feature = "sign")]
+use crate::signing::LicenseSigner;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde_derive::{Deserialize, Serialize};
+
+pub(crate) const KEY_CERTIFICATE_TYP: &str = "jls-key-certificate+jwt";
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct KeyCertificate {
+    #[serde(rename = "issuerKey")]
+    pub issuer_key: serde_json::Value,
+    #[serde(rename = "notBefore", with = "crate::datetime::rfc3339")]
+    pub not_before: DateTime,
+    #[serde(rename = "notAfter", with = "crate::datetime::rfc3339")]
+    pub not_after: DateTime,
+    pub products: Vec<String>,
+}
+
+impl KeyCertificate {
+    pub fn new(
+        issuer_public_key: serde_json::Value,
+        not_before: DateTime,
+        not_after: DateTime,
+    ) -> Self {
+        Self {
+            issuer_key: issuer_public_key,
+            not_before,
+            not_after,
+            products: Vec::new(),
+        }
+    }
+
+    pub fn product(mut self, product: &str) -> Self {
+        self.products.push(product.to_owned());
+        self
+    }
+
+    #[cfg(feature = "sign")]
+    pub fn sign(&self, root_signer: &LicenseSigner) -> String {
+        root_signer.sign_token(KEY_CERTIFICATE_TYP, self)
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::datetime::{self, Duration};
+    use crate::test_keys::{
+        ISSUER_PRIVATE_KEY_JWK_JSON, ISSUER_PUBLIC_KEY_JWK_JSON, OTHER_PRIVATE_KEY_JWK_JSON,
+        OTHER_PUBLIC_KEY_JWK_JSON,
+    };
+    use crate::verification::{LicenseVerificationError, LicenseVerifier};
+    use crate::License;
+
+    fn license(product: &str) -> License {
+        serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "product": product,
+            "expirationDate": "2024-10-01T00:00:00Z",
+            "customData": {
+                "owner": "John Doe"
+            }
+        }))
+        .unwrap()
+    }
+
+    fn root_signer() -> LicenseSigner {
+        LicenseSigner::new(OTHER_PRIVATE_KEY_JWK_JSON.clone()).unwrap()
+    }
+
+    fn root_verifier() -> LicenseVerifier {
+        LicenseVerifier::with_root(OTHER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work")
+    }
+
+    fn issuer_certificate(not_after: DateTime) -> KeyCertificate {
+        KeyCertificate::new(
+            ISSUER_PUBLIC_KEY_JWK_JSON.clone(),
+            datetime::now() - Duration::days(1),
+            not_after,
+        )
+        .product("jls-pro")
+    }
+
+    fn issuer_signer(issuer_certificate: &str) -> LicenseSigner {
+        LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .with_issuer_certificate(issuer_certificate)
+    }
+
+    #[test]
+    fn license_with_certified_issuer() {
+        let certificate =
+            issuer_certificate(datetime::now() + Duration::days(90)).sign(&root_signer());
+        let verifiable_license = issuer_signer(&certificate)
+            .sign(&license("jls-pro"))
+            .unwrap();
+
+        let verified_license = root_verifier()
+            .verify(verifiable_license)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license, license("jls-pro"));
+    }
+
+    #[test]
+    fn license_without_issuer_certificate() {
+        let verifiable_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license("jls-pro"))
+            .unwrap();
+
+        let result = root_verifier().verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::MissingIssuerCertificate);
+    }
+
+    #[test]
+    fn license_with_self_signed_issuer_certificate() {
+        let self_signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let certificate =
+            issuer_certificate(datetime::now() + Duration::days(90)).sign(&self_signer);
+        let verifiable_license = issuer_signer(&certificate)
+            .sign(&license("jls-pro"))
+            .unwrap();
+
+        let result = root_verifier().verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::BrokenCertificateChain);
+    }
+
+    #[test]
+    fn license_signed_by_uncertified_key() {
+        let certificate =
+            issuer_certificate(datetime::now() + Duration::days(90)).sign(&root_signer());
+        let verifiable_license = root_signer()
+            .with_issuer_certificate(&certificate)
+            .sign(&license("jls-pro"))
+            .unwrap();
+
+        let result = root_verifier().verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::VerificationFailure);
+    }
+
+    #[test]
+    fn license_with_expired_issuer_certificate() {
+        let certificate =
+            issuer_certificate(datetime::now() - Duration::hours(1)).sign(&root_signer());
+        let verifiable_license = issuer_signer(&certificate)
+            .sign(&license("jls-pro"))
+            .unwrap();
+
+        let result = root_verifier().verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::IssuerCertificateExpired);
+    }
+
+    #[test]
+    fn license_for_product_out_of_certificate_scope() {
+        let certificate =
+            issuer_certificate(datetime::now() + Duration::days(90)).sign(&root_signer());
+        let verifiable_license = issuer_signer(&certificate)
+            .sign(&license("jls-enterprise"))
+            .unwrap();
+
+        let result = root_verifier().verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::ProductOutOfScope);
+    }
+}
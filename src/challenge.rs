@@ -0,0 +1,226 @@
+use crate::jws;
+use crate::License;
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+const MIN_CHALLENGE_DIGITS: u32 = 6;
+const MAX_CHALLENGE_DIGITS: u32 = 18;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChallengeError {
+    InvalidChallengeCode,
+    LicenseWithoutActivationSecret,
+    InvalidActivationSecret,
+    ResponseMismatch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChallengeConfig {
+    digits: u32,
+    group_size: usize,
+}
+
+impl ChallengeConfig {
+    pub fn new() -> Self {
+        Self {
+            digits: 12,
+            group_size: 4,
+        }
+    }
+
+    pub fn digits(mut self, digits: u32) -> Self {
+        self.digits = digits.clamp(MIN_CHALLENGE_DIGITS, MAX_CHALLENGE_DIGITS);
+        self
+    }
+
+    pub fn group_size(mut self, group_size: usize) -> Self {
+        self.group_size = group_size.max(1);
+        self
+    }
+
+    fn format(&self, digest: &[u8]) -> String {
+        let value = u64::from_be_bytes(digest[..8].try_into().unwrap()) % 10u64.pow(self.digits);
+        let digits = format!("{:0width$}", value, width = self.digits as usize);
+        digits
+            .as_bytes()
+            .chunks(self.group_size)
+            .map(|group| std::str::from_utf8(group).unwrap())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    fn normalize(&self, code: &str) -> Result<String, ChallengeError> {
+        let digits = code
+            .chars()
+            .filter(|c| !matches!(c, '-' | ' '))
+            .collect::<String>();
+        if digits.len() != self.digits as usize || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(ChallengeError::InvalidChallengeCode);
+        }
+
+        Ok(digits)
+    }
+}
+
+impl Default for ChallengeConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Challenge {
+    code: String,
+    config: ChallengeConfig,
+}
+
+impl Challenge {
+    pub fn generate(fingerprint: &str, config: &ChallengeConfig) -> Self {
+        let mut nonce = [0u8; 16];
+        OsRng.fill_bytes(&mut nonce);
+
+        let mut hasher = Sha256::new();
+        hasher.update(fingerprint.as_bytes());
+        hasher.update([0]);
+        hasher.update(nonce);
+
+        Self {
+            code: config.format(&hasher.finalize()),
+            config: config.clone(),
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn verify_response(&self, response: &str, license: &License) -> Result<(), ChallengeError> {
+        let expected_response = respond_to_challenge(&self.code, license, &self.config)?;
+        let response = self
+            .config
+            .normalize(response)
+            .map_err(|_| ChallengeError::ResponseMismatch)?;
+        if response != self.config.normalize(&expected_response)? {
+            return Err(ChallengeError::ResponseMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+pub fn generate_activation_secret() -> String {
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+    jws::encode(&secret)
+}
+
+pub fn respond_to_challenge(
+    challenge_code: &str,
+    license: &License,
+    config: &ChallengeConfig,
+) -> Result<String, ChallengeError> {
+    let challenge_code = config.normalize(challenge_code)?;
+    let activation_secret = license
+        .activation_secret
+        .as_deref()
+        .ok_or(ChallengeError::LicenseWithoutActivationSecret)?;
+    let activation_secret =
+        jws::decode(activation_secret).ok_or(ChallengeError::InvalidActivationSecret)?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&activation_secret)
+        .map_err(|_| ChallengeError::InvalidActivationSecret)?;
+    mac.update(license.id.as_bytes());
+    mac.update(challenge_code.as_bytes());
+
+    Ok(config.format(&mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::{self, Duration};
+    use uuid::Uuid;
+
+    fn license() -> License {
+        License {
+            id: Uuid::new_v4().into(),
+            product: None,
+            kind: None,
+            issuer: None,
+            issued_at: None,
+            expiration_date: datetime::now() + Duration::days(365),
+            custom_data: serde_json::json!({ "owner": "John Doe" }),
+            device_key: None,
+            features: Vec::new(),
+            max_seats: None,
+            delegated_key: None,
+            activation_secret: Some(generate_activation_secret()),
+            allowed_hosts: Vec::new(),
+            allowed_networks: Vec::new(),
+            upgrades_from: None,
+            state_required: false,
+            offline_validity_days: None,
+            maintenance_until: None,
+            watermark: None,
+        }
+    }
+
+    #[test]
+    fn challenge_response_exchange() {
+        let license = license();
+        let config = ChallengeConfig::new();
+        let challenge = Challenge::generate("machine-a", &config);
+        assert_eq!(challenge.code().len(), 14);
+
+        let response = respond_to_challenge(challenge.code(), &license, &config)
+            .expect("Responding should work");
+
+        assert_eq!(challenge.verify_response(&response, &license), Ok(()));
+    }
+
+    #[test]
+    fn challenge_response_with_custom_length() {
+        let license = license();
+        let config = ChallengeConfig::new().digits(8).group_size(2);
+        let challenge = Challenge::generate("machine-a", &config);
+        assert_eq!(challenge.code().len(), 11);
+
+        let response = respond_to_challenge(challenge.code(), &license, &config).unwrap();
+        assert_eq!(
+            challenge.verify_response(&response.replace('-', ""), &license),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn challenge_response_for_other_challenge() {
+        let license = license();
+        let config = ChallengeConfig::new();
+        let challenge = Challenge::generate("machine-a", &config);
+        let other_challenge = Challenge::generate("machine-a", &config);
+
+        let response = respond_to_challenge(other_challenge.code(), &license, &config).unwrap();
+
+        let result = challenge.verify_response(&response, &license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, ChallengeError::ResponseMismatch);
+    }
+
+    #[test]
+    fn challenge_response_for_license_without_secret() {
+        let mut license = license();
+        license.activation_secret = None;
+        let config = ChallengeConfig::new();
+        let challenge = Challenge::generate("machine-a", &config);
+
+        let result = respond_to_challenge(challenge.code(), &license, &config);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, ChallengeError::LicenseWithoutActivationSecret);
+    }
+}
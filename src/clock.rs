@@ -0,0 +1,33 @@
+#[cfg(feature = "std")]
+use crate::datetime::Duration;
+use crate::datetime::{self, DateTime};
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime;
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime {
+        datetime::now()
+    }
+}
+
+// Clamps to the backend's representable range instead of panicking on overflow
+#[cfg(feature = "std")]
+pub(crate) fn saturating_add(at: DateTime, duration: Duration) -> DateTime {
+    datetime::checked_add(at, duration).unwrap_or(if duration < Duration::seconds(0) {
+        datetime::min()
+    } else {
+        datetime::max()
+    })
+}
+
+// Licenses that never expire still carry an explicit expiration date: the last second of year 9999
+pub(crate) fn perpetual_expiration_date() -> DateTime {
+    datetime::from_unix(253_402_300_799, 0).expect("Year 9999 must be representable")
+}
@@ -0,0 +1,572 @@
+use crate::datetime::{self, Duration};
+use crate::discovery::DocumentFetcher;
+use crate::downgrade::DowngradePolicy;
+use crate::id::LicenseId;
+use crate::jwa::Algorithm;
+use crate::policy::{OfflineWindowAction, SuspiciousClockAction, ValidationPolicy};
+use crate::trust_bundle::TrustBundle;
+use crate::verification::LicenseVerifier;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifierConfigError {
+    Io(std::io::ErrorKind),
+    UnsupportedFormat(String),
+    Malformed(String),
+    UnknownField(String),
+    InvalidField { field: String, reason: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum KeySource {
+    #[serde(rename = "jwk")]
+    Jwk(serde_json::Value),
+    // Relative paths are resolved against the directory of the configuration file
+    #[serde(rename = "path")]
+    Path(PathBuf),
+    #[serde(rename = "jwksUrl")]
+    JwksUrl(String),
+}
+
+// Unknown fields are collected at every level: a strict configuration rejects them, otherwise
+// they are reported through `unknown_fields` so they can be logged as warnings.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct VerifierConfig {
+    #[serde(default)]
+    pub strict: bool,
+    pub key: KeySource,
+    #[serde(
+        rename = "allowedAlgorithms",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    pub allowed_algorithms: Vec<String>,
+    #[serde(
+        rename = "expectedIssuer",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub expected_issuer: Option<String>,
+    #[serde(rename = "requireKid", default)]
+    pub require_kid: bool,
+    #[serde(default)]
+    pub policy: PolicyConfig,
+    #[serde(flatten, skip_serializing)]
+    unknown: BTreeMap<String, serde_json::Value>,
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+// Durations are written as a sequence of amounts with a unit, e.g. `90s`, `5m` or `1h30m`
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+pub struct PolicyConfig {
+    #[serde(
+        rename = "expectedLicenseId",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub expected_license_id: Option<String>,
+    #[serde(
+        rename = "expectedProduct",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub expected_product: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub host: Option<String>,
+    #[serde(rename = "requireAllowedHosts", default)]
+    pub require_allowed_hosts: bool,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub address: Option<String>,
+    #[serde(rename = "clockSkew", skip_serializing_if = "Option::is_none", default)]
+    pub clock_skew: Option<String>,
+    #[serde(
+        rename = "gracePeriod",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub grace_period: Option<String>,
+    #[serde(
+        rename = "onSuspiciousClock",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub on_suspicious_clock: Option<String>,
+    #[serde(
+        rename = "onOfflineWindowExceeded",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub on_offline_window_exceeded: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub downgrade: Option<DowngradeConfig>,
+    #[serde(flatten, skip_serializing)]
+    unknown: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+pub struct DowngradeConfig {
+    #[serde(rename = "byKind", skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub by_kind: BTreeMap<String, Vec<String>>,
+    #[serde(
+        rename = "byProduct",
+        skip_serializing_if = "BTreeMap::is_empty",
+        default
+    )]
+    pub by_product: BTreeMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fallback: Option<Vec<String>>,
+    #[serde(flatten, skip_serializing)]
+    unknown: BTreeMap<String, serde_json::Value>,
+}
+
+impl VerifierConfig {
+    // `.toml` files require the `toml` feature; every other extension is read as JSON
+    pub fn load(path: &Path) -> Result<Self, VerifierConfigError> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| VerifierConfigError::Io(e.kind()))?;
+        let is_toml = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+        let mut config = if is_toml {
+            Self::from_toml_str(&contents)?
+        } else {
+            let document = serde_json::from_str(&contents)
+                .map_err(|error| VerifierConfigError::Malformed(error.to_string()))?;
+            Self::from_json(document)?
+        };
+        config.base_dir = path.parent().map(Path::to_owned).unwrap_or_default();
+        Ok(config)
+    }
+
+    pub fn from_json(document: serde_json::Value) -> Result<Self, VerifierConfigError> {
+        let config: Self = serde_json::from_value(document)
+            .map_err(|error| VerifierConfigError::Malformed(error.to_string()))?;
+        config.checked()
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(toml: &str) -> Result<Self, VerifierConfigError> {
+        let config: Self = ::toml::from_str(toml)
+            .map_err(|error| VerifierConfigError::Malformed(error.message().to_owned()))?;
+        config.checked()
+    }
+
+    #[cfg(not(feature = "toml"))]
+    pub fn from_toml_str(_toml: &str) -> Result<Self, VerifierConfigError> {
+        Err(VerifierConfigError::UnsupportedFormat("toml".to_owned()))
+    }
+
+    pub fn unknown_fields(&self) -> Vec<String> {
+        let policy = &self.policy;
+        let downgrade = policy.downgrade.iter().flat_map(|downgrade| {
+            downgrade
+                .unknown
+                .keys()
+                .map(|field| format!("policy.downgrade.{}", field))
+        });
+        self.unknown
+            .keys()
+            .cloned()
+            .chain(
+                policy
+                    .unknown
+                    .keys()
+                    .map(|field| format!("policy.{}", field)),
+            )
+            .chain(downgrade)
+            .collect()
+    }
+
+    pub fn validation_policy(&self) -> Result<ValidationPolicy, VerifierConfigError> {
+        let config = &self.policy;
+        let mut policy = ValidationPolicy::new();
+        if let Some(license_id) = &config.expected_license_id {
+            policy = policy.expected_license_id(LicenseId::from(license_id.clone()));
+        }
+        if let Some(product) = &config.expected_product {
+            policy = policy.expected_product(product);
+        }
+        if let Some(host) = &config.host {
+            policy = policy.bind_to_host(host);
+        }
+        if config.require_allowed_hosts {
+            policy = policy.require_allowed_hosts();
+        }
+        if let Some(address) = &config.address {
+            let address = address
+                .parse()
+                .map_err(|_| invalid_field("policy.address", "invalid IP address"))?;
+            policy = policy.bind_to_ip(address);
+        }
+        if let Some(clock_skew) = &config.clock_skew {
+            policy = policy.clock_skew(duration_field("policy.clockSkew", clock_skew)?);
+        }
+        if let Some(grace_period) = &config.grace_period {
+            policy = policy.grace_period(duration_field("policy.gracePeriod", grace_period)?);
+        }
+        match config.on_suspicious_clock.as_deref() {
+            None => {}
+            Some("warn") => policy = policy.on_suspicious_clock(SuspiciousClockAction::Warn),
+            Some("fail") => policy = policy.on_suspicious_clock(SuspiciousClockAction::Fail),
+            Some(_) => {
+                return Err(invalid_field(
+                    "policy.onSuspiciousClock",
+                    "expected `warn` or `fail`",
+                ))
+            }
+        }
+        match config.on_offline_window_exceeded.as_deref() {
+            None => {}
+            Some("grace") => policy = policy.on_offline_window_exceeded(OfflineWindowAction::Grace),
+            Some("fail") => policy = policy.on_offline_window_exceeded(OfflineWindowAction::Fail),
+            Some(_) => {
+                return Err(invalid_field(
+                    "policy.onOfflineWindowExceeded",
+                    "expected `grace` or `fail`",
+                ))
+            }
+        }
+        if let Some(config) = &config.downgrade {
+            let mut downgrade = DowngradePolicy::new();
+            for (kind, features) in &config.by_kind {
+                downgrade = downgrade.kind(kind, &as_strs(features));
+            }
+            for (product, features) in &config.by_product {
+                downgrade = downgrade.product(product, &as_strs(features));
+            }
+            if let Some(features) = &config.fallback {
+                downgrade = downgrade.fallback(&as_strs(features));
+            }
+            policy = policy.downgrade(downgrade);
+        }
+        Ok(policy)
+    }
+
+    // A `jwksUrl` key source needs a fetcher; with the `http` feature it defaults to `HttpFetcher`
+    pub fn verifier(
+        &self,
+        fetcher: Option<&dyn DocumentFetcher>,
+    ) -> Result<LicenseVerifier, VerifierConfigError> {
+        let policy = self.validation_policy()?;
+        let mut allowed_algorithms = Vec::new();
+        for (index, algorithm) in self.allowed_algorithms.iter().enumerate() {
+            let algorithm: Algorithm = serde_json::from_value(serde_json::json!(algorithm))
+                .map_err(|_| {
+                    invalid_field(
+                        &format!("allowedAlgorithms[{}]", index),
+                        &format!("unknown algorithm `{}`", algorithm),
+                    )
+                })?;
+            allowed_algorithms.push(algorithm);
+        }
+
+        let mut builder = match &self.key {
+            KeySource::Jwk(jwk) => LicenseVerifier::builder().key_jwk(jwk.clone()),
+            KeySource::Path(path) => {
+                let path = self.base_dir.join(path);
+                let jwk = std::fs::read(&path)
+                    .map_err(|e| format!("{}: {:?}", path.display(), e.kind()))
+                    .and_then(|contents| {
+                        serde_json::from_slice(&contents)
+                            .map_err(|_| format!("{}: not a JSON document", path.display()))
+                    })
+                    .map_err(|reason| invalid_field("key.path", &reason))?;
+                LicenseVerifier::builder().key_jwk(jwk)
+            }
+            KeySource::JwksUrl(url) => {
+                #[cfg(feature = "http")]
+                let http_fetcher = crate::discovery::HttpFetcher::new();
+                #[cfg(feature = "http")]
+                let fetcher = fetcher.or(Some(&http_fetcher as &dyn DocumentFetcher));
+                let fetcher =
+                    fetcher.ok_or_else(|| invalid_field("key.jwksUrl", "no fetcher available"))?;
+                let jwks = fetcher
+                    .fetch(url)
+                    .map_err(|error| invalid_field("key.jwksUrl", &format!("{:?}", error)))?;
+                let bundle = TrustBundle::from_json(&serde_json::json!({
+                    "version": 1,
+                    "issuers": [{
+                        "name": url,
+                        "keys": jwks.get("keys").cloned().unwrap_or_default(),
+                        "algorithms": ["RS512"],
+                    }]
+                }))
+                .map_err(|error| invalid_field("key.jwksUrl", &format!("{:?}", error)))?;
+                LicenseVerifier::builder().trust_bundle(bundle)
+            }
+        };
+        if !allowed_algorithms.is_empty() {
+            builder = builder.allowed_algorithms(&allowed_algorithms);
+        }
+        if let Some(issuer) = &self.expected_issuer {
+            builder = builder.expected_issuer(issuer);
+        }
+        builder
+            .require_kid(self.require_kid)
+            .policy(policy)
+            .build()
+            .map_err(|error| {
+                let field = match &self.key {
+                    KeySource::Jwk(_) => "key.jwk",
+                    KeySource::Path(_) => "key.path",
+                    KeySource::JwksUrl(_) => "key.jwksUrl",
+                };
+                invalid_field(field, &format!("{:?}", error))
+            })
+    }
+
+    fn checked(self) -> Result<Self, VerifierConfigError> {
+        if self.strict {
+            if let Some(field) = self.unknown_fields().into_iter().next() {
+                return Err(VerifierConfigError::UnknownField(field));
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl LicenseVerifier {
+    pub fn from_config_file(path: &Path) -> Result<Self, VerifierConfigError> {
+        VerifierConfig::load(path)?.verifier(None)
+    }
+
+    pub fn from_config_file_with_fetcher(
+        path: &Path,
+        fetcher: &dyn DocumentFetcher,
+    ) -> Result<Self, VerifierConfigError> {
+        VerifierConfig::load(path)?.verifier(Some(fetcher))
+    }
+}
+
+fn duration_field(field: &str, value: &str) -> Result<Duration, VerifierConfigError> {
+    parse_duration(value)
+        .ok_or_else(|| invalid_field(field, &format!("invalid duration `{}`", value)))
+}
+
+fn parse_duration(value: &str) -> Option<Duration> {
+    let mut total: i64 = 0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        let unit_seconds = match unit {
+            'd' => 86_400,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        total = total.checked_add(amount.checked_mul(unit_seconds)?)?;
+        rest = &rest[digits + unit.len_utf8()..];
+    }
+    if value.is_empty() {
+        return None;
+    }
+    datetime::try_seconds(total)
+}
+
+fn as_strs(values: &[String]) -> Vec<&str> {
+    values.iter().map(String::as_str).collect()
+}
+
+fn invalid_field(field: &str, reason: &str) -> VerifierConfigError {
+    VerifierConfigError::InvalidField {
+        field: field.to_owned(),
+        reason: reason.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "sign")]
+    use crate::signing::LicenseSigner;
+    #[cfg(feature = "sign")]
+    use crate::test_keys::ISSUER_PRIVATE_KEY_JWK_JSON;
+    use crate::test_keys::ISSUER_PUBLIC_KEY_JWK_JSON;
+    #[cfg(feature = "sign")]
+    use crate::verification::LicenseVerificationError;
+    #[cfg(feature = "sign")]
+    use crate::License;
+
+    fn write_config(dir: &Path, config: serde_json::Value) -> PathBuf {
+        std::fs::write(
+            dir.join("issuer.jwk"),
+            serde_json::to_vec(&*ISSUER_PUBLIC_KEY_JWK_JSON).unwrap(),
+        )
+        .unwrap();
+        let path = dir.join("verifier.json");
+        std::fs::write(&path, serde_json::to_vec_pretty(&config).unwrap()).unwrap();
+        path
+    }
+
+    fn complete_config() -> serde_json::Value {
+        serde_json::json!({
+            "strict": true,
+            "key": { "path": "issuer.jwk" },
+            "allowedAlgorithms": ["RS512"],
+            "policy": {
+                "expectedProduct": "acme-cad",
+                "clockSkew": "2m30s",
+                "gracePeriod": "7d",
+                "onSuspiciousClock": "fail",
+                "onOfflineWindowExceeded": "grace",
+                "downgrade": { "fallback": ["viewer"] }
+            }
+        })
+    }
+
+    #[cfg(feature = "sign")]
+    fn license(product: &str) -> serde_json::Value {
+        let license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "product": product,
+            "expirationDate": "2124-10-01T00:00:00Z",
+            "customData": {}
+        }))
+        .unwrap();
+        LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license)
+            .unwrap()
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn load_complete_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(dir.path(), complete_config());
+
+        let config = VerifierConfig::load(&path).expect("Loading should work");
+        assert_eq!(
+            config.validation_policy(),
+            Ok(ValidationPolicy::new()
+                .expected_product("acme-cad")
+                .clock_skew(Duration::seconds(150))
+                .grace_period(Duration::days(7))
+                .on_suspicious_clock(SuspiciousClockAction::Fail)
+                .on_offline_window_exceeded(OfflineWindowAction::Grace)
+                .downgrade(DowngradePolicy::new().fallback(&["viewer"])))
+        );
+
+        let verifier = LicenseVerifier::from_config_file(&path).expect("Loading should work");
+        assert!(verifier.verify_license(license("acme-cad")).is_ok());
+        let Err(error) = verifier.verify_license(license("acme-cam")) else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::ProductOutOfScope);
+    }
+
+    #[test]
+    fn unknown_fields_per_strictness() {
+        let mut config = complete_config();
+        config["policy"]["clockSkw"] = serde_json::json!("5m");
+
+        let Err(error) = VerifierConfig::from_json(config.clone()) else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            VerifierConfigError::UnknownField("policy.clockSkw".to_owned())
+        );
+
+        config["strict"] = serde_json::json!(false);
+        let config = VerifierConfig::from_json(config).expect("Loading should work");
+        assert_eq!(config.unknown_fields(), ["policy.clockSkw"]);
+    }
+
+    #[test]
+    fn invalid_configs() {
+        let mut bad_key_path = complete_config();
+        bad_key_path["key"] = serde_json::json!({ "path": "missing.jwk" });
+        let mut bad_duration = complete_config();
+        bad_duration["policy"]["gracePeriod"] = serde_json::json!("7 days");
+        let mut bad_algorithm = complete_config();
+        bad_algorithm["allowedAlgorithms"] = serde_json::json!(["RS512", "RS999"]);
+        let mut jwks_url = complete_config();
+        jwks_url["key"] = serde_json::json!({ "jwksUrl": "http://acme.example/jwks.json" });
+        // Without a fetcher of its own the configuration falls back to the HTTPS-only client
+        #[cfg(feature = "http")]
+        let jwks_url_reason = r#"InsecureUrl("http://acme.example/jwks.json")"#.to_owned();
+        #[cfg(not(feature = "http"))]
+        let jwks_url_reason = "no fetcher available".to_owned();
+
+        let dir = tempfile::tempdir().unwrap();
+        let missing_key_path = dir.path().join("missing.jwk");
+        let cases = [
+            (
+                bad_key_path,
+                "key.path",
+                format!("{}: NotFound", missing_key_path.display()),
+            ),
+            (
+                bad_duration,
+                "policy.gracePeriod",
+                "invalid duration `7 days`".to_owned(),
+            ),
+            (
+                bad_algorithm,
+                "allowedAlgorithms[1]",
+                "unknown algorithm `RS999`".to_owned(),
+            ),
+            (jwks_url, "key.jwksUrl", jwks_url_reason),
+        ];
+        for (config, field, reason) in cases {
+            let path = write_config(dir.path(), config);
+            let result = LicenseVerifier::from_config_file(&path);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(
+                error,
+                VerifierConfigError::InvalidField {
+                    field: field.to_owned(),
+                    reason
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn duration_strings() {
+        let cases = [
+            ("90s", Some(Duration::seconds(90))),
+            ("1h30m", Some(Duration::minutes(90))),
+            ("7d", Some(Duration::days(7))),
+            ("", None),
+            ("5", None),
+            ("5 m", None),
+            ("-5m", None),
+            ("5w", None),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_duration(value), expected, "{}", value);
+        }
+    }
+
+    #[cfg(feature = "sign")]
+    #[cfg(feature = "toml")]
+    #[test]
+    fn load_toml_config() {
+        let dir = tempfile::tempdir().unwrap();
+        write_config(dir.path(), complete_config());
+        let path = dir.path().join("verifier.toml");
+        std::fs::write(
+            &path,
+            "strict = true\n\
+             [key]\n\
+             path = \"issuer.jwk\"\n\
+             [policy]\n\
+             expectedProduct = \"acme-cad\"\n\
+             gracePeriod = \"7d\"\n",
+        )
+        .unwrap();
+
+        let verifier = LicenseVerifier::from_config_file(&path).expect("Loading should work");
+        assert!(verifier.verify_license(license("acme-cad")).is_ok());
+    }
+}
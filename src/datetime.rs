@@ -0,0 +1,643 @@
+// The date and time types of the public API. chrono's are the default; with only the `time` feature
+// enabled they are time's. Dates are parsed and printed through `Timestamp` under both, so a license
+// reads and serializes to the same RFC 3339 text whichever backend is compiled in.
+
+use alloc::string::{String, ToString};
+use core::fmt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "chrono")]
+pub type DateTime = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "chrono")]
+pub type Duration = chrono::TimeDelta;
+
+#[cfg(not(feature = "chrono"))]
+pub type DateTime = time::OffsetDateTime;
+#[cfg(not(feature = "chrono"))]
+pub type Duration = time::Duration;
+
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+const SECONDS_PER_DAY: i64 = 86_400;
+// Far beyond either backend; keeps the calendar arithmetic clear of overflow
+const MAX_YEAR_MAGNITUDE: i64 = 1_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ParseError {
+    Invalid,
+    // Well-formed, but an instant the backend cannot represent
+    OutOfRange,
+}
+
+// An instant as seconds and nanoseconds since the Unix epoch. Nanoseconds past one billion are a
+// leap second, as chrono represents them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Timestamp {
+    seconds: i64,
+    nanos: u32,
+}
+
+impl Timestamp {
+    #[cfg(feature = "chrono")]
+    fn from_datetime(at: DateTime) -> Self {
+        Timestamp {
+            seconds: at.timestamp(),
+            nanos: at.timestamp_subsec_nanos(),
+        }
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    fn from_datetime(at: DateTime) -> Self {
+        Timestamp {
+            seconds: at.unix_timestamp(),
+            nanos: at.nanosecond(),
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    fn to_datetime(self) -> Option<DateTime> {
+        DateTime::from_timestamp(self.seconds, self.nanos)
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    fn to_datetime(self) -> Option<DateTime> {
+        DateTime::from_unix_timestamp(self.seconds)
+            .and_then(|at| at.replace_nanosecond(self.nanos))
+            .ok()
+    }
+
+    // RFC 3339 with the relaxations chrono's `DateTime<Utc>` parser accepts: `t` or a space as the
+    // separator, `z` or `UTC` for a zero offset, single-digit fields, whitespace between fields and
+    // more than nine fractional digits. Leap seconds are not accepted.
+    fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut scanner = Scanner(text);
+        let year = scanner.year()?;
+        scanner.literal("-")?;
+        let month = scanner.number(2)?;
+        scanner.literal("-")?;
+        let day = scanner.number(2)?;
+        scanner.separator()?;
+        let hour = scanner.number(2)?;
+        scanner.literal(":")?;
+        let minute = scanner.number(2)?;
+        scanner.literal(":")?;
+        let second = scanner.number(2)?;
+        let nanos = scanner.fraction()?;
+        let offset = scanner.offset()?;
+        if !scanner.0.is_empty()
+            || !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(ParseError::Invalid);
+        }
+
+        let seconds = days_from_civil(year, month, day) * SECONDS_PER_DAY
+            + i64::from(hour * 3600 + minute * 60 + second)
+            - offset;
+        Ok(Timestamp { seconds, nanos })
+    }
+
+    // Only the `%Y-%m-%d` part of the RFC 3339 form
+    fn date(self) -> impl fmt::Display {
+        struct Date(i64, u32, u32);
+
+        impl fmt::Display for Date {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let Date(year, month, day) = *self;
+                // ISO 8601 requires the explicit sign for years outside 0000-9999
+                if (0..=9999).contains(&year) {
+                    write!(f, "{:04}-{:02}-{:02}", year, month, day)
+                } else {
+                    write!(f, "{:+05}-{:02}-{:02}", year, month, day)
+                }
+            }
+        }
+
+        let (year, month, day) = civil_from_days(self.seconds.div_euclid(SECONDS_PER_DAY));
+        Date(year, month, day)
+    }
+}
+
+// Seconds without a fraction, milliseconds, microseconds or nanoseconds, whichever is the shortest
+// exact form, and always `Z`: what chrono's `Serialize` implementation writes
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds_of_day = self.seconds.rem_euclid(SECONDS_PER_DAY);
+        let (mut second, mut nanos) = (seconds_of_day % 60, self.nanos);
+        if nanos >= NANOS_PER_SECOND {
+            second += 1;
+            nanos -= NANOS_PER_SECOND;
+        }
+        write!(
+            f,
+            "{}T{:02}:{:02}:{:02}",
+            self.date(),
+            seconds_of_day / 3600,
+            seconds_of_day / 60 % 60,
+            second
+        )?;
+        if nanos == 0 {
+        } else if nanos % 1_000_000 == 0 {
+            write!(f, ".{:03}", nanos / 1_000_000)?;
+        } else if nanos % 1_000 == 0 {
+            write!(f, ".{:06}", nanos / 1_000)?;
+        } else {
+            write!(f, ".{:09}", nanos)?;
+        }
+        f.write_str("Z")
+    }
+}
+
+struct Scanner<'a>(&'a str);
+
+impl Scanner<'_> {
+    // Four digits at most, unless the year carries an explicit sign
+    fn year(&mut self) -> Result<i64, ParseError> {
+        self.0 = self.0.trim_start();
+        let (negative, digits) = match self.0.as_bytes().first() {
+            Some(b'-') => (true, usize::MAX),
+            Some(b'+') => (false, usize::MAX),
+            _ => (false, 4),
+        };
+        if digits == usize::MAX {
+            self.0 = &self.0[1..];
+        }
+        let magnitude = self.digits(digits)?;
+        let year = i64::try_from(magnitude).map_err(|_| ParseError::OutOfRange)?;
+        if year > MAX_YEAR_MAGNITUDE {
+            return Err(ParseError::OutOfRange);
+        }
+        Ok(if negative { -year } else { year })
+    }
+
+    fn number(&mut self, max_digits: usize) -> Result<u32, ParseError> {
+        self.0 = self.0.trim_start();
+        let value = self.digits(max_digits)?;
+        u32::try_from(value).map_err(|_| ParseError::Invalid)
+    }
+
+    fn digits(&mut self, max_digits: usize) -> Result<u64, ParseError> {
+        let count = self
+            .0
+            .bytes()
+            .take(max_digits)
+            .take_while(u8::is_ascii_digit)
+            .count();
+        if count == 0 {
+            return Err(ParseError::Invalid);
+        }
+        let (digits, rest) = self.0.split_at(count);
+        self.0 = rest;
+        digits.parse().map_err(|_| ParseError::OutOfRange)
+    }
+
+    fn literal(&mut self, literal: &str) -> Result<(), ParseError> {
+        self.0 = self
+            .0
+            .trim_start()
+            .strip_prefix(literal)
+            .ok_or(ParseError::Invalid)?;
+        Ok(())
+    }
+
+    fn separator(&mut self) -> Result<(), ParseError> {
+        match self.0.as_bytes().first() {
+            Some(b'T' | b't' | b' ') => {
+                self.0 = &self.0[1..];
+                Ok(())
+            }
+            _ => Err(ParseError::Invalid),
+        }
+    }
+
+    // Digits past the ninth are accepted and dropped
+    fn fraction(&mut self) -> Result<u32, ParseError> {
+        let Some(rest) = self.0.strip_prefix('.') else {
+            return Ok(0);
+        };
+        self.0 = rest;
+        let count = self.0.bytes().take_while(u8::is_ascii_digit).count();
+        if count == 0 {
+            return Err(ParseError::Invalid);
+        }
+        let (digits, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(digits
+            .bytes()
+            .chain(core::iter::repeat(b'0'))
+            .take(9)
+            .fold(0, |nanos, digit| nanos * 10 + u32::from(digit - b'0')))
+    }
+
+    // Seconds east of UTC
+    fn offset(&mut self) -> Result<i64, ParseError> {
+        self.0 = self.0.trim_start();
+        if self
+            .0
+            .get(..3)
+            .is_some_and(|utc| utc.eq_ignore_ascii_case("UTC"))
+        {
+            self.0 = &self.0[3..];
+            return Ok(0);
+        }
+        let mut chars = self.0.chars();
+        let negative = match chars.next() {
+            Some('Z' | 'z') => {
+                self.0 = chars.as_str();
+                return Ok(0);
+            }
+            Some('+') => false,
+            // Also the Unicode minus sign
+            Some('-' | '\u{2212}') => true,
+            _ => return Err(ParseError::Invalid),
+        };
+        self.0 = chars.as_str();
+        let hours = self.two_digits()?;
+        self.0 = self
+            .0
+            .trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+        let minutes = self.two_digits()?;
+        if minutes > 59 {
+            return Err(ParseError::Invalid);
+        }
+        let offset = i64::from(hours * 3600 + minutes * 60);
+        // chrono only has offsets of less than a day
+        if offset >= SECONDS_PER_DAY {
+            return Err(ParseError::Invalid);
+        }
+        Ok(if negative { -offset } else { offset })
+    }
+
+    fn two_digits(&mut self) -> Result<u32, ParseError> {
+        match self.0.as_bytes() {
+            [tens @ b'0'..=b'9', ones @ b'0'..=b'9', ..] => {
+                let value = u32::from(tens - b'0') * 10 + u32::from(ones - b'0');
+                self.0 = &self.0[2..];
+                Ok(value)
+            }
+            _ => Err(ParseError::Invalid),
+        }
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since 1970-01-01 in the proleptic Gregorian calendar (Howard Hinnant's algorithm)
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let month_from_march = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_from_march + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month as u32, day as u32)
+}
+
+pub(crate) fn parse(text: &str) -> Result<DateTime, ParseError> {
+    Timestamp::parse(text)?
+        .to_datetime()
+        .ok_or(ParseError::OutOfRange)
+}
+
+pub(crate) fn to_rfc3339(at: DateTime) -> String {
+    Timestamp::from_datetime(at).to_string()
+}
+
+// `%Y-%m-%d`
+#[cfg(feature = "std")]
+pub(crate) fn date(at: DateTime) -> impl fmt::Display {
+    Timestamp::from_datetime(at).date()
+}
+
+pub(crate) fn from_unix(seconds: i64, nanos: u32) -> Option<DateTime> {
+    Timestamp { seconds, nanos }.to_datetime()
+}
+
+#[cfg(any(feature = "std", feature = "time"))]
+pub(crate) fn unix_seconds(at: DateTime) -> i64 {
+    Timestamp::from_datetime(at).seconds
+}
+
+#[cfg(feature = "time")]
+pub(crate) fn subsec_nanos(at: DateTime) -> u32 {
+    Timestamp::from_datetime(at).nanos
+}
+
+#[cfg(all(feature = "std", feature = "chrono"))]
+pub(crate) fn now() -> DateTime {
+    chrono::Utc::now()
+}
+
+#[cfg(all(feature = "std", not(feature = "chrono")))]
+pub(crate) fn now() -> DateTime {
+    DateTime::now_utc()
+}
+
+#[cfg(all(feature = "std", feature = "chrono"))]
+pub(crate) fn checked_add(at: DateTime, duration: Duration) -> Option<DateTime> {
+    at.checked_add_signed(duration)
+}
+
+#[cfg(all(feature = "std", not(feature = "chrono")))]
+pub(crate) fn checked_add(at: DateTime, duration: Duration) -> Option<DateTime> {
+    at.checked_add(duration)
+}
+
+#[cfg(all(feature = "std", feature = "chrono"))]
+pub(crate) fn min() -> DateTime {
+    DateTime::MIN_UTC
+}
+
+#[cfg(all(feature = "std", feature = "chrono"))]
+pub(crate) fn max() -> DateTime {
+    DateTime::MAX_UTC
+}
+
+#[cfg(all(feature = "std", not(feature = "chrono")))]
+pub(crate) fn min() -> DateTime {
+    time::PrimitiveDateTime::MIN.assume_utc()
+}
+
+#[cfg(all(feature = "std", not(feature = "chrono")))]
+pub(crate) fn max() -> DateTime {
+    time::PrimitiveDateTime::MAX.assume_utc()
+}
+
+#[cfg(all(feature = "std", feature = "chrono"))]
+pub(crate) fn whole_seconds(duration: Duration) -> i64 {
+    duration.num_seconds()
+}
+
+#[cfg(all(feature = "std", not(feature = "chrono")))]
+pub(crate) fn whole_seconds(duration: Duration) -> i64 {
+    duration.whole_seconds()
+}
+
+// `None` where the backend's durations cannot hold that many seconds
+#[cfg(all(feature = "std", feature = "chrono"))]
+pub(crate) fn try_seconds(seconds: i64) -> Option<Duration> {
+    Duration::try_seconds(seconds)
+}
+
+#[cfg(all(feature = "std", not(feature = "chrono")))]
+pub(crate) fn try_seconds(seconds: i64) -> Option<Duration> {
+    Some(Duration::seconds(seconds))
+}
+
+struct Rfc3339(DateTime);
+
+impl Serialize for Rfc3339 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&Timestamp::from_datetime(self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Rfc3339 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse(&text).map(Rfc3339).map_err(|error| {
+            serde::de::Error::custom(match error {
+                ParseError::Invalid => "invalid RFC 3339 date",
+                ParseError::OutOfRange => "RFC 3339 date out of range",
+            })
+        })
+    }
+}
+
+// `#[serde(with = "crate::datetime::rfc3339")]`
+pub(crate) mod rfc3339 {
+    use super::{DateTime, Rfc3339};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        at: &DateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Rfc3339(*at).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime, D::Error> {
+        Rfc3339::deserialize(deserializer).map(|at| at.0)
+    }
+
+    // Optional fields also need `default`, which serde only implies for `Option` without `with`
+    pub(crate) mod option {
+        use super::{DateTime, Rfc3339};
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        pub(crate) fn serialize<S: Serializer>(
+            at: &Option<DateTime>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            at.map(Rfc3339).serialize(serializer)
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<DateTime>, D::Error> {
+            Option::<Rfc3339>::deserialize(deserializer).map(|at| at.map(|at| at.0))
+        }
+    }
+}
+
+// Whole seconds since the Unix epoch, as JWT claims carry them
+#[cfg(feature = "std")]
+pub(crate) mod unix_seconds {
+    use super::DateTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        at: &DateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(super::unix_seconds(*at))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime, D::Error> {
+        super::from_unix(i64::deserialize(deserializer)?, 0)
+            .ok_or_else(|| serde::de::Error::custom("timestamp out of range"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_print_round_trip() {
+        for text in [
+            "2024-10-01T00:00:00Z",
+            "2024-10-01T00:00:00.250Z",
+            "2024-10-01T00:00:00.000250Z",
+            "2024-10-01T00:00:00.000000250Z",
+            "1969-12-31T23:59:59Z",
+            "2024-02-29T12:30:45Z",
+            "0000-01-01T00:00:00Z",
+            "9999-12-31T23:59:59Z",
+        ] {
+            assert_eq!(to_rfc3339(parse(text).unwrap()), text);
+        }
+    }
+
+    #[test]
+    fn parse_accepts_relaxed_forms() {
+        let expected = parse("2024-10-01T00:00:00Z").unwrap();
+        for text in [
+            "2024-10-01t00:00:00z",
+            "2024-10-01 00:00:00Z",
+            "2024-10-01T00:00:00.000000000000Z",
+            "2024-10-01T02:00:00+02:00",
+            "2024-10-01T02:00:00+0200",
+            "2024-09-30T19:00:00-05:00",
+            "2024-09-30T19:00:00\u{2212}05:00",
+            "2024-10-01T00:00:00 UTC",
+            "2024-10-1T0:0:0Z",
+            "+2024-10-01T00:00:00Z",
+        ] {
+            assert_eq!(parse(text), Ok(expected), "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_rejects_malformed_dates() {
+        for text in [
+            "",
+            "2024-10-01",
+            "2024-10-01T00:00:00",
+            "2024-13-01T00:00:00Z",
+            "2023-02-29T00:00:00Z",
+            "2024-10-01T24:00:00Z",
+            "2024-10-01T00:00:60Z",
+            "2024-10-01T00:00:00.Z",
+            "2024-10-01T00:00:00+24:00",
+            "2024-10-01T00:00:00Z trailing",
+            "20245-10-01T00:00:00Z",
+        ] {
+            assert_eq!(parse(text), Err(ParseError::Invalid), "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_rejects_instants_the_backend_cannot_hold() {
+        for text in [
+            "+262143-01-01T00:00:00Z",
+            "-262144-01-01T00:00:00Z",
+            "+99999999999999999999-01-01T00:00:00Z",
+        ] {
+            assert_eq!(parse(text), Err(ParseError::OutOfRange), "{}", text);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn extreme_dates_round_trip() {
+        for at in [min(), max()] {
+            assert_eq!(parse(&to_rfc3339(at)), Ok(at));
+        }
+        assert_eq!(
+            to_rfc3339(parse("-0001-12-31T23:59:59Z").unwrap()),
+            "-0001-12-31T23:59:59Z"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn date_only() {
+        let at = parse("2024-10-01T23:59:59.999Z").unwrap();
+        assert_eq!(date(at).to_string(), "2024-10-01");
+    }
+
+    #[test]
+    fn serde_uses_the_rfc3339_form() {
+        #[derive(Debug, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+        struct Dates {
+            #[serde(with = "rfc3339")]
+            at: DateTime,
+            #[serde(with = "rfc3339::option", default)]
+            until: Option<DateTime>,
+        }
+
+        let dates: Dates =
+            serde_json::from_value(serde_json::json!({ "at": "2024-10-01T02:00:00+02:00" }))
+                .unwrap();
+        assert_eq!(
+            dates,
+            Dates {
+                at: parse("2024-10-01T00:00:00Z").unwrap(),
+                until: None
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&dates).unwrap(),
+            serde_json::json!({ "at": "2024-10-01T00:00:00Z", "until": null })
+        );
+        assert!(serde_json::from_value::<Dates>(serde_json::json!({ "at": "yesterday" })).is_err());
+    }
+
+    // chrono is the reference for both the accepted syntax and the printed form
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn matches_chrono() {
+        for text in [
+            "2024-10-01T00:00:00Z",
+            "2024-10-01t00:00:00.123456789z",
+            "2024-10-01 00:00:00.1+05:30",
+            "2024-10-01T00:00:00.000001-00:01",
+            "2024-10-01T00:00:00 UTC",
+            "1900-02-28T23:59:59Z",
+            "2000-02-29T00:00:00Z",
+            "+12345-06-07T08:09:10Z",
+            "-0100-03-01T00:00:00Z",
+            "2024-02-30T00:00:00Z",
+            "2024-10-01T00:00:00",
+        ] {
+            let expected = text.parse::<DateTime>().ok();
+            assert_eq!(parse(text).ok(), expected, "{}", text);
+            if let Some(expected) = expected {
+                assert_eq!(
+                    to_rfc3339(expected),
+                    expected.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+                    "{}",
+                    text
+                );
+            }
+        }
+    }
+}
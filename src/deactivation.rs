@@ -0,0 +1,182 @@
+use crate::datetime::{self, DateTime};
+use crate::device::{DeviceKey, DeviceKeyError, DevicePublicKey};
+use crate::id::LicenseId;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::License;
+use serde_derive::{Deserialize, Serialize};
+
+const DEACTIVATION_TOKEN_TYP: &str = "jls-deactivation+jwt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeactivationError {
+    LicenseWithoutDeviceKey,
+    InvalidBoundDeviceKey,
+    InvalidDeactivationToken,
+    VerificationFailure,
+    LicenseMismatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DeactivationToken {
+    #[serde(rename = "licenseId")]
+    pub license_id: LicenseId,
+    pub fingerprint: String,
+    #[serde(rename = "deactivatedAt", with = "crate::datetime::rfc3339")]
+    pub deactivated_at: DateTime,
+}
+
+impl DeactivationToken {
+    pub fn new(license: &License, fingerprint: &str) -> Self {
+        Self {
+            license_id: license.id.clone(),
+            fingerprint: fingerprint.to_owned(),
+            deactivated_at: datetime::now(),
+        }
+    }
+
+    pub fn sign(&self, device_key: &DeviceKey) -> Result<String, DeviceKeyError> {
+        device_key.sign_token(DEACTIVATION_TOKEN_TYP, self)
+    }
+}
+
+pub fn verify_deactivation(
+    verifier: &LicenseVerifier,
+    token: &str,
+    original_license: &License,
+) -> Result<DeactivationToken, DeactivationError> {
+    let bound_device_key = original_license
+        .device_key
+        .as_ref()
+        .ok_or(DeactivationError::LicenseWithoutDeviceKey)?;
+    let bound_device_key = DevicePublicKey::from_jwk(bound_device_key)
+        .map_err(|_| DeactivationError::InvalidBoundDeviceKey)?;
+
+    let deactivation: DeactivationToken = bound_device_key
+        .verify_token_reported(verifier, token, DEACTIVATION_TOKEN_TYP)
+        .map_err(|error| match error {
+            LicenseVerificationError::VerificationFailure => DeactivationError::VerificationFailure,
+            _ => DeactivationError::InvalidDeactivationToken,
+        })?;
+
+    if deactivation.license_id != original_license.id {
+        return Err(DeactivationError::LicenseMismatch);
+    }
+
+    Ok(deactivation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::datetime::Duration;
+    use crate::test_keys::{verifier, ISSUER_PUBLIC_KEY_JWK_JSON};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn opaque_verifier(audit_sink: Arc<MemoryAuditSink>) -> LicenseVerifier {
+        LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink)
+            .build()
+            .unwrap()
+    }
+
+    fn license_bound_to(device_key: &DeviceKey) -> License {
+        License {
+            id: Uuid::new_v4().into(),
+            product: None,
+            kind: None,
+            issuer: None,
+            issued_at: None,
+            expiration_date: datetime::now() + Duration::days(365),
+            custom_data: serde_json::json!({ "owner": "John Doe" }),
+            device_key: Some(device_key.public_jwk()),
+            features: Vec::new(),
+            max_seats: None,
+            delegated_key: None,
+            activation_secret: None,
+            allowed_hosts: Vec::new(),
+            allowed_networks: Vec::new(),
+            upgrades_from: None,
+            state_required: false,
+            offline_validity_days: None,
+            maintenance_until: None,
+            watermark: None,
+        }
+    }
+
+    #[test]
+    fn license_transfer_between_machines() {
+        let machine_a_key = DeviceKey::generate();
+        let machine_b_key = DeviceKey::generate();
+        let original_license = license_bound_to(&machine_a_key);
+
+        let token = DeactivationToken::new(&original_license, "machine-a")
+            .sign(&machine_a_key)
+            .unwrap();
+        let deactivation = verify_deactivation(&verifier(), &token, &original_license)
+            .expect("Verification should succeed");
+        assert_eq!(deactivation.license_id, original_license.id);
+        assert_eq!(deactivation.fingerprint, "machine-a");
+
+        let transferred_license = license_bound_to(&machine_b_key);
+        let token = DeactivationToken::new(&transferred_license, "machine-b")
+            .sign(&machine_b_key)
+            .unwrap();
+        verify_deactivation(&verifier(), &token, &transferred_license)
+            .expect("Transferred license should be deactivatable");
+
+        let result = verify_deactivation(&verifier(), &token, &original_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, DeactivationError::VerificationFailure);
+    }
+
+    #[test]
+    fn deactivation_signed_by_non_bound_key() {
+        let device_key = DeviceKey::generate();
+        let license = license_bound_to(&device_key);
+
+        let token = DeactivationToken::new(&license, "machine-a")
+            .sign(&DeviceKey::generate())
+            .unwrap();
+
+        let result = verify_deactivation(&verifier(), &token, &license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, DeactivationError::VerificationFailure);
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let result = verify_deactivation(&opaque_verifier(audit_sink.clone()), &token, &license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, DeactivationError::InvalidDeactivationToken);
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::VerificationFailure]
+        );
+    }
+
+    #[test]
+    fn deactivation_for_other_license() {
+        let device_key = DeviceKey::generate();
+        let license = license_bound_to(&device_key);
+        let mut other_license = license.clone();
+        other_license.id = Uuid::new_v4().into();
+
+        let token = DeactivationToken::new(&other_license, "machine-a")
+            .sign(&device_key)
+            .unwrap();
+
+        let result = verify_deactivation(&verifier(), &token, &license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, DeactivationError::LicenseMismatch);
+    }
+}
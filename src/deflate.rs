@@ -0,0 +1,486 @@
+// Raw DEFLATE (RFC 1951) as used by the JWS/JWE `"zip": "DEF"` header. Compression emits a single
+// fixed-Huffman block, which is enough for the repetitive JSON of license payloads; decompression
+// accepts every block type. Only signers compress, so verifier builds leave the compressor out.
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+#[cfg(feature = "sign")]
+const WINDOW_SIZE: usize = 32 * 1024;
+#[cfg(feature = "sign")]
+const MIN_MATCH: usize = 3;
+#[cfg(feature = "sign")]
+const MAX_MATCH: usize = 258;
+#[cfg(feature = "sign")]
+const MAX_CHAIN: usize = 64;
+#[cfg(feature = "sign")]
+const HASH_BITS: u32 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum InflateError {
+    Invalid,
+    TooLarge,
+}
+
+#[cfg(feature = "sign")]
+pub(crate) fn compress(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; data.len()];
+    let mut position = 0;
+    while position < data.len() {
+        let (length, distance) = longest_match(data, position, &head, &previous);
+        let length = if length >= MIN_MATCH {
+            write_length(&mut writer, length);
+            write_distance(&mut writer, distance);
+            length
+        } else {
+            write_literal(&mut writer, data[position] as u16);
+            1
+        };
+        for position in position..position + length {
+            if position + MIN_MATCH <= data.len() {
+                let hash = hash(&data[position..position + MIN_MATCH]);
+                previous[position] = head[hash];
+                head[hash] = position;
+            }
+        }
+        position += length;
+    }
+    write_literal(&mut writer, 256);
+    writer.finish()
+}
+
+#[cfg(feature = "sign")]
+fn hash(bytes: &[u8]) -> usize {
+    let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+    (value.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+#[cfg(feature = "sign")]
+fn longest_match(
+    data: &[u8],
+    position: usize,
+    head: &[usize],
+    previous: &[usize],
+) -> (usize, usize) {
+    if position + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+    let max_length = MAX_MATCH.min(data.len() - position);
+    let (mut best_length, mut best_distance) = (0, 0);
+    let mut candidate = head[hash(&data[position..position + MIN_MATCH])];
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || position - candidate > WINDOW_SIZE {
+            break;
+        }
+        let length = data[candidate..]
+            .iter()
+            .zip(&data[position..position + max_length])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if length > best_length {
+            (best_length, best_distance) = (length, position - candidate);
+            if length == max_length {
+                break;
+            }
+        }
+        candidate = previous[candidate];
+    }
+    (best_length, best_distance)
+}
+
+// Fixed Huffman codes of RFC 1951 §3.2.6
+#[cfg(feature = "sign")]
+fn write_literal(writer: &mut BitWriter, symbol: u16) {
+    let (code, length) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xC0 + symbol - 280, 8),
+    };
+    writer.write_code(code, length);
+}
+
+#[cfg(feature = "sign")]
+fn write_length(writer: &mut BitWriter, length: usize) {
+    let index = LENGTH_BASE
+        .iter()
+        .rposition(|base| *base as usize <= length)
+        .expect("Match lengths are at least 3");
+    write_literal(writer, 257 + index as u16);
+    writer.write_bits(
+        (length - LENGTH_BASE[index] as usize) as u32,
+        LENGTH_EXTRA[index],
+    );
+}
+
+#[cfg(feature = "sign")]
+fn write_distance(writer: &mut BitWriter, distance: usize) {
+    let index = DISTANCE_BASE
+        .iter()
+        .rposition(|base| *base as usize <= distance)
+        .expect("Match distances are at least 1");
+    writer.write_code(index as u16, 5);
+    writer.write_bits(
+        (distance - DISTANCE_BASE[index] as usize) as u32,
+        DISTANCE_EXTRA[index],
+    );
+}
+
+#[cfg(feature = "sign")]
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    filled: u8,
+}
+
+#[cfg(feature = "sign")]
+impl BitWriter {
+    fn write_bits(&mut self, value: u32, count: u8) {
+        for bit in 0..count {
+            self.buffer |= ((value >> bit) & 1) << self.filled;
+            self.filled += 1;
+            if self.filled == 8 {
+                self.bytes.push(self.buffer as u8);
+                self.buffer = 0;
+                self.filled = 0;
+            }
+        }
+    }
+
+    // Huffman codes are packed starting from their most significant bit
+    fn write_code(&mut self, code: u16, length: u8) {
+        let reversed = (code.reverse_bits() >> (16 - length)) as u32;
+        self.write_bits(reversed, length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.filled > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+// Stops as soon as the output would exceed `max_size`, so a small bomb never allocates its
+// expanded size.
+pub(crate) fn decompress(data: &[u8], max_size: usize) -> Result<Vec<u8>, InflateError> {
+    let mut reader = BitReader { data, position: 0 };
+    let mut output = Inflated {
+        bytes: Vec::new(),
+        max_size,
+    };
+    loop {
+        let last_block = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let length = reader.bits(16)? as usize;
+                let complement = reader.bits(16)? as usize;
+                if length != !complement & 0xFFFF {
+                    return Err(InflateError::Invalid);
+                }
+                let start = reader.position / 8;
+                let stored = data
+                    .get(start..start + length)
+                    .ok_or(InflateError::Invalid)?;
+                output.reserve(length)?;
+                output.bytes.extend_from_slice(stored);
+                reader.position += length * 8;
+            }
+            1 => {
+                let mut lengths = [8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                let literals = Huffman::new(&lengths)?;
+                let distances = Huffman::new(&[5; 30])?;
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_tables(&mut reader)?;
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            _ => return Err(InflateError::Invalid),
+        }
+        if last_block {
+            return Ok(output.bytes);
+        }
+    }
+}
+
+struct Inflated {
+    bytes: Vec<u8>,
+    max_size: usize,
+}
+
+impl Inflated {
+    fn reserve(&self, additional: usize) -> Result<(), InflateError> {
+        if self.bytes.len() + additional > self.max_size {
+            return Err(InflateError::TooLarge);
+        }
+        Ok(())
+    }
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    output: &mut Inflated,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), InflateError> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 => {
+                output.reserve(1)?;
+                output.bytes.push(symbol as u8);
+            }
+            256 => return Ok(()),
+            257..=285 => {
+                let index = symbol - 257;
+                let length =
+                    LENGTH_BASE[index] as usize + reader.bits(LENGTH_EXTRA[index])? as usize;
+                let index = distances.decode(reader)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err(InflateError::Invalid);
+                }
+                let distance =
+                    DISTANCE_BASE[index] as usize + reader.bits(DISTANCE_EXTRA[index])? as usize;
+                if distance > output.bytes.len() {
+                    return Err(InflateError::Invalid);
+                }
+                output.reserve(length)?;
+                let start = output.bytes.len() - distance;
+                for offset in 0..length {
+                    output.bytes.push(output.bytes[start + offset]);
+                }
+            }
+            _ => return Err(InflateError::Invalid),
+        }
+    }
+}
+
+fn dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), InflateError> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(InflateError::Invalid);
+    }
+
+    let mut code_lengths = [0; 19];
+    for index in CODE_LENGTH_ORDER.into_iter().take(code_length_count) {
+        code_lengths[index] = reader.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (length, repeat) = match code_lengths.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (
+                *lengths.last().ok_or(InflateError::Invalid)?,
+                3 + reader.bits(2)?,
+            ),
+            17 => (0, 3 + reader.bits(3)?),
+            18 => (0, 11 + reader.bits(7)?),
+            _ => return Err(InflateError::Invalid),
+        };
+        if lengths.len() + repeat as usize > literal_count + distance_count {
+            return Err(InflateError::Invalid);
+        }
+        lengths.extend(std::iter::repeat_n(length, repeat as usize));
+    }
+    if lengths[256] == 0 {
+        return Err(InflateError::Invalid);
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u8) -> Result<u32, InflateError> {
+        let mut value = 0;
+        for bit in 0..count {
+            let byte = self
+                .data
+                .get(self.position / 8)
+                .ok_or(InflateError::Invalid)?;
+            value |= ((*byte as u32 >> (self.position % 8)) & 1) << bit;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+// Canonical Huffman decoding table: code counts per length and symbols ordered by code
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, InflateError> {
+        let mut counts = [0u16; 16];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        // Over-subscribed code lengths do not describe a prefix code
+        let mut left = 1i32;
+        for count in &counts[1..] {
+            left = (left << 1) - *count as i32;
+            if left < 0 {
+                return Err(InflateError::Invalid);
+            }
+        }
+
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, InflateError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for count in &self.counts[1..] {
+            code |= reader.bits(1)? as i32;
+            let count = *count as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(InflateError::Invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn compressed_data_round_trips() {
+        let json = serde_json::to_vec(&serde_json::json!({
+            "matrix": (0..500).map(|row| format!("feature-{}:enabled", row % 7)).collect::<Vec<_>>()
+        }))
+        .unwrap();
+        let cases: [&[u8]; 4] = [b"", b"a", b"abcabcabcabcabcabc", &json];
+
+        for data in cases {
+            let compressed = compress(data);
+            assert_eq!(decompress(&compressed, data.len()), Ok(data.to_vec()));
+        }
+        assert!(compress(&json).len() < json.len() / 5);
+    }
+
+    #[test]
+    fn stored_and_dynamic_blocks() {
+        // Produced by zlib with raw deflate at level 0 and level 9
+        let stored = [0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o'];
+        let dynamic = [
+            0xb5, 0xca, 0x47, 0x01, 0x80, 0x30, 0x0c, 0x00, 0x40, 0x2b, 0x51, 0x80, 0x9a, 0x1a,
+            0xe8, 0x9e, 0x34, 0xdd, 0x4b, 0x3d, 0x18, 0xe0, 0xc9, 0xfb, 0x8e, 0x18, 0x09, 0xb9,
+            0x5b, 0xee, 0x81, 0x15, 0x9c, 0x11, 0x14, 0x2e, 0x70, 0xfd, 0x4e, 0x15, 0x70, 0xc8,
+            0x02, 0xed, 0xe5, 0x40, 0xcf, 0x06, 0x81, 0xfa, 0x02, 0xf2, 0x5b, 0xa6, 0x8c, 0x0b,
+            0xa9, 0xb4, 0xb1, 0xce, 0x87, 0x3b, 0x62, 0xca, 0xa5, 0xb6, 0x3e, 0xe6, 0xda, 0xe7,
+            0x5b, 0x1e,
+        ];
+        let mut text = b"The quick brown fox jumps over the lazy dog. ".repeat(3);
+        text.extend((b'a'..=b'z').chain(b'a'..=b'z'));
+
+        assert_eq!(decompress(&stored, 5), Ok(b"hello".to_vec()));
+        assert_eq!(decompress(&dynamic, text.len()), Ok(text));
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn invalid_compressed_data() {
+        let bomb = compress(&vec![b' '; 1024 * 1024]);
+        let truncated = compress(&b"abcabcabcabcabcabc".repeat(4));
+        let cases: [(&[u8], InflateError); 4] = [
+            (&bomb, InflateError::TooLarge),
+            (&[0x07], InflateError::Invalid),
+            (&[0x01, 0x05, 0x00, 0x00, 0x00], InflateError::Invalid),
+            (&truncated[..truncated.len() / 2], InflateError::Invalid),
+        ];
+        for (data, expected) in cases {
+            let result = decompress(data, 64 * 1024);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, expected);
+        }
+    }
+
+    // A fixed-seed stand-in for the fuzz target under fuzz/, so every test run covers a slice of
+    // arbitrary and mutated input
+    #[cfg(feature = "sign")]
+    #[test]
+    fn arbitrary_input_never_panics_or_exceeds_the_cap() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0x6a6c73);
+        let seed = compress(&b"feature-1:enabled,feature-2:disabled;".repeat(64));
+        for round in 0..20_000 {
+            let data: Vec<u8> = if round % 2 == 0 {
+                (0..rng.gen_range(0..256)).map(|_| rng.gen()).collect()
+            } else {
+                let mut mutated = seed.clone();
+                for _ in 0..rng.gen_range(1..8) {
+                    let position = rng.gen_range(0..mutated.len());
+                    mutated[position] ^= 1 << rng.gen_range(0..8);
+                }
+                mutated.truncate(rng.gen_range(1..=mutated.len()));
+                mutated
+            };
+            let max_size = rng.gen_range(0..4096);
+
+            if let Ok(inflated) = decompress(&data, max_size) {
+                assert!(inflated.len() <= max_size);
+            }
+        }
+    }
+}
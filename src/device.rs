@@ -0,0 +1,493 @@
+use crate::jws;
+use crate::store::{LicenseStore, LicenseStoreError};
+#[cfg(feature = "tpm")]
+use crate::tpm::{Tpm, TpmError, TpmSigningKey};
+use crate::verification::{LicenseVerification, LicenseVerificationError};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+#[cfg(feature = "ecdsa")]
+use p256::ecdsa::signature::Verifier as _;
+use rand::rngs::OsRng;
+use zeroize::Zeroizing;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeviceKeyError {
+    KeyIsNotJwk,
+    KeyTypeNotSupported,
+    InvalidToken,
+    VerificationFailure,
+    KeyIsNotPrivate,
+    Store(LicenseStoreError),
+    #[cfg(feature = "tpm")]
+    Tpm(TpmError),
+}
+
+pub struct DeviceKey {
+    key: DeviceSigningKey,
+}
+
+enum DeviceSigningKey {
+    // Zeroized on drop
+    Software(SigningKey),
+    #[cfg(feature = "tpm")]
+    Tpm(TpmSigningKey),
+}
+
+impl std::fmt::Debug for DeviceKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeviceKey")
+            .field("thumbprint", &self.thumbprint())
+            .finish_non_exhaustive()
+    }
+}
+
+impl DeviceKey {
+    pub fn generate() -> Self {
+        Self {
+            key: DeviceSigningKey::Software(SigningKey::generate(&mut OsRng)),
+        }
+    }
+
+    pub fn load_or_generate(store: &dyn LicenseStore) -> Result<Self, DeviceKeyError> {
+        match store.load() {
+            Ok(private_jwk) => Self::from_private_jwk(&private_jwk),
+            Err(LicenseStoreError::NotFound) => {
+                let signing_key = SigningKey::generate(&mut OsRng);
+                store
+                    .save(&private_jwk(&signing_key))
+                    .map_err(DeviceKeyError::Store)?;
+                Ok(Self {
+                    key: DeviceSigningKey::Software(signing_key),
+                })
+            }
+            Err(error) => Err(DeviceKeyError::Store(error)),
+        }
+    }
+
+    // The key is an ECDSA P-256 key whose private part never leaves the TPM; `store` only holds it
+    // wrapped by the TPM
+    #[cfg(feature = "tpm")]
+    pub fn load_or_generate_in_tpm(
+        tpm: &Tpm,
+        store: &dyn LicenseStore,
+    ) -> Result<Self, DeviceKeyError> {
+        let signing_key = match store.load() {
+            Ok(wrapped_key) => tpm
+                .load_signing_key(&wrapped_key)
+                .map_err(DeviceKeyError::Tpm)?,
+            Err(LicenseStoreError::NotFound) => {
+                let signing_key = tpm.generate_signing_key().map_err(DeviceKeyError::Tpm)?;
+                store
+                    .save(&signing_key.wrapped_key())
+                    .map_err(DeviceKeyError::Store)?;
+                signing_key
+            }
+            Err(error) => return Err(DeviceKeyError::Store(error)),
+        };
+        Ok(Self {
+            key: DeviceSigningKey::Tpm(signing_key),
+        })
+    }
+
+    // Falls back to a software key only when there is no TPM, like `TpmStoreSelection::select`
+    #[cfg(feature = "tpm")]
+    pub fn load_or_generate_preferring_tpm(
+        tpm: Result<Tpm, TpmError>,
+        store: &dyn LicenseStore,
+    ) -> Result<Self, DeviceKeyError> {
+        match tpm {
+            Ok(tpm) => Self::load_or_generate_in_tpm(&tpm, store),
+            Err(TpmError::NotPresent) => Self::load_or_generate(store),
+            Err(error) => Err(DeviceKeyError::Tpm(error)),
+        }
+    }
+
+    fn from_private_jwk(jwk: &serde_json::Value) -> Result<Self, DeviceKeyError> {
+        let public_key = DevicePublicKey::from_jwk(jwk)?;
+        let d = jwk
+            .get("d")
+            .and_then(|v| v.as_str())
+            .ok_or(DeviceKeyError::KeyIsNotPrivate)?;
+        let key_bytes = jws::decode(d)
+            .map(Zeroizing::new)
+            .ok_or(DeviceKeyError::KeyIsNotJwk)?;
+        let key_bytes: &[u8; 32] = key_bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| DeviceKeyError::KeyIsNotJwk)?;
+
+        let signing_key = SigningKey::from_bytes(key_bytes);
+        if public_key.key != DeviceVerifyingKey::Ed25519(signing_key.verifying_key()) {
+            return Err(DeviceKeyError::KeyIsNotJwk);
+        }
+        Ok(Self {
+            key: DeviceSigningKey::Software(signing_key),
+        })
+    }
+
+    pub fn public_key(&self) -> DevicePublicKey {
+        let key = match &self.key {
+            DeviceSigningKey::Software(signing_key) => {
+                DeviceVerifyingKey::Ed25519(signing_key.verifying_key())
+            }
+            #[cfg(feature = "tpm")]
+            DeviceSigningKey::Tpm(signing_key) => {
+                DeviceVerifyingKey::P256(*signing_key.verifying_key())
+            }
+        };
+        DevicePublicKey { key }
+    }
+
+    pub fn public_jwk(&self) -> serde_json::Value {
+        self.public_key().to_jwk()
+    }
+
+    pub fn thumbprint(&self) -> String {
+        self.public_key().thumbprint()
+    }
+
+    // Only a TPM-backed key can fail to sign, when the TPM does
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, DeviceKeyError> {
+        match &self.key {
+            DeviceSigningKey::Software(signing_key) => {
+                Ok(signing_key.sign(data).to_bytes().to_vec())
+            }
+            #[cfg(feature = "tpm")]
+            DeviceSigningKey::Tpm(signing_key) => {
+                signing_key.sign(data).map_err(DeviceKeyError::Tpm)
+            }
+        }
+    }
+
+    pub(crate) fn sign_token<T: serde::Serialize>(
+        &self,
+        typ: &str,
+        claims: &T,
+    ) -> Result<String, DeviceKeyError> {
+        let header = jws::Header::new(self.public_key().alg(), typ);
+        let signing_input = jws::signing_input(&header, claims);
+        let signature = self.sign(signing_input.as_bytes())?;
+        Ok(jws::compact(signing_input, &signature))
+    }
+}
+
+fn private_jwk(signing_key: &SigningKey) -> serde_json::Value {
+    let mut jwk = DevicePublicKey {
+        key: DeviceVerifyingKey::Ed25519(signing_key.verifying_key()),
+    }
+    .to_jwk();
+    let d = Zeroizing::new(jws::encode(signing_key.as_bytes()));
+    jwk["d"] = serde_json::Value::String(d.as_str().to_owned());
+    jwk
+}
+
+// Software device keys are Ed25519 (EdDSA tokens), TPM-backed ones P-256 (ES256 tokens)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DevicePublicKey {
+    key: DeviceVerifyingKey,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DeviceVerifyingKey {
+    Ed25519(VerifyingKey),
+    #[cfg(feature = "ecdsa")]
+    P256(p256::ecdsa::VerifyingKey),
+}
+
+impl DevicePublicKey {
+    pub fn from_jwk(jwk: &serde_json::Value) -> Result<Self, DeviceKeyError> {
+        let jwk = jwk.as_object().ok_or(DeviceKeyError::KeyIsNotJwk)?;
+        let kty = jwk.get("kty").and_then(|v| v.as_str());
+        let crv = jwk.get("crv").and_then(|v| v.as_str());
+        let coordinate = |name: &str| -> Result<[u8; 32], DeviceKeyError> {
+            jwk.get(name)
+                .and_then(|v| v.as_str())
+                .and_then(jws::decode)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or(DeviceKeyError::KeyIsNotJwk)
+        };
+
+        let key = match (kty, crv) {
+            (Some("OKP"), Some("Ed25519")) => VerifyingKey::from_bytes(&coordinate("x")?)
+                .map(DeviceVerifyingKey::Ed25519)
+                .map_err(|_| DeviceKeyError::KeyTypeNotSupported)?,
+            #[cfg(feature = "ecdsa")]
+            (Some("EC"), Some("P-256")) => {
+                let mut point = vec![0x04];
+                point.extend_from_slice(&coordinate("x")?);
+                point.extend_from_slice(&coordinate("y")?);
+                p256::ecdsa::VerifyingKey::from_sec1_bytes(&point)
+                    .map(DeviceVerifyingKey::P256)
+                    .map_err(|_| DeviceKeyError::KeyTypeNotSupported)?
+            }
+            _ => return Err(DeviceKeyError::KeyTypeNotSupported),
+        };
+        Ok(Self { key })
+    }
+
+    pub fn to_jwk(&self) -> serde_json::Value {
+        match &self.key {
+            DeviceVerifyingKey::Ed25519(verifying_key) => serde_json::json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": jws::encode(verifying_key.as_bytes()),
+            }),
+            #[cfg(feature = "ecdsa")]
+            DeviceVerifyingKey::P256(verifying_key) => {
+                let (x, y) = ec_coordinates(verifying_key);
+                serde_json::json!({
+                    "kty": "EC",
+                    "crv": "P-256",
+                    "x": x,
+                    "y": y,
+                })
+            }
+        }
+    }
+
+    pub fn thumbprint(&self) -> String {
+        match &self.key {
+            DeviceVerifyingKey::Ed25519(verifying_key) => {
+                let x = jws::encode(verifying_key.as_bytes());
+                jws::thumbprint(&[("crv", "Ed25519"), ("kty", "OKP"), ("x", &x)])
+            }
+            #[cfg(feature = "ecdsa")]
+            DeviceVerifyingKey::P256(verifying_key) => {
+                let (x, y) = ec_coordinates(verifying_key);
+                jws::thumbprint(&[("crv", "P-256"), ("kty", "EC"), ("x", &x), ("y", &y)])
+            }
+        }
+    }
+
+    // The `alg` header value of the tokens the key signs
+    pub fn alg(&self) -> &'static str {
+        match &self.key {
+            DeviceVerifyingKey::Ed25519(_) => "EdDSA",
+            #[cfg(feature = "ecdsa")]
+            DeviceVerifyingKey::P256(_) => "ES256",
+        }
+    }
+
+    pub fn verify(&self, data: &[u8], signature: &[u8]) -> Result<(), DeviceKeyError> {
+        let verified = match &self.key {
+            DeviceVerifyingKey::Ed25519(verifying_key) => Signature::from_slice(signature)
+                .is_ok_and(|signature| verifying_key.verify_strict(data, &signature).is_ok()),
+            #[cfg(feature = "ecdsa")]
+            DeviceVerifyingKey::P256(verifying_key) => {
+                p256::ecdsa::Signature::from_slice(signature)
+                    .is_ok_and(|signature| verifying_key.verify(data, &signature).is_ok())
+            }
+        };
+        if !verified {
+            return Err(DeviceKeyError::VerificationFailure);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn verify_token<T: serde::de::DeserializeOwned>(
+        &self,
+        token: &str,
+        typ: &str,
+    ) -> Result<T, DeviceKeyError> {
+        let compact = jws::parse_compact(token).ok_or(DeviceKeyError::InvalidToken)?;
+        if compact.header.alg != self.alg() || compact.header.typ.as_deref() != Some(typ) {
+            return Err(DeviceKeyError::InvalidToken);
+        }
+
+        self.verify(compact.signing_input.as_bytes(), &compact.signature)?;
+
+        serde_json::from_slice(&compact.payload).map_err(|_| DeviceKeyError::InvalidToken)
+    }
+
+    // Failures reach the verifier's audit sink and are hidden in opaque mode, like those of the
+    // verifier's own tokens
+    pub(crate) fn verify_token_reported<T: serde::de::DeserializeOwned>(
+        &self,
+        verifier: &dyn LicenseVerification,
+        token: &str,
+        typ: &str,
+    ) -> Result<T, LicenseVerificationError> {
+        self.verify_token(token, typ).map_err(|error| {
+            verifier.report_failure(match error {
+                DeviceKeyError::VerificationFailure => {
+                    LicenseVerificationError::VerificationFailure
+                }
+                _ => LicenseVerificationError::InvalidVerifiableLicense,
+            })
+        })
+    }
+}
+
+#[cfg(feature = "ecdsa")]
+fn ec_coordinates(verifying_key: &p256::ecdsa::VerifyingKey) -> (String, String) {
+    let point = verifying_key.to_encoded_point(false);
+    let x = point
+        .x()
+        .expect("Uncompressed points carry both coordinates");
+    let y = point
+        .y()
+        .expect("Uncompressed points carry both coordinates");
+    (jws::encode(x), jws::encode(y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::{self, Duration};
+    use crate::store::MemoryLicenseStore;
+    use crate::test_keys::ISSUER_PUBLIC_KEY_JWK_JSON;
+    use crate::usage::{verify_usage_report, UsageReport};
+    use crate::verification::LicenseVerifier;
+    use crate::License;
+
+    #[test]
+    fn device_key_signature_verifies_with_exported_jwk() {
+        let device_key = DeviceKey::generate();
+        let signature = device_key.sign(b"some data").unwrap();
+
+        let public_key = DevicePublicKey::from_jwk(&device_key.public_jwk())
+            .expect("Exported JWK should be importable");
+
+        assert_eq!(public_key.verify(b"some data", &signature), Ok(()));
+        assert_eq!(
+            public_key.verify(b"other data", &signature),
+            Err(DeviceKeyError::VerificationFailure)
+        );
+        assert_eq!(public_key.thumbprint(), device_key.thumbprint());
+    }
+
+    #[test]
+    fn device_public_key_with_non_okp_key() {
+        let rsa_key = serde_json::json!({
+            "kty": "RSA",
+            "x": "AQAB",
+        });
+
+        let result = DevicePublicKey::from_jwk(&rsa_key);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, DeviceKeyError::KeyTypeNotSupported);
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn p256_device_public_key_verifies_es256_tokens() {
+        use crate::test_keys::{P256_PRIVATE_KEY_JWK_JSON, P256_PUBLIC_KEY_JWK_JSON};
+
+        let public_key = DevicePublicKey::from_jwk(&P256_PUBLIC_KEY_JWK_JSON)
+            .expect("P-256 JWKs should be importable");
+        assert_eq!(public_key.alg(), "ES256");
+        assert_eq!(
+            DevicePublicKey::from_jwk(&public_key.to_jwk()),
+            Ok(public_key.clone())
+        );
+
+        let d = jws::decode(P256_PRIVATE_KEY_JWK_JSON["d"].as_str().unwrap()).unwrap();
+        let signing_key = p256::ecdsa::SigningKey::from_slice(&d).unwrap();
+        let claims = serde_json::json!({ "sequence": 1 });
+        let token_with_alg = |alg: &str| {
+            let header = jws::Header::new(alg, "test+jwt");
+            let signing_input = jws::signing_input(&header, &claims);
+            let signature: p256::ecdsa::Signature =
+                p256::ecdsa::signature::Signer::sign(&signing_key, signing_input.as_bytes());
+            jws::compact(signing_input, &signature.to_bytes())
+        };
+
+        assert_eq!(
+            public_key.verify_token::<serde_json::Value>(&token_with_alg("ES256"), "test+jwt"),
+            Ok(claims.clone())
+        );
+        assert_eq!(
+            public_key.verify_token::<serde_json::Value>(&token_with_alg("EdDSA"), "test+jwt"),
+            Err(DeviceKeyError::InvalidToken)
+        );
+    }
+
+    #[cfg(feature = "tpm")]
+    #[test]
+    fn missing_tpm_falls_back_to_a_software_key() {
+        let store = MemoryLicenseStore::new();
+        let device_key =
+            DeviceKey::load_or_generate_preferring_tpm(Err(TpmError::NotPresent), &store)
+                .expect("A missing TPM should fall back");
+        assert_eq!(device_key.public_key().alg(), "EdDSA");
+        assert_eq!(
+            DeviceKey::load_or_generate(&store).unwrap().thumbprint(),
+            device_key.thumbprint()
+        );
+
+        let result = DeviceKey::load_or_generate_preferring_tpm(
+            Err(TpmError::Backend("TPM_RC_LOCKOUT".to_owned())),
+            &store,
+        );
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            DeviceKeyError::Tpm(TpmError::Backend("TPM_RC_LOCKOUT".to_owned()))
+        );
+    }
+
+    #[test]
+    fn device_key_persisted_and_reloaded() {
+        let store = MemoryLicenseStore::new();
+
+        let device_key = DeviceKey::load_or_generate(&store).expect("Generation should work");
+        let reloaded_device_key = DeviceKey::load_or_generate(&store).expect("Reload should work");
+
+        assert_eq!(reloaded_device_key.public_jwk(), device_key.public_jwk());
+    }
+
+    #[test]
+    fn reloaded_device_key_proves_possession() {
+        let store = MemoryLicenseStore::new();
+        let device_key = DeviceKey::load_or_generate(&store).unwrap();
+        let mut license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": {}
+        }))
+        .unwrap();
+        license.device_key = Some(device_key.public_jwk());
+        drop(device_key);
+
+        let reloaded_device_key = DeviceKey::load_or_generate(&store).unwrap();
+        let period_end = datetime::now();
+        let signed_report = UsageReport::new(
+            &license,
+            period_end - Duration::days(1),
+            period_end,
+            &reloaded_device_key,
+        )
+        .sign(&reloaded_device_key)
+        .unwrap();
+
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone()).unwrap();
+        assert!(verify_usage_report(&verifier, &signed_report, &license).is_ok());
+    }
+
+    #[test]
+    fn device_key_debug_hides_private_material() {
+        let store = MemoryLicenseStore::new();
+        let device_key = DeviceKey::load_or_generate(&store).unwrap();
+        let d = store.load().unwrap()["d"].as_str().unwrap().to_owned();
+
+        let debug_output = format!("{:?}", device_key);
+
+        assert!(debug_output.contains(&device_key.thumbprint()));
+        assert!(!debug_output.contains(&d));
+    }
+
+    #[test]
+    fn device_key_load_from_public_jwk() {
+        let store = MemoryLicenseStore::new();
+        store.save(&DeviceKey::generate().public_jwk()).unwrap();
+
+        let result = DeviceKey::load_or_generate(&store);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, DeviceKeyError::KeyIsNotPrivate);
+    }
+}
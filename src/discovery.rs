@@ -0,0 +1,518 @@
+use crate::clock::{saturating_add, Clock, SystemClock};
+use crate::datetime::{DateTime, Duration};
+use crate::id::LicenseId;
+use crate::trust_bundle::{TrustBundle, TrustBundleError};
+use crate::verification::{LicenseVerifier, PublicKey, RevocationProvider};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+pub const DISCOVERY_PATH: &str = "/.well-known/jls-issuer";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiscoveryError {
+    Unreachable(String),
+    InsecureUrl(String),
+    MalformedDocument,
+    IssuerMismatch { expected: String, got: String },
+    UnsupportedAlgorithms,
+    InvalidKeySet(TrustBundleError),
+}
+
+// Transport used to retrieve discovery, JWKS and revocation documents. `HttpFetcher` is the one
+// shipped with the `http` feature.
+pub trait DocumentFetcher: Send + Sync {
+    fn fetch(&self, url: &str) -> Result<serde_json::Value, DiscoveryError>;
+}
+
+impl<T: DocumentFetcher + ?Sized> DocumentFetcher for &T {
+    fn fetch(&self, url: &str) -> Result<serde_json::Value, DiscoveryError> {
+        (**self).fetch(url)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct IssuerMetadata {
+    pub issuer: String,
+    pub jwks_uri: String,
+    pub algorithms_supported: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub revocation_endpoint: Option<String>,
+}
+
+impl IssuerMetadata {
+    pub fn discover(base_url: &str, fetcher: &dyn DocumentFetcher) -> Result<Self, DiscoveryError> {
+        let base_url = https_url(base_url)?.trim_end_matches('/');
+        let document = fetcher.fetch(&format!("{}{}", base_url, DISCOVERY_PATH))?;
+        Self::from_json(base_url, document)
+    }
+
+    pub fn from_json(base_url: &str, document: serde_json::Value) -> Result<Self, DiscoveryError> {
+        let metadata: IssuerMetadata =
+            serde_json::from_value(document).map_err(|_| DiscoveryError::MalformedDocument)?;
+
+        // A document served for another issuer must not be able to redirect key lookups
+        let expected = base_url.trim_end_matches('/');
+        if metadata.issuer.trim_end_matches('/') != expected {
+            return Err(DiscoveryError::IssuerMismatch {
+                expected: expected.to_owned(),
+                got: metadata.issuer,
+            });
+        }
+        https_url(&metadata.issuer)?;
+        https_url(&metadata.jwks_uri)?;
+        if let Some(revocation_endpoint) = &metadata.revocation_endpoint {
+            https_url(revocation_endpoint)?;
+        }
+        if !metadata
+            .algorithms_supported
+            .iter()
+            .any(|alg| PublicKey::is_supported_alg(alg))
+        {
+            return Err(DiscoveryError::UnsupportedAlgorithms);
+        }
+        Ok(metadata)
+    }
+}
+
+fn https_url(url: &str) -> Result<&str, DiscoveryError> {
+    let is_https = url
+        .get(..8)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"))
+        && url.len() > 8;
+    if !is_https {
+        return Err(DiscoveryError::InsecureUrl(url.to_owned()));
+    }
+    Ok(url)
+}
+
+#[cfg(feature = "http")]
+pub struct HttpFetcher {
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "http")]
+impl HttpFetcher {
+    pub fn new() -> Self {
+        // Redirects are followed, but never to a plain HTTP location
+        let agent = ureq::AgentBuilder::new()
+            .https_only(true)
+            .timeout(std::time::Duration::from_secs(10))
+            .build();
+        Self { agent }
+    }
+}
+
+#[cfg(feature = "http")]
+impl Default for HttpFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "http")]
+impl DocumentFetcher for HttpFetcher {
+    fn fetch(&self, url: &str) -> Result<serde_json::Value, DiscoveryError> {
+        let response = self
+            .agent
+            .get(https_url(url)?)
+            .call()
+            .map_err(|_| DiscoveryError::Unreachable(url.to_owned()))?;
+        response
+            .into_json()
+            .map_err(|_| DiscoveryError::MalformedDocument)
+    }
+}
+
+// The document at the issuer's revocation endpoint, `{"revoked": [<license id>, ...]}`. A list
+// that cannot be fetched or read revokes every license, so blocking the endpoint does not bring
+// revoked licenses back.
+pub struct RemoteRevocationList {
+    endpoint: String,
+    fetcher: Arc<dyn DocumentFetcher>,
+}
+
+impl RemoteRevocationList {
+    pub fn new(endpoint: &str, fetcher: Arc<dyn DocumentFetcher>) -> Result<Self, DiscoveryError> {
+        Ok(Self {
+            endpoint: https_url(endpoint)?.to_owned(),
+            fetcher,
+        })
+    }
+}
+
+impl RevocationProvider for RemoteRevocationList {
+    fn is_revoked(&self, license_id: &LicenseId) -> bool {
+        let Ok(document) = self.fetcher.fetch(&self.endpoint) else {
+            return true;
+        };
+        let Some(revoked) = document.get("revoked").and_then(|v| v.as_array()) else {
+            return true;
+        };
+        revoked.iter().any(|revoked_id| {
+            serde_json::from_value::<LicenseId>(revoked_id.clone())
+                .is_ok_and(|revoked_id| revoked_id == *license_id)
+        })
+    }
+}
+
+#[cfg(feature = "http")]
+impl LicenseVerifier {
+    // Discovery, key set and revocation list documents are fetched over HTTPS and cached for five
+    // minutes
+    pub fn discover(base_url: &str) -> Result<Self, DiscoveryError> {
+        let fetcher: Arc<dyn DocumentFetcher> = Arc::new(CachingFetcher::new(
+            HttpFetcher::new(),
+            Duration::minutes(5),
+        ));
+        let metadata = IssuerMetadata::discover(base_url, &*fetcher)?;
+        Self::from_discovery(&metadata, fetcher)
+    }
+}
+
+impl LicenseVerifier {
+    // Keys come from the issuer's JWKS; verified licenses must name the discovered issuer and, when
+    // the issuer publishes a revocation endpoint, must not be on its list. The fetcher is kept to
+    // retrieve that list on every verification.
+    pub fn from_discovery(
+        metadata: &IssuerMetadata,
+        fetcher: Arc<dyn DocumentFetcher>,
+    ) -> Result<Self, DiscoveryError> {
+        let jwks = fetcher.fetch(&metadata.jwks_uri)?;
+        let keys = jwks
+            .get("keys")
+            .filter(|keys| keys.is_array())
+            .ok_or(DiscoveryError::MalformedDocument)?;
+        let bundle = TrustBundle::from_json(&serde_json::json!({
+            "version": 1,
+            "issuers": [{
+                "name": metadata.issuer,
+                "keys": keys,
+                "algorithms": metadata.algorithms_supported,
+            }]
+        }))
+        .map_err(DiscoveryError::InvalidKeySet)?;
+
+        let mut builder = LicenseVerifier::builder()
+            .trust_bundle(bundle)
+            .expected_issuer(&metadata.issuer);
+        if let Some(revocation_endpoint) = &metadata.revocation_endpoint {
+            builder = builder.revocation_provider(Arc::new(RemoteRevocationList::new(
+                revocation_endpoint,
+                fetcher,
+            )?));
+        }
+        builder
+            .build()
+            .map_err(|_| DiscoveryError::MalformedDocument)
+    }
+}
+
+pub struct CachingFetcher<F> {
+    inner: F,
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+    documents: Mutex<BTreeMap<String, (DateTime, serde_json::Value)>>,
+}
+
+impl<F: DocumentFetcher> CachingFetcher<F> {
+    pub fn new(inner: F, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            clock: Arc::new(SystemClock),
+            documents: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+impl<F: DocumentFetcher> DocumentFetcher for CachingFetcher<F> {
+    fn fetch(&self, url: &str) -> Result<serde_json::Value, DiscoveryError> {
+        let now = self.clock.now();
+        let mut documents = self.documents.lock().unwrap();
+        if let Some((fetched_at, document)) = documents.get(url) {
+            if now < saturating_add(*fetched_at, self.ttl) {
+                return Ok(document.clone());
+            }
+        }
+
+        let document = self.inner.fetch(url)?;
+        documents.insert(url.to_owned(), (now, document.clone()));
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "sign")]
+    use crate::signing::LicenseSigner;
+    use crate::test_keys::ISSUER_PUBLIC_KEY_JWK_JSON;
+    #[cfg(feature = "sign")]
+    use crate::test_keys::{ISSUER_PRIVATE_KEY_JWK_JSON, OTHER_PUBLIC_KEY_JWK_JSON};
+    use crate::test_util::FixedClock;
+    #[cfg(feature = "sign")]
+    use crate::verification::LicenseVerificationError;
+    #[cfg(feature = "sign")]
+    use crate::License;
+
+    const BASE_URL: &str = "https://licensing.acme.example";
+
+    struct MockServer {
+        documents: Mutex<BTreeMap<String, serde_json::Value>>,
+        requests: Mutex<Vec<String>>,
+    }
+
+    impl MockServer {
+        fn new(jwk: &serde_json::Value) -> Self {
+            let mut jwk = jwk.clone();
+            jwk["kid"] = serde_json::json!("issuer-2024");
+            let documents = BTreeMap::from([
+                (
+                    format!("{}{}", BASE_URL, DISCOVERY_PATH),
+                    serde_json::json!({
+                        "issuer": BASE_URL,
+                        "jwks_uri": format!("{}/jwks.json", BASE_URL),
+                        "algorithms_supported": ["RS256", "RS512"],
+                        "revocation_endpoint": format!("{}/revoked", BASE_URL),
+                    }),
+                ),
+                (
+                    format!("{}/jwks.json", BASE_URL),
+                    serde_json::json!({ "keys": [jwk] }),
+                ),
+                (
+                    format!("{}/revoked", BASE_URL),
+                    serde_json::json!({ "revoked": ["ACME-2023-00481"] }),
+                ),
+            ]);
+            Self {
+                documents: Mutex::new(documents),
+                requests: Mutex::new(vec![]),
+            }
+        }
+
+        fn serve(&self, url: &str, document: serde_json::Value) {
+            self.documents
+                .lock()
+                .unwrap()
+                .insert(url.to_owned(), document);
+        }
+    }
+
+    impl DocumentFetcher for MockServer {
+        fn fetch(&self, url: &str) -> Result<serde_json::Value, DiscoveryError> {
+            self.requests.lock().unwrap().push(url.to_owned());
+            self.documents
+                .lock()
+                .unwrap()
+                .get(url)
+                .cloned()
+                .ok_or_else(|| DiscoveryError::Unreachable(url.to_owned()))
+        }
+    }
+
+    #[cfg(feature = "sign")]
+    fn signed_license(issuer: &str) -> serde_json::Value {
+        let license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "issuer": issuer,
+            "customData": { "owner": "John Doe" }
+        }))
+        .unwrap();
+        LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .with_key_id("issuer-2024")
+            .sign(&license)
+            .unwrap()
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn verify_with_discovered_keys() {
+        let server = Arc::new(MockServer::new(&ISSUER_PUBLIC_KEY_JWK_JSON));
+
+        let metadata = IssuerMetadata::discover(&format!("{}/", BASE_URL), &*server)
+            .expect("Discovery should work");
+        assert_eq!(
+            metadata.revocation_endpoint.as_deref(),
+            Some("https://licensing.acme.example/revoked")
+        );
+        let verifier = LicenseVerifier::from_discovery(&metadata, server.clone())
+            .expect("Key retrieval should work");
+
+        let license = verifier
+            .verify(signed_license(BASE_URL))
+            .expect("Verification should succeed");
+        assert_eq!(license.issuer.as_deref(), Some(BASE_URL));
+
+        let result = verifier.verify(signed_license("https://evil.example"));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::IssuerMismatch {
+                expected: BASE_URL.to_owned(),
+                got: Some("https://evil.example".to_owned()),
+            }
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn tampered_key_set_rejects_licenses() {
+        let server = Arc::new(MockServer::new(&OTHER_PUBLIC_KEY_JWK_JSON));
+
+        let metadata = IssuerMetadata::discover(BASE_URL, &*server).unwrap();
+        let verifier = LicenseVerifier::from_discovery(&metadata, server).unwrap();
+
+        let result = verifier.verify(signed_license(BASE_URL));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::VerificationFailure);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn discovered_revocation_list_is_checked() {
+        let server = Arc::new(MockServer::new(&ISSUER_PUBLIC_KEY_JWK_JSON));
+        let metadata = IssuerMetadata::discover(BASE_URL, &*server).unwrap();
+        let verifier = LicenseVerifier::from_discovery(&metadata, server.clone()).unwrap();
+        let revocation_url = format!("{}/revoked", BASE_URL);
+
+        verifier
+            .verify(signed_license(BASE_URL))
+            .expect("Verification should succeed");
+
+        let revoked = [
+            serde_json::json!({ "revoked": ["ACME-2023-00481", "0b5b88f5-a264-4f90-8406-50b01d9515c8"] }),
+            serde_json::json!({ "revoked": "0b5b88f5-a264-4f90-8406-50b01d9515c8" }),
+            serde_json::json!({}),
+        ];
+        for document in revoked {
+            server.serve(&revocation_url, document);
+
+            let result = verifier.verify(signed_license(BASE_URL));
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::LicenseRevoked);
+        }
+
+        server.documents.lock().unwrap().remove(&revocation_url);
+        let result = verifier.verify(signed_license(BASE_URL));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::LicenseRevoked);
+    }
+
+    #[test]
+    fn invalid_discovery_documents() {
+        let discovery_url = format!("{}{}", BASE_URL, DISCOVERY_PATH);
+        let cases = [
+            (
+                serde_json::json!({ "issuer": BASE_URL }),
+                DiscoveryError::MalformedDocument,
+            ),
+            (
+                serde_json::json!({
+                    "issuer": "https://evil.example",
+                    "jwks_uri": "https://evil.example/jwks.json",
+                    "algorithms_supported": ["RS512"],
+                }),
+                DiscoveryError::IssuerMismatch {
+                    expected: BASE_URL.to_owned(),
+                    got: "https://evil.example".to_owned(),
+                },
+            ),
+            (
+                serde_json::json!({
+                    "issuer": BASE_URL,
+                    "jwks_uri": "http://licensing.acme.example/jwks.json",
+                    "algorithms_supported": ["RS512"],
+                }),
+                DiscoveryError::InsecureUrl("http://licensing.acme.example/jwks.json".to_owned()),
+            ),
+            (
+                serde_json::json!({
+                    "issuer": BASE_URL,
+                    "jwks_uri": format!("{}/jwks.json", BASE_URL),
+                    "algorithms_supported": ["ES512"],
+                }),
+                DiscoveryError::UnsupportedAlgorithms,
+            ),
+        ];
+        for (document, expected) in cases {
+            let server = MockServer::new(&ISSUER_PUBLIC_KEY_JWK_JSON);
+            server.serve(&discovery_url, document);
+
+            let result = IssuerMetadata::discover(BASE_URL, &server);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, expected);
+        }
+
+        let server = MockServer::new(&ISSUER_PUBLIC_KEY_JWK_JSON);
+        let result = IssuerMetadata::discover("http://licensing.acme.example", &server);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            DiscoveryError::InsecureUrl("http://licensing.acme.example".to_owned())
+        );
+        assert!(server.requests.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn insecure_revocation_endpoint() {
+        let server = Arc::new(MockServer::new(&ISSUER_PUBLIC_KEY_JWK_JSON));
+
+        let result = RemoteRevocationList::new("http://licensing.acme.example/revoked", server);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            DiscoveryError::InsecureUrl("http://licensing.acme.example/revoked".to_owned())
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn http_fetcher_refuses_plain_http() {
+        let result = HttpFetcher::new().fetch("http://127.0.0.1:1/.well-known/jls-issuer");
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            DiscoveryError::InsecureUrl("http://127.0.0.1:1/.well-known/jls-issuer".to_owned())
+        );
+    }
+
+    #[test]
+    fn cached_documents_expire() {
+        let server = MockServer::new(&ISSUER_PUBLIC_KEY_JWK_JSON);
+        let clock = Arc::new(FixedClock::at("2024-09-01T00:00:00Z"));
+        let fetcher = CachingFetcher::new(&server, Duration::hours(1)).clock(clock.clone());
+
+        IssuerMetadata::discover(BASE_URL, &fetcher).unwrap();
+        IssuerMetadata::discover(BASE_URL, &fetcher).unwrap();
+        assert_eq!(server.requests.lock().unwrap().len(), 1);
+
+        clock.advance(Duration::hours(1));
+        IssuerMetadata::discover(BASE_URL, &fetcher).unwrap();
+        assert_eq!(server.requests.lock().unwrap().len(), 2);
+    }
+}
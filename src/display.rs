@@ -0,0 +1,281 @@
+use crate::datetime::{self, DateTime, Duration};
+use crate::id::LicenseId;
+use crate::License;
+use std::fmt;
+
+const OWNER_HINT_FIELDS: [&str; 2] = ["owner", "licensee"];
+
+pub struct LicenseDisplay<'a> {
+    license: &'a License,
+    now: DateTime,
+}
+
+impl License {
+    pub fn display_at(&self, now: DateTime) -> LicenseDisplay<'_> {
+        LicenseDisplay { license: self, now }
+    }
+
+    pub fn humanize_remaining(&self, now: DateTime) -> String {
+        let expiration_date = self.expiration_date;
+        if self.is_perpetual() {
+            "never expires".to_owned()
+        } else if expiration_date > now {
+            format!("expires in {}", humanize(expiration_date - now))
+        } else {
+            format!("expired {} ago", humanize(now - expiration_date))
+        }
+    }
+}
+
+impl fmt::Display for License {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display_at(datetime::now()).fmt(f)
+    }
+}
+
+impl fmt::Display for LicenseDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let license = self.license;
+        match &license.id {
+            LicenseId::Uuid(uuid) => write!(f, "License {}…", &uuid.simple().to_string()[..8])?,
+            LicenseId::Opaque(id) => write!(f, "License {}", id)?,
+        }
+
+        let owner_hint = OWNER_HINT_FIELDS.iter().find_map(|&field| {
+            let value = license.custom_data.get(field)?.as_str()?;
+            Some((field, value))
+        });
+        if let Some((field, value)) = owner_hint {
+            write!(f, " ({}: {})", field, value)?;
+        }
+
+        let expiration_date = license.expiration_date;
+        if license.is_perpetual() {
+            write!(f, " never expires")?;
+        } else if expiration_date > self.now {
+            write!(
+                f,
+                " expires {} (in {})",
+                datetime::date(expiration_date),
+                humanize(expiration_date - self.now)
+            )?;
+        } else {
+            write!(
+                f,
+                " expired {} ({} ago)",
+                datetime::date(expiration_date),
+                humanize(self.now - expiration_date)
+            )?;
+        }
+
+        let features = license
+            .feature_statuses_at(self.now)
+            .into_iter()
+            .map(|status| match status.expires {
+                None => status.name,
+                Some(expires) if status.active => {
+                    format!("{} (until {})", status.name, datetime::date(expires))
+                }
+                Some(expires) => {
+                    format!("{} (expired {})", status.name, datetime::date(expires))
+                }
+            })
+            .collect::<Vec<_>>();
+        if !features.is_empty() {
+            write!(f, "; features: {}", features.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn humanize(duration: Duration) -> String {
+    const DAY: i64 = 86_400;
+    let seconds = datetime::whole_seconds(duration);
+    let (amount, unit) = if duration < Duration::minutes(1) {
+        return "less than a minute".to_owned();
+    } else if duration < Duration::hours(1) {
+        (seconds / 60, "minute")
+    } else if duration < Duration::days(1) {
+        (seconds / 3600, "hour")
+    } else if duration < Duration::days(14) {
+        (seconds / DAY, "day")
+    } else if duration < Duration::weeks(8) {
+        (seconds / (7 * DAY), "week")
+    } else if duration < Duration::days(365) {
+        (seconds / DAY / 30, "month")
+    } else {
+        (seconds / DAY / 365, "year")
+    };
+
+    if amount == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", amount, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::Feature;
+
+    const PERPETUAL: &str = "9999-12-31T23:59:59Z";
+
+    fn license(expiration_date: &str, custom_data: serde_json::Value) -> License {
+        serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": expiration_date,
+            "customData": custom_data,
+        }))
+        .unwrap()
+    }
+
+    fn expiring_license() -> License {
+        license(
+            "2024-10-01T00:00:00Z",
+            serde_json::json!({ "owner": "John Doe" }),
+        )
+    }
+
+    fn expiration_date() -> DateTime {
+        datetime::parse("2024-10-01T00:00:00Z").unwrap()
+    }
+
+    #[test]
+    fn humanize_remaining_for_future_expiration() {
+        let cases = [
+            (Duration::seconds(30), "expires in less than a minute"),
+            (Duration::minutes(1), "expires in 1 minute"),
+            (Duration::minutes(45), "expires in 45 minutes"),
+            (Duration::hours(5), "expires in 5 hours"),
+            (Duration::days(3), "expires in 3 days"),
+            (Duration::days(42), "expires in 6 weeks"),
+            (Duration::days(200), "expires in 6 months"),
+            (Duration::days(365 * 3 + 10), "expires in 3 years"),
+        ];
+
+        for (remaining, expected) in cases {
+            let now = expiration_date() - remaining;
+            assert_eq!(expiring_license().humanize_remaining(now), expected);
+        }
+    }
+
+    #[test]
+    fn humanize_remaining_for_past_expiration() {
+        let cases = [
+            (Duration::minutes(10), "expired 10 minutes ago"),
+            (Duration::hours(1), "expired 1 hour ago"),
+            (Duration::days(15), "expired 2 weeks ago"),
+            (Duration::days(365 * 12), "expired 12 years ago"),
+        ];
+
+        for (elapsed, expected) in cases {
+            let now = expiration_date() + elapsed;
+            assert_eq!(expiring_license().humanize_remaining(now), expected);
+        }
+    }
+
+    #[test]
+    fn humanize_remaining_for_perpetual_license() {
+        let license = license(PERPETUAL, serde_json::json!({}));
+        assert_eq!(license.humanize_remaining(datetime::now()), "never expires");
+    }
+
+    #[test]
+    fn display_license() {
+        let now = expiration_date() - Duration::days(42);
+        assert_eq!(
+            expiring_license().display_at(now).to_string(),
+            "License 0b5b88f5… (owner: John Doe) expires 2024-10-01 (in 6 weeks)"
+        );
+
+        let now = expiration_date() + Duration::days(3);
+        assert_eq!(
+            expiring_license().display_at(now).to_string(),
+            "License 0b5b88f5… (owner: John Doe) expired 2024-10-01 (3 days ago)"
+        );
+    }
+
+    #[test]
+    fn display_license_without_owner_hides_custom_data() {
+        let licensee_license = license(
+            PERPETUAL,
+            serde_json::json!({ "tier": "gold", "price": 1200, "licensee": "ACME Corp" }),
+        );
+        assert_eq!(
+            licensee_license.to_string(),
+            "License 0b5b88f5… (licensee: ACME Corp) never expires"
+        );
+
+        let anonymous_license = license(PERPETUAL, serde_json::json!({ "tier": "gold" }));
+        assert_eq!(
+            anonymous_license.to_string(),
+            "License 0b5b88f5… never expires"
+        );
+    }
+
+    #[test]
+    fn display_license_with_expiring_features() {
+        let mut license = expiring_license();
+        license.features = vec![
+            "base".into(),
+            Feature::expiring(
+                "premium-support",
+                datetime::parse("2024-06-01T00:00:00Z").unwrap(),
+            ),
+        ];
+
+        let now = datetime::parse("2024-05-01T00:00:00Z").unwrap();
+        assert!(license
+            .display_at(now)
+            .to_string()
+            .ends_with("; features: base, premium-support (until 2024-06-01)"));
+
+        let now = datetime::parse("2024-07-01T00:00:00Z").unwrap();
+        assert!(license
+            .display_at(now)
+            .to_string()
+            .ends_with("; features: base, premium-support (expired 2024-06-01)"));
+    }
+
+    #[test]
+    fn display_license_with_opaque_id() {
+        let mut license = license(PERPETUAL, serde_json::json!({ "owner": "John Doe" }));
+        license.id = "ACME-2023-00481".into();
+        assert_eq!(
+            license.to_string(),
+            "License ACME-2023-00481 (owner: John Doe) never expires"
+        );
+    }
+
+    // chrono covers years -262143 to +262142, time only -9999 to 9999
+    #[cfg(feature = "chrono")]
+    const EXTREMES: [&str; 3] = [
+        "expires in 524634 years",
+        "expires +262142-12-31 (in 524634 years)",
+        "expired 252310 years ago",
+    ];
+    #[cfg(not(feature = "chrono"))]
+    const EXTREMES: [&str; 3] = [
+        "expires in 20012 years",
+        "expires 9999-12-31 (in 20012 years)",
+        "expired 23 hours ago",
+    ];
+
+    #[test]
+    fn display_license_with_extreme_dates() {
+        let mut license = expiring_license();
+        license.expiration_date = datetime::max();
+        let now = datetime::min();
+        assert_eq!(license.humanize_remaining(now), EXTREMES[0]);
+        assert!(license.display_at(now).to_string().ends_with(EXTREMES[1]));
+
+        license.expiration_date = datetime::parse("9999-12-31T00:00:00Z").unwrap();
+        assert_eq!(
+            license.humanize_remaining(expiration_date()),
+            "expires in 7980 years"
+        );
+        assert_eq!(license.humanize_remaining(datetime::max()), EXTREMES[2]);
+    }
+}
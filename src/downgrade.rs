@@ -0,0 +1,60 @@
+use crate::License;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectiveEntitlements {
+    pub features: Vec<String>,
+    pub degraded: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DowngradePolicy {
+    by_kind: BTreeMap<String, Vec<String>>,
+    by_product: BTreeMap<String, Vec<String>>,
+    fallback: Option<Vec<String>>,
+}
+
+impl DowngradePolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn kind(mut self, kind: &str, features: &[&str]) -> Self {
+        self.by_kind.insert(kind.to_owned(), to_owned(features));
+        self
+    }
+
+    pub fn product(mut self, product: &str, features: &[&str]) -> Self {
+        self.by_product
+            .insert(product.to_owned(), to_owned(features));
+        self
+    }
+
+    pub fn fallback(mut self, features: &[&str]) -> Self {
+        self.fallback = Some(to_owned(features));
+        self
+    }
+
+    pub(crate) fn downgrade(&self, license: &License) -> Option<EffectiveEntitlements> {
+        let by_kind = license
+            .kind
+            .as_ref()
+            .and_then(|kind| self.by_kind.get(kind));
+        let by_product = license
+            .product
+            .as_ref()
+            .and_then(|product| self.by_product.get(product));
+
+        by_kind
+            .or(by_product)
+            .or(self.fallback.as_ref())
+            .map(|features| EffectiveEntitlements {
+                features: features.clone(),
+                degraded: true,
+            })
+    }
+}
+
+fn to_owned(features: &[&str]) -> Vec<String> {
+    features.iter().map(|&feature| feature.to_owned()).collect()
+}
@@ -0,0 +1,351 @@
+use crate::jws;
+use crate::verification::{LicenseVerificationError, LicenseVerifier, PublicKey};
+use crate::VerifiableLicense;
+use serde_derive::Serialize;
+use std::collections::BTreeSet;
+use std::fmt;
+
+const STEPS: [&str; 6] = [
+    "parse",
+    "protected header",
+    "payload",
+    "outer/inner match",
+    "signature",
+    "validity",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepOutcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+// Details are limited to sizes, names and thumbprints so traces can be shared with support
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraceStep {
+    pub name: String,
+    pub outcome: StepOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VerificationTrace {
+    pub steps: Vec<TraceStep>,
+}
+
+impl VerificationTrace {
+    pub fn is_valid(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|step| step.outcome == StepOutcome::Passed)
+    }
+
+    fn record(&mut self, outcome: StepOutcome, detail: impl Into<Option<String>>) {
+        self.steps.push(TraceStep {
+            name: STEPS[self.steps.len()].to_owned(),
+            outcome,
+            detail: detail.into(),
+        });
+    }
+
+    fn passed(&mut self, detail: impl Into<Option<String>>) -> Option<()> {
+        self.record(StepOutcome::Passed, detail);
+        Some(())
+    }
+
+    fn failed(&mut self, detail: String) -> Option<()> {
+        self.record(StepOutcome::Failed, detail);
+        None
+    }
+}
+
+impl LicenseVerifier {
+    // Runs the verification steps one by one; steps after the first failure are recorded as skipped.
+    // A failure is reported like any other: in opaque mode every step is skipped so the trace does
+    // not tell which check failed.
+    pub fn explain(&self, verifiable_license_json: &serde_json::Value) -> VerificationTrace {
+        let mut trace = VerificationTrace { steps: Vec::new() };
+        let _ = self.trace_steps(verifiable_license_json, &mut trace);
+        if !trace.is_valid() {
+            let result = self.verify_license_detailed(verifiable_license_json.clone());
+            if let Err(LicenseVerificationError::LicenseInvalid) = self.reported(result) {
+                trace.steps.clear();
+            }
+        }
+        while trace.steps.len() < STEPS.len() {
+            trace.record(StepOutcome::Skipped, None);
+        }
+        trace
+    }
+
+    fn trace_steps(
+        &self,
+        verifiable_license_json: &serde_json::Value,
+        trace: &mut VerificationTrace,
+    ) -> Option<()> {
+        let Some(license_validation) =
+            serde_json::from_value::<VerifiableLicense>(verifiable_license_json.clone())
+                .ok()
+                .and_then(|verifiable_license| {
+                    verifiable_license.license_validation.as_object().cloned()
+                })
+        else {
+            return trace.failed("expected license and licenseValidation objects".to_owned());
+        };
+        let members = license_validation.keys().cloned().collect::<Vec<_>>();
+        trace.passed(format!("licenseValidation members: {}", members.join(", ")))?;
+
+        let base64_policy = self.base64_policy();
+        let mut protected_header = None;
+        if let Some(signatures) = license_validation.get("signatures") {
+            let count = signatures.as_array().map_or(0, Vec::len);
+            trace.passed(format!("{} signature entries", count))?;
+        } else {
+            let header = license_validation
+                .get("protected")
+                .and_then(|v| v.as_str())
+                .and_then(|protected| base64_policy.decode(protected))
+                .and_then(|header| serde_json::from_slice::<jws::Header>(&header).ok());
+            let Some(header) = header else {
+                return trace.failed("missing or undecodable protected header".to_owned());
+            };
+            let mut detail = format!("alg {}", header.alg);
+            if let Some(typ) = &header.typ {
+                detail.push_str(&format!(", typ {}", typ));
+            }
+            if let Some(kid) = &header.kid {
+                detail.push_str(&format!(", kid {}", kid));
+            }
+            if let Some(zip) = &header.zip {
+                detail.push_str(&format!(", zip {}", zip));
+            }
+            if let Some(name) = header.unsupported_critical() {
+                return trace.failed(format!(
+                    "{}, unsupported critical parameter {}",
+                    detail, name
+                ));
+            }
+            trace.passed(detail)?;
+            protected_header = Some(header);
+        }
+
+        let Some(payload) = license_validation
+            .get("payload")
+            .and_then(|v| v.as_str())
+            .and_then(|payload| base64_policy.decode(payload))
+        else {
+            return trace.failed("missing or undecodable payload".to_owned());
+        };
+        let encoded_len = payload.len();
+        let payload = match protected_header {
+            Some(header) if header.zip.is_some() => match self.inflated(&header, payload) {
+                Ok(payload) => payload,
+                Err(error) => return trace.failed(format!("{:?}", error)),
+            },
+            _ => payload,
+        };
+        if encoded_len == payload.len() {
+            trace.passed(format!("{} bytes", payload.len()))?;
+        } else {
+            trace.passed(format!(
+                "{} bytes inflated from {}",
+                payload.len(),
+                encoded_len
+            ))?;
+        }
+
+        let outer_license = &verifiable_license_json["license"];
+        if let Err(error) = self.untampered_license(outer_license, &payload) {
+            let detail = match serde_json::from_slice(&payload) {
+                Ok(serde_json::Value::Object(signed)) => {
+                    let outer = outer_license.as_object().cloned().unwrap_or_default();
+                    let differing = outer
+                        .keys()
+                        .chain(signed.keys())
+                        .filter(|field| outer.get(*field) != signed.get(*field))
+                        .map(String::as_str)
+                        .collect::<BTreeSet<_>>();
+                    format!(
+                        "{:?}, differing fields: {}",
+                        error,
+                        differing.into_iter().collect::<Vec<_>>().join(", ")
+                    )
+                }
+                _ => format!("{:?}", error),
+            };
+            return trace.failed(detail);
+        }
+        trace.passed(None)?;
+
+        let now = self.now();
+        let (license, validating_keys) =
+            match self.verify_signed(verifiable_license_json.clone(), now) {
+                Ok(verified) => verified,
+                Err(error) => return trace.failed(format!("{:?}", error)),
+            };
+        let thumbprints = validating_keys
+            .iter()
+            .map(PublicKey::thumbprint)
+            .collect::<Vec<_>>();
+        trace.passed(format!("verified with key {}", thumbprints.join(", ")))?;
+
+        match self.validated(license, &validating_keys, now) {
+            Ok(_) => trace.passed(None),
+            Err(error) => trace.failed(format!("{:?}", error)),
+        }
+    }
+}
+
+impl fmt::Display for StepOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepOutcome::Passed => write!(f, "passed"),
+            StepOutcome::Failed => write!(f, "FAILED"),
+            StepOutcome::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+impl fmt::Display for VerificationTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, step) in self.steps.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}. {}: {}", index + 1, step.name, step.outcome)?;
+            if let Some(detail) = &step.detail {
+                write!(f, " ({})", detail)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::signing::LicenseSigner;
+    use crate::test_keys::{verifier, ISSUER_PRIVATE_KEY_JWK_JSON, ISSUER_PUBLIC_KEY_JWK_JSON};
+    use crate::test_util::Tamper;
+    use crate::License;
+    use std::sync::Arc;
+
+    fn verifiable_license() -> serde_json::Value {
+        let license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": { "owner": "John Doe" }
+        }))
+        .unwrap();
+        LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .with_key_id("k-2024")
+            .sign(&license)
+            .unwrap()
+    }
+
+    #[test]
+    fn explain_valid_license() {
+        let trace = verifier().explain(&verifiable_license());
+        let thumbprint = PublicKey::from_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .unwrap()
+            .thumbprint();
+
+        assert!(trace.is_valid());
+        assert_eq!(
+            serde_json::to_value(&trace).unwrap(),
+            serde_json::json!({
+                "steps": [
+                    {
+                        "name": "parse",
+                        "outcome": "passed",
+                        "detail": "licenseValidation members: payload, protected, signature"
+                    },
+                    {
+                        "name": "protected header",
+                        "outcome": "passed",
+                        "detail": "alg RS512, typ JWT, kid k-2024"
+                    },
+                    { "name": "payload", "outcome": "passed", "detail": "119 bytes" },
+                    { "name": "outer/inner match", "outcome": "passed" },
+                    {
+                        "name": "signature",
+                        "outcome": "passed",
+                        "detail": format!("verified with key {}", thumbprint)
+                    },
+                    { "name": "validity", "outcome": "passed" }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn explain_tampered_license() {
+        let tampered_license = Tamper::new(verifiable_license())
+            .modify_outer_field("/customData/owner", serde_json::json!("Jane Doe"))
+            .into_value();
+
+        let trace = verifier().explain(&tampered_license);
+        assert!(!trace.is_valid());
+        assert_eq!(
+            trace.to_string(),
+            "1. parse: passed (licenseValidation members: payload, protected, signature)\n\
+             2. protected header: passed (alg RS512, typ JWT, kid k-2024)\n\
+             3. payload: passed (119 bytes)\n\
+             4. outer/inner match: FAILED (TamperedLicense, differing fields: customData)\n\
+             5. signature: skipped\n\
+             6. validity: skipped"
+        );
+    }
+
+    #[test]
+    fn explain_tampered_license_in_opaque_mode() {
+        let tampered_license = Tamper::new(verifiable_license())
+            .modify_outer_field("/customData/owner", serde_json::json!("Jane Doe"))
+            .into_value();
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let trace = verifier.explain(&tampered_license);
+        assert!(!trace.is_valid());
+        assert!(trace
+            .steps
+            .iter()
+            .all(|step| step.outcome == StepOutcome::Skipped && step.detail.is_none()));
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::TamperedLicense]
+        );
+    }
+
+    #[test]
+    fn explain_malformed_license() {
+        let trace = verifier().explain(&serde_json::json!({ "license": 1 }));
+        assert_eq!(
+            serde_json::to_value(&trace).unwrap(),
+            serde_json::json!({
+                "steps": [
+                    {
+                        "name": "parse",
+                        "outcome": "failed",
+                        "detail": "expected license and licenseValidation objects"
+                    },
+                    { "name": "protected header", "outcome": "skipped" },
+                    { "name": "payload", "outcome": "skipped" },
+                    { "name": "outer/inner match", "outcome": "skipped" },
+                    { "name": "signature", "outcome": "skipped" },
+                    { "name": "validity", "outcome": "skipped" }
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,193 @@
+#[cfg(feature = "sign")]
+use crate::datetime;
+use crate::datetime::DateTime;
+use crate::id::LicenseId;
+#[cfg(feature = "sign")]
+use crate::signing::LicenseSigner;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+#[cfg(feature = "sign")]
+use crate::License;
+use crate::VerifiedLicense;
+use serde_derive::{Deserialize, Serialize};
+
+const EXPIRATION_EXTENSION_TYP: &str = "jls-expiration-extension+jwt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtensionError {
+    InvalidExtension(LicenseVerificationError),
+    LicenseMismatch,
+    ShortensTerm,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ExpirationExtension {
+    #[serde(rename = "licenseId")]
+    pub license_id: LicenseId,
+    #[serde(rename = "newExpiration", with = "crate::datetime::rfc3339")]
+    pub new_expiration: DateTime,
+    #[serde(rename = "issuedAt", with = "crate::datetime::rfc3339")]
+    pub issued_at: DateTime,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectiveLicense {
+    license: VerifiedLicense,
+    expiration_date: DateTime,
+}
+
+impl EffectiveLicense {
+    pub fn new(license: VerifiedLicense) -> Self {
+        Self {
+            expiration_date: license.expiration_date,
+            license,
+        }
+    }
+
+    pub fn license(&self) -> &VerifiedLicense {
+        &self.license
+    }
+
+    pub fn expiration_date(&self) -> DateTime {
+        self.expiration_date
+    }
+
+    pub fn apply_extension(
+        mut self,
+        extension: &str,
+        verifier: &LicenseVerifier,
+    ) -> Result<Self, ExtensionError> {
+        let extension: ExpirationExtension = verifier
+            .reported(verifier.verify_token(extension, EXPIRATION_EXTENSION_TYP))
+            .map_err(ExtensionError::InvalidExtension)?;
+
+        if extension.license_id != self.license.id {
+            return Err(ExtensionError::LicenseMismatch);
+        }
+        if extension.new_expiration <= self.license.expiration_date {
+            return Err(ExtensionError::ShortensTerm);
+        }
+
+        self.expiration_date = self.expiration_date.max(extension.new_expiration);
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "sign")]
+impl LicenseSigner {
+    pub fn issue_extension(&self, license: &License, new_expiration: DateTime) -> String {
+        let extension = ExpirationExtension {
+            license_id: license.id.clone(),
+            new_expiration,
+            issued_at: datetime::now(),
+        };
+        self.sign_token(EXPIRATION_EXTENSION_TYP, &extension)
+    }
+}
+
+pub fn apply_extension(
+    license: &VerifiedLicense,
+    extension: &str,
+    verifier: &LicenseVerifier,
+) -> Result<EffectiveLicense, ExtensionError> {
+    EffectiveLicense::new(license.clone()).apply_extension(extension, verifier)
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::test_keys::{
+        license, signer, verifier, ISSUER_PUBLIC_KEY_JWK_JSON, OTHER_PRIVATE_KEY_JWK_JSON,
+    };
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn verified_license() -> VerifiedLicense {
+        verifier()
+            .verify_license(signer().sign(&license()).unwrap())
+            .unwrap()
+    }
+
+    fn date(date: &str) -> DateTime {
+        datetime::parse(date).unwrap()
+    }
+
+    #[test]
+    fn license_with_valid_extension() {
+        let extension = signer().issue_extension(&license(), date("2025-10-01T00:00:00Z"));
+
+        let effective_license = apply_extension(&verified_license(), &extension, &verifier())
+            .expect("Applying the extension should work");
+        assert_eq!(
+            effective_license.expiration_date(),
+            date("2025-10-01T00:00:00Z")
+        );
+        assert_eq!(**effective_license.license(), license());
+    }
+
+    #[test]
+    fn license_with_extension_for_other_license() {
+        let mut other_license = license();
+        other_license.id = Uuid::new_v4().into();
+        let extension = signer().issue_extension(&other_license, date("2025-10-01T00:00:00Z"));
+
+        let result = apply_extension(&verified_license(), &extension, &verifier());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, ExtensionError::LicenseMismatch);
+    }
+
+    #[test]
+    fn license_with_shortening_extension() {
+        let extension = signer().issue_extension(&license(), date("2024-09-01T00:00:00Z"));
+
+        let result = apply_extension(&verified_license(), &extension, &verifier());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, ExtensionError::ShortensTerm);
+    }
+
+    #[test]
+    fn license_with_forged_extension_in_opaque_mode() {
+        let extension = LicenseSigner::new(OTHER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .issue_extension(&license(), date("2025-10-01T00:00:00Z"));
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let opaque_verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let result = apply_extension(&verified_license(), &extension, &opaque_verifier);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            ExtensionError::InvalidExtension(LicenseVerificationError::LicenseInvalid)
+        );
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::VerificationFailure]
+        );
+    }
+
+    #[test]
+    fn license_with_stacked_extensions() {
+        let longer_extension = signer().issue_extension(&license(), date("2026-10-01T00:00:00Z"));
+        let shorter_extension = signer().issue_extension(&license(), date("2025-10-01T00:00:00Z"));
+
+        let effective_license =
+            apply_extension(&verified_license(), &longer_extension, &verifier())
+                .and_then(|license| license.apply_extension(&shorter_extension, &verifier()))
+                .expect("Applying the extensions should work");
+        assert_eq!(
+            effective_license.expiration_date(),
+            date("2026-10-01T00:00:00Z")
+        );
+    }
+}
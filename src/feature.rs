@@ -0,0 +1,275 @@
+use crate::datetime::DateTime;
+use crate::License;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(from = "FeatureRepr", into = "FeatureRepr")]
+pub struct Feature {
+    pub name: String,
+    pub expires: Option<DateTime>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuantityCheck {
+    Allowed,
+    AtLimit,
+    Exceeded,
+    Unlimited,
+    NotLicensed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureStatus {
+    pub name: String,
+    pub expires: Option<DateTime>,
+    pub active: bool,
+}
+
+// Plain features keep the bare string form so existing licenses stay valid
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum FeatureRepr {
+    Name(String),
+    Detailed {
+        name: String,
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            default,
+            with = "crate::datetime::rfc3339::option"
+        )]
+        expires: Option<DateTime>,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        limit: Option<u64>,
+    },
+}
+
+impl Feature {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            expires: None,
+            limit: None,
+        }
+    }
+
+    pub fn expiring(name: &str, expires: DateTime) -> Self {
+        Self {
+            expires: Some(expires),
+            ..Self::new(name)
+        }
+    }
+
+    pub fn limited(name: &str, limit: u64) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::new(name)
+        }
+    }
+
+    pub fn is_active_at(&self, now: DateTime) -> bool {
+        self.expires.is_none_or(|expires| now < expires)
+    }
+}
+
+impl From<&str> for Feature {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<FeatureRepr> for Feature {
+    fn from(repr: FeatureRepr) -> Self {
+        match repr {
+            FeatureRepr::Name(name) => Self {
+                name,
+                expires: None,
+                limit: None,
+            },
+            FeatureRepr::Detailed {
+                name,
+                expires,
+                limit,
+            } => Self {
+                name,
+                expires,
+                limit,
+            },
+        }
+    }
+}
+
+impl From<Feature> for FeatureRepr {
+    fn from(feature: Feature) -> Self {
+        match feature {
+            Feature {
+                name,
+                expires: None,
+                limit: None,
+            } => FeatureRepr::Name(name),
+            Feature {
+                name,
+                expires,
+                limit,
+            } => FeatureRepr::Detailed {
+                name,
+                expires,
+                limit,
+            },
+        }
+    }
+}
+
+impl License {
+    pub fn feature_limit(&self, feature: &str) -> Option<u64> {
+        self.features
+            .iter()
+            .find(|f| f.name == feature)
+            .and_then(|f| f.limit)
+    }
+
+    pub fn allows_quantity(&self, feature: &str, current: u64) -> QuantityCheck {
+        let Some(feature) = self.features.iter().find(|f| f.name == feature) else {
+            return QuantityCheck::NotLicensed;
+        };
+        match feature.limit {
+            None => QuantityCheck::Unlimited,
+            Some(limit) if current < limit => QuantityCheck::Allowed,
+            Some(limit) if current == limit => QuantityCheck::AtLimit,
+            Some(_) => QuantityCheck::Exceeded,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::{self, Duration};
+    #[cfg(feature = "sign")]
+    use crate::test_util::{ephemeral_signer_and_verifier, Tamper};
+    #[cfg(feature = "sign")]
+    use crate::verification::LicenseVerificationError;
+
+    fn license() -> License {
+        serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "2025-01-01T00:00:00Z",
+            "customData": {},
+            "features": [
+                "base",
+                { "name": "premium-support", "expires": "2024-06-01T00:00:00Z" },
+                { "name": "beta-access", "expires": "2026-01-01T00:00:00Z" }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn features_serialize_in_both_forms() {
+        let license = license();
+        assert_eq!(license.features[0], Feature::new("base"));
+        assert_eq!(
+            license.features[1],
+            Feature::expiring(
+                "premium-support",
+                datetime::parse("2024-06-01T00:00:00Z").unwrap()
+            )
+        );
+
+        let serialized = serde_json::to_value(&license).unwrap();
+        assert_eq!(serialized["features"][0], "base");
+        assert_eq!(serialized["features"][1]["name"], "premium-support");
+        assert_eq!(
+            serde_json::from_value::<License>(serialized).unwrap(),
+            license
+        );
+    }
+
+    #[test]
+    fn feature_expiration_boundaries() {
+        let license = license();
+        let premium_expiration = datetime::parse("2024-06-01T00:00:00Z").unwrap();
+
+        let just_before = premium_expiration - Duration::seconds(1);
+        assert!(license.has_feature_at("premium-support", just_before));
+        assert!(!license.has_feature_at("premium-support", premium_expiration));
+        assert!(license.has_feature_at("base", premium_expiration));
+        assert!(license.has_feature("premium-support"));
+    }
+
+    #[test]
+    fn license_expiration_bounds_features() {
+        let license = license();
+        let license_expiration = datetime::parse("2025-01-01T00:00:00Z").unwrap();
+
+        let just_before = license_expiration - Duration::seconds(1);
+        assert_eq!(
+            license.features_at(just_before),
+            vec!["base", "beta-access"]
+        );
+        assert!(!license.has_feature_at("base", license_expiration));
+        assert!(!license.has_feature_at("beta-access", license_expiration));
+        assert!(license
+            .feature_statuses_at(license_expiration)
+            .iter()
+            .all(|status| !status.active));
+    }
+
+    fn quantity_license() -> License {
+        serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": {},
+            "features": [
+                "sync",
+                { "name": "projects", "limit": 10 }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn quantity_checks() {
+        let license = quantity_license();
+
+        assert_eq!(license.feature_limit("projects"), Some(10));
+        assert_eq!(license.feature_limit("sync"), None);
+        assert_eq!(
+            license.allows_quantity("projects", 9),
+            QuantityCheck::Allowed
+        );
+        assert_eq!(
+            license.allows_quantity("projects", 10),
+            QuantityCheck::AtLimit
+        );
+        assert_eq!(
+            license.allows_quantity("projects", 11),
+            QuantityCheck::Exceeded
+        );
+        assert_eq!(
+            license.allows_quantity("sync", 5000),
+            QuantityCheck::Unlimited
+        );
+        assert_eq!(
+            license.allows_quantity("renders", 0),
+            QuantityCheck::NotLicensed
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn quantity_limit_is_signature_protected() {
+        let (signer, verifier) = ephemeral_signer_and_verifier();
+        let verifiable_license = Tamper::new(signer.sign(&quantity_license()).unwrap())
+            .modify_outer_field("/features/1/limit", serde_json::json!(1000))
+            .into_value();
+
+        let result = verifier.verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::TamperedLicense);
+    }
+}
@@ -0,0 +1,302 @@
+#[cfg(feature = "sign")]
+use crate::clock::saturating_add;
+use crate::datetime::DateTime;
+#[cfg(feature = "sign")]
+use crate::datetime::{self, Duration};
+use crate::id::LicenseId;
+#[cfg(feature = "sign")]
+use crate::signing::{LicenseSigner, LicenseSigningError};
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::License;
+use serde_derive::{Deserialize, Serialize};
+
+const FLOATING_SEAT_LEASE_TYP: &str = "jls-seat-lease+jwt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FloatingSeatLeaseError {
+    InvalidLease(LicenseVerificationError),
+    LeaseExpired,
+    ParentLicenseMismatch,
+    SeatOutOfRange(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FloatingSeatLease {
+    #[serde(rename = "parentLicenseId")]
+    pub parent_license_id: LicenseId,
+    pub seat: u32,
+    pub holder: String,
+    #[serde(rename = "issuedAt", with = "crate::datetime::rfc3339")]
+    pub issued_at: DateTime,
+    #[serde(rename = "expiresAt", with = "crate::datetime::rfc3339")]
+    pub expires_at: DateTime,
+}
+
+#[cfg(feature = "sign")]
+pub fn issue_lease(
+    signer: &LicenseSigner,
+    license: &License,
+    seat: u32,
+    holder: &str,
+    ttl: Duration,
+) -> Result<String, LicenseSigningError> {
+    if !is_licensed_seat(license, seat) {
+        return Err(LicenseSigningError::SeatOutOfRange(seat));
+    }
+
+    let issued_at = datetime::now();
+    let lease = FloatingSeatLease {
+        parent_license_id: license.id.clone(),
+        seat,
+        holder: holder.to_owned(),
+        issued_at,
+        expires_at: saturating_add(issued_at, ttl),
+    };
+    Ok(signer.sign_token(FLOATING_SEAT_LEASE_TYP, &lease))
+}
+
+pub fn verify_lease(
+    verifier: &LicenseVerifier,
+    lease: &str,
+    license: &License,
+) -> Result<FloatingSeatLease, FloatingSeatLeaseError> {
+    verify_lease_at(verifier, lease, license, verifier.now())
+}
+
+pub fn verify_lease_at(
+    verifier: &LicenseVerifier,
+    lease: &str,
+    license: &License,
+    now: DateTime,
+) -> Result<FloatingSeatLease, FloatingSeatLeaseError> {
+    // The lease checks go through `reported` too, so opaque mode hides them and the audit sink
+    // records them like a bad signature
+    verifier
+        .reported(
+            verifier
+                .verify_token(lease, FLOATING_SEAT_LEASE_TYP)
+                .and_then(|lease: FloatingSeatLease| {
+                    if lease.parent_license_id != license.id {
+                        return Err(LicenseVerificationError::LicenseIdMismatch {
+                            expected: license.id.clone(),
+                            got: lease.parent_license_id,
+                        });
+                    }
+                    if !is_licensed_seat(license, lease.seat) {
+                        return Err(LicenseVerificationError::SeatOutOfRange(lease.seat));
+                    }
+                    if now >= lease.expires_at {
+                        return Err(LicenseVerificationError::ExpiredLease);
+                    }
+                    Ok(lease)
+                }),
+        )
+        .map_err(|error| match error {
+            LicenseVerificationError::LicenseIdMismatch { .. } => {
+                FloatingSeatLeaseError::ParentLicenseMismatch
+            }
+            LicenseVerificationError::SeatOutOfRange(seat) => {
+                FloatingSeatLeaseError::SeatOutOfRange(seat)
+            }
+            LicenseVerificationError::ExpiredLease => FloatingSeatLeaseError::LeaseExpired,
+            error => FloatingSeatLeaseError::InvalidLease(error),
+        })
+}
+
+// Seats are numbered from 1; a license without `maxSeats` has a single one
+fn is_licensed_seat(license: &License, seat: u32) -> bool {
+    (1..=license.max_seats.unwrap_or(1)).contains(&seat)
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::test_keys::{
+        license_with, signer, verifier, ISSUER_PUBLIC_KEY_JWK_JSON, OTHER_PRIVATE_KEY_JWK_JSON,
+    };
+    use crate::test_util::FixedClock;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn license() -> License {
+        license_with(serde_json::json!({
+            "maxSeats": 3
+        }))
+    }
+
+    #[test]
+    fn floating_seat_lease_round_trip() {
+        let lease = issue_lease(
+            &signer(),
+            &license(),
+            3,
+            "alice@build-01",
+            Duration::minutes(15),
+        )
+        .unwrap();
+
+        let verified_lease =
+            verify_lease(&verifier(), &lease, &license()).expect("Verification should succeed");
+
+        assert_eq!(verified_lease.parent_license_id, license().id);
+        assert_eq!(verified_lease.seat, 3);
+        assert_eq!(verified_lease.holder, "alice@build-01");
+    }
+
+    #[test]
+    fn floating_seat_lease_expired() {
+        let lease = issue_lease(
+            &signer(),
+            &license(),
+            1,
+            "alice@build-01",
+            Duration::minutes(15),
+        )
+        .unwrap();
+
+        let result = verify_lease_at(
+            &verifier(),
+            &lease,
+            &license(),
+            datetime::now() + Duration::minutes(30),
+        );
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, FloatingSeatLeaseError::LeaseExpired);
+    }
+
+    #[test]
+    fn floating_seat_lease_for_other_license() {
+        let mut other_license = license();
+        other_license.id = Uuid::new_v4().into();
+        let lease =
+            issue_lease(&signer(), &other_license, 1, "bob", Duration::minutes(15)).unwrap();
+
+        let result = verify_lease(&verifier(), &lease, &license());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, FloatingSeatLeaseError::ParentLicenseMismatch);
+    }
+
+    #[test]
+    fn floating_seat_lease_signed_by_other_broker() {
+        let other_signer = LicenseSigner::new(OTHER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let lease =
+            issue_lease(&other_signer, &license(), 1, "bob", Duration::minutes(15)).unwrap();
+
+        let result = verify_lease(&verifier(), &lease, &license());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            FloatingSeatLeaseError::InvalidLease(LicenseVerificationError::VerificationFailure)
+        );
+    }
+
+    #[test]
+    fn floating_seat_lease_signed_by_other_broker_in_opaque_mode() {
+        let other_signer = LicenseSigner::new(OTHER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let lease =
+            issue_lease(&other_signer, &license(), 1, "bob", Duration::minutes(15)).unwrap();
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let result = verify_lease(&verifier, &lease, &license());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            FloatingSeatLeaseError::InvalidLease(LicenseVerificationError::LicenseInvalid)
+        );
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::VerificationFailure]
+        );
+    }
+
+    #[test]
+    fn floating_seat_lease_expired_on_the_verifier_clock() {
+        let lease = issue_lease(&signer(), &license(), 1, "bob", Duration::minutes(15)).unwrap();
+
+        let clock = Arc::new(FixedClock::new(datetime::now()));
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        assert!(verify_lease(&verifier, &lease, &license()).is_ok());
+
+        clock.advance(Duration::minutes(16));
+        let result = verify_lease(&verifier, &lease, &license());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, FloatingSeatLeaseError::LeaseExpired);
+    }
+
+    #[test]
+    fn floating_seat_lease_outside_the_licensed_seats() {
+        for seat in [0, 4] {
+            let result = issue_lease(&signer(), &license(), seat, "bob", Duration::minutes(15));
+            assert_eq!(result, Err(LicenseSigningError::SeatOutOfRange(seat)));
+        }
+
+        let issued_at = datetime::now();
+        let lease = signer().sign_token(
+            FLOATING_SEAT_LEASE_TYP,
+            &FloatingSeatLease {
+                parent_license_id: license().id,
+                seat: 99,
+                holder: "bob".to_owned(),
+                issued_at,
+                expires_at: issued_at + Duration::minutes(15),
+            },
+        );
+        let result = verify_lease(&verifier(), &lease, &license());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, FloatingSeatLeaseError::SeatOutOfRange(99));
+    }
+
+    #[test]
+    fn floating_seat_lease_checks_are_opaque_and_audited() {
+        let mut other_license = license();
+        other_license.id = Uuid::new_v4().into();
+        let lease =
+            issue_lease(&signer(), &other_license, 1, "bob", Duration::minutes(15)).unwrap();
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let result = verify_lease(&verifier, &lease, &license());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            FloatingSeatLeaseError::InvalidLease(LicenseVerificationError::LicenseInvalid)
+        );
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::LicenseIdMismatch {
+                expected: license().id,
+                got: other_license.id,
+            }]
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use core::fmt;
+use uuid::Uuid;
+
+// Only the canonical hyphenated lowercase form is read as a UUID so that every id serializes back
+// byte-for-byte. Ordering and hashing follow the variants: UUID ids sort before opaque ids.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LicenseId {
+    Uuid(Uuid),
+    Opaque(String),
+}
+
+impl LicenseId {
+    pub fn as_uuid(&self) -> Option<Uuid> {
+        match self {
+            LicenseId::Uuid(uuid) => Some(*uuid),
+            LicenseId::Opaque(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            LicenseId::Uuid(_) => None,
+            LicenseId::Opaque(id) => Some(id),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        match self {
+            LicenseId::Uuid(uuid) => uuid.as_bytes(),
+            LicenseId::Opaque(id) => id.as_bytes(),
+        }
+    }
+}
+
+impl From<Uuid> for LicenseId {
+    fn from(uuid: Uuid) -> Self {
+        LicenseId::Uuid(uuid)
+    }
+}
+
+impl From<String> for LicenseId {
+    fn from(id: String) -> Self {
+        match Uuid::parse_str(&id) {
+            Ok(uuid) if uuid.hyphenated().to_string() == id => LicenseId::Uuid(uuid),
+            _ => LicenseId::Opaque(id),
+        }
+    }
+}
+
+impl From<&str> for LicenseId {
+    fn from(id: &str) -> Self {
+        LicenseId::from(id.to_owned())
+    }
+}
+
+impl PartialEq<Uuid> for LicenseId {
+    fn eq(&self, other: &Uuid) -> bool {
+        self.as_uuid() == Some(*other)
+    }
+}
+
+impl fmt::Display for LicenseId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseId::Uuid(uuid) => uuid.hyphenated().fmt(f),
+            LicenseId::Opaque(id) => id.fmt(f),
+        }
+    }
+}
+
+impl serde::Serialize for LicenseId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for LicenseId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = <String as serde::Deserialize>::deserialize(deserializer)?;
+        if id.is_empty() {
+            return Err(serde::de::Error::custom("license ids cannot be empty"));
+        }
+        Ok(LicenseId::from(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uuid_license_id() {
+        let id: LicenseId =
+            serde_json::from_value(serde_json::json!("0b5b88f5-a264-4f90-8406-50b01d9515c8"))
+                .unwrap();
+        let uuid = Uuid::parse_str("0b5b88f5-a264-4f90-8406-50b01d9515c8").unwrap();
+        assert_eq!(id, LicenseId::Uuid(uuid));
+        assert_eq!(id.as_uuid(), Some(uuid));
+        assert_eq!(id.as_str(), None);
+        assert_eq!(
+            serde_json::to_string(&id).unwrap(),
+            "\"0b5b88f5-a264-4f90-8406-50b01d9515c8\""
+        );
+    }
+
+    #[test]
+    fn opaque_license_id_round_trip() {
+        for original in [
+            "\"ACME-2023-00481\"",
+            "\"0B5B88F5-A264-4F90-8406-50B01D9515C8\"",
+            "\"0b5b88f5a2644f90840650b01d9515c8\"",
+        ] {
+            let id: LicenseId = serde_json::from_str(original).unwrap();
+            assert_eq!(id.as_uuid(), None);
+            assert_eq!(id.as_str(), Some(original.trim_matches('"')));
+            assert_eq!(serde_json::to_string(&id).unwrap(), original);
+        }
+
+        let result = serde_json::from_str::<LicenseId>("\"\"");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn license_id_ordering() {
+        let mut ids: Vec<LicenseId> = vec![
+            "ACME-2023-00481".into(),
+            "5b4ea9a4-3bd4-4c7f-a4e4-b1c7c0e8c0d3".into(),
+            "ACME-2023-00017".into(),
+            "0b5b88f5-a264-4f90-8406-50b01d9515c8".into(),
+        ];
+        ids.sort();
+        assert_eq!(
+            ids.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+                "5b4ea9a4-3bd4-4c7f-a4e4-b1c7c0e8c0d3",
+                "ACME-2023-00017",
+                "ACME-2023-00481",
+            ]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use crate::verification::Base64Policy;
+use crate::{License, VerifiableLicense};
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InspectError {
+    MissingSegment(String),
+    InvalidHeader,
+    InvalidPayload,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ProtectedHeader {
+    pub alg: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub typ: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub kid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub x5t: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub crit: Vec<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentSizes {
+    pub protected: usize,
+    pub payload: usize,
+    pub signature: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LicenseInspection {
+    pub header: ProtectedHeader,
+    pub license: License,
+    pub segment_sizes: SegmentSizes,
+}
+
+impl VerifiableLicense {
+    // Diagnostics only: nothing returned here has had its signature checked
+    pub fn inspect(&self) -> Result<LicenseInspection, InspectError> {
+        let segment = |name: &str| {
+            self.license_validation
+                .get(name)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| InspectError::MissingSegment(name.to_owned()))
+        };
+        let protected = segment("protected")?;
+        let payload = segment("payload")?;
+        let signature = segment("signature")?;
+
+        let header = Base64Policy::Lenient
+            .decode(protected)
+            .and_then(|header| serde_json::from_slice(&header).ok())
+            .ok_or(InspectError::InvalidHeader)?;
+        let license = Base64Policy::Lenient
+            .decode(payload)
+            .and_then(|payload| serde_json::from_slice(&payload).ok())
+            .ok_or(InspectError::InvalidPayload)?;
+
+        Ok(LicenseInspection {
+            header,
+            license,
+            segment_sizes: SegmentSizes {
+                protected: protected.len(),
+                payload: payload.len(),
+                signature: signature.len(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jws;
+    use crate::test_keys::license;
+    #[cfg(feature = "sign")]
+    use crate::test_keys::signer;
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn inspect_signed_license() {
+        let verifiable_license: VerifiableLicense =
+            serde_json::from_value(signer().sign(&license()).unwrap()).unwrap();
+
+        let inspection = verifiable_license
+            .inspect()
+            .expect("Inspection should succeed");
+        assert_eq!(inspection.header.alg, "RS512");
+        assert_eq!(inspection.header.typ.as_deref(), Some("JWT"));
+        assert!(inspection.header.extra.is_empty());
+        assert_eq!(inspection.license, license());
+        assert_eq!(inspection.segment_sizes.signature, 683);
+    }
+
+    #[test]
+    fn inspect_header_with_extra_parameters() {
+        let protected = jws::encode(
+            br#"{"alg":"RS512","kid":"issuer-2024","x5t":"dGh1bWI","crit":["exp"],"exp":1727740800,"vendor":"acme"}"#,
+        );
+        let payload = jws::encode(&serde_json::to_vec(&license()).unwrap());
+        let verifiable_license: VerifiableLicense = serde_json::from_value(serde_json::json!({
+            "license": license(),
+            "licenseValidation": {
+                "protected": protected,
+                "payload": payload,
+                "signature": "not-a-signature",
+            }
+        }))
+        .unwrap();
+
+        let inspection = verifiable_license
+            .inspect()
+            .expect("Inspection should succeed");
+        assert_eq!(
+            inspection.header,
+            ProtectedHeader {
+                alg: "RS512".to_owned(),
+                typ: None,
+                kid: Some("issuer-2024".to_owned()),
+                x5t: Some("dGh1bWI".to_owned()),
+                crit: vec!["exp".to_owned()],
+                extra: serde_json::from_value(
+                    serde_json::json!({ "exp": 1727740800, "vendor": "acme" })
+                )
+                .unwrap(),
+            }
+        );
+        assert_eq!(
+            inspection.segment_sizes,
+            SegmentSizes {
+                protected: protected.len(),
+                payload: payload.len(),
+                signature: 15,
+            }
+        );
+    }
+
+    #[test]
+    fn inspect_without_signature() {
+        let verifiable_license: VerifiableLicense = serde_json::from_value(serde_json::json!({
+            "license": license(),
+            "licenseValidation": {
+                "payload": "e30",
+                "signatures": [],
+            }
+        }))
+        .unwrap();
+
+        let result = verifiable_license.inspect();
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, InspectError::MissingSegment("protected".to_owned()));
+    }
+}
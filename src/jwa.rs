@@ -0,0 +1,58 @@
+// The algorithm identifiers used across the public API. They are jose-jwa's own types whenever the
+// jose crates are in the tree, so enabling `minimal-deps` alongside them never changes a signature.
+#[cfg(feature = "jose")]
+pub use jose_jwk::jose_jwa::{Algorithm, Signing};
+
+#[cfg(not(feature = "jose"))]
+use serde_derive::{Deserialize, Serialize};
+
+#[cfg(not(feature = "jose"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(untagged)]
+pub enum Algorithm {
+    Signing(Signing),
+}
+
+#[cfg(not(feature = "jose"))]
+impl From<Signing> for Algorithm {
+    fn from(alg: Signing) -> Self {
+        Self::Signing(alg)
+    }
+}
+
+#[cfg(not(feature = "jose"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum Signing {
+    #[serde(rename = "EdDSA")]
+    EdDsa,
+    #[serde(rename = "ES256")]
+    Es256,
+    #[serde(rename = "ES256K")]
+    Es256K,
+    #[serde(rename = "ES384")]
+    Es384,
+    #[serde(rename = "ES512")]
+    Es512,
+    #[serde(rename = "HS256")]
+    Hs256,
+    #[serde(rename = "HS384")]
+    Hs384,
+    #[serde(rename = "HS512")]
+    Hs512,
+    #[serde(rename = "PS256")]
+    Ps256,
+    #[serde(rename = "PS384")]
+    Ps384,
+    #[serde(rename = "PS512")]
+    Ps512,
+    #[serde(rename = "RS256")]
+    Rs256,
+    #[serde(rename = "RS384")]
+    Rs384,
+    #[serde(rename = "RS512")]
+    Rs512,
+    #[serde(rename = "none")]
+    Null,
+}
@@ -0,0 +1,397 @@
+use crate::jwk::{Jwk, Key};
+use crate::jws;
+#[cfg(feature = "sign")]
+use crate::signing::LicenseSigner;
+use crate::signing::LicenseSigningError;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::License;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use rsa::sha2::Sha256;
+use rsa::{Oaep, RsaPrivateKey, RsaPublicKey};
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseEncryptionError {
+    InvalidRecipientKey,
+    InvalidVerifiableLicense,
+    Signing(LicenseSigningError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseDecryptionError {
+    InvalidDecryptionKey,
+    InvalidJwe,
+    WrongDecryptionKey,
+    TamperedCiphertext,
+    Verification(LicenseVerificationError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomDataState {
+    Plaintext,
+    Decrypted,
+    EncryptedCustomData,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnsealedLicense {
+    pub license: License,
+    pub custom_data: CustomDataState,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+struct JweHeader {
+    alg: String,
+    enc: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    cty: Option<String>,
+}
+
+pub fn encrypt_for(
+    verifiable_license: &serde_json::Value,
+    recipient_public_key: &serde_json::Value,
+) -> Result<String, LicenseEncryptionError> {
+    if verifiable_license.get("licenseValidation").is_none() {
+        return Err(LicenseEncryptionError::InvalidVerifiableLicense);
+    }
+    let recipient_public_key = public_key_from_jwk(recipient_public_key)
+        .ok_or(LicenseEncryptionError::InvalidRecipientKey)?;
+
+    let plaintext =
+        serde_json::to_vec(verifiable_license).expect("Verifiable licenses are serializable");
+    encrypt(&plaintext, "json", &recipient_public_key)
+        .ok_or(LicenseEncryptionError::InvalidRecipientKey)
+}
+
+#[cfg(feature = "sign")]
+impl LicenseSigner {
+    pub fn sign_with_sealed_custom_data(
+        &self,
+        license: &License,
+        recipient_public_key: &serde_json::Value,
+    ) -> Result<serde_json::Value, LicenseEncryptionError> {
+        let recipient_public_key = public_key_from_jwk(recipient_public_key)
+            .ok_or(LicenseEncryptionError::InvalidRecipientKey)?;
+        let plaintext =
+            serde_json::to_vec(&license.custom_data).expect("Custom data is always serializable");
+        let sealed_custom_data = encrypt(&plaintext, "json", &recipient_public_key)
+            .ok_or(LicenseEncryptionError::InvalidRecipientKey)?;
+
+        let mut sealed_license = license.clone();
+        sealed_license.custom_data = serde_json::json!({ "enc": sealed_custom_data });
+        self.sign(&sealed_license)
+            .map_err(LicenseEncryptionError::Signing)
+    }
+}
+
+impl LicenseVerifier {
+    pub fn verify_with_sealed_custom_data(
+        &self,
+        verifiable_license: serde_json::Value,
+        decryption_key: Option<&serde_json::Value>,
+    ) -> Result<UnsealedLicense, LicenseDecryptionError> {
+        let mut license = self
+            .verify(verifiable_license)
+            .map_err(LicenseDecryptionError::Verification)?;
+        let Some(sealed_custom_data) = sealed_custom_data(&license.custom_data) else {
+            return Ok(UnsealedLicense {
+                license,
+                custom_data: CustomDataState::Plaintext,
+            });
+        };
+        let Some(decryption_key) = decryption_key else {
+            return Ok(UnsealedLicense {
+                license,
+                custom_data: CustomDataState::EncryptedCustomData,
+            });
+        };
+
+        let decryption_key = private_key_from_jwk(decryption_key)
+            .ok_or(LicenseDecryptionError::InvalidDecryptionKey)?;
+        let plaintext = decrypt(sealed_custom_data, &decryption_key)?;
+        license.custom_data =
+            serde_json::from_slice(&plaintext).map_err(|_| LicenseDecryptionError::InvalidJwe)?;
+        Ok(UnsealedLicense {
+            license,
+            custom_data: CustomDataState::Decrypted,
+        })
+    }
+
+    pub fn decrypt_and_verify(
+        &self,
+        jwe_compact: &str,
+        decryption_key: &serde_json::Value,
+    ) -> Result<License, LicenseDecryptionError> {
+        let decryption_key = private_key_from_jwk(decryption_key)
+            .ok_or(LicenseDecryptionError::InvalidDecryptionKey)?;
+        let plaintext = decrypt(jwe_compact, &decryption_key)?;
+        let verifiable_license: serde_json::Value =
+            serde_json::from_slice(&plaintext).map_err(|_| LicenseDecryptionError::InvalidJwe)?;
+
+        self.verify(verifiable_license)
+            .map_err(LicenseDecryptionError::Verification)
+    }
+}
+
+fn sealed_custom_data(custom_data: &serde_json::Value) -> Option<&str> {
+    let custom_data = custom_data.as_object()?;
+    if custom_data.len() != 1 {
+        return None;
+    }
+    custom_data.get("enc")?.as_str()
+}
+
+pub(crate) fn public_key_from_jwk(jwk: &serde_json::Value) -> Option<RsaPublicKey> {
+    let jwk: Jwk = serde_json::from_value(jwk.clone()).ok()?;
+    let Key::Rsa(rsa_key) = jwk.key else {
+        return None;
+    };
+    RsaPublicKey::try_from(&rsa_key).ok()
+}
+
+pub(crate) fn private_key_from_jwk(jwk: &serde_json::Value) -> Option<RsaPrivateKey> {
+    let jwk: Jwk = serde_json::from_value(jwk.clone()).ok()?;
+    let Key::Rsa(rsa_key) = jwk.key else {
+        return None;
+    };
+    rsa_key.prv.as_ref()?;
+    RsaPrivateKey::try_from(&rsa_key).ok()
+}
+
+pub(crate) fn encrypt(
+    plaintext: &[u8],
+    content_type: &str,
+    recipient_public_key: &RsaPublicKey,
+) -> Option<String> {
+    let header = JweHeader {
+        alg: "RSA-OAEP-256".to_owned(),
+        enc: "A256GCM".to_owned(),
+        cty: Some(content_type.to_owned()),
+    };
+    let protected =
+        jws::encode(&serde_json::to_vec(&header).expect("JWE headers are always serializable"));
+
+    let content_key = Aes256Gcm::generate_key(&mut OsRng);
+    let mut iv = [0u8; 12];
+    OsRng.fill_bytes(&mut iv);
+    let encrypted_key = recipient_public_key
+        .encrypt(&mut OsRng, Oaep::new::<Sha256>(), &content_key)
+        .ok()?;
+
+    let mut ciphertext = Aes256Gcm::new(&content_key)
+        .encrypt(
+            &Nonce::from(iv),
+            Payload {
+                msg: plaintext,
+                aad: protected.as_bytes(),
+            },
+        )
+        .ok()?;
+    let tag = ciphertext.split_off(ciphertext.len() - 16);
+
+    Some(format!(
+        "{}.{}.{}.{}.{}",
+        protected,
+        jws::encode(&encrypted_key),
+        jws::encode(&iv),
+        jws::encode(&ciphertext),
+        jws::encode(&tag)
+    ))
+}
+
+pub(crate) fn decrypt(
+    jwe_compact: &str,
+    decryption_key: &RsaPrivateKey,
+) -> Result<Vec<u8>, LicenseDecryptionError> {
+    let parts = jwe_compact.split('.').collect::<Vec<_>>();
+    let [protected, encrypted_key, iv, ciphertext, tag] = parts[..] else {
+        return Err(LicenseDecryptionError::InvalidJwe);
+    };
+    let header: JweHeader = jws::decode(protected)
+        .and_then(|header| serde_json::from_slice(&header).ok())
+        .ok_or(LicenseDecryptionError::InvalidJwe)?;
+    if header.alg != "RSA-OAEP-256" || header.enc != "A256GCM" {
+        return Err(LicenseDecryptionError::InvalidJwe);
+    }
+    let (Some(encrypted_key), Some(iv), Some(mut ciphertext), Some(tag)) = (
+        jws::decode(encrypted_key),
+        jws::decode(iv),
+        jws::decode(ciphertext),
+        jws::decode(tag),
+    ) else {
+        return Err(LicenseDecryptionError::InvalidJwe);
+    };
+    let Ok(iv) = <[u8; 12]>::try_from(iv) else {
+        return Err(LicenseDecryptionError::InvalidJwe);
+    };
+    if tag.len() != 16 {
+        return Err(LicenseDecryptionError::InvalidJwe);
+    }
+
+    let content_key = decryption_key
+        .decrypt(Oaep::new::<Sha256>(), &encrypted_key)
+        .map_err(|_| LicenseDecryptionError::WrongDecryptionKey)?;
+    let cipher = Aes256Gcm::new_from_slice(&content_key)
+        .map_err(|_| LicenseDecryptionError::WrongDecryptionKey)?;
+
+    ciphertext.extend_from_slice(&tag);
+    cipher
+        .decrypt(
+            &Nonce::from(iv),
+            Payload {
+                msg: &ciphertext,
+                aad: protected.as_bytes(),
+            },
+        )
+        .map_err(|_| LicenseDecryptionError::TamperedCiphertext)
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::test_keys::{
+        license_with, signer, verifier, ISSUER_PRIVATE_KEY_JWK_JSON, OTHER_PRIVATE_KEY_JWK_JSON,
+        OTHER_PUBLIC_KEY_JWK_JSON,
+    };
+
+    fn license() -> License {
+        license_with(serde_json::json!({
+            "customData": {
+                "owner": "John Doe",
+                "pricingTier": "enterprise"
+            }
+        }))
+    }
+
+    fn encrypted_license() -> String {
+        let verifiable_license = signer().sign(&license()).unwrap();
+        encrypt_for(&verifiable_license, &OTHER_PUBLIC_KEY_JWK_JSON)
+            .expect("Encryption should work")
+    }
+
+    #[test]
+    fn encrypted_license_round_trip() {
+        let encrypted_license = encrypted_license();
+        assert!(!encrypted_license.contains(&jws::encode(b"enterprise")));
+
+        let verified_license = verifier()
+            .decrypt_and_verify(&encrypted_license, &OTHER_PRIVATE_KEY_JWK_JSON)
+            .expect("Decryption and verification should succeed");
+        assert_eq!(verified_license, license());
+    }
+
+    #[test]
+    fn encrypted_license_with_wrong_key() {
+        let result =
+            verifier().decrypt_and_verify(&encrypted_license(), &ISSUER_PRIVATE_KEY_JWK_JSON);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseDecryptionError::WrongDecryptionKey);
+    }
+
+    #[test]
+    fn encrypted_license_with_tampered_ciphertext() {
+        let encrypted_license = encrypted_license();
+        let mut parts = encrypted_license.split('.').collect::<Vec<_>>();
+        let mut ciphertext = jws::decode(parts[3]).unwrap();
+        ciphertext[0] ^= 0x01;
+        let ciphertext = jws::encode(&ciphertext);
+        parts[3] = &ciphertext;
+
+        let result = verifier().decrypt_and_verify(&parts.join("."), &OTHER_PRIVATE_KEY_JWK_JSON);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseDecryptionError::TamperedCiphertext);
+    }
+
+    #[test]
+    fn encrypted_license_signed_by_other_issuer() {
+        let verifiable_license = LicenseSigner::new(OTHER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license())
+            .unwrap();
+        let encrypted_license =
+            encrypt_for(&verifiable_license, &OTHER_PUBLIC_KEY_JWK_JSON).unwrap();
+
+        let result = verifier().decrypt_and_verify(&encrypted_license, &OTHER_PRIVATE_KEY_JWK_JSON);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseDecryptionError::Verification(LicenseVerificationError::VerificationFailure)
+        );
+    }
+
+    #[test]
+    fn sealed_custom_data_with_decryption_key() {
+        let verifiable_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign_with_sealed_custom_data(&license(), &OTHER_PUBLIC_KEY_JWK_JSON)
+            .expect("Signing should work");
+        assert!(verifiable_license["license"]["customData"]["enc"].is_string());
+        assert_eq!(
+            verifiable_license["license"]["id"],
+            license().id.to_string()
+        );
+
+        let unsealed_license = verifier()
+            .verify_with_sealed_custom_data(verifiable_license, Some(&OTHER_PRIVATE_KEY_JWK_JSON))
+            .expect("Verification should succeed");
+        assert_eq!(unsealed_license.custom_data, CustomDataState::Decrypted);
+        assert_eq!(unsealed_license.license, license());
+    }
+
+    #[test]
+    fn sealed_custom_data_without_decryption_key() {
+        let verifiable_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign_with_sealed_custom_data(&license(), &OTHER_PUBLIC_KEY_JWK_JSON)
+            .unwrap();
+
+        let unsealed_license = verifier()
+            .verify_with_sealed_custom_data(verifiable_license.clone(), None)
+            .expect("Verification should succeed");
+        assert_eq!(
+            unsealed_license.custom_data,
+            CustomDataState::EncryptedCustomData
+        );
+        assert_eq!(
+            unsealed_license.license.custom_data,
+            verifiable_license["license"]["customData"]
+        );
+        assert_eq!(
+            unsealed_license.license.expiration_date,
+            license().expiration_date
+        );
+    }
+
+    #[test]
+    fn sealed_custom_data_tampered() {
+        let mut verifiable_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign_with_sealed_custom_data(&license(), &OTHER_PUBLIC_KEY_JWK_JSON)
+            .unwrap();
+        let other_sealed_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign_with_sealed_custom_data(&license(), &OTHER_PUBLIC_KEY_JWK_JSON)
+            .unwrap();
+        verifiable_license["license"]["customData"] =
+            other_sealed_license["license"]["customData"].clone();
+
+        let result = verifier()
+            .verify_with_sealed_custom_data(verifiable_license, Some(&OTHER_PRIVATE_KEY_JWK_JSON));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseDecryptionError::Verification(LicenseVerificationError::TamperedLicense)
+        );
+    }
+}
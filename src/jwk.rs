@@ -0,0 +1,160 @@
+// JWK parsing for the RSA, P-256 and Ed25519 keys the crate signs and verifies with. The built-in
+// parser accepts and rejects exactly what jose-jwk does; it is used with `minimal-deps` or when the
+// jose crates are off.
+#[cfg(any(test, feature = "minimal-deps", not(feature = "jose")))]
+mod builtin;
+
+#[cfg(all(
+    feature = "eddsa",
+    any(feature = "minimal-deps", not(feature = "jose"))
+))]
+pub(crate) use builtin::OkpCurves;
+#[cfg(any(feature = "minimal-deps", not(feature = "jose")))]
+pub(crate) use builtin::{Jwk, Key, KeyInfo};
+#[cfg(all(feature = "eddsa", feature = "jose", not(feature = "minimal-deps")))]
+pub(crate) use jose_jwk::OkpCurves;
+#[cfg(all(feature = "jose", not(feature = "minimal-deps")))]
+pub(crate) use jose_jwk::{crypto::KeyInfo, Jwk, Key};
+
+#[cfg(all(test, feature = "jose"))]
+mod tests {
+    use super::builtin;
+    use crate::jwa::{Algorithm, Signing};
+    use crate::test_keys::{
+        ISSUER_PRIVATE_KEY_JWK_JSON, ISSUER_PUBLIC_KEY_JWK_JSON, OTHER_PUBLIC_KEY_JWK_JSON,
+    };
+    use jose_jwk::crypto::KeyInfo;
+    use rsa::{RsaPrivateKey, RsaPublicKey};
+
+    #[allow(clippy::large_enum_variant)]
+    #[derive(Debug, PartialEq)]
+    enum Parsed {
+        NotJwk,
+        Other {
+            rs512: bool,
+        },
+        Rsa {
+            rs512: bool,
+            public_key: Option<RsaPublicKey>,
+            private_key: Option<Option<RsaPrivateKey>>,
+        },
+    }
+
+    fn through_jose(jwk: &serde_json::Value) -> Parsed {
+        let Ok(jwk) = serde_json::from_value::<jose_jwk::Jwk>(jwk.clone()) else {
+            return Parsed::NotJwk;
+        };
+        let rs512 = jwk.is_supported(&Algorithm::from(Signing::Rs512));
+        let jose_jwk::Key::Rsa(rsa_key) = jwk.key else {
+            return Parsed::Other { rs512 };
+        };
+        Parsed::Rsa {
+            rs512,
+            public_key: RsaPublicKey::try_from(&rsa_key).ok(),
+            private_key: rsa_key
+                .prv
+                .as_ref()
+                .map(|_| RsaPrivateKey::try_from(&rsa_key).ok()),
+        }
+    }
+
+    fn through_builtin(jwk: &serde_json::Value) -> Parsed {
+        use builtin::KeyInfo;
+
+        let Ok(jwk) = serde_json::from_value::<builtin::Jwk>(jwk.clone()) else {
+            return Parsed::NotJwk;
+        };
+        let rs512 = jwk.is_supported(&Algorithm::from(Signing::Rs512));
+        let builtin::Key::Rsa(rsa_key) = jwk.key else {
+            return Parsed::Other { rs512 };
+        };
+        Parsed::Rsa {
+            rs512,
+            public_key: RsaPublicKey::try_from(&rsa_key).ok(),
+            private_key: rsa_key
+                .prv
+                .as_ref()
+                .map(|_| RsaPrivateKey::try_from(&rsa_key).ok()),
+        }
+    }
+
+    fn with(jwk: &serde_json::Value, field: &str, value: serde_json::Value) -> serde_json::Value {
+        let mut jwk = jwk.clone();
+        jwk[field] = value;
+        jwk
+    }
+
+    fn without(jwk: &serde_json::Value, field: &str) -> serde_json::Value {
+        let mut jwk = jwk.clone();
+        jwk.as_object_mut().unwrap().remove(field);
+        jwk
+    }
+
+    fn fixtures() -> Vec<serde_json::Value> {
+        let public_key = ISSUER_PUBLIC_KEY_JWK_JSON.clone();
+        let private_key = ISSUER_PRIVATE_KEY_JWK_JSON.clone();
+        let n = public_key["n"].as_str().unwrap().to_owned();
+        vec![
+            public_key.clone(),
+            private_key.clone(),
+            OTHER_PUBLIC_KEY_JWK_JSON.clone(),
+            serde_json::json!("not a jwk"),
+            serde_json::json!({}),
+            without(&public_key, "kty"),
+            without(&public_key, "n"),
+            without(&public_key, "e"),
+            with(&public_key, "kty", serde_json::json!("rsa")),
+            with(&public_key, "kty", serde_json::json!("DSA")),
+            with(&public_key, "n", serde_json::json!(format!("{}=", n))),
+            with(&public_key, "n", serde_json::json!("not*base64")),
+            with(&public_key, "n", serde_json::json!(&n[..n.len() / 2])),
+            with(&public_key, "e", serde_json::json!("")),
+            with(&public_key, "alg", serde_json::json!("RS512")),
+            with(&public_key, "alg", serde_json::json!("RS256")),
+            with(&public_key, "alg", serde_json::json!("RS999")),
+            with(&public_key, "use", serde_json::json!("sig")),
+            with(&public_key, "use", serde_json::json!("wrap")),
+            with(&public_key, "key_ops", serde_json::json!(["verify"])),
+            with(
+                &public_key,
+                "key_ops",
+                serde_json::json!(["verify", "bless"]),
+            ),
+            with(&public_key, "kid", serde_json::json!(7)),
+            with(&public_key, "x5c", serde_json::json!(["AAAA"])),
+            with(&public_key, "x5c", serde_json::json!(["AA-_"])),
+            with(
+                &public_key,
+                "x5t",
+                serde_json::json!("AAAAAAAAAAAAAAAAAAAAAAAAAAA"),
+            ),
+            with(&public_key, "x5t", serde_json::json!("AAAA")),
+            with(&public_key, "x5t#S256", serde_json::json!("AAAA")),
+            with(&public_key, "unknown", serde_json::json!(true)),
+            without(&private_key, "dp"),
+            without(&private_key, "p"),
+            with(&private_key, "q", private_key["p"].clone()),
+            serde_json::json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+                "y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"
+            }),
+            serde_json::json!({"kty": "EC", "crv": "P-192", "x": "AA", "y": "AA"}),
+            serde_json::json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+            }),
+            serde_json::json!({"kty": "OKP", "crv": "Ed25519"}),
+            serde_json::json!({"kty": "oct", "k": "AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow"}),
+        ]
+    }
+
+    #[test]
+    fn builtin_parser_matches_jose() {
+        for jwk in fixtures() {
+            assert_eq!(through_builtin(&jwk), through_jose(&jwk), "JWK: {}", jwk);
+        }
+    }
+}
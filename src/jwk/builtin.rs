@@ -0,0 +1,287 @@
+use crate::jwa::{Algorithm, Signing::*};
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+use base64ct::{Base64, Base64UrlUnpadded, Encoding};
+#[cfg(feature = "rsa")]
+use rsa::{BigUint, RsaPrivateKey, RsaPublicKey};
+use serde::de::Error as _;
+use serde::Deserializer;
+use serde_derive::Deserialize;
+use zeroize::Zeroizing;
+
+// Mirrors the shape of jose-jwk's `Jwk`, flattening included, so both reject the same documents
+#[derive(Debug, Deserialize)]
+pub(crate) struct Jwk {
+    #[serde(flatten)]
+    pub key: Key,
+    #[serde(flatten)]
+    prm: Parameters,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kty")]
+pub(crate) enum Key {
+    #[serde(rename = "EC")]
+    Ec(Ec),
+    #[serde(rename = "RSA")]
+    Rsa(Rsa),
+    #[serde(rename = "oct")]
+    Oct(Oct),
+    #[serde(rename = "OKP")]
+    Okp(#[allow(dead_code)] Okp),
+}
+
+// Only `alg` takes part in key selection; the rest are parsed to reject what jose-jwk rejects
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct Parameters {
+    #[serde(default)]
+    alg: Option<Algorithm>,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(rename = "use", default)]
+    cls: Option<Class>,
+    #[serde(rename = "key_ops", default)]
+    ops: Option<BTreeSet<Operation>>,
+    #[serde(default)]
+    x5c: Option<Vec<Bytes<Base64>>>,
+    #[serde(flatten)]
+    x5t: Thumbprint,
+}
+
+#[derive(Debug, Deserialize)]
+enum Class {
+    #[serde(rename = "enc")]
+    Encryption,
+    #[serde(rename = "sig")]
+    Signing,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+enum Operation {
+    #[serde(rename = "decrypt")]
+    Decrypt,
+    #[serde(rename = "deriveBits")]
+    DeriveBits,
+    #[serde(rename = "deriveKey")]
+    DeriveKey,
+    #[serde(rename = "encrypt")]
+    Encrypt,
+    #[serde(rename = "sign")]
+    Sign,
+    #[serde(rename = "unwrapKey")]
+    UnwrapKey,
+    #[serde(rename = "verify")]
+    Verify,
+    #[serde(rename = "wrapKey")]
+    WrapKey,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct Thumbprint {
+    #[serde(rename = "x5t", default)]
+    s1: Option<Digest<20>>,
+    #[serde(rename = "x5t#S256", default)]
+    s256: Option<Digest<32>>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+pub(crate) struct Ec {
+    pub crv: EcCurves,
+    pub x: Bytes,
+    pub y: Bytes,
+    #[serde(default)]
+    pub d: Option<Bytes>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) enum EcCurves {
+    #[serde(rename = "P-256")]
+    P256,
+    #[serde(rename = "P-384")]
+    P384,
+    #[serde(rename = "P-521")]
+    P521,
+    #[serde(rename = "secp256k1")]
+    P256K,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Oct {
+    k: Bytes,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+pub(crate) struct Okp {
+    pub crv: OkpCurves,
+    pub x: Bytes,
+    #[serde(default)]
+    pub d: Option<Bytes>,
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Deserialize)]
+pub(crate) enum OkpCurves {
+    Ed25519,
+    Ed448,
+    X25519,
+    X448,
+}
+
+// Without the `rsa` backend, RSA keys are only parsed to be rejected
+#[cfg_attr(not(feature = "rsa"), allow(dead_code))]
+#[derive(Debug, Deserialize)]
+pub(crate) struct Rsa {
+    n: Bytes,
+    e: Bytes,
+    #[serde(flatten, default)]
+    pub prv: Option<RsaPrivate>,
+}
+
+#[cfg_attr(not(feature = "rsa"), allow(dead_code))]
+#[derive(Debug, Deserialize)]
+pub(crate) struct RsaPrivate {
+    d: Bytes,
+    #[serde(flatten, default)]
+    opt: Option<RsaOptional>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct RsaOptional {
+    p: Bytes,
+    q: Bytes,
+    dp: Bytes,
+    dq: Bytes,
+    qi: Bytes,
+    #[serde(default)]
+    oth: Vec<RsaOtherPrime>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct RsaOtherPrime {
+    r: Bytes,
+    d: Bytes,
+    t: Bytes,
+}
+
+// Key material is zeroized on drop, like jose-jwk's `Secret`
+pub(crate) struct Bytes<E = Base64UrlUnpadded>(Zeroizing<Vec<u8>>, core::marker::PhantomData<E>);
+
+impl<E> core::fmt::Debug for Bytes<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Bytes(..)")
+    }
+}
+
+impl<'de, E: Encoding> serde::Deserialize<'de> for Bytes<E> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded: String = serde::Deserialize::deserialize(deserializer)?;
+        let bytes = E::decode_vec(&encoded).map_err(|_| D::Error::custom("invalid base64"))?;
+        Ok(Self(Zeroizing::new(bytes), core::marker::PhantomData))
+    }
+}
+
+impl<E> core::ops::Deref for Bytes<E> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "rsa")]
+impl<E> Bytes<E> {
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from_bytes_be(&self.0)
+    }
+}
+
+#[derive(Debug)]
+struct Digest<const N: usize>;
+
+impl<'de, const N: usize> serde::Deserialize<'de> for Digest<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes: Bytes = serde::Deserialize::deserialize(deserializer)?;
+        if bytes.0.len() != N {
+            return Err(D::Error::custom("invalid base64"));
+        }
+        Ok(Self)
+    }
+}
+
+pub(crate) trait KeyInfo {
+    fn is_supported(&self, algo: &Algorithm) -> bool;
+}
+
+impl KeyInfo for Jwk {
+    fn is_supported(&self, algo: &Algorithm) -> bool {
+        self.key.is_supported(algo) && algo == self.prm.alg.as_ref().unwrap_or(algo)
+    }
+}
+
+impl KeyInfo for Key {
+    #[allow(clippy::match_like_matches_macro)]
+    fn is_supported(&self, algo: &Algorithm) -> bool {
+        match (self, algo) {
+            (Key::Ec(ec), Algorithm::Signing(signing)) => match (&ec.crv, signing) {
+                (EcCurves::P256, Es256) => true,
+                (EcCurves::P256K, Es256K) => true,
+                (EcCurves::P384, Es384) => true,
+                (EcCurves::P521, Es512) => true,
+                _ => false,
+            },
+            (Key::Rsa(rsa), Algorithm::Signing(signing)) => {
+                // The strength is measured in bytes of an equivalent symmetric key
+                match (signing, rsa.n.0.len() / 16) {
+                    (Rs256 | Ps256, 16..) => true,
+                    (Rs384 | Ps384, 24..) => true,
+                    (Rs512 | Ps512, 32..) => true,
+                    _ => false,
+                }
+            }
+            (Key::Oct(oct), Algorithm::Signing(signing)) => match (signing, oct.k.0.len()) {
+                (Hs256, 16..) => true,
+                (Hs384, 24..) => true,
+                (Hs512, 32..) => true,
+                _ => false,
+            },
+            (Key::Okp(_), Algorithm::Signing(signing)) => *signing == EdDsa,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "rsa")]
+impl TryFrom<&Rsa> for RsaPublicKey {
+    type Error = rsa::Error;
+
+    fn try_from(value: &Rsa) -> Result<Self, Self::Error> {
+        RsaPublicKey::new(value.n.to_biguint(), value.e.to_biguint())
+    }
+}
+
+#[cfg(feature = "rsa")]
+impl TryFrom<&Rsa> for RsaPrivateKey {
+    type Error = rsa::Error;
+
+    fn try_from(value: &Rsa) -> Result<Self, Self::Error> {
+        // Like jose-jwk, only keys carrying their prime factors can be imported
+        let prv = value.prv.as_ref().ok_or(rsa::Error::InvalidArguments)?;
+        let opt = prv.opt.as_ref().ok_or(rsa::Error::InvalidArguments)?;
+        let mut primes = alloc::vec![opt.p.to_biguint(), opt.q.to_biguint()];
+        primes.extend(opt.oth.iter().map(|other| other.r.to_biguint()));
+        RsaPrivateKey::from_components(
+            value.n.to_biguint(),
+            value.e.to_biguint(),
+            prv.d.to_biguint(),
+            primes,
+        )
+    }
+}
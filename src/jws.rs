@@ -0,0 +1,104 @@
+#[cfg(feature = "std")]
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use base64ct::{Base64UrlUnpadded, Encoding};
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "compression")]
+pub(crate) const DEFLATE: &str = "DEF";
+
+#[cfg(feature = "compression")]
+const UNDERSTOOD_CRITICAL: &[&str] = &["zip"];
+#[cfg(not(feature = "compression"))]
+const UNDERSTOOD_CRITICAL: &[&str] = &[];
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub(crate) struct Header {
+    pub alg: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub typ: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub kid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub zip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub crit: Option<Vec<String>>,
+}
+
+impl Header {
+    #[cfg(feature = "std")]
+    pub fn new(alg: &str, typ: &str) -> Self {
+        Self {
+            alg: alg.to_owned(),
+            typ: Some(typ.to_owned()),
+            kid: None,
+            zip: None,
+            crit: None,
+        }
+    }
+
+    // RFC 7515 §4.1.11: tokens listing a parameter this build does not implement are rejected
+    pub fn unsupported_critical(&self) -> Option<&str> {
+        self.crit
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .find(|name| !UNDERSTOOD_CRITICAL.contains(name))
+    }
+}
+
+pub(crate) struct CompactJws<'a> {
+    pub header: Header,
+    pub payload: Vec<u8>,
+    pub signing_input: &'a str,
+    pub signature: Vec<u8>,
+}
+
+pub(crate) fn encode(data: &[u8]) -> String {
+    Base64UrlUnpadded::encode_string(data)
+}
+
+pub(crate) fn decode(data: &str) -> Option<Vec<u8>> {
+    Base64UrlUnpadded::decode_vec(data).ok()
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn signing_input<T: serde::Serialize>(header: &Header, claims: &T) -> String {
+    let header = serde_json::to_vec(header).expect("JWS headers are always serializable");
+    let payload = serde_json::to_vec(claims).expect("JWS claims must be serializable");
+    format!("{}.{}", encode(&header), encode(&payload))
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn compact(signing_input: String, signature: &[u8]) -> String {
+    format!("{}.{}", signing_input, encode(signature))
+}
+
+pub(crate) fn parse_compact(token: &str) -> Option<CompactJws<'_>> {
+    let (signing_input, signature) = token.rsplit_once('.')?;
+    let (header, payload) = signing_input.split_once('.')?;
+    if payload.contains('.') {
+        return None;
+    }
+
+    let header = serde_json::from_slice(&decode(header)?).ok()?;
+    Some(CompactJws {
+        header,
+        payload: decode(payload)?,
+        signing_input,
+        signature: decode(signature)?,
+    })
+}
+
+// RFC 7638: `members` must hold the required key members in lexicographic order.
+pub(crate) fn thumbprint(members: &[(&str, &str)]) -> String {
+    let canonical = members
+        .iter()
+        .map(|(name, value)| format!("\"{}\":\"{}\"", name, value))
+        .collect::<Vec<_>>()
+        .join(",");
+    encode(&Sha256::digest(format!("{{{}}}", canonical).as_bytes()))
+}
@@ -0,0 +1,254 @@
+use crate::clock::perpetual_expiration_date;
+use crate::datetime::{self, DateTime};
+use crate::id::LicenseId;
+#[cfg(feature = "sign")]
+use crate::signing::LicenseSigningError;
+use crate::verification::LicenseVerificationError;
+use crate::License;
+
+pub(crate) const CUSTOM_DATA_CLAIM: &str = "urn:jls:customData";
+
+// Registered claim names mapped onto license fields. customData is read from the namespaced claim
+// when present; every other claim is merged into it without overriding its members.
+// Numeric dates are whole seconds: fractional parts are truncated towards the past.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct JwtClaims {
+    pub license: License,
+    pub not_before: Option<DateTime>,
+}
+
+impl JwtClaims {
+    pub(crate) fn from_claims(
+        mut claims: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Self, LicenseVerificationError> {
+        let id = match claims.remove("jti") {
+            Some(serde_json::Value::String(jti)) if !jti.is_empty() => LicenseId::from(jti),
+            Some(_) => return Err(invalid_claim("jti")),
+            None => return Err(LicenseVerificationError::MissingLicenseId),
+        };
+        let expiration_date = numeric_date(&mut claims, "exp")
+            .map_err(|error| match error {
+                NumericDateError::OutOfRange => LicenseVerificationError::ExpirationOutOfRange,
+                NumericDateError::Invalid => invalid_claim("exp"),
+            })?
+            // A JWT without `exp` never expires
+            .unwrap_or_else(perpetual_expiration_date);
+        let issued_at = numeric_date(&mut claims, "iat").map_err(|_| invalid_claim("iat"))?;
+        let not_before = numeric_date(&mut claims, "nbf").map_err(|_| invalid_claim("nbf"))?;
+        let issuer = string_claim(&mut claims, "iss")?;
+        let product = match claims.remove("aud") {
+            None => None,
+            Some(serde_json::Value::String(aud)) => Some(aud),
+            // Only a single audience names a product unambiguously
+            Some(serde_json::Value::Array(mut audiences)) if audiences.len() == 1 => {
+                match audiences.pop() {
+                    Some(serde_json::Value::String(aud)) => Some(aud),
+                    _ => return Err(invalid_claim("aud")),
+                }
+            }
+            Some(aud) => {
+                claims.insert("aud".to_owned(), aud);
+                None
+            }
+        };
+        if let Some(sub) = string_claim(&mut claims, "sub")? {
+            claims.insert("licensee".to_owned(), serde_json::json!(sub));
+        }
+        let custom_data = match claims.remove(CUSTOM_DATA_CLAIM) {
+            None => serde_json::Value::Object(claims),
+            Some(serde_json::Value::Object(mut custom_data)) => {
+                for (name, value) in claims {
+                    custom_data.entry(name).or_insert(value);
+                }
+                serde_json::Value::Object(custom_data)
+            }
+            Some(custom_data) if claims.is_empty() => custom_data,
+            Some(_) => return Err(invalid_claim(CUSTOM_DATA_CLAIM)),
+        };
+
+        let license = License {
+            id,
+            product,
+            kind: None,
+            issuer,
+            issued_at,
+            expiration_date,
+            custom_data,
+            device_key: None,
+            features: vec![],
+            max_seats: None,
+            delegated_key: None,
+            activation_secret: None,
+            allowed_hosts: vec![],
+            allowed_networks: vec![],
+            upgrades_from: None,
+            state_required: false,
+            offline_validity_days: None,
+            maintenance_until: None,
+            watermark: None,
+        };
+        Ok(Self {
+            license,
+            not_before,
+        })
+    }
+}
+
+// The inverse of `JwtClaims::from_claims`. Fields without a registered claim cannot be carried, so
+// they are rejected instead of silently dropped.
+#[cfg(feature = "sign")]
+pub(crate) fn license_claims(
+    license: &License,
+) -> Result<serde_json::Map<String, serde_json::Value>, LicenseSigningError> {
+    let serde_json::Value::Object(mut fields) =
+        serde_json::to_value(license).map_err(|_| LicenseSigningError::InvalidLicense)?
+    else {
+        return Err(LicenseSigningError::InvalidLicense);
+    };
+    let mut claims = serde_json::Map::new();
+    for (field, claim) in [
+        ("id", "jti"),
+        ("issuer", "iss"),
+        ("product", "aud"),
+        ("customData", CUSTOM_DATA_CLAIM),
+    ] {
+        if let Some(value) = fields.remove(field) {
+            claims.insert(claim.to_owned(), value);
+        }
+    }
+    for (field, claim, date) in [
+        ("issuedAt", "iat", license.issued_at),
+        (
+            "expirationDate",
+            "exp",
+            Some(license.expiration_date).filter(|_| !license.is_perpetual()),
+        ),
+    ] {
+        fields.remove(field);
+        if let Some(date) = date {
+            claims.insert(
+                claim.to_owned(),
+                serde_json::json!(datetime::unix_seconds(date)),
+            );
+        }
+    }
+
+    match fields.into_iter().next() {
+        Some((field, _)) => Err(LicenseSigningError::NotRepresentableAsJwt(field)),
+        None => Ok(claims),
+    }
+}
+
+enum NumericDateError {
+    Invalid,
+    OutOfRange,
+}
+
+fn numeric_date(
+    claims: &mut serde_json::Map<String, serde_json::Value>,
+    name: &str,
+) -> Result<Option<DateTime>, NumericDateError> {
+    let Some(value) = claims.remove(name) else {
+        return Ok(None);
+    };
+    let seconds = match value.as_i64() {
+        Some(seconds) => seconds,
+        None => {
+            let seconds = value.as_f64().ok_or(NumericDateError::Invalid)?.floor();
+            if seconds < i64::MIN as f64 || seconds >= i64::MAX as f64 {
+                return Err(NumericDateError::OutOfRange);
+            }
+            seconds as i64
+        }
+    };
+    datetime::from_unix(seconds, 0)
+        .map(Some)
+        .ok_or(NumericDateError::OutOfRange)
+}
+
+fn string_claim(
+    claims: &mut serde_json::Map<String, serde_json::Value>,
+    name: &str,
+) -> Result<Option<String>, LicenseVerificationError> {
+    match claims.remove(name) {
+        None => Ok(None),
+        Some(serde_json::Value::String(value)) => Ok(Some(value)),
+        Some(_) => Err(invalid_claim(name)),
+    }
+}
+
+fn invalid_claim(name: &str) -> LicenseVerificationError {
+    LicenseVerificationError::InvalidClaimFormat(name.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn fractional_numeric_dates_are_truncated() {
+        let jwt_claims = JwtClaims::from_claims(claims(serde_json::json!({
+            "jti": "ACME-2023-00481",
+            "exp": 1727740800.999,
+            "iat": -0.5,
+        })))
+        .unwrap();
+
+        assert_eq!(
+            jwt_claims.license.expiration_date,
+            datetime::parse("2024-10-01T00:00:00Z").unwrap()
+        );
+        assert_eq!(
+            jwt_claims.license.issued_at,
+            Some(datetime::parse("1969-12-31T23:59:59Z").unwrap())
+        );
+    }
+
+    #[test]
+    fn audience_lists_stay_in_custom_data() {
+        let jwt_claims = JwtClaims::from_claims(claims(serde_json::json!({
+            "jti": "ACME-2023-00481",
+            "aud": ["acme-cad", "acme-cam"],
+        })))
+        .unwrap();
+
+        assert_eq!(jwt_claims.license.product, None);
+        assert_eq!(
+            jwt_claims.license.custom_data,
+            serde_json::json!({ "aud": ["acme-cad", "acme-cam"] })
+        );
+    }
+
+    #[test]
+    fn invalid_jwt_claims() {
+        let cases = [
+            (
+                serde_json::json!({ "sub": "ACME Corp" }),
+                LicenseVerificationError::MissingLicenseId,
+            ),
+            (
+                serde_json::json!({ "jti": 481 }),
+                LicenseVerificationError::InvalidClaimFormat("jti".to_owned()),
+            ),
+            (
+                serde_json::json!({ "jti": "ACME-2023-00481", "nbf": "2024-01-01" }),
+                LicenseVerificationError::InvalidClaimFormat("nbf".to_owned()),
+            ),
+            (
+                serde_json::json!({ "jti": "ACME-2023-00481", "exp": 1e300 }),
+                LicenseVerificationError::ExpirationOutOfRange,
+            ),
+        ];
+        for (value, expected) in cases {
+            let result = JwtClaims::from_claims(claims(value));
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, expected);
+        }
+    }
+}
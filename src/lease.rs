@@ -0,0 +1,181 @@
+#[cfg(feature = "sign")]
+use crate::clock::saturating_add;
+#[cfg(feature = "sign")]
+use crate::datetime;
+use crate::datetime::DateTime;
+#[cfg(feature = "sign")]
+use crate::datetime::Duration;
+use crate::id::LicenseId;
+#[cfg(feature = "sign")]
+use crate::signing::{LicenseSigner, LicenseSigningError};
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+#[cfg(feature = "sign")]
+use crate::License;
+use serde_derive::{Deserialize, Serialize};
+
+const LEASE_TYP: &str = "jls-lease+jwt";
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Lease {
+    #[serde(rename = "parentLicenseId")]
+    pub parent_license_id: LicenseId,
+    pub features: Vec<String>,
+    #[serde(rename = "iat", with = "crate::datetime::unix_seconds")]
+    pub issued_at: DateTime,
+    #[serde(rename = "exp", with = "crate::datetime::unix_seconds")]
+    pub expires_at: DateTime,
+}
+
+impl Lease {
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+}
+
+#[cfg(feature = "sign")]
+impl LicenseSigner {
+    pub fn issue_lease(
+        &self,
+        license: &License,
+        features: &[String],
+        ttl: Duration,
+    ) -> Result<String, LicenseSigningError> {
+        if let Some(feature) = features.iter().find(|f| !license.has_feature(f)) {
+            return Err(LicenseSigningError::FeatureNotLicensed(feature.clone()));
+        }
+
+        let issued_at = datetime::now();
+        let lease = Lease {
+            parent_license_id: license.id.clone(),
+            features: features.to_vec(),
+            issued_at,
+            expires_at: saturating_add(issued_at, ttl),
+        };
+        Ok(self.sign_token(LEASE_TYP, &lease))
+    }
+}
+
+impl LicenseVerifier {
+    pub fn verify_lease(&self, lease: &str) -> Result<Lease, LicenseVerificationError> {
+        self.verify_lease_at(lease, self.now())
+    }
+
+    pub fn verify_lease_at(
+        &self,
+        lease: &str,
+        now: DateTime,
+    ) -> Result<Lease, LicenseVerificationError> {
+        self.reported(
+            self.verify_token(lease, LEASE_TYP)
+                .and_then(|lease: Lease| {
+                    if now >= lease.expires_at {
+                        return Err(LicenseVerificationError::ExpiredLease);
+                    }
+                    Ok(lease)
+                }),
+        )
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::test_keys::{license_with, signer, verifier, ISSUER_PUBLIC_KEY_JWK_JSON};
+    use crate::test_util::FixedClock;
+    use std::sync::Arc;
+
+    fn license() -> License {
+        license_with(serde_json::json!({
+            "features": ["export", "render", "sync"]
+        }))
+    }
+
+    #[test]
+    fn lease_round_trip() {
+        let features = vec!["render".to_owned()];
+        let lease = signer()
+            .issue_lease(&license(), &features, Duration::minutes(15))
+            .expect("Issuing the lease should work");
+
+        let verified_lease = verifier()
+            .verify_lease(&lease)
+            .expect("Verification should succeed");
+        assert_eq!(verified_lease.parent_license_id, license().id);
+        assert!(verified_lease.has_feature("render"));
+        assert!(!verified_lease.has_feature("export"));
+    }
+
+    #[test]
+    fn lease_expired() {
+        let features = vec!["render".to_owned()];
+        let lease = signer()
+            .issue_lease(&license(), &features, Duration::minutes(15))
+            .unwrap();
+
+        let result = verifier().verify_lease_at(&lease, datetime::now() + Duration::minutes(16));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::ExpiredLease);
+    }
+
+    #[test]
+    fn lease_expired_under_configured_clock() {
+        let features = vec!["render".to_owned()];
+        let lease = signer()
+            .issue_lease(&license(), &features, Duration::minutes(15))
+            .unwrap();
+
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .clock(Arc::new(FixedClock::new(
+                datetime::now() + Duration::minutes(16),
+            )))
+            .build()
+            .unwrap();
+        let Err(error) = verifier.verify_lease(&lease) else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::ExpiredLease);
+    }
+
+    #[test]
+    fn lease_failures_in_opaque_mode() {
+        let features = vec!["render".to_owned()];
+        let lease = signer()
+            .issue_lease(&license(), &features, Duration::minutes(15))
+            .unwrap();
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let result = verifier.verify_lease_at(&lease, datetime::now() + Duration::minutes(16));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::LicenseInvalid);
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::ExpiredLease]
+        );
+    }
+
+    #[test]
+    fn lease_with_unlicensed_feature() {
+        let features = vec!["render".to_owned(), "admin".to_owned()];
+
+        let result = signer().issue_lease(&license(), &features, Duration::minutes(15));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseSigningError::FeatureNotLicensed("admin".to_owned())
+        );
+    }
+}
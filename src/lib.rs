@@ -1,22 +1,336 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(any(feature = "rsa", feature = "ecdsa", feature = "eddsa")))]
+compile_error!("jls needs at least one signature backend: enable `rsa`, `ecdsa` or `eddsa`");
+
+#[cfg(not(any(feature = "chrono", feature = "time")))]
+compile_error!("jls needs a date and time backend: enable `chrono` or `time`");
+
+#[cfg(feature = "std")]
+pub mod at_rest;
+pub mod audit;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod capability;
+pub mod certificate;
+#[cfg(feature = "std")]
+pub mod challenge;
+pub mod clock;
+#[cfg(feature = "std")]
+pub mod config;
+pub mod datetime;
+#[cfg(feature = "std")]
+pub mod deactivation;
+#[cfg(feature = "std")]
+pub mod device;
+#[cfg(feature = "std")]
+pub mod discovery;
+#[cfg(feature = "std")]
+pub mod display;
+#[cfg(feature = "std")]
+pub mod downgrade;
+#[cfg(feature = "std")]
+pub mod explain;
+#[cfg(feature = "std")]
+pub mod extension;
+pub mod feature;
+#[cfg(feature = "std")]
+pub mod floating;
+pub mod id;
+#[cfg(feature = "std")]
+pub mod inspect;
+pub mod jwa;
+#[cfg(feature = "jwe")]
+pub mod jwe;
+#[cfg(feature = "std")]
+pub mod lease;
+#[cfg(feature = "std")]
+pub mod maintenance;
+#[cfg(feature = "std")]
+pub mod manager;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+#[cfg(feature = "std")]
+pub mod offline;
+#[cfg(feature = "std")]
+pub mod policy;
+#[cfg(feature = "std")]
+pub mod receipt;
+#[cfg(feature = "std")]
+pub mod registry;
+#[cfg(feature = "std")]
+pub mod request;
+#[cfg(feature = "std")]
+pub mod resilient;
+#[cfg(feature = "std")]
+pub mod seat_lock;
+#[cfg(feature = "std")]
+pub mod section;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod signing;
+#[cfg(feature = "std")]
+pub mod state;
+#[cfg(feature = "std")]
+pub mod store;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+#[cfg(feature = "time")]
+pub mod time_compat;
+#[cfg(feature = "toml")]
+pub mod toml;
+#[cfg(feature = "tpm")]
+pub mod tpm;
+pub mod trust_bundle;
+#[cfg(feature = "std")]
+pub mod upgrade;
+#[cfg(feature = "std")]
+pub mod usage;
 pub mod verification;
+#[cfg(feature = "std")]
+pub mod verification_log;
+#[cfg(feature = "std")]
+pub mod watermark;
+#[cfg(feature = "yaml")]
+pub mod yaml;
 
-use chrono::{DateTime, Utc};
-use serde_derive::{Deserialize, Serialize};
+#[cfg(feature = "compression")]
+mod deflate;
+mod jwk;
+mod jws;
+#[cfg(feature = "std")]
+mod jwt;
+#[cfg(test)]
+mod test_keys;
 
-use uuid::Uuid;
+// The inflater reads issuer-signed but otherwise arbitrary bytes; fuzz/ drives it through this
+// entry point, which only exists in `--cfg fuzzing` builds
+#[cfg(all(fuzzing, feature = "compression"))]
+#[doc(hidden)]
+pub fn fuzz_inflate(data: &[u8], max_size: usize) -> Option<usize> {
+    deflate::decompress(data, max_size)
+        .ok()
+        .map(|inflated| inflated.len())
+}
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Deref;
+use datetime::DateTime;
+use feature::{Feature, FeatureStatus};
+use id::LicenseId;
+use serde::ser::SerializeStruct;
+use serde_derive::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
 pub struct License {
-    pub id: Uuid,
-    #[serde(rename = "expirationDate")]
-    pub expiration_date: DateTime<Utc>,
+    pub id: LicenseId,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub product: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub issuer: Option<String>,
+    #[serde(
+        rename = "issuedAt",
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::datetime::rfc3339::option"
+    )]
+    pub issued_at: Option<DateTime>,
+    #[serde(rename = "expirationDate", with = "crate::datetime::rfc3339")]
+    pub expiration_date: DateTime,
     #[serde(rename = "customData")]
     pub custom_data: serde_json::Value,
+    #[serde(rename = "deviceKey", skip_serializing_if = "Option::is_none", default)]
+    pub device_key: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub features: Vec<Feature>,
+    #[serde(rename = "maxSeats", skip_serializing_if = "Option::is_none", default)]
+    pub max_seats: Option<u32>,
+    #[serde(
+        rename = "delegatedKey",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub delegated_key: Option<serde_json::Value>,
+    #[serde(
+        rename = "activationSecret",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub activation_secret: Option<String>,
+    #[serde(
+        rename = "allowedHosts",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    pub allowed_hosts: Vec<String>,
+    #[serde(
+        rename = "allowedNetworks",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    pub allowed_networks: Vec<String>,
+    #[serde(
+        rename = "upgradesFrom",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub upgrades_from: Option<LicenseId>,
+    #[serde(
+        rename = "stateRequired",
+        skip_serializing_if = "core::ops::Not::not",
+        default
+    )]
+    pub state_required: bool,
+    #[serde(
+        rename = "offlineValidityDays",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub offline_validity_days: Option<u32>,
+    #[serde(
+        rename = "maintenanceUntil",
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::datetime::rfc3339::option"
+    )]
+    pub maintenance_until: Option<DateTime>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub watermark: Option<String>,
 }
 
+impl License {
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f.name == feature)
+    }
+
+    pub fn has_feature_at(&self, feature: &str, now: DateTime) -> bool {
+        self.is_active_at(now)
+            && self
+                .features
+                .iter()
+                .any(|f| f.name == feature && f.is_active_at(now))
+    }
+
+    pub fn features_at(&self, now: DateTime) -> Vec<String> {
+        self.feature_statuses_at(now)
+            .into_iter()
+            .filter(|status| status.active)
+            .map(|status| status.name)
+            .collect()
+    }
+
+    pub fn feature_statuses_at(&self, now: DateTime) -> Vec<FeatureStatus> {
+        let license_active = self.is_active_at(now);
+        self.features
+            .iter()
+            .map(|feature| FeatureStatus {
+                name: feature.name.clone(),
+                expires: feature.expires,
+                active: license_active && feature.is_active_at(now),
+            })
+            .collect()
+    }
+
+    pub fn is_perpetual(&self) -> bool {
+        self.expiration_date == clock::perpetual_expiration_date()
+    }
+
+    fn is_active_at(&self, now: DateTime) -> bool {
+        now < self.expiration_date
+    }
+}
+
+// Every signed document shares the license envelope: the outer object under `license` and a
+// flattened JWS over it under `licenseValidation`
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct VerifiableLicense {
-    license: License,
+pub struct Verifiable<T> {
+    license: T,
     #[serde(rename = "licenseValidation")]
     license_validation: serde_json::Value,
+    #[serde(
+        rename = "issuerCertificate",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    issuer_certificate: Option<String>,
+}
+
+pub type VerifiableLicense = Verifiable<License>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedLicense {
+    license: License,
+    verified_at: DateTime,
+    alg: String,
+    key_thumbprints: Vec<String>,
+}
+
+impl VerifiedLicense {
+    pub(crate) fn new(
+        license: License,
+        alg: &str,
+        key_thumbprints: Vec<String>,
+        verified_at: DateTime,
+    ) -> Self {
+        Self {
+            license,
+            verified_at,
+            alg: alg.to_owned(),
+            key_thumbprints,
+        }
+    }
+
+    // Test doubles hand out licenses that were never signed; the constructor only exists in builds
+    // with `test-util`
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn unverified_for_testing(license: License) -> Self {
+        Self::new(license, "none", Vec::new(), datetime::now())
+    }
+
+    pub fn license(&self) -> &License {
+        &self.license
+    }
+
+    pub fn into_license(self) -> License {
+        self.license
+    }
+
+    pub fn verified_at(&self) -> DateTime {
+        self.verified_at
+    }
+
+    pub fn alg(&self) -> &str {
+        &self.alg
+    }
+
+    pub fn key_thumbprints(&self) -> &[String] {
+        &self.key_thumbprints
+    }
+}
+
+impl Deref for VerifiedLicense {
+    type Target = License;
+
+    fn deref(&self) -> &License {
+        &self.license
+    }
+}
+
+impl serde::Serialize for VerifiedLicense {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("VerifiedLicense", 5)?;
+        state.serialize_field("verified", &true)?;
+        state.serialize_field("license", &self.license)?;
+        state.serialize_field("verifiedAt", &datetime::to_rfc3339(self.verified_at))?;
+        state.serialize_field("alg", &self.alg)?;
+        state.serialize_field("keyThumbprints", &self.key_thumbprints)?;
+        state.end()
+    }
 }
@@ -0,0 +1,107 @@
+use crate::datetime::DateTime;
+use crate::License;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceStatus {
+    Covered,
+    NotCovered { maintenance_until: DateTime },
+}
+
+impl License {
+    // Independent from `expiration_date`: a perpetual license still only covers builds released
+    // up to the end of its maintenance period.
+    pub fn allows_build(&self, build_date: DateTime) -> MaintenanceStatus {
+        match self.maintenance_until {
+            Some(maintenance_until) if build_date > maintenance_until => {
+                MaintenanceStatus::NotCovered { maintenance_until }
+            }
+            _ => MaintenanceStatus::Covered,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::LicenseBuilder;
+    use crate::datetime;
+    #[cfg(feature = "sign")]
+    use crate::datetime::Duration;
+    #[cfg(feature = "sign")]
+    use crate::signing::LicenseSigner;
+    #[cfg(feature = "sign")]
+    use crate::test_keys::{ISSUER_PRIVATE_KEY_JWK_JSON, ISSUER_PUBLIC_KEY_JWK_JSON};
+    #[cfg(feature = "sign")]
+    use crate::verification::LicenseVerifier;
+
+    fn perpetual_license() -> License {
+        serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "maintenanceUntil": "2024-10-01T00:00:00Z",
+            "customData": {}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn builds_around_the_maintenance_boundary() {
+        let license = perpetual_license();
+        let maintenance_until = datetime::parse("2024-10-01T00:00:00Z").unwrap();
+
+        let cases = [
+            ("2024-09-30T23:59:59Z", MaintenanceStatus::Covered),
+            ("2024-10-01T00:00:00Z", MaintenanceStatus::Covered),
+            (
+                "2024-10-01T00:00:01Z",
+                MaintenanceStatus::NotCovered { maintenance_until },
+            ),
+        ];
+        for (build_date, expected) in cases {
+            assert_eq!(
+                license.allows_build(datetime::parse(build_date).unwrap()),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn license_without_maintenance_claim_covers_every_build() {
+        let license = LicenseBuilder::new().build();
+
+        assert_eq!(
+            license.allows_build(datetime::max()),
+            MaintenanceStatus::Covered
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn maintenance_claim_coexists_with_expiration() {
+        let license = LicenseBuilder::new()
+            .validity(Duration::days(365))
+            .maintenance_until(datetime::parse("2024-10-01T00:00:00Z").unwrap())
+            .build();
+        let verifiable_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license)
+            .unwrap();
+        assert_eq!(
+            verifiable_license["license"]["maintenanceUntil"],
+            "2024-10-01T00:00:00Z"
+        );
+
+        let verified_license = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work")
+            .verify(verifiable_license)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license, license);
+        assert!(!verified_license.is_perpetual());
+        assert_eq!(
+            verified_license.allows_build(datetime::parse("2025-01-01T00:00:00Z").unwrap()),
+            MaintenanceStatus::NotCovered {
+                maintenance_until: datetime::parse("2024-10-01T00:00:00Z").unwrap()
+            }
+        );
+    }
+}
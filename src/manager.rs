@@ -0,0 +1,643 @@
+use crate::clock::{Clock, SystemClock};
+use crate::datetime;
+use crate::policy::{ValidationPolicy, ValidationStatus};
+use crate::resilient::LicenseLoadError;
+use crate::store::LicenseStore;
+use crate::verification::{LicenseVerification, LicenseVerificationError};
+use crate::VerifiedLicense;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+#[cfg(feature = "async")]
+use tokio::sync::broadcast;
+
+// Events not yet received by a `subscribe_async` receiver before it lags behind
+#[cfg(feature = "async")]
+const ASYNC_EVENT_CAPACITY: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ManagerStatus {
+    Valid,
+    Degraded(ValidationStatus),
+    Invalid(LicenseLoadError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseEvent {
+    ExpiringSoon { remaining: datetime::Duration },
+    Expired,
+    Renewed,
+    Revoked,
+}
+
+type SharedStore = Box<dyn LicenseStore + Send + Sync>;
+
+struct ManagerState {
+    status: ManagerStatus,
+    license: Option<Arc<VerifiedLicense>>,
+    crossed_thresholds: usize,
+}
+
+struct ManagerInner {
+    verifier: Arc<dyn LicenseVerification>,
+    source: SharedStore,
+    store: Option<SharedStore>,
+    policy: ValidationPolicy,
+    clock: Arc<dyn Clock>,
+    expiring_soon_thresholds: Vec<datetime::Duration>,
+    state: RwLock<ManagerState>,
+    subscribers: Mutex<Vec<Sender<LicenseEvent>>>,
+    #[cfg(feature = "async")]
+    async_subscribers: broadcast::Sender<LicenseEvent>,
+}
+
+pub struct LicenseManagerBuilder {
+    verifier: Arc<dyn LicenseVerification>,
+    source: SharedStore,
+    store: Option<SharedStore>,
+    policy: ValidationPolicy,
+    clock: Arc<dyn Clock>,
+    expiring_soon_thresholds: Vec<datetime::Duration>,
+    revalidation_interval: Option<Duration>,
+}
+
+impl LicenseManagerBuilder {
+    pub fn store(mut self, store: impl LicenseStore + Send + Sync + 'static) -> Self {
+        self.store = Some(Box::new(store));
+        self
+    }
+
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn expiring_soon_thresholds(mut self, thresholds: &[datetime::Duration]) -> Self {
+        self.expiring_soon_thresholds = thresholds.to_vec();
+        self
+    }
+
+    pub fn revalidate_every(mut self, interval: Duration) -> Self {
+        self.revalidation_interval = Some(interval);
+        self
+    }
+
+    // Revalidation, if enabled, runs on a background thread
+    pub fn build(self) -> LicenseManager {
+        let interval = self.revalidation_interval;
+        let mut manager = self.into_manager();
+        if let Some(interval) = interval {
+            manager.spawn_revalidation(interval);
+        }
+        manager
+    }
+
+    // Revalidation, if enabled, runs as a task of the current tokio runtime. Panics when called
+    // outside of one.
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> LicenseManager {
+        let interval = self.revalidation_interval;
+        let mut manager = self.into_manager();
+        if let Some(interval) = interval {
+            manager.spawn_revalidation_task(interval);
+        }
+        manager
+    }
+
+    fn into_manager(mut self) -> LicenseManager {
+        self.expiring_soon_thresholds.sort_by(|a, b| b.cmp(a));
+        let inner = Arc::new(ManagerInner {
+            verifier: self.verifier,
+            source: self.source,
+            store: self.store,
+            policy: self.policy,
+            clock: self.clock,
+            expiring_soon_thresholds: self.expiring_soon_thresholds,
+            state: RwLock::new(ManagerState {
+                status: ManagerStatus::Invalid(LicenseLoadError::InvalidCache),
+                license: None,
+                crossed_thresholds: 0,
+            }),
+            subscribers: Mutex::new(Vec::new()),
+            #[cfg(feature = "async")]
+            async_subscribers: broadcast::channel(ASYNC_EVENT_CAPACITY).0,
+        });
+        inner.refresh();
+
+        LicenseManager {
+            inner,
+            worker: None,
+        }
+    }
+}
+
+pub struct LicenseManager {
+    inner: Arc<ManagerInner>,
+    worker: Option<Worker>,
+}
+
+enum Worker {
+    Thread {
+        shutdown: Sender<()>,
+        handle: JoinHandle<()>,
+    },
+    #[cfg(feature = "async")]
+    Task(tokio::task::JoinHandle<()>),
+}
+
+impl LicenseManager {
+    pub fn builder(
+        verifier: Arc<dyn LicenseVerification>,
+        source: impl LicenseStore + Send + Sync + 'static,
+        policy: ValidationPolicy,
+    ) -> LicenseManagerBuilder {
+        LicenseManagerBuilder {
+            verifier,
+            source: Box::new(source),
+            store: None,
+            policy,
+            clock: Arc::new(SystemClock),
+            expiring_soon_thresholds: vec![datetime::Duration::days(30)],
+            revalidation_interval: None,
+        }
+    }
+
+    pub fn status(&self) -> ManagerStatus {
+        self.inner.state.read().unwrap().status.clone()
+    }
+
+    pub fn license(&self) -> Option<Arc<VerifiedLicense>> {
+        self.inner.state.read().unwrap().license.clone()
+    }
+
+    pub fn refresh_now(&self) -> ManagerStatus {
+        self.inner.refresh()
+    }
+
+    pub fn subscribe(&self) -> Receiver<LicenseEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.inner.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    // Receives the same events as `subscribe`, from the time of the call on
+    #[cfg(feature = "async")]
+    pub fn subscribe_async(&self) -> broadcast::Receiver<LicenseEvent> {
+        self.inner.async_subscribers.subscribe()
+    }
+
+    fn spawn_revalidation(&mut self, interval: Duration) {
+        let (shutdown, shutdown_signal) = mpsc::channel::<()>();
+        let inner = Arc::clone(&self.inner);
+        let handle = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = shutdown_signal.recv_timeout(interval) {
+                inner.refresh();
+            }
+        });
+        self.worker = Some(Worker::Thread { shutdown, handle });
+    }
+
+    #[cfg(feature = "async")]
+    fn spawn_revalidation_task(&mut self, interval: Duration) {
+        let inner = Arc::clone(&self.inner);
+        let task = tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes right away and the license was just loaded
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let inner = Arc::clone(&inner);
+                // Stores and verifiers block, so they stay off the runtime's workers
+                if tokio::task::spawn_blocking(move || inner.refresh())
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        });
+        self.worker = Some(Worker::Task(task));
+    }
+}
+
+impl Drop for LicenseManager {
+    fn drop(&mut self) {
+        match self.worker.take() {
+            Some(Worker::Thread { shutdown, handle }) => {
+                // Dropping the sender disconnects the channel and stops the worker
+                drop(shutdown);
+                let _ = handle.join();
+            }
+            #[cfg(feature = "async")]
+            Some(Worker::Task(task)) => task.abort(),
+            None => {}
+        }
+    }
+}
+
+impl ManagerInner {
+    fn refresh(&self) -> ManagerStatus {
+        let now = self.clock.now();
+        let (status, license) = match self.load() {
+            Ok(license) => {
+                let status = match self.policy.validate_at(&license, now) {
+                    Ok(ValidationStatus::Valid) => ManagerStatus::Valid,
+                    Ok(status) => ManagerStatus::Degraded(status),
+                    Err(error) => ManagerStatus::Invalid(LicenseLoadError::Verification(error)),
+                };
+                (status, Some(Arc::new(license)))
+            }
+            Err(error) => (ManagerStatus::Invalid(error), None),
+        };
+
+        let mut state = self.state.write().unwrap();
+        let mut events = Vec::new();
+        let expired = ManagerStatus::Invalid(LicenseLoadError::Verification(
+            LicenseVerificationError::LicenseExpired,
+        ));
+        if let (Some(previous), Some(current)) = (&state.license, &license) {
+            if current.expiration_date > previous.expiration_date {
+                state.crossed_thresholds = 0;
+                events.push(LicenseEvent::Renewed);
+            }
+        }
+        match &status {
+            ManagerStatus::Invalid(_) if status == expired && state.status != expired => {
+                events.push(LicenseEvent::Expired);
+            }
+            ManagerStatus::Invalid(_)
+                if status != expired && !matches!(state.status, ManagerStatus::Invalid(_)) =>
+            {
+                events.push(LicenseEvent::Revoked);
+            }
+            ManagerStatus::Valid | ManagerStatus::Degraded(_) => {
+                let remaining = license
+                    .as_ref()
+                    .filter(|license| !license.is_perpetual())
+                    .map(|license| license.expiration_date - now);
+                if let Some(remaining) = remaining {
+                    let crossed_thresholds = self
+                        .expiring_soon_thresholds
+                        .iter()
+                        .filter(|&&threshold| remaining <= threshold)
+                        .count();
+                    if crossed_thresholds > state.crossed_thresholds {
+                        state.crossed_thresholds = crossed_thresholds;
+                        events.push(LicenseEvent::ExpiringSoon { remaining });
+                    }
+                }
+            }
+            _ => {}
+        }
+        state.status = status.clone();
+        state.license = license;
+        drop(state);
+
+        let mut subscribers = self.subscribers.lock().unwrap();
+        for event in events {
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+            // Fails only when nobody is subscribed
+            #[cfg(feature = "async")]
+            let _ = self.async_subscribers.send(event);
+        }
+        status
+    }
+
+    fn load(&self) -> Result<VerifiedLicense, LicenseLoadError> {
+        let primary_error = match self.load_from(&*self.source) {
+            Ok((verifiable_license, license)) => {
+                if let Some(store) = &self.store {
+                    // A store that cannot be updated must not turn a verified license into a failure.
+                    let _ = store.save(&verifiable_license);
+                }
+                return Ok(license);
+            }
+            Err(error) => error,
+        };
+
+        match &self.store {
+            Some(store) => self
+                .load_from(&**store)
+                .map(|(_, license)| license)
+                .map_err(|_| primary_error),
+            None => Err(primary_error),
+        }
+    }
+
+    fn load_from(
+        &self,
+        store: &dyn LicenseStore,
+    ) -> Result<(serde_json::Value, VerifiedLicense), LicenseLoadError> {
+        let verifiable_license = store.load().map_err(LicenseLoadError::Store)?;
+        let license = self
+            .verifier
+            .verify_license(verifiable_license.clone())
+            .map_err(LicenseLoadError::Verification)?;
+        Ok((verifiable_license, license))
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::datetime::{self, DateTime};
+    use crate::mock::MockVerifier;
+    use crate::signing::LicenseSigner;
+    use crate::store::{LicenseStoreError, MemoryLicenseStore};
+    use crate::test_keys::{ISSUER_PRIVATE_KEY_JWK_JSON, ISSUER_PUBLIC_KEY_JWK_JSON};
+    use crate::verification::LicenseVerifier;
+    use crate::License;
+
+    struct ManualClock(Mutex<DateTime>);
+
+    impl ManualClock {
+        fn set(&self, now: &str) {
+            *self.0.lock().unwrap() = datetime::parse(now).unwrap();
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> DateTime {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn clock_at(now: &str) -> Arc<ManualClock> {
+        Arc::new(ManualClock(Mutex::new(datetime::parse(now).unwrap())))
+    }
+
+    fn signed_license(expiration_date: &str) -> serde_json::Value {
+        let license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": expiration_date,
+            "customData": {
+                "owner": "John Doe"
+            }
+        }))
+        .unwrap();
+        LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license)
+            .unwrap()
+    }
+
+    fn verifier() -> Arc<dyn LicenseVerification> {
+        Arc::new(
+            LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+                .expect("Verifier instantiation must work"),
+        )
+    }
+
+    #[test]
+    fn manager_across_expiration_and_renewal() {
+        let source = Arc::new(MemoryLicenseStore::new());
+        source
+            .save(&signed_license("2024-10-01T00:00:00Z"))
+            .unwrap();
+        let clock = clock_at("2024-09-30T00:00:00Z");
+
+        let manager = LicenseManager::builder(verifier(), source.clone(), ValidationPolicy::new())
+            .clock(clock.clone())
+            .build();
+        let events = manager.subscribe();
+        assert_eq!(manager.status(), ManagerStatus::Valid);
+        assert!(manager.license().is_some());
+
+        clock.set("2024-10-02T00:00:00Z");
+        assert_eq!(
+            manager.refresh_now(),
+            ManagerStatus::Invalid(LicenseLoadError::Verification(
+                LicenseVerificationError::LicenseExpired
+            ))
+        );
+        assert_eq!(events.try_recv(), Ok(LicenseEvent::Expired));
+
+        source
+            .save(&signed_license("2025-10-01T00:00:00Z"))
+            .unwrap();
+        assert_eq!(manager.refresh_now(), ManagerStatus::Valid);
+        assert_eq!(events.try_recv(), Ok(LicenseEvent::Renewed));
+        assert_eq!(
+            manager.license().unwrap().expiration_date,
+            datetime::parse("2025-10-01T00:00:00Z").unwrap()
+        );
+    }
+
+    #[test]
+    fn manager_revalidates_periodically() {
+        let source = MemoryLicenseStore::new();
+        source
+            .save(&signed_license("2024-10-01T00:00:00Z"))
+            .unwrap();
+        let clock = clock_at("2024-09-30T00:00:00Z");
+
+        let manager = LicenseManager::builder(verifier(), source, ValidationPolicy::new())
+            .clock(clock.clone())
+            .revalidate_every(Duration::from_millis(10))
+            .build();
+        let events = manager.subscribe();
+
+        clock.set("2024-10-02T00:00:00Z");
+        let event = events
+            .recv_timeout(Duration::from_secs(5))
+            .expect("Revalidation should report the expiration");
+        assert_eq!(event, LicenseEvent::Expired);
+    }
+
+    #[test]
+    fn manager_falls_back_to_store() {
+        let store = Arc::new(MemoryLicenseStore::new());
+        let clock = clock_at("2024-09-30T00:00:00Z");
+
+        let source = Arc::new(MemoryLicenseStore::new());
+        source
+            .save(&signed_license("2024-10-01T00:00:00Z"))
+            .unwrap();
+        LicenseManager::builder(verifier(), source, ValidationPolicy::new())
+            .store(store.clone())
+            .clock(clock.clone())
+            .build();
+
+        let manager = LicenseManager::builder(
+            verifier(),
+            MemoryLicenseStore::new(),
+            ValidationPolicy::new(),
+        )
+        .store(store.clone())
+        .clock(clock)
+        .build();
+        assert_eq!(manager.status(), ManagerStatus::Valid);
+
+        let manager = LicenseManager::builder(
+            verifier(),
+            MemoryLicenseStore::new(),
+            ValidationPolicy::new(),
+        )
+        .build();
+        assert_eq!(
+            manager.status(),
+            ManagerStatus::Invalid(LicenseLoadError::Store(LicenseStoreError::NotFound))
+        );
+    }
+
+    #[test]
+    fn manager_events_across_license_lifetime() {
+        let source = Arc::new(MemoryLicenseStore::new());
+        source
+            .save(&signed_license("2024-10-01T00:00:00Z"))
+            .unwrap();
+        let clock = clock_at("2024-07-01T00:00:00Z");
+
+        let manager = LicenseManager::builder(verifier(), source.clone(), ValidationPolicy::new())
+            .clock(clock.clone())
+            .expiring_soon_thresholds(&[datetime::Duration::days(7), datetime::Duration::days(30)])
+            .build();
+        let events = manager.subscribe();
+        let other_events = manager.subscribe();
+        #[cfg(feature = "async")]
+        let mut async_events = manager.subscribe_async();
+
+        for now in [
+            "2024-08-01T00:00:00Z",
+            "2024-09-01T00:00:00Z",
+            "2024-09-02T00:00:00Z",
+            "2024-09-03T00:00:00Z",
+            "2024-09-24T00:00:00Z",
+            "2024-09-25T00:00:00Z",
+            "2024-10-01T00:00:00Z",
+            "2024-10-02T00:00:00Z",
+        ] {
+            clock.set(now);
+            manager.refresh_now();
+        }
+        source
+            .save(&signed_license("2025-10-01T00:00:00Z"))
+            .unwrap();
+        manager.refresh_now();
+        manager.refresh_now();
+        source.save(&serde_json::json!({})).unwrap();
+        manager.refresh_now();
+        manager.refresh_now();
+
+        let expected_events = vec![
+            LicenseEvent::ExpiringSoon {
+                remaining: datetime::Duration::days(30),
+            },
+            LicenseEvent::ExpiringSoon {
+                remaining: datetime::Duration::days(7),
+            },
+            LicenseEvent::Expired,
+            LicenseEvent::Renewed,
+            LicenseEvent::Revoked,
+        ];
+        assert_eq!(events.try_iter().collect::<Vec<_>>(), expected_events);
+        assert_eq!(other_events.try_iter().collect::<Vec<_>>(), expected_events);
+        #[cfg(feature = "async")]
+        assert_eq!(
+            std::iter::from_fn(|| async_events.try_recv().ok()).collect::<Vec<_>>(),
+            expected_events
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn manager_revalidates_on_tokio() {
+        let source = MemoryLicenseStore::new();
+        source
+            .save(&signed_license("2024-10-01T00:00:00Z"))
+            .unwrap();
+        let clock = clock_at("2024-09-30T00:00:00Z");
+
+        let manager = LicenseManager::builder(verifier(), source, ValidationPolicy::new())
+            .clock(clock.clone())
+            .revalidate_every(Duration::from_millis(10))
+            .build_async();
+        assert_eq!(manager.status(), ManagerStatus::Valid);
+
+        clock.set("2024-10-02T00:00:00Z");
+        let expired = ManagerStatus::Invalid(LicenseLoadError::Verification(
+            LicenseVerificationError::LicenseExpired,
+        ));
+        let revalidated = async {
+            while manager.status() != expired {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), revalidated)
+            .await
+            .expect("Revalidation should report the expiration");
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_subscribers_receive_revalidation_events() {
+        let source = MemoryLicenseStore::new();
+        source
+            .save(&signed_license("2024-10-01T00:00:00Z"))
+            .unwrap();
+        let clock = clock_at("2024-09-30T00:00:00Z");
+
+        let manager = LicenseManager::builder(verifier(), source, ValidationPolicy::new())
+            .clock(clock.clone())
+            .expiring_soon_thresholds(&[datetime::Duration::hours(12)])
+            .revalidate_every(Duration::from_millis(10))
+            .build_async();
+        let mut events = manager.subscribe_async();
+
+        clock.set("2024-09-30T18:00:00Z");
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("Revalidation should report the remaining time");
+        assert_eq!(
+            event,
+            Ok(LicenseEvent::ExpiringSoon {
+                remaining: datetime::Duration::hours(6)
+            })
+        );
+
+        clock.set("2024-10-02T00:00:00Z");
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("Revalidation should report the expiration");
+        assert_eq!(event, Ok(LicenseEvent::Expired));
+    }
+
+    #[test]
+    fn manager_driven_by_mock_verifier() {
+        let license: License = serde_json::from_value(serde_json::json!({
+            "id": "ACME-2023-00481",
+            "expirationDate": "2024-10-01T00:00:00Z",
+            "customData": {}
+        }))
+        .unwrap();
+        let mock = Arc::new(MockVerifier::returning(license));
+        let source = MemoryLicenseStore::new();
+        source.save(&serde_json::json!({ "opaque": true })).unwrap();
+        let clock = clock_at("2024-09-20T00:00:00Z");
+
+        let manager = LicenseManager::builder(mock.clone(), source, ValidationPolicy::new())
+            .clock(clock.clone())
+            .build();
+        let events = manager.subscribe();
+        assert_eq!(manager.status(), ManagerStatus::Valid);
+        assert_eq!(manager.license().unwrap().alg(), "none");
+
+        mock.return_error(LicenseVerificationError::VerificationFailure);
+        assert_eq!(
+            manager.refresh_now(),
+            ManagerStatus::Invalid(LicenseLoadError::Verification(
+                LicenseVerificationError::VerificationFailure
+            ))
+        );
+        assert_eq!(events.try_recv(), Ok(LicenseEvent::Revoked));
+        assert_eq!(
+            mock.calls(),
+            [
+                serde_json::json!({ "opaque": true }),
+                serde_json::json!({ "opaque": true })
+            ]
+        );
+    }
+}
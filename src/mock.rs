@@ -0,0 +1,102 @@
+use crate::verification::{LicenseVerification, LicenseVerificationError};
+use crate::{License, VerifiedLicense};
+use std::sync::Mutex;
+
+pub struct MockVerifier {
+    response: Mutex<Result<License, LicenseVerificationError>>,
+    calls: Mutex<Vec<serde_json::Value>>,
+}
+
+impl MockVerifier {
+    pub fn returning(license: License) -> Self {
+        Self::with_response(Ok(license))
+    }
+
+    pub fn failing(error: LicenseVerificationError) -> Self {
+        Self::with_response(Err(error))
+    }
+
+    fn with_response(response: Result<License, LicenseVerificationError>) -> Self {
+        Self {
+            response: Mutex::new(response),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn return_license(&self, license: License) {
+        *self.response.lock().unwrap() = Ok(license);
+    }
+
+    pub fn return_error(&self, error: LicenseVerificationError) {
+        *self.response.lock().unwrap() = Err(error);
+    }
+
+    pub fn calls(&self) -> Vec<serde_json::Value> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl LicenseVerification for MockVerifier {
+    fn verify(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<License, LicenseVerificationError> {
+        self.calls.lock().unwrap().push(verifiable_license_json);
+        self.response.lock().unwrap().clone()
+    }
+
+    fn verify_license(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        self.verify(verifiable_license_json)
+            .map(VerifiedLicense::unverified_for_testing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license() -> License {
+        serde_json::from_value(serde_json::json!({
+            "id": "ACME-2023-00481",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": {}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn mock_verifier_records_calls() {
+        let mock = MockVerifier::returning(license());
+        let verifier: &dyn LicenseVerification = &mock;
+
+        assert_eq!(
+            verifier.verify(serde_json::json!({ "n": 1 })),
+            Ok(license())
+        );
+        mock.return_error(LicenseVerificationError::VerificationFailure);
+        assert_eq!(
+            verifier.verify(serde_json::json!({ "n": 2 })),
+            Err(LicenseVerificationError::VerificationFailure)
+        );
+
+        assert_eq!(
+            mock.calls(),
+            [serde_json::json!({ "n": 1 }), serde_json::json!({ "n": 2 })]
+        );
+    }
+
+    #[test]
+    fn mock_verifier_hands_out_unsigned_licenses() {
+        let mock = MockVerifier::returning(license());
+
+        let verified_license = mock
+            .verify_license(serde_json::json!({}))
+            .expect("Verification should succeed");
+        assert_eq!(verified_license.license(), &license());
+        assert_eq!(verified_license.alg(), "none");
+        assert!(verified_license.key_thumbprints().is_empty());
+    }
+}
@@ -0,0 +1,466 @@
+use crate::device::DeviceKey;
+use crate::request::{
+    sign_license_request, LicenseRequest, LicenseRequestBuilder, LicenseRequestError,
+};
+#[cfg(feature = "sign")]
+use crate::request::{verify_license_request, LicenseRequestPolicy};
+#[cfg(feature = "sign")]
+use crate::signing::LicenseSigner;
+use crate::signing::LicenseSigningError;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+#[cfg(feature = "sign")]
+use crate::License;
+use crate::VerifiableLicense;
+use serde_derive::{Deserialize, Serialize};
+use std::path::Path;
+
+const ACTIVATION_RESPONSE_TYP: &str = "jls-activation-response+jwt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OfflineActivationError {
+    Io(std::io::ErrorKind),
+    InvalidRequestFile,
+    InvalidResponseFile,
+    InvalidLicenseRequest(LicenseRequestError),
+    LicenseSigning(LicenseSigningError),
+    ResponseVerification(LicenseVerificationError),
+    StaleRequest,
+    MachineMismatch,
+    NonceMismatch,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RequestFile {
+    #[serde(rename = "licenseRequest")]
+    license_request: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct ResponseFile {
+    #[serde(rename = "activationResponse")]
+    activation_response: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct ActivationResponse {
+    nonce: String,
+    fingerprint: String,
+    #[serde(rename = "verifiableLicense")]
+    verifiable_license: serde_json::Value,
+}
+
+pub struct OfflineActivation<'a> {
+    device_key: &'a DeviceKey,
+    request: LicenseRequest,
+}
+
+impl<'a> OfflineActivation<'a> {
+    pub fn new(
+        device_key: &'a DeviceKey,
+        request_builder: LicenseRequestBuilder,
+    ) -> Result<Self, OfflineActivationError> {
+        Ok(Self {
+            request: request_builder
+                .build(device_key)
+                .map_err(OfflineActivationError::InvalidLicenseRequest)?,
+            device_key,
+        })
+    }
+
+    pub fn resume(device_key: &'a DeviceKey, request: LicenseRequest) -> Self {
+        Self {
+            device_key,
+            request,
+        }
+    }
+
+    pub fn request(&self) -> &LicenseRequest {
+        &self.request
+    }
+
+    pub fn export_request(&self, path: &Path) -> Result<(), OfflineActivationError> {
+        let request_file = RequestFile {
+            license_request: sign_license_request(&self.request, self.device_key)
+                .map_err(OfflineActivationError::InvalidLicenseRequest)?,
+        };
+        write_json(path, &request_file)
+    }
+
+    pub fn import_response(
+        &self,
+        path: &Path,
+        verifier: &LicenseVerifier,
+    ) -> Result<VerifiableLicense, OfflineActivationError> {
+        let response_file: ResponseFile =
+            read_json(path, OfflineActivationError::InvalidResponseFile)?;
+        let response: ActivationResponse = verifier
+            .reported(
+                verifier.verify_token(&response_file.activation_response, ACTIVATION_RESPONSE_TYP),
+            )
+            .map_err(OfflineActivationError::ResponseVerification)?;
+
+        if verifier.now() >= self.request.expires_at {
+            return Err(OfflineActivationError::StaleRequest);
+        }
+        if response.fingerprint != self.request.fingerprint {
+            return Err(OfflineActivationError::MachineMismatch);
+        }
+        if response.nonce != self.request.nonce {
+            return Err(OfflineActivationError::NonceMismatch);
+        }
+
+        verifier
+            .verify(response.verifiable_license.clone())
+            .map_err(OfflineActivationError::ResponseVerification)?;
+        serde_json::from_value(response.verifiable_license)
+            .map_err(|_| OfflineActivationError::InvalidResponseFile)
+    }
+}
+
+#[cfg(feature = "sign")]
+pub fn process_request<F>(
+    request_path: &Path,
+    response_path: &Path,
+    policy: &LicenseRequestPolicy,
+    signer: &LicenseSigner,
+    issue_license: F,
+) -> Result<LicenseRequest, OfflineActivationError>
+where
+    F: FnOnce(&LicenseRequest) -> License,
+{
+    let request_file: RequestFile =
+        read_json(request_path, OfflineActivationError::InvalidRequestFile)?;
+    let request = verify_license_request(&request_file.license_request, policy)
+        .map_err(OfflineActivationError::InvalidLicenseRequest)?;
+
+    let verifiable_license = signer
+        .sign(&issue_license(&request))
+        .map_err(OfflineActivationError::LicenseSigning)?;
+    let response = ActivationResponse {
+        nonce: request.nonce.clone(),
+        fingerprint: request.fingerprint.clone(),
+        verifiable_license,
+    };
+    let response_file = ResponseFile {
+        activation_response: signer.sign_token(ACTIVATION_RESPONSE_TYP, &response),
+    };
+    write_json(response_path, &response_file)?;
+
+    Ok(request)
+}
+
+// A file that cannot be read is an I/O failure; `invalid` is reserved for contents that do not parse
+fn read_json<T: serde::de::DeserializeOwned>(
+    path: &Path,
+    invalid: OfflineActivationError,
+) -> Result<T, OfflineActivationError> {
+    let contents = std::fs::read(path).map_err(|e| OfflineActivationError::Io(e.kind()))?;
+    serde_json::from_slice(&contents).map_err(|_| invalid)
+}
+
+fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> Result<(), OfflineActivationError> {
+    let contents = serde_json::to_vec_pretty(value).expect("Activation files are serializable");
+    std::fs::write(path, contents).map_err(|e| OfflineActivationError::Io(e.kind()))
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::datetime::Duration;
+    use crate::test_keys::{
+        signer, verifier, ISSUER_PUBLIC_KEY_JWK_JSON, OTHER_PRIVATE_KEY_JWK_JSON,
+    };
+    use crate::test_util::FixedClock;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn request_builder(machine_id: &str) -> LicenseRequestBuilder {
+        LicenseRequestBuilder::new("jls-pro").fingerprint_component("machineId", machine_id)
+    }
+
+    fn issue_license(request: &LicenseRequest) -> License {
+        License {
+            id: Uuid::new_v4().into(),
+            product: Some(request.product.clone()),
+            kind: None,
+            issuer: None,
+            issued_at: Some(request.issued_at),
+            expiration_date: request.issued_at + Duration::days(365),
+            custom_data: serde_json::json!({ "fingerprint": request.fingerprint }),
+            device_key: Some(request.device_public_key.clone()),
+            features: Vec::new(),
+            max_seats: None,
+            delegated_key: None,
+            activation_secret: None,
+            allowed_hosts: Vec::new(),
+            allowed_networks: Vec::new(),
+            upgrades_from: None,
+            state_required: false,
+            offline_validity_days: None,
+            maintenance_until: None,
+            watermark: None,
+        }
+    }
+
+    #[test]
+    fn offline_activation_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let request_path = dir.path().join("activation.req");
+        let response_path = dir.path().join("activation.resp");
+        let device_key = DeviceKey::generate();
+
+        let activation = OfflineActivation::new(&device_key, request_builder("machine-a")).unwrap();
+        activation.export_request(&request_path).unwrap();
+
+        process_request(
+            &request_path,
+            &response_path,
+            &LicenseRequestPolicy::new(),
+            &signer(),
+            issue_license,
+        )
+        .expect("Processing the request should work");
+
+        let verifiable_license = activation
+            .import_response(&response_path, &verifier())
+            .expect("Importing the response should work");
+        assert_eq!(
+            verifiable_license.license.custom_data["fingerprint"],
+            activation.request().fingerprint
+        );
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn offline_activation_round_trip_with_es256_device_key() {
+        use crate::request::LICENSE_REQUEST_TYP;
+        use crate::test_keys::{p256_device_token, P256_PUBLIC_KEY_JWK_JSON};
+
+        let dir = tempfile::tempdir().unwrap();
+        let request_path = dir.path().join("activation.req");
+        let response_path = dir.path().join("activation.resp");
+        let device_key = DeviceKey::generate();
+
+        let mut request = request_builder("machine-a").build(&device_key).unwrap();
+        request.device_public_key = P256_PUBLIC_KEY_JWK_JSON.clone();
+        let request_file = RequestFile {
+            license_request: p256_device_token(LICENSE_REQUEST_TYP, &request),
+        };
+        write_json(&request_path, &request_file).unwrap();
+
+        process_request(
+            &request_path,
+            &response_path,
+            &LicenseRequestPolicy::new(),
+            &signer(),
+            issue_license,
+        )
+        .expect("ES256 requests should be processed");
+
+        let activation = OfflineActivation::resume(&device_key, request);
+        let verifiable_license = activation
+            .import_response(&response_path, &verifier())
+            .expect("Importing the response should work");
+        assert_eq!(
+            verifiable_license.license.device_key,
+            Some(P256_PUBLIC_KEY_JWK_JSON.clone())
+        );
+    }
+
+    #[test]
+    fn offline_activation_with_unreadable_or_invalid_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let request_path = dir.path().join("activation.req");
+        let response_path = dir.path().join("activation.resp");
+        let device_key = DeviceKey::generate();
+        let activation = OfflineActivation::new(&device_key, request_builder("machine-a")).unwrap();
+
+        let result = process_request(
+            &request_path,
+            &response_path,
+            &LicenseRequestPolicy::new(),
+            &signer(),
+            issue_license,
+        );
+        assert_eq!(
+            result,
+            Err(OfflineActivationError::Io(std::io::ErrorKind::NotFound))
+        );
+        let result = activation.import_response(&response_path, &verifier());
+        assert_eq!(
+            result.unwrap_err(),
+            OfflineActivationError::Io(std::io::ErrorKind::NotFound)
+        );
+
+        std::fs::write(&request_path, b"not json").unwrap();
+        std::fs::write(&response_path, b"not json").unwrap();
+        let result = process_request(
+            &request_path,
+            &response_path,
+            &LicenseRequestPolicy::new(),
+            &signer(),
+            issue_license,
+        );
+        assert_eq!(result, Err(OfflineActivationError::InvalidRequestFile));
+        let result = activation.import_response(&response_path, &verifier());
+        assert_eq!(
+            result.unwrap_err(),
+            OfflineActivationError::InvalidResponseFile
+        );
+    }
+
+    #[test]
+    fn offline_activation_with_response_for_other_machine() {
+        let dir = tempfile::tempdir().unwrap();
+        let request_path = dir.path().join("activation.req");
+        let response_path = dir.path().join("activation.resp");
+        let device_key = DeviceKey::generate();
+
+        let activation = OfflineActivation::new(&device_key, request_builder("machine-a")).unwrap();
+        let other_activation =
+            OfflineActivation::new(&device_key, request_builder("machine-b")).unwrap();
+        other_activation.export_request(&request_path).unwrap();
+        process_request(
+            &request_path,
+            &response_path,
+            &LicenseRequestPolicy::new(),
+            &signer(),
+            issue_license,
+        )
+        .unwrap();
+
+        let result = activation.import_response(&response_path, &verifier());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, OfflineActivationError::MachineMismatch);
+    }
+
+    #[test]
+    fn offline_activation_with_response_for_previous_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let request_path = dir.path().join("activation.req");
+        let response_path = dir.path().join("activation.resp");
+        let device_key = DeviceKey::generate();
+
+        let previous_activation =
+            OfflineActivation::new(&device_key, request_builder("machine-a")).unwrap();
+        previous_activation.export_request(&request_path).unwrap();
+        process_request(
+            &request_path,
+            &response_path,
+            &LicenseRequestPolicy::new(),
+            &signer(),
+            issue_license,
+        )
+        .unwrap();
+
+        let activation = OfflineActivation::new(&device_key, request_builder("machine-a")).unwrap();
+        let result = activation.import_response(&response_path, &verifier());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, OfflineActivationError::NonceMismatch);
+    }
+
+    #[test]
+    fn offline_activation_with_stale_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let request_path = dir.path().join("activation.req");
+        let response_path = dir.path().join("activation.resp");
+        let device_key = DeviceKey::generate();
+        let clock = Arc::new(FixedClock::at("2024-09-01T00:00:00Z"));
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+
+        let activation = OfflineActivation::new(
+            &device_key,
+            request_builder("machine-a")
+                .issued_at(verifier.now())
+                .validity(Duration::hours(24)),
+        )
+        .unwrap();
+        activation.export_request(&request_path).unwrap();
+
+        let result = process_request(
+            &request_path,
+            &response_path,
+            &LicenseRequestPolicy::new(),
+            &signer(),
+            issue_license,
+        );
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            OfflineActivationError::InvalidLicenseRequest(
+                LicenseRequestError::ExpiredLicenseRequest
+            )
+        );
+
+        process_request(
+            &request_path,
+            &response_path,
+            &LicenseRequestPolicy::new().at(verifier.now()),
+            &signer(),
+            issue_license,
+        )
+        .unwrap();
+
+        clock.advance(Duration::hours(23));
+        activation
+            .import_response(&response_path, &verifier)
+            .expect("A response within the request's validity should import");
+
+        clock.advance(Duration::hours(1));
+        let result = activation.import_response(&response_path, &verifier);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, OfflineActivationError::StaleRequest);
+    }
+
+    #[test]
+    fn offline_activation_with_forged_response_in_opaque_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let request_path = dir.path().join("activation.req");
+        let response_path = dir.path().join("activation.resp");
+        let device_key = DeviceKey::generate();
+
+        let activation = OfflineActivation::new(&device_key, request_builder("machine-a")).unwrap();
+        activation.export_request(&request_path).unwrap();
+        process_request(
+            &request_path,
+            &response_path,
+            &LicenseRequestPolicy::new(),
+            &LicenseSigner::new(OTHER_PRIVATE_KEY_JWK_JSON.clone()).unwrap(),
+            issue_license,
+        )
+        .unwrap();
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let result = activation.import_response(&response_path, &verifier);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            OfflineActivationError::ResponseVerification(LicenseVerificationError::LicenseInvalid)
+        );
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::VerificationFailure]
+        );
+    }
+}
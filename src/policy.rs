@@ -0,0 +1,778 @@
+use crate::clock::saturating_add;
+use crate::datetime::{self, DateTime, Duration};
+use crate::downgrade::{DowngradePolicy, EffectiveEntitlements};
+use crate::id::LicenseId;
+use crate::state::LocalState;
+use crate::verification::LicenseVerificationError;
+use crate::License;
+use ipnet::IpNet;
+use std::net::IpAddr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SuspiciousClockAction {
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OfflineWindowAction {
+    Grace,
+    Fail,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationStatus {
+    Valid,
+    ClockSuspicious { now: DateTime, issued_at: DateTime },
+    OfflineWindowExceeded { last_online: Option<DateTime> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationPolicy {
+    expected_license_id: Option<LicenseId>,
+    expected_product: Option<String>,
+    host: Option<String>,
+    require_allowed_hosts: bool,
+    address: Option<IpAddr>,
+    clock_skew: Duration,
+    grace_period: Duration,
+    suspicious_clock_action: SuspiciousClockAction,
+    offline_window_action: OfflineWindowAction,
+    downgrade: Option<DowngradePolicy>,
+}
+
+impl ValidationPolicy {
+    pub fn new() -> Self {
+        Self {
+            expected_license_id: None,
+            expected_product: None,
+            host: None,
+            require_allowed_hosts: false,
+            address: None,
+            clock_skew: Duration::minutes(5),
+            grace_period: Duration::seconds(0),
+            suspicious_clock_action: SuspiciousClockAction::Warn,
+            offline_window_action: OfflineWindowAction::Fail,
+            downgrade: None,
+        }
+    }
+
+    pub fn expected_license_id(mut self, license_id: impl Into<LicenseId>) -> Self {
+        self.expected_license_id = Some(license_id.into());
+        self
+    }
+
+    pub fn expected_product(mut self, product: &str) -> Self {
+        self.expected_product = Some(product.to_owned());
+        self
+    }
+
+    pub fn bind_to_host(mut self, host: &str) -> Self {
+        self.host = Some(host.to_owned());
+        self
+    }
+
+    pub fn require_allowed_hosts(mut self) -> Self {
+        self.require_allowed_hosts = true;
+        self
+    }
+
+    pub fn bind_to_ip(mut self, address: IpAddr) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    pub fn clock_skew(mut self, clock_skew: Duration) -> Self {
+        self.clock_skew = clock_skew;
+        self
+    }
+
+    // Licenses keep validating for this long after their expiration date
+    pub fn grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    pub fn on_suspicious_clock(mut self, action: SuspiciousClockAction) -> Self {
+        self.suspicious_clock_action = action;
+        self
+    }
+
+    pub fn on_offline_window_exceeded(mut self, action: OfflineWindowAction) -> Self {
+        self.offline_window_action = action;
+        self
+    }
+
+    pub fn downgrade(mut self, downgrade: DowngradePolicy) -> Self {
+        self.downgrade = Some(downgrade);
+        self
+    }
+
+    pub fn validate(
+        &self,
+        license: &License,
+    ) -> Result<ValidationStatus, LicenseVerificationError> {
+        self.validate_at(license, datetime::now())
+    }
+
+    pub fn validate_at(
+        &self,
+        license: &License,
+        now: DateTime,
+    ) -> Result<ValidationStatus, LicenseVerificationError> {
+        self.check(license)?;
+        if now >= saturating_add(license.expiration_date, self.grace_period) {
+            return Err(LicenseVerificationError::LicenseExpired);
+        }
+
+        match license.issued_at {
+            Some(issued_at) if saturating_add(now, self.clock_skew) < issued_at => {
+                match self.suspicious_clock_action {
+                    SuspiciousClockAction::Warn => {
+                        Ok(ValidationStatus::ClockSuspicious { now, issued_at })
+                    }
+                    SuspiciousClockAction::Fail => {
+                        Err(LicenseVerificationError::ClockSuspicious { now, issued_at })
+                    }
+                }
+            }
+            _ => Ok(ValidationStatus::Valid),
+        }
+    }
+
+    pub fn entitlements_at(
+        &self,
+        license: &License,
+        now: DateTime,
+    ) -> Result<EffectiveEntitlements, LicenseVerificationError> {
+        match self.validate_at(license, now) {
+            Ok(_) => Ok(EffectiveEntitlements {
+                features: license.features_at(now),
+                degraded: false,
+            }),
+            Err(LicenseVerificationError::LicenseExpired) => self
+                .downgrade
+                .as_ref()
+                .and_then(|downgrade| downgrade.downgrade(license))
+                .ok_or(LicenseVerificationError::LicenseExpired),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn validate_offline_at(
+        &self,
+        license: &License,
+        state: Option<&LocalState>,
+        now: DateTime,
+    ) -> Result<ValidationStatus, LicenseVerificationError> {
+        let status = self.validate_at(license, now)?;
+        let Some(offline_validity_days) = license.offline_validity_days else {
+            return Ok(status);
+        };
+        if status != ValidationStatus::Valid {
+            return Ok(status);
+        }
+
+        let last_online = state.and_then(|state| state.last_online);
+        let within_window = last_online.is_some_and(|last_online| {
+            now - last_online <= Duration::days(offline_validity_days.into())
+        });
+        if within_window {
+            return Ok(ValidationStatus::Valid);
+        }
+
+        match self.offline_window_action {
+            OfflineWindowAction::Grace => {
+                Ok(ValidationStatus::OfflineWindowExceeded { last_online })
+            }
+            OfflineWindowAction::Fail => {
+                Err(LicenseVerificationError::OfflineWindowExceeded { last_online })
+            }
+        }
+    }
+
+    fn check(&self, license: &License) -> Result<(), LicenseVerificationError> {
+        if let Some(expected) = &self.expected_license_id {
+            if &license.id != expected {
+                return Err(LicenseVerificationError::LicenseIdMismatch {
+                    expected: expected.clone(),
+                    got: license.id.clone(),
+                });
+            }
+        }
+        if let Some(expected_product) = &self.expected_product {
+            if license.product.as_ref() != Some(expected_product) {
+                return Err(LicenseVerificationError::ProductOutOfScope);
+            }
+        }
+        if let Some(host) = &self.host {
+            check_host(license, host, self.require_allowed_hosts)?;
+        }
+        if let Some(address) = self.address {
+            check_address(license, address)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn check_host(
+    license: &License,
+    host: &str,
+    require_allowed_hosts: bool,
+) -> Result<(), LicenseVerificationError> {
+    if license.allowed_hosts.is_empty() && !require_allowed_hosts {
+        return Ok(());
+    }
+
+    let host = idna::domain_to_ascii(host.trim_end_matches('.'))
+        .map_err(|_| LicenseVerificationError::HostNotLicensed)?;
+    let licensed = license.allowed_hosts.iter().any(|pattern| {
+        let Ok(pattern) = idna::domain_to_ascii(pattern.trim_end_matches('.')) else {
+            return false;
+        };
+        match pattern.strip_prefix("*.") {
+            Some(parent) => host
+                .strip_suffix(parent)
+                .and_then(|label| label.strip_suffix('.'))
+                .is_some_and(|label| !label.is_empty() && !label.contains('.')),
+            None => host == pattern,
+        }
+    });
+    if !licensed {
+        return Err(LicenseVerificationError::HostNotLicensed);
+    }
+
+    Ok(())
+}
+
+impl Default for ValidationPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn check_address(license: &License, address: IpAddr) -> Result<(), LicenseVerificationError> {
+    if license.allowed_networks.is_empty() {
+        return Ok(());
+    }
+
+    let allowed_networks = license
+        .allowed_networks
+        .iter()
+        .map(|network| network.parse::<IpNet>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| LicenseVerificationError::InvalidClaimFormat("allowedNetworks".to_owned()))?;
+    if !allowed_networks
+        .iter()
+        .any(|network| network.contains(&address))
+    {
+        return Err(LicenseVerificationError::AddressNotLicensed);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::record_online_validation;
+    use crate::test_keys::license;
+    #[cfg(feature = "sign")]
+    use crate::test_keys::{signer, ISSUER_PUBLIC_KEY_JWK_JSON};
+    #[cfg(feature = "sign")]
+    use crate::test_util::FixedClock;
+    #[cfg(feature = "sign")]
+    use crate::verification::LicenseVerifier;
+    #[cfg(feature = "sign")]
+    use std::sync::Arc;
+    #[cfg(feature = "sign")]
+    use uuid::Uuid;
+
+    #[cfg(feature = "sign")]
+    fn sign(license: &License) -> serde_json::Value {
+        signer().sign(license).unwrap()
+    }
+
+    #[cfg(feature = "sign")]
+    fn verifiable_license() -> serde_json::Value {
+        sign(&license())
+    }
+
+    #[cfg(feature = "sign")]
+    fn network_bound_license(allowed_networks: &[&str]) -> serde_json::Value {
+        let mut license = license();
+        license.allowed_networks = allowed_networks
+            .iter()
+            .map(|&network| network.to_owned())
+            .collect();
+        sign(&license)
+    }
+
+    #[cfg(feature = "sign")]
+    fn host_bound_license(allowed_hosts: &[&str]) -> serde_json::Value {
+        let mut license = license();
+        license.allowed_hosts = allowed_hosts.iter().map(|&host| host.to_owned()).collect();
+        sign(&license)
+    }
+
+    #[cfg(feature = "sign")]
+    fn verifier_at(now: &str) -> LicenseVerifier {
+        LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .clock(Arc::new(FixedClock::at(now)))
+            .build()
+            .expect("Verifier instantiation must work")
+    }
+
+    #[cfg(feature = "sign")]
+    fn verifier() -> LicenseVerifier {
+        verifier_at("2024-09-01T00:00:00Z")
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn policy_with_matching_license_id() {
+        let policy = ValidationPolicy::new().expected_license_id(license().id);
+
+        let verified_license = verifier()
+            .verify_with_policy(verifiable_license(), &policy)
+            .expect("Verification should succeed");
+        assert_eq!(*verified_license, license());
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn policy_with_different_license_id() {
+        let expected = Uuid::parse_str("5b4ea9a4-3bd4-4c7f-a4e4-b1c7c0e8c0d3").unwrap();
+        let policy = ValidationPolicy::new().expected_license_id(expected);
+
+        let result = verifier().verify_with_policy(verifiable_license(), &policy);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::LicenseIdMismatch {
+                expected: expected.into(),
+                got: license().id,
+            }
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn default_policy_accepts_any_license_id() {
+        let verified_license = verifier()
+            .verify_with_policy(verifiable_license(), &ValidationPolicy::default())
+            .expect("Verification should succeed");
+        assert_eq!(verified_license.id, license().id);
+    }
+
+    #[test]
+    fn policy_with_expected_product() {
+        let mut licensed_product = license();
+        licensed_product.product = Some("acme-cad".to_owned());
+        let policy = ValidationPolicy::new().expected_product("acme-cad");
+        let now = datetime::parse("2024-09-01T00:00:00Z").unwrap();
+
+        assert_eq!(
+            policy.validate_at(&licensed_product, now),
+            Ok(ValidationStatus::Valid)
+        );
+        let result = policy.validate_at(&license(), now);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::ProductOutOfScope);
+    }
+
+    #[test]
+    fn policy_with_grace_period() {
+        let policy = ValidationPolicy::new().grace_period(Duration::days(7));
+
+        assert_eq!(
+            policy.validate_at(&license(), datetime::parse("2024-10-07T23:59:59Z").unwrap()),
+            Ok(ValidationStatus::Valid)
+        );
+        let result =
+            policy.validate_at(&license(), datetime::parse("2024-10-08T00:00:00Z").unwrap());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::LicenseExpired);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn verification_with_policy_grace_period() {
+        let policy = ValidationPolicy::new().grace_period(Duration::days(7));
+
+        verifier_at("2024-10-05T00:00:00Z")
+            .verify_with_policy(verifiable_license(), &policy)
+            .expect("Verification should succeed");
+        let result =
+            verifier_at("2024-10-08T00:00:00Z").verify_with_policy(verifiable_license(), &policy);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::LicenseExpired);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn verification_with_policy_suspicious_clock() {
+        let mut license = license();
+        license.issued_at = Some(datetime::parse("2024-09-01T00:00:00Z").unwrap());
+        let policy = ValidationPolicy::new().on_suspicious_clock(SuspiciousClockAction::Fail);
+
+        let result =
+            verifier_at("2024-08-01T00:00:00Z").verify_with_policy(sign(&license), &policy);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::ClockSuspicious {
+                now: datetime::parse("2024-08-01T00:00:00Z").unwrap(),
+                issued_at: datetime::parse("2024-09-01T00:00:00Z").unwrap(),
+            }
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn policy_with_exact_host_match() {
+        let verifiable_license = host_bound_license(&["licensed.example.com"]);
+        let policy = ValidationPolicy::new().bind_to_host("Licensed.Example.COM");
+
+        verifier()
+            .verify_with_policy(verifiable_license, &policy)
+            .expect("Verification should succeed");
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn policy_with_wildcard_host_match() {
+        let verifiable_license = host_bound_license(&["*.example.com"]);
+        let policy = ValidationPolicy::new().bind_to_host("eu.example.com");
+
+        verifier()
+            .verify_with_policy(verifiable_license.clone(), &policy)
+            .expect("Verification should succeed");
+
+        let policy = ValidationPolicy::new().bind_to_host("example.com");
+        let result = verifier().verify_with_policy(verifiable_license, &policy);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::HostNotLicensed);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn policy_with_unlicensed_host() {
+        let verifiable_license = host_bound_license(&["licensed.example.com", "*.example.org"]);
+        let policy = ValidationPolicy::new().bind_to_host("other.example.com");
+
+        let result = verifier().verify_with_policy(verifiable_license, &policy);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::HostNotLicensed);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn policy_with_unicode_host() {
+        let verifiable_license = host_bound_license(&["xn--bcher-kva.example"]);
+        let policy = ValidationPolicy::new().bind_to_host("BÜCHER.example");
+
+        verifier()
+            .verify_with_policy(verifiable_license, &policy)
+            .expect("Verification should succeed");
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn policy_with_host_and_license_without_allowed_hosts() {
+        let policy = ValidationPolicy::new().bind_to_host("any.example.com");
+        verifier()
+            .verify_with_policy(verifiable_license(), &policy)
+            .expect("Verification should succeed");
+
+        let result =
+            verifier().verify_with_policy(verifiable_license(), &policy.require_allowed_hosts());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::HostNotLicensed);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn policy_with_address_in_ipv4_network() {
+        let verifiable_license = network_bound_license(&["10.20.0.0/16", "192.0.2.0/24"]);
+        let policy = ValidationPolicy::new().bind_to_ip("192.0.2.77".parse().unwrap());
+
+        verifier()
+            .verify_with_policy(verifiable_license, &policy)
+            .expect("Verification should succeed");
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn policy_with_address_in_ipv6_network() {
+        let verifiable_license = network_bound_license(&["10.20.0.0/16", "2001:db8:abcd::/48"]);
+        let policy = ValidationPolicy::new().bind_to_ip("2001:db8:abcd:12::1".parse().unwrap());
+
+        verifier()
+            .verify_with_policy(verifiable_license, &policy)
+            .expect("Verification should succeed");
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn policy_with_address_outside_networks() {
+        let verifiable_license = network_bound_license(&["10.20.0.0/16", "2001:db8:abcd::/48"]);
+
+        for address in ["10.21.0.1", "2001:db8:abce::1"] {
+            let policy = ValidationPolicy::new().bind_to_ip(address.parse().unwrap());
+            let result = verifier().verify_with_policy(verifiable_license.clone(), &policy);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::AddressNotLicensed);
+        }
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn policy_with_malformed_network_claim() {
+        let verifiable_license = network_bound_license(&["10.20.0.0/16", "10.30.0.0/33"]);
+        let policy = ValidationPolicy::new().bind_to_ip("10.20.0.1".parse().unwrap());
+
+        let result = verifier().verify_with_policy(verifiable_license, &policy);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::InvalidClaimFormat("allowedNetworks".to_owned())
+        );
+    }
+
+    fn issued_license() -> License {
+        let mut license = license();
+        license.issued_at = Some(datetime::parse("2024-03-01T00:00:00Z").unwrap());
+        license.expiration_date = datetime::parse("2025-03-01T00:00:00Z").unwrap();
+        license
+    }
+
+    #[test]
+    fn validation_within_license_term() {
+        let now = datetime::parse("2024-06-01T00:00:00Z").unwrap();
+
+        let status = ValidationPolicy::new().validate_at(&issued_license(), now);
+        assert_eq!(status, Ok(ValidationStatus::Valid));
+
+        let now = datetime::parse("2024-02-29T23:58:00Z").unwrap();
+        let status = ValidationPolicy::new().validate_at(&issued_license(), now);
+        assert_eq!(status, Ok(ValidationStatus::Valid));
+    }
+
+    #[test]
+    fn validation_after_expiration() {
+        let now = datetime::parse("2025-03-01T00:00:00Z").unwrap();
+
+        let result = ValidationPolicy::new().validate_at(&issued_license(), now);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::LicenseExpired);
+    }
+
+    #[test]
+    fn validation_with_clock_before_issuance_warns() {
+        let now = datetime::parse("2023-01-01T00:00:00Z").unwrap();
+
+        let status = ValidationPolicy::new().validate_at(&issued_license(), now);
+        assert_eq!(
+            status,
+            Ok(ValidationStatus::ClockSuspicious {
+                now,
+                issued_at: issued_license().issued_at.unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn validation_with_clock_before_issuance_fails() {
+        let now = datetime::parse("2023-01-01T00:00:00Z").unwrap();
+        let policy = ValidationPolicy::new()
+            .clock_skew(Duration::seconds(0))
+            .on_suspicious_clock(SuspiciousClockAction::Fail);
+
+        let result = policy.validate_at(&issued_license(), now);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::ClockSuspicious {
+                now,
+                issued_at: issued_license().issued_at.unwrap(),
+            }
+        );
+    }
+
+    fn subscription_license() -> License {
+        let mut license = issued_license();
+        license.offline_validity_days = Some(30);
+        license
+    }
+
+    fn state_online_at(last_online: DateTime) -> LocalState {
+        let mut state = LocalState::new(datetime::parse("2024-03-01T00:00:00Z").unwrap());
+        record_online_validation(&mut state, last_online);
+        state
+    }
+
+    #[test]
+    fn offline_validation_inside_window() {
+        let last_online = datetime::parse("2024-06-01T00:00:00Z").unwrap();
+        let state = state_online_at(last_online);
+
+        let status = ValidationPolicy::new().validate_offline_at(
+            &subscription_license(),
+            Some(&state),
+            last_online + Duration::days(30),
+        );
+        assert_eq!(status, Ok(ValidationStatus::Valid));
+    }
+
+    #[test]
+    fn offline_validation_just_after_window() {
+        let last_online = datetime::parse("2024-06-01T00:00:00Z").unwrap();
+        let state = state_online_at(last_online);
+        let now = last_online + Duration::days(30) + Duration::seconds(1);
+
+        let result =
+            ValidationPolicy::new().validate_offline_at(&subscription_license(), Some(&state), now);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::OfflineWindowExceeded {
+                last_online: Some(last_online)
+            }
+        );
+
+        let status = ValidationPolicy::new()
+            .on_offline_window_exceeded(OfflineWindowAction::Grace)
+            .validate_offline_at(&subscription_license(), Some(&state), now);
+        assert_eq!(
+            status,
+            Ok(ValidationStatus::OfflineWindowExceeded {
+                last_online: Some(last_online)
+            })
+        );
+    }
+
+    #[test]
+    fn offline_validation_without_state() {
+        let now = datetime::parse("2024-06-01T00:00:00Z").unwrap();
+
+        let result =
+            ValidationPolicy::new().validate_offline_at(&subscription_license(), None, now);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::OfflineWindowExceeded { last_online: None }
+        );
+
+        let status = ValidationPolicy::new().validate_offline_at(&issued_license(), None, now);
+        assert_eq!(status, Ok(ValidationStatus::Valid));
+    }
+
+    fn downgrade_policy() -> ValidationPolicy {
+        ValidationPolicy::new().downgrade(
+            DowngradePolicy::new()
+                .kind("subscription", &["viewer", "export-pdf"])
+                .product("jls-pro", &["viewer"])
+                .fallback(&[]),
+        )
+    }
+
+    fn pro_subscription() -> License {
+        let mut license = issued_license();
+        license.product = Some("jls-pro".to_owned());
+        license.kind = Some("subscription".to_owned());
+        license.features = vec!["editor".into(), "viewer".into()];
+        license
+    }
+
+    #[test]
+    fn entitlements_of_valid_license() {
+        let now = datetime::parse("2024-06-01T00:00:00Z").unwrap();
+
+        let entitlements = downgrade_policy().entitlements_at(&pro_subscription(), now);
+        assert_eq!(
+            entitlements,
+            Ok(EffectiveEntitlements {
+                features: vec!["editor".to_owned(), "viewer".to_owned()],
+                degraded: false,
+            })
+        );
+    }
+
+    #[test]
+    fn entitlements_of_expired_license() {
+        let now = datetime::parse("2025-06-01T00:00:00Z").unwrap();
+
+        let entitlements = downgrade_policy().entitlements_at(&pro_subscription(), now);
+        assert_eq!(
+            entitlements,
+            Ok(EffectiveEntitlements {
+                features: vec!["viewer".to_owned(), "export-pdf".to_owned()],
+                degraded: true,
+            })
+        );
+
+        let mut license = pro_subscription();
+        license.kind = None;
+        let entitlements = downgrade_policy().entitlements_at(&license, now);
+        assert_eq!(
+            entitlements,
+            Ok(EffectiveEntitlements {
+                features: vec!["viewer".to_owned()],
+                degraded: true,
+            })
+        );
+
+        let result = ValidationPolicy::new().entitlements_at(&license, now);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::LicenseExpired);
+    }
+
+    #[test]
+    fn entitlements_of_perpetual_license() {
+        let mut license = pro_subscription();
+        license.expiration_date = datetime::parse("9999-12-31T23:59:59Z").unwrap();
+        let now = datetime::parse("2099-06-01T00:00:00Z").unwrap();
+
+        let entitlements = downgrade_policy().entitlements_at(&license, now);
+        assert_eq!(
+            entitlements,
+            Ok(EffectiveEntitlements {
+                features: vec!["editor".to_owned(), "viewer".to_owned()],
+                degraded: false,
+            })
+        );
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(feature = "sign")]
+use crate::datetime;
+use crate::datetime::DateTime;
+use crate::id::LicenseId;
+#[cfg(feature = "sign")]
+use crate::signing::LicenseSigner;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::License;
+use serde_derive::{Deserialize, Serialize};
+
+const ACTIVATION_RECEIPT_TYP: &str = "jls-activation-receipt+jwt";
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ActivationReceipt {
+    #[serde(rename = "licenseId")]
+    pub license_id: LicenseId,
+    #[serde(rename = "activationCount")]
+    pub activation_count: u32,
+    pub fingerprint: String,
+    #[serde(rename = "activatedAt", with = "crate::datetime::rfc3339")]
+    pub activated_at: DateTime,
+}
+
+#[cfg(feature = "sign")]
+impl LicenseSigner {
+    pub fn issue_activation_receipt(
+        &self,
+        license: &License,
+        activation_count: u32,
+        fingerprint: &str,
+    ) -> String {
+        let receipt = ActivationReceipt {
+            license_id: license.id.clone(),
+            activation_count,
+            fingerprint: fingerprint.to_owned(),
+            activated_at: datetime::now(),
+        };
+        self.sign_token(ACTIVATION_RECEIPT_TYP, &receipt)
+    }
+}
+
+impl LicenseVerifier {
+    pub fn verify_activation_receipt(
+        &self,
+        receipt: &str,
+    ) -> Result<ActivationReceipt, LicenseVerificationError> {
+        self.reported(self.verify_token(receipt, ACTIVATION_RECEIPT_TYP))
+    }
+
+    pub fn verify_activation_receipt_for(
+        &self,
+        receipt: &str,
+        license: &License,
+    ) -> Result<ActivationReceipt, LicenseVerificationError> {
+        self.reported(self.verify_token(receipt, ACTIVATION_RECEIPT_TYP).and_then(
+            |receipt: ActivationReceipt| {
+                if receipt.license_id != license.id {
+                    return Err(LicenseVerificationError::ReceiptLicenseMismatch);
+                }
+                Ok(receipt)
+            },
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::jws;
+    use crate::test_keys::{license, signer, verifier, ISSUER_PUBLIC_KEY_JWK_JSON};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    #[test]
+    fn activation_receipt_round_trip() {
+        let receipt = signer().issue_activation_receipt(&license(), 2, "machine-a");
+
+        let verified_receipt = verifier()
+            .verify_activation_receipt_for(&receipt, &license())
+            .expect("Verification should succeed");
+        assert_eq!(verified_receipt.license_id, license().id);
+        assert_eq!(verified_receipt.activation_count, 2);
+        assert_eq!(verified_receipt.fingerprint, "machine-a");
+    }
+
+    #[test]
+    fn activation_receipt_for_other_license() {
+        let mut other_license = license();
+        other_license.id = Uuid::new_v4().into();
+        let receipt = signer().issue_activation_receipt(&other_license, 1, "machine-a");
+
+        let result = verifier().verify_activation_receipt_for(&receipt, &license());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::ReceiptLicenseMismatch);
+    }
+
+    #[test]
+    fn activation_receipt_with_tampered_counter() {
+        let receipt = signer().issue_activation_receipt(&license(), 1, "machine-a");
+        let mut parts = receipt.split('.').map(str::to_owned).collect::<Vec<_>>();
+        let mut claims: serde_json::Value =
+            serde_json::from_slice(&jws::decode(&parts[1]).unwrap()).unwrap();
+        claims["activationCount"] = serde_json::json!(0);
+        parts[1] = jws::encode(&serde_json::to_vec(&claims).unwrap());
+        let tampered_receipt = parts.join(".");
+
+        let result = verifier().verify_activation_receipt(&tampered_receipt);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::VerificationFailure);
+    }
+
+    #[test]
+    fn activation_receipt_failures_in_opaque_mode() {
+        let mut other_license = license();
+        other_license.id = Uuid::new_v4().into();
+        let receipt = signer().issue_activation_receipt(&other_license, 1, "machine-a");
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let result = verifier.verify_activation_receipt_for(&receipt, &license());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::LicenseInvalid);
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::ReceiptLicenseMismatch]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use crate::policy::ValidationPolicy;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::VerifiedLicense;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistryError {
+    InvalidVerifiableLicense,
+    MissingIssuer,
+    UnknownIssuer(String),
+    Verification(LicenseVerificationError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistryVerification {
+    pub issuer: String,
+    pub license: VerifiedLicense,
+}
+
+#[derive(Default)]
+pub struct VerifierRegistry {
+    issuers: BTreeMap<String, (LicenseVerifier, ValidationPolicy)>,
+}
+
+impl VerifierRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, issuer: &str, verifier: LicenseVerifier, policy: ValidationPolicy) {
+        self.issuers.insert(issuer.to_owned(), (verifier, policy));
+    }
+
+    pub fn verify(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<RegistryVerification, RegistryError> {
+        // The claim is unverified at this point and only used to pick the verifier
+        let issuer = verifiable_license_json
+            .get("license")
+            .ok_or(RegistryError::InvalidVerifiableLicense)?
+            .get("issuer")
+            .and_then(|v| v.as_str())
+            .ok_or(RegistryError::MissingIssuer)?
+            .to_owned();
+        let (verifier, policy) = self
+            .issuers
+            .get(&issuer)
+            .ok_or_else(|| RegistryError::UnknownIssuer(issuer.clone()))?;
+
+        let license = verifier
+            .verify_with_policy(verifiable_license_json, policy)
+            .map_err(RegistryError::Verification)?;
+
+        Ok(RegistryVerification { issuer, license })
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::signing::LicenseSigner;
+    use crate::test_keys::{
+        ISSUER_PRIVATE_KEY_JWK_JSON, ISSUER_PUBLIC_KEY_JWK_JSON, OTHER_PRIVATE_KEY_JWK_JSON,
+        OTHER_PUBLIC_KEY_JWK_JSON,
+    };
+    use crate::License;
+
+    fn license(issuer: Option<&str>) -> License {
+        let mut license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": {}
+        }))
+        .unwrap();
+        license.issuer = issuer.map(|issuer| issuer.to_owned());
+        license
+    }
+
+    fn sign(private_key: &serde_json::Value, license: &License) -> serde_json::Value {
+        LicenseSigner::new(private_key.clone())
+            .unwrap()
+            .sign(license)
+            .unwrap()
+    }
+
+    fn registry() -> VerifierRegistry {
+        let mut registry = VerifierRegistry::new();
+        registry.register(
+            "acme",
+            LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone()).unwrap(),
+            ValidationPolicy::new(),
+        );
+        registry.register(
+            "globex",
+            LicenseVerifier::new(OTHER_PUBLIC_KEY_JWK_JSON.clone()).unwrap(),
+            ValidationPolicy::new(),
+        );
+        registry
+    }
+
+    #[test]
+    fn registry_routes_by_issuer() {
+        let registry = registry();
+
+        let verification = registry
+            .verify(sign(&ISSUER_PRIVATE_KEY_JWK_JSON, &license(Some("acme"))))
+            .expect("Verification should succeed");
+        assert_eq!(verification.issuer, "acme");
+
+        let verification = registry
+            .verify(sign(&OTHER_PRIVATE_KEY_JWK_JSON, &license(Some("globex"))))
+            .expect("Verification should succeed");
+        assert_eq!(verification.issuer, "globex");
+        assert_eq!(*verification.license, license(Some("globex")));
+    }
+
+    #[test]
+    fn registry_with_forged_issuer() {
+        let result = registry().verify(sign(&OTHER_PRIVATE_KEY_JWK_JSON, &license(Some("acme"))));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            RegistryError::Verification(LicenseVerificationError::VerificationFailure)
+        );
+    }
+
+    #[test]
+    fn registry_with_unknown_or_missing_issuer() {
+        let result = registry().verify(sign(
+            &ISSUER_PRIVATE_KEY_JWK_JSON,
+            &license(Some("initech")),
+        ));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, RegistryError::UnknownIssuer("initech".to_owned()));
+
+        let result = registry().verify(sign(&ISSUER_PRIVATE_KEY_JWK_JSON, &license(None)));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, RegistryError::MissingIssuer);
+    }
+}
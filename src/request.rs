@@ -0,0 +1,405 @@
+use crate::datetime::{self, DateTime, Duration};
+use crate::device::{DeviceKey, DeviceKeyError, DevicePublicKey};
+use crate::jws;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+pub(crate) const LICENSE_REQUEST_TYP: &str = "jls-request+jwt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseRequestError {
+    InvalidLicenseRequest,
+    InvalidDeviceKey,
+    VerificationFailure,
+    ExpiredLicenseRequest,
+    ReplayedLicenseRequest,
+    ProductMismatch,
+    InvalidValidity,
+    ValidityTooLong,
+    IssuedInTheFuture,
+    DeviceKey(DeviceKeyError),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LicenseRequest {
+    pub product: String,
+    pub fingerprint: String,
+    pub nonce: String,
+    #[serde(rename = "devicePublicKey")]
+    pub device_public_key: serde_json::Value,
+    #[serde(rename = "issuedAt", with = "crate::datetime::rfc3339")]
+    pub issued_at: DateTime,
+    #[serde(rename = "expiresAt", with = "crate::datetime::rfc3339")]
+    pub expires_at: DateTime,
+}
+
+pub struct LicenseRequestBuilder {
+    product: String,
+    fingerprint_components: BTreeMap<String, String>,
+    nonce: Option<String>,
+    issued_at: Option<DateTime>,
+    validity: Duration,
+}
+
+impl LicenseRequestBuilder {
+    pub fn new(product: &str) -> Self {
+        Self {
+            product: product.to_owned(),
+            fingerprint_components: BTreeMap::new(),
+            nonce: None,
+            issued_at: None,
+            validity: Duration::hours(24),
+        }
+    }
+
+    pub fn fingerprint_component(mut self, name: &str, value: &str) -> Self {
+        self.fingerprint_components
+            .insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    pub fn nonce(mut self, nonce: &str) -> Self {
+        self.nonce = Some(nonce.to_owned());
+        self
+    }
+
+    pub fn issued_at(mut self, issued_at: DateTime) -> Self {
+        self.issued_at = Some(issued_at);
+        self
+    }
+
+    pub fn validity(mut self, validity: Duration) -> Self {
+        self.validity = validity;
+        self
+    }
+
+    pub fn build(self, device_key: &DeviceKey) -> Result<LicenseRequest, LicenseRequestError> {
+        let issued_at = self.issued_at.unwrap_or_else(datetime::now);
+        if self.validity <= Duration::seconds(0) {
+            return Err(LicenseRequestError::InvalidValidity);
+        }
+        let expires_at = datetime::checked_add(issued_at, self.validity)
+            .ok_or(LicenseRequestError::InvalidValidity)?;
+        Ok(LicenseRequest {
+            product: self.product,
+            fingerprint: fingerprint(&self.fingerprint_components),
+            nonce: self.nonce.unwrap_or_else(random_nonce),
+            device_public_key: device_key.public_jwk(),
+            issued_at,
+            expires_at,
+        })
+    }
+
+    pub fn sign(self, device_key: &DeviceKey) -> Result<String, LicenseRequestError> {
+        sign_license_request(&self.build(device_key)?, device_key)
+    }
+}
+
+pub fn sign_license_request(
+    request: &LicenseRequest,
+    device_key: &DeviceKey,
+) -> Result<String, LicenseRequestError> {
+    device_key
+        .sign_token(LICENSE_REQUEST_TYP, request)
+        .map_err(LicenseRequestError::DeviceKey)
+}
+
+// Nonces are remembered until their request expires: an expired request is rejected anyway, so the
+// replay cache never holds more than the requests accepted within `max_validity`
+pub struct LicenseRequestPolicy {
+    now: Option<DateTime>,
+    expected_product: Option<String>,
+    max_validity: Duration,
+    clock_skew: Duration,
+    seen_nonces: Mutex<HashMap<String, DateTime>>,
+}
+
+impl LicenseRequestPolicy {
+    pub fn new() -> Self {
+        Self {
+            now: None,
+            expected_product: None,
+            max_validity: Duration::hours(24),
+            clock_skew: Duration::minutes(5),
+            seen_nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn at(mut self, now: DateTime) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    pub fn expected_product(mut self, product: &str) -> Self {
+        self.expected_product = Some(product.to_owned());
+        self
+    }
+
+    // Requests asking to stay valid for longer are rejected, whatever validity the client chose
+    pub fn max_validity(mut self, max_validity: Duration) -> Self {
+        self.max_validity = max_validity;
+        self
+    }
+
+    pub fn clock_skew(mut self, clock_skew: Duration) -> Self {
+        self.clock_skew = clock_skew;
+        self
+    }
+}
+
+impl Default for LicenseRequestPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn verify_license_request(
+    signed_request: &str,
+    policy: &LicenseRequestPolicy,
+) -> Result<LicenseRequest, LicenseRequestError> {
+    let compact =
+        jws::parse_compact(signed_request).ok_or(LicenseRequestError::InvalidLicenseRequest)?;
+    if compact.header.typ.as_deref() != Some(LICENSE_REQUEST_TYP) {
+        return Err(LicenseRequestError::InvalidLicenseRequest);
+    }
+
+    let request: LicenseRequest = serde_json::from_slice(&compact.payload)
+        .map_err(|_| LicenseRequestError::InvalidLicenseRequest)?;
+    let device_public_key = DevicePublicKey::from_jwk(&request.device_public_key)
+        .map_err(|_| LicenseRequestError::InvalidDeviceKey)?;
+    // Software device keys sign EdDSA and TPM-backed ones ES256: the request's own key decides
+    if compact.header.alg != device_public_key.alg() {
+        return Err(LicenseRequestError::InvalidLicenseRequest);
+    }
+    device_public_key
+        .verify(compact.signing_input.as_bytes(), &compact.signature)
+        .map_err(|_| LicenseRequestError::VerificationFailure)?;
+
+    let now = policy.now.unwrap_or_else(datetime::now);
+    if request.expires_at <= request.issued_at {
+        return Err(LicenseRequestError::InvalidValidity);
+    }
+    if request.expires_at - request.issued_at > policy.max_validity {
+        return Err(LicenseRequestError::ValidityTooLong);
+    }
+    let issued_in_the_future = datetime::checked_add(now, policy.clock_skew)
+        .is_some_and(|latest| request.issued_at > latest);
+    if issued_in_the_future {
+        return Err(LicenseRequestError::IssuedInTheFuture);
+    }
+    if now >= request.expires_at {
+        return Err(LicenseRequestError::ExpiredLicenseRequest);
+    }
+    if let Some(expected_product) = &policy.expected_product {
+        if expected_product != &request.product {
+            return Err(LicenseRequestError::ProductMismatch);
+        }
+    }
+
+    let mut seen_nonces = policy.seen_nonces.lock().unwrap();
+    seen_nonces.retain(|_, expires_at| now < *expires_at);
+    if seen_nonces.contains_key(&request.nonce) {
+        return Err(LicenseRequestError::ReplayedLicenseRequest);
+    }
+    seen_nonces.insert(request.nonce.clone(), request.expires_at);
+
+    Ok(request)
+}
+
+fn fingerprint(components: &BTreeMap<String, String>) -> String {
+    let mut hasher = Sha256::new();
+    for (name, value) in components {
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        hasher.update(value.as_bytes());
+        hasher.update([0]);
+    }
+    jws::encode(&hasher.finalize())
+}
+
+fn random_nonce() -> String {
+    let mut nonce = [0u8; 16];
+    OsRng.fill_bytes(&mut nonce);
+    jws::encode(&nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_builder() -> LicenseRequestBuilder {
+        LicenseRequestBuilder::new("jls-pro")
+            .fingerprint_component("machineId", "4c4c4544-0034")
+            .fingerprint_component("hostname", "build-01")
+    }
+
+    #[test]
+    fn license_request_round_trip() {
+        let device_key = DeviceKey::generate();
+        let signed_request = request_builder().sign(&device_key).unwrap();
+
+        let policy = LicenseRequestPolicy::new().expected_product("jls-pro");
+        let request =
+            verify_license_request(&signed_request, &policy).expect("Verification should succeed");
+
+        assert_eq!(request.product, "jls-pro");
+        assert_eq!(request.device_public_key, device_key.public_jwk());
+        assert_eq!(
+            request.fingerprint,
+            request_builder().build(&device_key).unwrap().fingerprint
+        );
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn es256_license_request_round_trip() {
+        use crate::test_keys::{p256_device_token, P256_PUBLIC_KEY_JWK_JSON};
+
+        let mut request = request_builder().build(&DeviceKey::generate()).unwrap();
+        request.device_public_key = P256_PUBLIC_KEY_JWK_JSON.clone();
+        let signed_request = p256_device_token(LICENSE_REQUEST_TYP, &request);
+
+        let verified_request =
+            verify_license_request(&signed_request, &LicenseRequestPolicy::new())
+                .expect("ES256 requests should verify");
+        assert_eq!(verified_request, request);
+
+        // The header must match the request's key, not just any supported algorithm
+        let software_key = DeviceKey::generate();
+        let mut request = request_builder().build(&software_key).unwrap();
+        request.device_public_key = P256_PUBLIC_KEY_JWK_JSON.clone();
+        let signed_request = sign_license_request(&request, &software_key).unwrap();
+        let result = verify_license_request(&signed_request, &LicenseRequestPolicy::new());
+        assert_eq!(result, Err(LicenseRequestError::InvalidLicenseRequest));
+    }
+
+    #[test]
+    fn license_request_replayed() {
+        let device_key = DeviceKey::generate();
+        let signed_request = request_builder().sign(&device_key).unwrap();
+        let policy = LicenseRequestPolicy::new();
+
+        verify_license_request(&signed_request, &policy).expect("First use should succeed");
+
+        let result = verify_license_request(&signed_request, &policy);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseRequestError::ReplayedLicenseRequest);
+    }
+
+    #[test]
+    fn license_request_expired() {
+        let device_key = DeviceKey::generate();
+        let issued_at = datetime::now() - Duration::hours(2);
+        let signed_request = request_builder()
+            .issued_at(issued_at)
+            .validity(Duration::hours(1))
+            .sign(&device_key)
+            .unwrap();
+
+        let result = verify_license_request(&signed_request, &LicenseRequestPolicy::new());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseRequestError::ExpiredLicenseRequest);
+    }
+
+    #[test]
+    fn license_request_signed_by_other_device() {
+        let device_key = DeviceKey::generate();
+        let other_device_key = DeviceKey::generate();
+        let request = request_builder().build(&device_key).unwrap();
+        let signed_request = sign_license_request(&request, &other_device_key).unwrap();
+
+        let result = verify_license_request(&signed_request, &LicenseRequestPolicy::new());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseRequestError::VerificationFailure);
+    }
+
+    #[test]
+    fn license_request_with_unrepresentable_validity() {
+        let device_key = DeviceKey::generate();
+
+        for validity in [Duration::MAX, Duration::seconds(0), Duration::hours(-1)] {
+            let result = request_builder().validity(validity).build(&device_key);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseRequestError::InvalidValidity);
+        }
+    }
+
+    #[test]
+    fn license_request_with_validity_beyond_policy() {
+        let device_key = DeviceKey::generate();
+        let signed_request = request_builder()
+            .validity(Duration::days(30))
+            .sign(&device_key)
+            .unwrap();
+
+        let result = verify_license_request(&signed_request, &LicenseRequestPolicy::new());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseRequestError::ValidityTooLong);
+
+        let policy = LicenseRequestPolicy::new().max_validity(Duration::days(30));
+        verify_license_request(&signed_request, &policy).expect("Verification should succeed");
+    }
+
+    #[test]
+    fn license_request_issued_in_the_future() {
+        let device_key = DeviceKey::generate();
+        let now = datetime::now();
+        let signed_request = request_builder()
+            .issued_at(now + Duration::hours(1))
+            .sign(&device_key)
+            .unwrap();
+
+        let result = verify_license_request(&signed_request, &LicenseRequestPolicy::new().at(now));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseRequestError::IssuedInTheFuture);
+
+        let skewed_request = request_builder()
+            .issued_at(now + Duration::minutes(1))
+            .sign(&device_key)
+            .unwrap();
+        verify_license_request(&skewed_request, &LicenseRequestPolicy::new().at(now))
+            .expect("Verification should succeed");
+    }
+
+    #[test]
+    fn license_request_nonces_expire_with_their_requests() {
+        let device_key = DeviceKey::generate();
+        let issued_at = datetime::now();
+        let policy = LicenseRequestPolicy::new().at(issued_at);
+        for nonce in ["nonce-1", "nonce-2"] {
+            let signed_request = request_builder()
+                .nonce(nonce)
+                .issued_at(issued_at)
+                .validity(Duration::hours(1))
+                .sign(&device_key)
+                .unwrap();
+            verify_license_request(&signed_request, &policy).expect("First use should succeed");
+        }
+        assert_eq!(policy.seen_nonces.lock().unwrap().len(), 2);
+
+        let later = issued_at + Duration::hours(2);
+        let policy = policy.at(later);
+        let signed_request = request_builder()
+            .issued_at(later)
+            .sign(&device_key)
+            .unwrap();
+        verify_license_request(&signed_request, &policy).expect("Verification should succeed");
+        assert_eq!(policy.seen_nonces.lock().unwrap().len(), 1);
+    }
+}
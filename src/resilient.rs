@@ -0,0 +1,401 @@
+use crate::datetime::{DateTime, Duration};
+use crate::device::DeviceKey;
+use crate::store::{LicenseStore, LicenseStoreError};
+use crate::verification::{LicenseVerification, LicenseVerificationError};
+use crate::License;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseLoadError {
+    Store(LicenseStoreError),
+    Verification(LicenseVerificationError),
+    InvalidCache,
+    CacheTooOld,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResilientLoadError {
+    pub primary_error: LicenseLoadError,
+    pub cache_error: LicenseLoadError,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseStatus {
+    Verified,
+    UsingCachedLicense { primary_error: LicenseLoadError },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedLicense {
+    pub license: License,
+    pub status: LicenseStatus,
+}
+
+const CACHED_LICENSE_TYP: &str = "jls-cached-license+jwt";
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+struct CachedLicense {
+    #[serde(rename = "verifiableLicense")]
+    verifiable_license: serde_json::Value,
+    #[serde(rename = "cachedAt", with = "crate::datetime::rfc3339")]
+    cached_at: DateTime,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct SignedCachedLicense {
+    #[serde(flatten)]
+    cached_license: CachedLicense,
+    #[serde(
+        rename = "cacheValidation",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    cache_validation: Option<String>,
+}
+
+pub struct ResilientLicenseLoader<'a> {
+    verifier: &'a dyn LicenseVerification,
+    primary: Box<dyn LicenseStore + 'a>,
+    cache: Box<dyn LicenseStore + 'a>,
+    max_cache_age: Duration,
+    device_key: Option<&'a DeviceKey>,
+}
+
+impl<'a> ResilientLicenseLoader<'a> {
+    pub fn new(
+        verifier: &'a dyn LicenseVerification,
+        primary: Box<dyn LicenseStore + 'a>,
+        cache: Box<dyn LicenseStore + 'a>,
+    ) -> Self {
+        Self {
+            verifier,
+            primary,
+            cache,
+            max_cache_age: Duration::days(7),
+            device_key: None,
+        }
+    }
+
+    pub fn max_cache_age(mut self, max_cache_age: Duration) -> Self {
+        self.max_cache_age = max_cache_age;
+        self
+    }
+
+    // Without a device key anyone able to write the cache can move `cachedAt` forward and keep a
+    // stale license around; with one, only caches written by this device are accepted
+    pub fn device_key(mut self, device_key: &'a DeviceKey) -> Self {
+        self.device_key = Some(device_key);
+        self
+    }
+
+    pub fn load(&self) -> Result<LoadedLicense, ResilientLoadError> {
+        let primary_error = match self.load_primary() {
+            Ok(license) => {
+                return Ok(LoadedLicense {
+                    license,
+                    status: LicenseStatus::Verified,
+                })
+            }
+            Err(error) => error,
+        };
+
+        match self.load_cache() {
+            Ok(license) => Ok(LoadedLicense {
+                license,
+                status: LicenseStatus::UsingCachedLicense { primary_error },
+            }),
+            Err(cache_error) => Err(ResilientLoadError {
+                primary_error,
+                cache_error,
+            }),
+        }
+    }
+
+    fn load_primary(&self) -> Result<License, LicenseLoadError> {
+        let verifiable_license = self.primary.load().map_err(LicenseLoadError::Store)?;
+        let license = self
+            .verifier
+            .verify(verifiable_license.clone())
+            .map_err(LicenseLoadError::Verification)?;
+
+        let cached_license = CachedLicense {
+            verifiable_license,
+            cached_at: self.verifier.now(),
+        };
+        let cache_validation = self
+            .device_key
+            .map(|device_key| device_key.sign_token(CACHED_LICENSE_TYP, &cached_license))
+            .transpose();
+        // A cache that cannot be refreshed must not turn a verified license into a failure.
+        if let Ok(cache_validation) = cache_validation {
+            let cached_license = SignedCachedLicense {
+                cache_validation,
+                cached_license,
+            };
+            let cached_license =
+                serde_json::to_value(cached_license).expect("Cached licenses are serializable");
+            let _ = self.cache.save(&cached_license);
+        }
+
+        Ok(license)
+    }
+
+    fn load_cache(&self) -> Result<License, LicenseLoadError> {
+        let signed_cached_license: SignedCachedLicense =
+            serde_json::from_value(self.cache.load().map_err(LicenseLoadError::Store)?)
+                .map_err(|_| LicenseLoadError::InvalidCache)?;
+        let cached_license = signed_cached_license.cached_license;
+        if let Some(device_key) = self.device_key {
+            let token = signed_cached_license
+                .cache_validation
+                .ok_or(LicenseLoadError::InvalidCache)?;
+            let protected_cached_license: CachedLicense = device_key
+                .public_key()
+                .verify_token_reported(self.verifier, &token, CACHED_LICENSE_TYP)
+                .map_err(|error| match error {
+                    LicenseVerificationError::LicenseInvalid => {
+                        LicenseLoadError::Verification(error)
+                    }
+                    _ => LicenseLoadError::InvalidCache,
+                })?;
+            if protected_cached_license != cached_license {
+                return Err(LicenseLoadError::InvalidCache);
+            }
+        }
+
+        let now = self.verifier.now();
+        if cached_license.cached_at > now {
+            return Err(LicenseLoadError::InvalidCache);
+        }
+        if now - cached_license.cached_at > self.max_cache_age {
+            return Err(LicenseLoadError::CacheTooOld);
+        }
+
+        self.verifier
+            .verify(cached_license.verifiable_license)
+            .map_err(LicenseLoadError::Verification)
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::datetime;
+    use crate::store::{FileLicenseStore, MemoryLicenseStore};
+    use crate::test_keys::{license, signer, verifier, ISSUER_PUBLIC_KEY_JWK_JSON};
+    use crate::test_util::FixedClock;
+    use crate::verification::LicenseVerifier;
+    use std::sync::Arc;
+
+    fn verifiable_license() -> serde_json::Value {
+        signer().sign(&license()).unwrap()
+    }
+
+    fn verifier_with_clock(clock: Arc<FixedClock>) -> LicenseVerifier {
+        LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .clock(clock)
+            .build()
+            .expect("Verifier instantiation must work")
+    }
+
+    #[test]
+    fn resilient_loader_with_valid_primary() {
+        let dir = tempfile::tempdir().unwrap();
+        let license_path = dir.path().join("license.json");
+        std::fs::write(&license_path, verifiable_license().to_string()).unwrap();
+        let verifier = verifier();
+        let cache = MemoryLicenseStore::new();
+
+        let loader = ResilientLicenseLoader::new(
+            &verifier,
+            Box::new(FileLicenseStore::new(&license_path)),
+            Box::new(&cache),
+        );
+
+        let loaded_license = loader.load().expect("Loading should work");
+        assert_eq!(loaded_license.license, license());
+        assert_eq!(loaded_license.status, LicenseStatus::Verified);
+        assert_eq!(
+            cache.load().unwrap()["verifiableLicense"],
+            verifiable_license()
+        );
+    }
+
+    #[test]
+    fn resilient_loader_with_corrupted_primary_and_valid_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let license_path = dir.path().join("license.json");
+        std::fs::write(&license_path, verifiable_license().to_string()).unwrap();
+        let verifier = verifier();
+        let cache = MemoryLicenseStore::new();
+        let loader = ResilientLicenseLoader::new(
+            &verifier,
+            Box::new(FileLicenseStore::new(&license_path)),
+            Box::new(&cache),
+        );
+        loader.load().unwrap();
+
+        std::fs::write(&license_path, b"{\"license\": {\"id\"").unwrap();
+
+        let loaded_license = loader
+            .load()
+            .expect("Loading should fall back to the cache");
+        assert_eq!(loaded_license.license, license());
+        assert_eq!(
+            loaded_license.status,
+            LicenseStatus::UsingCachedLicense {
+                primary_error: LicenseLoadError::Store(LicenseStoreError::InvalidContents)
+            }
+        );
+    }
+
+    #[test]
+    fn resilient_loader_with_cache_too_old() {
+        let dir = tempfile::tempdir().unwrap();
+        let license_path = dir.path().join("license.json");
+        std::fs::write(&license_path, verifiable_license().to_string()).unwrap();
+        let clock = Arc::new(FixedClock::at("2024-09-01T00:00:00Z"));
+        let verifier = verifier_with_clock(clock.clone());
+        let cache = MemoryLicenseStore::new();
+        let loader = ResilientLicenseLoader::new(
+            &verifier,
+            Box::new(FileLicenseStore::new(&license_path)),
+            Box::new(&cache),
+        );
+        loader.load().unwrap();
+        assert_eq!(cache.load().unwrap()["cachedAt"], "2024-09-01T00:00:00Z");
+        std::fs::remove_file(&license_path).unwrap();
+
+        clock.advance(Duration::days(7));
+        loader
+            .load()
+            .expect("Loading should fall back to the cache");
+
+        clock.advance(Duration::seconds(1));
+        let result = loader.load();
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            ResilientLoadError {
+                primary_error: LicenseLoadError::Store(LicenseStoreError::NotFound),
+                cache_error: LicenseLoadError::CacheTooOld,
+            }
+        );
+    }
+
+    #[test]
+    fn resilient_loader_with_cache_from_the_future() {
+        let dir = tempfile::tempdir().unwrap();
+        let license_path = dir.path().join("license.json");
+        std::fs::write(&license_path, verifiable_license().to_string()).unwrap();
+        let clock = Arc::new(FixedClock::at("2024-09-01T00:00:00Z"));
+        let verifier = verifier_with_clock(clock.clone());
+        let cache = MemoryLicenseStore::new();
+        let loader = ResilientLicenseLoader::new(
+            &verifier,
+            Box::new(FileLicenseStore::new(&license_path)),
+            Box::new(&cache),
+        );
+        loader.load().unwrap();
+        std::fs::remove_file(&license_path).unwrap();
+
+        clock.set(datetime::parse("2024-08-31T00:00:00Z").unwrap());
+        let result = loader.load();
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error.cache_error, LicenseLoadError::InvalidCache);
+    }
+
+    #[test]
+    fn resilient_loader_with_device_bound_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let license_path = dir.path().join("license.json");
+        std::fs::write(&license_path, verifiable_license().to_string()).unwrap();
+        let clock = Arc::new(FixedClock::at("2024-09-01T00:00:00Z"));
+        let verifier = verifier_with_clock(clock.clone());
+        let device_key = DeviceKey::generate();
+        let cache = MemoryLicenseStore::new();
+        let loader = ResilientLicenseLoader::new(
+            &verifier,
+            Box::new(FileLicenseStore::new(&license_path)),
+            Box::new(&cache),
+        )
+        .device_key(&device_key);
+        loader.load().unwrap();
+        std::fs::remove_file(&license_path).unwrap();
+
+        let loaded_license = loader
+            .load()
+            .expect("Loading should fall back to the cache");
+        assert_eq!(loaded_license.license, license());
+
+        // Moving `cachedAt` forward breaks the device signature over it
+        clock.advance(Duration::days(6));
+        let mut contents = cache.load().unwrap();
+        contents["cachedAt"] = serde_json::json!("2024-09-07T00:00:00Z");
+        cache.save(&contents).unwrap();
+        let result = loader.load();
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error.cache_error, LicenseLoadError::InvalidCache);
+
+        contents.as_object_mut().unwrap().remove("cacheValidation");
+        cache.save(&contents).unwrap();
+        let result = loader.load();
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error.cache_error, LicenseLoadError::InvalidCache);
+    }
+
+    #[test]
+    fn resilient_loader_with_cache_from_other_device_in_opaque_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let license_path = dir.path().join("license.json");
+        std::fs::write(&license_path, verifiable_license().to_string()).unwrap();
+        let cache = MemoryLicenseStore::new();
+        let other_device_key = DeviceKey::generate();
+        ResilientLicenseLoader::new(
+            &verifier(),
+            Box::new(FileLicenseStore::new(&license_path)),
+            Box::new(&cache),
+        )
+        .device_key(&other_device_key)
+        .load()
+        .unwrap();
+        std::fs::remove_file(&license_path).unwrap();
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let opaque_verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let device_key = DeviceKey::generate();
+        let loader = ResilientLicenseLoader::new(
+            &opaque_verifier,
+            Box::new(FileLicenseStore::new(&license_path)),
+            Box::new(&cache),
+        )
+        .device_key(&device_key);
+        let result = loader.load();
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error.cache_error,
+            LicenseLoadError::Verification(LicenseVerificationError::LicenseInvalid)
+        );
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::VerificationFailure]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use crate::id::LicenseId;
+use crate::jws;
+use crate::License;
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeatLockError {
+    Io(std::io::ErrorKind),
+}
+
+#[derive(Debug)]
+pub enum SeatAcquisition {
+    SeatAcquired(SeatGuard),
+    NoSeatsAvailable { in_use: u32 },
+}
+
+// Holds an advisory lock on one slot file until dropped. The operating system releases the lock
+// when a process dies, so slot files left behind by crashed instances are simply locked again.
+#[derive(Debug)]
+pub struct SeatGuard {
+    slot: Option<(u32, File, PathBuf)>,
+}
+
+impl SeatGuard {
+    // None for licenses without a seat limit
+    pub fn slot(&self) -> Option<u32> {
+        self.slot.as_ref().map(|(slot, _, _)| *slot)
+    }
+
+    pub fn lock_file(&self) -> Option<&Path> {
+        self.slot.as_ref().map(|(_, _, path)| path.as_path())
+    }
+}
+
+impl Drop for SeatGuard {
+    fn drop(&mut self) {
+        if let Some((_, file, _)) = &self.slot {
+            let _ = FileExt::unlock(file);
+        }
+    }
+}
+
+pub fn acquire_seat(license: &License, lock_dir: &Path) -> Result<SeatAcquisition, SeatLockError> {
+    let Some(seats) = license.max_seats else {
+        return Ok(SeatAcquisition::SeatAcquired(SeatGuard { slot: None }));
+    };
+    std::fs::create_dir_all(lock_dir).map_err(|e| SeatLockError::Io(e.kind()))?;
+
+    for slot in 0..seats {
+        let path = lock_dir.join(format!("{}.{}.lock", file_name(&license.id), slot));
+        // Slot files are never removed: deleting one could race with another instance locking it
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| SeatLockError::Io(e.kind()))?;
+        match file.try_lock_exclusive() {
+            Ok(()) => {
+                return Ok(SeatAcquisition::SeatAcquired(SeatGuard {
+                    slot: Some((slot, file, path)),
+                }))
+            }
+            Err(e) if e.kind() == fs2::lock_contended_error().kind() => continue,
+            Err(e) => return Err(SeatLockError::Io(e.kind())),
+        }
+    }
+    Ok(SeatAcquisition::NoSeatsAvailable { in_use: seats })
+}
+
+// Opaque ids may contain path separators, so they are encoded into a file-name-safe form
+fn file_name(id: &LicenseId) -> String {
+    match id {
+        LicenseId::Uuid(uuid) => uuid.hyphenated().to_string(),
+        LicenseId::Opaque(id) => format!("opaque-{}", jws::encode(id.as_bytes())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license(max_seats: Option<u32>) -> License {
+        let mut license = serde_json::json!({
+            "id": "ACME/2023/00481",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": {}
+        });
+        if let Some(max_seats) = max_seats {
+            license["maxSeats"] = serde_json::json!(max_seats);
+        }
+        serde_json::from_value(license).unwrap()
+    }
+
+    fn acquired(acquisition: SeatAcquisition) -> SeatGuard {
+        match acquisition {
+            SeatAcquisition::SeatAcquired(guard) => guard,
+            SeatAcquisition::NoSeatsAvailable { in_use } => {
+                panic!("A seat was expected, {} in use", in_use)
+            }
+        }
+    }
+
+    #[test]
+    fn third_instance_waits_for_a_free_seat() {
+        let dir = tempfile::tempdir().unwrap();
+        let license = license(Some(2));
+
+        let acquisitions = std::thread::scope(|scope| {
+            let handles = (0..3)
+                .map(|_| scope.spawn(|| acquire_seat(&license, dir.path()).unwrap()))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        let mut guards = vec![];
+        let mut rejections = vec![];
+        for acquisition in acquisitions {
+            match acquisition {
+                SeatAcquisition::SeatAcquired(guard) => guards.push(guard),
+                SeatAcquisition::NoSeatsAvailable { in_use } => rejections.push(in_use),
+            }
+        }
+        assert_eq!(rejections, [2]);
+        let mut slots = guards.iter().map(|guard| guard.slot()).collect::<Vec<_>>();
+        slots.sort();
+        assert_eq!(slots, [Some(0), Some(1)]);
+
+        let released_slot = guards[0].slot();
+        drop(guards.remove(0));
+        let guard = acquired(acquire_seat(&license, dir.path()).unwrap());
+        assert_eq!(guard.slot(), released_slot);
+    }
+
+    #[test]
+    fn stale_lock_files_are_reclaimed() {
+        let dir = tempfile::tempdir().unwrap();
+        let license = license(Some(1));
+        // A crashed instance leaves its slot file behind, but the lock died with the process
+        let stale_lock_file = dir
+            .path()
+            .join(format!("{}.0.lock", file_name(&license.id)));
+        std::fs::write(&stale_lock_file, b"crashed").unwrap();
+
+        let guard = acquired(acquire_seat(&license, dir.path()).unwrap());
+        assert_eq!(guard.slot(), Some(0));
+        assert_eq!(guard.lock_file(), Some(stale_lock_file.as_path()));
+        assert_eq!(
+            std::fs::read_dir(dir.path()).unwrap().count(),
+            1,
+            "Opaque ids must not escape the lock directory"
+        );
+    }
+
+    #[test]
+    fn unlimited_licenses_bypass_seat_locks() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_dir = dir.path().join("seats");
+
+        let guards = (0..3)
+            .map(|_| acquired(acquire_seat(&license(None), &lock_dir).unwrap()))
+            .collect::<Vec<_>>();
+        assert!(guards.iter().all(|guard| guard.slot().is_none()));
+        assert!(!lock_dir.exists());
+    }
+}
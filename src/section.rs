@@ -0,0 +1,177 @@
+#[cfg(feature = "sign")]
+use crate::signing::LicenseSigner;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::License;
+use serde_derive::{Deserialize, Serialize};
+
+pub(crate) const CUSTOM_SECTION_TYP: &str = "jls-custom-section+json";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomSectionError {
+    NotFound,
+    Unsigned,
+    SectionMismatch(String),
+    Verification(LicenseVerificationError),
+}
+
+// The section name is signed along with the data so a signed section cannot be moved under
+// another name.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct SectionClaims {
+    section: String,
+    data: serde_json::Value,
+}
+
+#[cfg(feature = "sign")]
+impl LicenseSigner {
+    // Produces a flattened JWS to embed as `customData[name]`, independent from the license signature
+    pub fn sign_custom_section(&self, name: &str, data: &serde_json::Value) -> serde_json::Value {
+        let claims = SectionClaims {
+            section: name.to_owned(),
+            data: data.clone(),
+        };
+        let token = self.sign_token(CUSTOM_SECTION_TYP, &claims);
+        let mut segments = token.splitn(3, '.');
+        serde_json::json!({
+            "protected": segments.next(),
+            "payload": segments.next(),
+            "signature": segments.next(),
+        })
+    }
+}
+
+impl License {
+    pub fn verify_custom_section(
+        &self,
+        name: &str,
+        verifier: &LicenseVerifier,
+    ) -> Result<serde_json::Value, CustomSectionError> {
+        let section = self
+            .custom_data
+            .get(name)
+            .ok_or(CustomSectionError::NotFound)?;
+        let token = signed_section_token(section).ok_or(CustomSectionError::Unsigned)?;
+
+        let claims: SectionClaims = verifier
+            .reported(verifier.verify_token(&token, CUSTOM_SECTION_TYP))
+            .map_err(CustomSectionError::Verification)?;
+        if claims.section != name {
+            return Err(CustomSectionError::SectionMismatch(claims.section));
+        }
+        Ok(claims.data)
+    }
+}
+
+// Only objects made of exactly the flattened JWS members count as signed sections
+fn signed_section_token(section: &serde_json::Value) -> Option<String> {
+    let section = section.as_object().filter(|section| section.len() == 3)?;
+    let segment = |name: &str| section.get(name).and_then(|v| v.as_str());
+    Some(format!(
+        "{}.{}.{}",
+        segment("protected")?,
+        segment("payload")?,
+        segment("signature")?
+    ))
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::test_keys::{
+        ISSUER_PRIVATE_KEY_JWK_JSON, ISSUER_PUBLIC_KEY_JWK_JSON, OTHER_PRIVATE_KEY_JWK_JSON,
+        OTHER_PUBLIC_KEY_JWK_JSON,
+    };
+    use lazy_static::lazy_static;
+
+    lazy_static! {
+        static ref SUPPORT_CONTRACT: serde_json::Value = serde_json::json!({
+            "reseller": "Globex Resellers",
+            "level": "24x7",
+            "until": "2025-10-01"
+        });
+        static ref VENDOR_VERIFIER: LicenseVerifier =
+            LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+                .expect("Verifier instantiation must work");
+        static ref RESELLER_VERIFIER: LicenseVerifier =
+            LicenseVerifier::new(OTHER_PUBLIC_KEY_JWK_JSON.clone())
+                .expect("Verifier instantiation must work");
+    }
+
+    fn license_with_support_section() -> serde_json::Value {
+        let support = LicenseSigner::new(OTHER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign_custom_section("support", &SUPPORT_CONTRACT);
+        let license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": {
+                "owner": "John Doe",
+                "support": support
+            }
+        }))
+        .unwrap();
+        LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license)
+            .unwrap()
+    }
+
+    #[test]
+    fn verify_reseller_section() {
+        let license = VENDOR_VERIFIER
+            .verify(license_with_support_section())
+            .expect("Verification should succeed");
+
+        let support = license
+            .verify_custom_section("support", &RESELLER_VERIFIER)
+            .expect("Section verification should succeed");
+        assert_eq!(support, *SUPPORT_CONTRACT);
+    }
+
+    #[test]
+    fn invalid_custom_sections() {
+        let license = VENDOR_VERIFIER
+            .verify(license_with_support_section())
+            .unwrap();
+        let mut tampered_license = license.clone();
+        let forged_payload = crate::jws::encode(
+            &serde_json::to_vec(&serde_json::json!({
+                "section": "support",
+                "data": { "reseller": "Globex Resellers", "level": "24x7", "until": "2035-10-01" }
+            }))
+            .unwrap(),
+        );
+        tampered_license.custom_data["support"]["payload"] = serde_json::json!(forged_payload);
+        let mut moved_license = license.clone();
+        moved_license.custom_data["warranty"] = license.custom_data["support"].clone();
+
+        let cases = [
+            (
+                license.verify_custom_section("support", &VENDOR_VERIFIER),
+                CustomSectionError::Verification(LicenseVerificationError::VerificationFailure),
+            ),
+            (
+                tampered_license.verify_custom_section("support", &RESELLER_VERIFIER),
+                CustomSectionError::Verification(LicenseVerificationError::VerificationFailure),
+            ),
+            (
+                moved_license.verify_custom_section("warranty", &RESELLER_VERIFIER),
+                CustomSectionError::SectionMismatch("support".to_owned()),
+            ),
+            (
+                license.verify_custom_section("owner", &RESELLER_VERIFIER),
+                CustomSectionError::Unsigned,
+            ),
+            (
+                license.verify_custom_section("warranty", &RESELLER_VERIFIER),
+                CustomSectionError::NotFound,
+            ),
+        ];
+        for (result, expected) in cases {
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, expected);
+        }
+    }
+}
@@ -0,0 +1,415 @@
+use crate::config::{VerifierConfig, VerifierConfigError};
+use crate::datetime;
+use crate::policy::{ValidationPolicy, ValidationStatus};
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::VerifiedLicense;
+use axum::body::Bytes;
+use axum::extract::rejection::BytesRejection;
+use axum::extract::{DefaultBodyLimit, Request, State};
+use axum::http::StatusCode;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::net::TcpListener;
+use tracing::Instrument;
+
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerError {
+    Config(VerifierConfigError),
+    Io(std::io::ErrorKind),
+}
+
+impl From<VerifierConfigError> for ServerError {
+    fn from(error: VerifierConfigError) -> Self {
+        ServerError::Config(error)
+    }
+}
+
+// Every request runs in a `request` span carrying its method, path and response status.
+// Verification failures are also reported through the verifier's audit sink.
+pub struct LicenseServer {
+    verifier: LicenseVerifier,
+    policy: ValidationPolicy,
+}
+
+impl LicenseServer {
+    pub fn new(verifier: LicenseVerifier, policy: ValidationPolicy) -> Self {
+        Self { verifier, policy }
+    }
+
+    pub fn from_config_file(path: &Path) -> Result<Self, ServerError> {
+        let config = VerifierConfig::load(path)?;
+        Ok(Self::new(
+            config.verifier(None)?,
+            config.validation_policy()?,
+        ))
+    }
+
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/healthz", get(health))
+            .route("/verify", post(verify))
+            .method_not_allowed_fallback(|| async {
+                error(StatusCode::METHOD_NOT_ALLOWED, "method_not_allowed")
+            })
+            .fallback(|| async { error(StatusCode::NOT_FOUND, "not_found") })
+            .layer(DefaultBodyLimit::max(MAX_BODY_SIZE))
+            .layer(middleware::from_fn(trace))
+            .with_state(Arc::new(self))
+    }
+
+    // Serves until `shutdown` completes, then lets the requests in flight finish
+    pub async fn serve(
+        self,
+        listener: TcpListener,
+        shutdown: impl Future<Output = ()> + Send + 'static,
+    ) -> Result<(), ServerError> {
+        axum::serve(listener, self.router())
+            .with_graceful_shutdown(shutdown)
+            .await
+            .map_err(|e| ServerError::Io(e.kind()))
+    }
+
+    fn verify(
+        &self,
+        verifiable_license: Value,
+    ) -> Result<(VerifiedLicense, ValidationStatus), LicenseVerificationError> {
+        let verified_license = self.verifier.verify_license(verifiable_license)?;
+        let status = self.status(&verified_license)?;
+        Ok((verified_license, status))
+    }
+
+    // The configured verifier already enforces the policy, this only recovers the status it
+    // tolerated
+    fn status(
+        &self,
+        verified_license: &VerifiedLicense,
+    ) -> Result<ValidationStatus, LicenseVerificationError> {
+        self.verifier.reported(
+            self.policy
+                .validate_at(verified_license, verified_license.verified_at()),
+        )
+    }
+}
+
+async fn trace(request: Request, next: Next) -> Response {
+    let span = tracing::info_span!(
+        "request",
+        method = %request.method(),
+        path = %request.uri().path(),
+        status = tracing::field::Empty,
+    );
+    let started = Instant::now();
+    let response = next.run(request).instrument(span.clone()).await;
+    span.record("status", response.status().as_u16());
+    tracing::info!(
+        parent: &span,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "request served"
+    );
+    response
+}
+
+async fn health() -> Response {
+    Json(json!({ "status": "ok" })).into_response()
+}
+
+async fn verify(
+    State(server): State<Arc<LicenseServer>>,
+    body: Result<Bytes, BytesRejection>,
+) -> Response {
+    let body = match body {
+        Ok(body) => body,
+        Err(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+            return error(StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large");
+        }
+        Err(_) => return malformed(),
+    };
+    let Ok(verifiable_license) = serde_json::from_slice(&body) else {
+        return malformed();
+    };
+    // Signature checks are CPU bound and would otherwise hold up the runtime's workers
+    let result = tokio::task::spawn_blocking(move || server.verify(verifiable_license)).await;
+    match result {
+        Ok(Ok((verified_license, status))) => Json(json!({
+            "status": status_json(&status),
+            "license": verified_license.license(),
+        }))
+        .into_response(),
+        Ok(Err(verification_error)) => {
+            tracing::warn!(code = verification_error.code(), "license rejected");
+            error(StatusCode::UNPROCESSABLE_ENTITY, verification_error.code())
+        }
+        Err(_) => error(StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+    }
+}
+
+fn error(status: StatusCode, code: &str) -> Response {
+    (status, Json(json!({ "error": { "code": code } }))).into_response()
+}
+
+fn malformed() -> Response {
+    error(StatusCode::BAD_REQUEST, "malformed_request")
+}
+
+fn status_json(status: &ValidationStatus) -> Value {
+    match status {
+        ValidationStatus::Valid => json!({ "code": "valid" }),
+        ValidationStatus::ClockSuspicious { now, issued_at } => json!({
+            "code": "clock_suspicious",
+            "now": datetime::to_rfc3339(*now),
+            "issuedAt": datetime::to_rfc3339(*issued_at),
+        }),
+        ValidationStatus::OfflineWindowExceeded { last_online } => json!({
+            "code": "offline_window_exceeded",
+            "lastOnline": last_online.map(datetime::to_rfc3339),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "sign")]
+    use crate::signing::LicenseSigner;
+    #[cfg(feature = "sign")]
+    use crate::test_keys::ISSUER_PRIVATE_KEY_JWK_JSON;
+    use crate::test_keys::ISSUER_PUBLIC_KEY_JWK_JSON;
+    #[cfg(feature = "sign")]
+    use crate::License;
+    use std::collections::BTreeMap;
+    use std::fmt::Debug;
+    use std::net::SocketAddr;
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    async fn start() -> SocketAddr {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("issuer.jwk"),
+            serde_json::to_vec(&*ISSUER_PUBLIC_KEY_JWK_JSON).unwrap(),
+        )
+        .unwrap();
+        let path = dir.path().join("verifier.json");
+        std::fs::write(
+            &path,
+            serde_json::to_vec(&json!({ "key": { "path": "issuer.jwk" } })).unwrap(),
+        )
+        .unwrap();
+        let server =
+            LicenseServer::from_config_file(&path).expect("Server configuration must load");
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Server must bind an ephemeral port");
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(server.serve(listener, std::future::pending()));
+        address
+    }
+
+    async fn request(address: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        );
+        // The server may answer before reading everything, so a failed write is not an error
+        let _ = stream.write_all(request.as_bytes()).await;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let status = response[9..12].parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[cfg(feature = "sign")]
+    fn verifiable_license(expiration_date: &str) -> Value {
+        let license: License = serde_json::from_value(json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": expiration_date,
+            "customData": { "owner": "John Doe" }
+        }))
+        .unwrap();
+        LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .expect("Signer instantiation must work")
+            .sign(&license)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn health_check() {
+        let address = start().await;
+
+        let (status, body) = request(address, "GET", "/healthz", "").await;
+        assert_eq!(status, 200);
+        assert_eq!(body, json!({ "status": "ok" }));
+    }
+
+    #[tokio::test]
+    async fn unknown_routes_and_methods() {
+        let address = start().await;
+
+        let (status, body) = request(address, "GET", "/licenses", "").await;
+        assert_eq!(status, 404);
+        assert_eq!(body, json!({ "error": { "code": "not_found" } }));
+
+        let (status, body) = request(address, "GET", "/verify", "").await;
+        assert_eq!(status, 405);
+        assert_eq!(body, json!({ "error": { "code": "method_not_allowed" } }));
+    }
+
+    #[tokio::test]
+    async fn malformed_verify_requests() {
+        let address = start().await;
+
+        let (status, body) = request(address, "POST", "/verify", "{not json").await;
+        assert_eq!(status, 400);
+        assert_eq!(body, json!({ "error": { "code": "malformed_request" } }));
+
+        let (status, body) = request(address, "POST", "/verify", "{}").await;
+        assert_eq!(status, 422);
+        assert_eq!(
+            body,
+            json!({ "error": { "code": "invalid_verifiable_license" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_requests() {
+        let address = start().await;
+
+        let body = format!("\"{}\"", "a".repeat(MAX_BODY_SIZE));
+        let (status, body) = request(address, "POST", "/verify", &body).await;
+        assert_eq!(status, 413);
+        assert_eq!(body, json!({ "error": { "code": "payload_too_large" } }));
+    }
+
+    // Records the fields of every `request` span
+    #[derive(Clone, Default)]
+    struct RequestSpans(Arc<Mutex<Vec<BTreeMap<&'static str, String>>>>);
+
+    struct Fields<'a>(&'a mut BTreeMap<&'static str, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.insert(field.name(), format!("{:?}", value));
+        }
+    }
+
+    impl Subscriber for RequestSpans {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.is_event() || metadata.name() == "request"
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut spans = self.0.lock().unwrap();
+            let mut fields = BTreeMap::new();
+            span.record(&mut Fields(&mut fields));
+            spans.push(fields);
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.0.lock().unwrap();
+            values.record(&mut Fields(&mut spans[span.into_u64() as usize - 1]));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[tokio::test]
+    async fn requests_are_traced() {
+        let spans = RequestSpans::default();
+        // The test runtime runs every task on this thread
+        let _guard = tracing::subscriber::set_default(spans.clone());
+        let address = start().await;
+
+        request(address, "GET", "/healthz", "").await;
+        request(address, "POST", "/verify", "{}").await;
+
+        let span = |method: &str, path: &str, status: &str| {
+            BTreeMap::from([
+                ("method", method.to_owned()),
+                ("path", path.to_owned()),
+                ("status", status.to_owned()),
+            ])
+        };
+        assert_eq!(
+            *spans.0.lock().unwrap(),
+            vec![
+                span("GET", "/healthz", "200"),
+                span("POST", "/verify", "422")
+            ]
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[tokio::test]
+    async fn verify_valid_license() {
+        let address = start().await;
+        let verifiable_license = verifiable_license("2124-10-01T00:00:00Z");
+
+        let (status, body) =
+            request(address, "POST", "/verify", &verifiable_license.to_string()).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], json!({ "code": "valid" }));
+        assert_eq!(body["license"], verifiable_license["license"]);
+    }
+
+    #[cfg(feature = "sign")]
+    #[tokio::test]
+    async fn verify_tampered_license() {
+        let address = start().await;
+        let mut verifiable_license = verifiable_license("2124-10-01T00:00:00Z");
+        verifiable_license["license"]["customData"]["owner"] = json!("Jane Doe");
+
+        let (status, body) =
+            request(address, "POST", "/verify", &verifiable_license.to_string()).await;
+        assert_eq!(status, 422);
+        assert_eq!(body, json!({ "error": { "code": "tampered_license" } }));
+    }
+
+    #[cfg(feature = "sign")]
+    #[tokio::test]
+    async fn verify_expired_license() {
+        let address = start().await;
+        let verifiable_license = verifiable_license("2024-10-01T00:00:00Z");
+
+        let (status, body) =
+            request(address, "POST", "/verify", &verifiable_license.to_string()).await;
+        assert_eq!(status, 422);
+        assert_eq!(body, json!({ "error": { "code": "license_expired" } }));
+    }
+
+    #[test]
+    fn error_codes_are_stable() {
+        assert_eq!(
+            LicenseVerificationError::TamperedLicense.code(),
+            "tampered_license"
+        );
+        assert_eq!(
+            LicenseVerificationError::OfflineWindowExceeded { last_online: None }.code(),
+            "offline_window_exceeded"
+        );
+    }
+}
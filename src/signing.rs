@@ -0,0 +1,15 @@
+use alloc::string::String;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseSigningError {
+    InvalidLicense,
+    FeatureNotLicensed(String),
+    NotRepresentableAsJwt(String),
+    SeatOutOfRange(u32),
+}
+
+#[cfg(feature = "sign")]
+mod signer;
+
+#[cfg(feature = "sign")]
+pub use signer::{sign_with_quorum, LicenseSigner, LicenseSignerError};
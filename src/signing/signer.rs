@@ -0,0 +1,581 @@
+use super::LicenseSigningError;
+use crate::jwa::{Algorithm, Signing};
+#[cfg(feature = "eddsa")]
+use crate::jwk::OkpCurves;
+use crate::jwk::{Jwk, Key, KeyInfo};
+use crate::jws;
+use crate::jwt;
+use crate::{License, Verifiable, VerifiableLicense};
+#[cfg(all(feature = "ecdsa", not(feature = "rsa")))]
+use p256::ecdsa::signature::Signer as _;
+#[cfg(feature = "rsa")]
+use rsa::signature::{SignatureEncoding, Signer as _};
+use serde::Deserialize;
+use zeroize::Zeroizing;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseSignerError {
+    KeyIsNotJwk,
+    KeyIsNotPrivate,
+    KeyTypeNotSupported,
+}
+
+// The private counterpart of `PublicKey`, for the same backends
+enum SigningKey {
+    #[cfg(feature = "rsa")]
+    Rsa(rsa::pkcs1v15::SigningKey<rsa::sha2::Sha512>),
+    #[cfg(feature = "ecdsa")]
+    P256(p256::ecdsa::SigningKey),
+    #[cfg(feature = "eddsa")]
+    Ed25519(ed25519_dalek::SigningKey),
+}
+
+impl SigningKey {
+    fn from_jwk(private_key: serde_json::Value) -> Result<Self, LicenseSignerError> {
+        let parsed_private_key: Jwk =
+            serde_json::from_value(private_key).map_err(|_| LicenseSignerError::KeyIsNotJwk)?;
+        let supports =
+            |signing: Signing| parsed_private_key.is_supported(&Algorithm::from(signing));
+
+        match &parsed_private_key.key {
+            #[cfg(feature = "rsa")]
+            Key::Rsa(rsa_key) if supports(Signing::Rs512) => {
+                if rsa_key.prv.is_none() {
+                    return Err(LicenseSignerError::KeyIsNotPrivate);
+                }
+                let rsa_key = rsa::RsaPrivateKey::try_from(rsa_key)
+                    .map_err(|_| LicenseSignerError::KeyTypeNotSupported)?;
+                Ok(Self::Rsa(rsa::pkcs1v15::SigningKey::new(rsa_key)))
+            }
+            #[cfg(feature = "ecdsa")]
+            Key::Ec(ec_key) if supports(Signing::Es256) => {
+                let d = ec_key
+                    .d
+                    .as_ref()
+                    .ok_or(LicenseSignerError::KeyIsNotPrivate)?;
+                let signing_key = p256::ecdsa::SigningKey::from_slice(&d[..])
+                    .map_err(|_| LicenseSignerError::KeyTypeNotSupported)?;
+                // The public members must belong to the private scalar
+                let point = signing_key.verifying_key().to_encoded_point(false);
+                if point.x().map(|x| &x[..]) != Some(&ec_key.x[..])
+                    || point.y().map(|y| &y[..]) != Some(&ec_key.y[..])
+                {
+                    return Err(LicenseSignerError::KeyTypeNotSupported);
+                }
+                Ok(Self::P256(signing_key))
+            }
+            #[cfg(feature = "eddsa")]
+            Key::Okp(okp_key)
+                if supports(Signing::EdDsa) && matches!(okp_key.crv, OkpCurves::Ed25519) =>
+            {
+                let d = okp_key
+                    .d
+                    .as_ref()
+                    .ok_or(LicenseSignerError::KeyIsNotPrivate)?;
+                let d: Zeroizing<[u8; 32]> = Zeroizing::new(
+                    d[..]
+                        .try_into()
+                        .map_err(|_| LicenseSignerError::KeyTypeNotSupported)?,
+                );
+                let signing_key = ed25519_dalek::SigningKey::from_bytes(&d);
+                if signing_key.verifying_key().as_bytes()[..] != okp_key.x[..] {
+                    return Err(LicenseSignerError::KeyTypeNotSupported);
+                }
+                Ok(Self::Ed25519(signing_key))
+            }
+            _ => Err(LicenseSignerError::KeyTypeNotSupported),
+        }
+    }
+
+    fn alg(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "rsa")]
+            SigningKey::Rsa(_) => "RS512",
+            #[cfg(feature = "ecdsa")]
+            SigningKey::P256(_) => "ES256",
+            #[cfg(feature = "eddsa")]
+            SigningKey::Ed25519(_) => "EdDSA",
+        }
+    }
+
+    fn sign(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            #[cfg(feature = "rsa")]
+            SigningKey::Rsa(signing_key) => signing_key.sign(data).to_vec(),
+            #[cfg(feature = "ecdsa")]
+            SigningKey::P256(signing_key) => {
+                let signature: p256::ecdsa::Signature = signing_key.sign(data);
+                signature.to_bytes().to_vec()
+            }
+            #[cfg(feature = "eddsa")]
+            SigningKey::Ed25519(signing_key) => ed25519_dalek::Signer::sign(signing_key, data)
+                .to_bytes()
+                .to_vec(),
+        }
+    }
+}
+
+pub struct LicenseSigner {
+    signing_key: SigningKey,
+    issuer_certificate: Option<String>,
+    key_id: Option<String>,
+    #[cfg(feature = "compression")]
+    compression: bool,
+}
+
+impl LicenseSigner {
+    pub fn new(private_key: serde_json::Value) -> Result<Self, LicenseSignerError> {
+        Ok(Self {
+            signing_key: SigningKey::from_jwk(private_key)?,
+            issuer_certificate: None,
+            key_id: None,
+            #[cfg(feature = "compression")]
+            compression: false,
+        })
+    }
+
+    pub fn with_key_id(mut self, key_id: &str) -> Self {
+        self.key_id = Some(key_id.to_owned());
+        self
+    }
+
+    pub fn with_issuer_certificate(mut self, issuer_certificate: &str) -> Self {
+        self.issuer_certificate = Some(issuer_certificate.to_owned());
+        self
+    }
+
+    // Deflates license payloads and marks them with a critical `"zip": "DEF"` header, so verifiers
+    // built without the `compression` feature reject them instead of mis-parsing them
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self) -> Self {
+        self.compression = true;
+        self
+    }
+
+    pub fn sign(&self, license: &License) -> Result<serde_json::Value, LicenseSigningError> {
+        self.sign_payload(license)
+    }
+
+    // Any payload signed into the same envelope as licenses, for `LicenseVerifier::verify_into`
+    pub fn sign_payload<T: serde::Serialize>(
+        &self,
+        payload: &T,
+    ) -> Result<serde_json::Value, LicenseSigningError> {
+        let signing_input = self.payload_signing_input(payload)?;
+        let signature = self.sign_bytes(signing_input.as_bytes());
+        let (protected, encoded_payload) = signing_input
+            .split_once('.')
+            .ok_or(LicenseSigningError::InvalidLicense)?;
+
+        let verifiable = Verifiable {
+            license: payload,
+            license_validation: serde_json::json!({
+                "protected": protected,
+                "payload": encoded_payload,
+                "signature": jws::encode(&signature),
+            }),
+            issuer_certificate: self.issuer_certificate.clone(),
+        };
+        serde_json::to_value(verifiable).map_err(|_| LicenseSigningError::InvalidLicense)
+    }
+
+    // Signs externally serialized bytes verbatim, so key order and whitespace survive for
+    // downstream hashing. Never compressed, even when compression is enabled.
+    pub fn sign_raw_payload(
+        &self,
+        payload: &[u8],
+    ) -> Result<serde_json::Value, LicenseSigningError> {
+        let header =
+            serde_json::to_vec(&self.header("JWT")).expect("JWS headers are always serializable");
+        let protected = jws::encode(&header);
+        let payload = jws::encode(payload);
+        let signature = self.sign_bytes(format!("{}.{}", protected, payload).as_bytes());
+        Ok(serde_json::json!({
+            "protected": protected,
+            "payload": payload,
+            "signature": jws::encode(&signature),
+        }))
+    }
+
+    // The outer license is the payload as parsed JSON rather than re-serialized from `License`,
+    // which would drop the members it does not model and fail the tamper check
+    pub fn sign_raw_license(
+        &self,
+        payload: &[u8],
+    ) -> Result<serde_json::Value, LicenseSigningError> {
+        let outer_license: serde_json::Value =
+            serde_json::from_slice(payload).map_err(|_| LicenseSigningError::InvalidLicense)?;
+        License::deserialize(&outer_license).map_err(|_| LicenseSigningError::InvalidLicense)?;
+
+        let verifiable_license = Verifiable {
+            license: outer_license,
+            license_validation: self.sign_raw_payload(payload)?,
+            issuer_certificate: self.issuer_certificate.clone(),
+        };
+        serde_json::to_value(verifiable_license).map_err(|_| LicenseSigningError::InvalidLicense)
+    }
+
+    fn payload_signing_input<T: serde::Serialize>(
+        &self,
+        payload: &T,
+    ) -> Result<String, LicenseSigningError> {
+        let payload =
+            serde_json::to_vec(payload).map_err(|_| LicenseSigningError::InvalidLicense)?;
+        let header = self.header("JWT");
+        #[cfg(feature = "compression")]
+        if self.compression {
+            let mut header = header;
+            header.zip = Some(jws::DEFLATE.to_owned());
+            header.crit = Some(vec!["zip".to_owned()]);
+            let header = serde_json::to_vec(&header).expect("JWS headers are always serializable");
+            return Ok(format!(
+                "{}.{}",
+                jws::encode(&header),
+                jws::encode(&crate::deflate::compress(&payload))
+            ));
+        }
+        let header = serde_json::to_vec(&header).expect("JWS headers are always serializable");
+        Ok(format!(
+            "{}.{}",
+            jws::encode(&header),
+            jws::encode(&payload)
+        ))
+    }
+
+    // Standard compact JWT for consumers that only understand registered claims. Dates lose their
+    // sub-second precision.
+    pub fn sign_as_jwt(&self, license: &License) -> Result<String, LicenseSigningError> {
+        Ok(self.sign_token("JWT", &jwt::license_claims(license)?))
+    }
+
+    pub(crate) fn sign_token<T: serde::Serialize>(&self, typ: &str, claims: &T) -> String {
+        let header = self.header(typ);
+        let signing_input = jws::signing_input(&header, claims);
+        let signature = self.sign_bytes(signing_input.as_bytes());
+        jws::compact(signing_input, &signature)
+    }
+
+    fn header(&self, typ: &str) -> jws::Header {
+        let mut header = jws::Header::new(self.signing_key.alg(), typ);
+        header.kid = self.key_id.clone();
+        header
+    }
+
+    pub(crate) fn sign_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.signing_key.sign(data)
+    }
+}
+
+pub fn sign_with_quorum(
+    license: &License,
+    signers: &[&LicenseSigner],
+) -> Result<serde_json::Value, LicenseSigningError> {
+    let payload = serde_json::to_vec(license).map_err(|_| LicenseSigningError::InvalidLicense)?;
+    let payload = jws::encode(&payload);
+
+    let signatures = signers
+        .iter()
+        .map(|signer| {
+            let header = serde_json::to_vec(&signer.header("JWT"))
+                .expect("JWS headers are always serializable");
+            let protected = jws::encode(&header);
+            let signature = signer.sign_bytes(format!("{}.{}", protected, payload).as_bytes());
+            serde_json::json!({
+                "protected": protected,
+                "signature": jws::encode(&signature),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let verifiable_license = VerifiableLicense {
+        license: license.clone(),
+        license_validation: serde_json::json!({
+            "payload": payload,
+            "signatures": signatures,
+        }),
+        issuer_certificate: None,
+    };
+    serde_json::to_value(verifiable_license).map_err(|_| LicenseSigningError::InvalidLicense)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime;
+    #[cfg(feature = "ecdsa")]
+    use crate::test_keys::{
+        ED25519_PRIVATE_KEY_JWK_JSON, ED25519_PUBLIC_KEY_JWK_JSON, P256_PRIVATE_KEY_JWK_JSON,
+        P256_PUBLIC_KEY_JWK_JSON,
+    };
+    use crate::test_keys::{ISSUER_PRIVATE_KEY_JWK_JSON, ISSUER_PUBLIC_KEY_JWK_JSON};
+    use crate::verification::LicenseVerifier;
+
+    #[test]
+    fn signed_license_is_verifiable() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .expect("Initialization should work");
+        let license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "2024-10-01T00:00:00Z",
+            "customData": {
+                "owner": "John Doe"
+            }
+        }))
+        .unwrap();
+
+        let verifiable_license = signer.sign(&license).expect("Signing should work");
+
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let verified_license = verifier
+            .verify(verifiable_license)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license, license);
+    }
+
+    #[test]
+    fn signer_with_public_key() {
+        let result = LicenseSigner::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseSignerError::KeyIsNotPrivate);
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn signer_with_public_ec_and_okp_keys() {
+        for public_key in [&*P256_PUBLIC_KEY_JWK_JSON, &*ED25519_PUBLIC_KEY_JWK_JSON] {
+            let result = LicenseSigner::new(public_key.clone());
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseSignerError::KeyIsNotPrivate);
+        }
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn signer_with_mismatched_public_members() {
+        let mut p256_key = P256_PRIVATE_KEY_JWK_JSON.clone();
+        p256_key["d"] = ED25519_PRIVATE_KEY_JWK_JSON["d"].clone();
+        let mut ed25519_key = ED25519_PRIVATE_KEY_JWK_JSON.clone();
+        ed25519_key["d"] = P256_PRIVATE_KEY_JWK_JSON["d"].clone();
+
+        for private_key in [p256_key, ed25519_key] {
+            let result = LicenseSigner::new(private_key);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseSignerError::KeyTypeNotSupported);
+        }
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn signature_header_names_the_key_algorithm() {
+        for (private_key, alg) in [
+            (&*ISSUER_PRIVATE_KEY_JWK_JSON, "RS512"),
+            (&*P256_PRIVATE_KEY_JWK_JSON, "ES256"),
+            (&*ED25519_PRIVATE_KEY_JWK_JSON, "EdDSA"),
+        ] {
+            let signer = LicenseSigner::new(private_key.clone()).unwrap();
+            let compact = signer.sign_token("JWT", &serde_json::json!({}));
+            let header = jws::parse_compact(&compact).unwrap().header;
+            assert_eq!(header.alg, alg);
+        }
+    }
+
+    #[test]
+    fn raw_payload_is_signed_verbatim() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .expect("Initialization should work");
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let payload = br#"{"customData": {"seats": 25, "owner": "ACME Corp"},
+            "expirationDate": "2030-01-01T01:00:00+01:00", "product": "acme-cad",
+            "id": "ACME-2023-00481"}"#;
+
+        let verifiable_license = signer
+            .sign_raw_license(payload)
+            .expect("Signing should work");
+        let license = verifier
+            .verify(verifiable_license.clone())
+            .expect("Verification should succeed");
+        assert_eq!(license.product.as_deref(), Some("acme-cad"));
+        assert_eq!(
+            license.custom_data,
+            serde_json::json!({ "owner": "ACME Corp", "seats": 25 })
+        );
+
+        let license_validation = &verifiable_license["licenseValidation"];
+        let signed_payload = license_validation["payload"]
+            .as_str()
+            .and_then(jws::decode)
+            .unwrap();
+        assert_eq!(signed_payload, payload);
+        assert_eq!(
+            signer.sign_raw_payload(payload).unwrap(),
+            *license_validation
+        );
+
+        for payload in [&b"not json"[..], br#"{"customData": {}}"#] {
+            let result = signer.sign_raw_license(payload);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseSigningError::InvalidLicense);
+        }
+    }
+
+    #[test]
+    fn jwt_round_trip() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .expect("Initialization should work")
+            .with_key_id("issuer-2024");
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let licenses: [License; 2] = [
+            serde_json::from_value(serde_json::json!({
+                "id": "ACME-2023-00481",
+                "product": "acme-cad",
+                "issuer": "https://licensing.acme.example",
+                "issuedAt": "2024-01-01T00:00:00Z",
+                "expirationDate": "2024-10-01T00:00:00Z",
+                "customData": { "licensee": "ACME Corp", "sub": "not-a-subject" }
+            }))
+            .unwrap(),
+            // Perpetual licenses carry no `exp` at all
+            serde_json::from_value(serde_json::json!({
+                "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+                "expirationDate": "9999-12-31T23:59:59Z",
+                "customData": ["owner", "John Doe"]
+            }))
+            .unwrap(),
+        ];
+
+        for license in licenses {
+            let token = signer.sign_as_jwt(&license).expect("Signing should work");
+            let compact = jws::parse_compact(&token).unwrap();
+            assert_eq!(compact.header, {
+                let mut header = jws::Header::new("RS512", "JWT");
+                header.kid = Some("issuer-2024".to_owned());
+                header
+            });
+            let claims: serde_json::Value = serde_json::from_slice(&compact.payload).unwrap();
+            assert_eq!(claims["jti"], serde_json::json!(license.id));
+            assert_eq!(
+                claims.get("exp").and_then(|exp| exp.as_i64()),
+                Some(datetime::unix_seconds(license.expiration_date))
+                    .filter(|_| !license.is_perpetual())
+            );
+
+            let verified_license = verifier
+                .verify_jwt_claims(&token)
+                .expect("Verification should succeed");
+            assert_eq!(verified_license.into_license(), license);
+        }
+    }
+
+    #[test]
+    fn jwt_with_unrepresentable_fields() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .expect("Initialization should work");
+        let license: License = serde_json::from_value(serde_json::json!({
+            "id": "ACME-2023-00481",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": {},
+            "maxSeats": 25
+        }))
+        .unwrap();
+
+        let result = signer.sign_as_jwt(&license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseSigningError::NotRepresentableAsJwt("maxSeats".to_owned())
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_license_round_trip() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let entitlements = (0..2000)
+            .map(|row| {
+                serde_json::json!({
+                    "module": format!("module-{}", row % 40),
+                    "site": format!("site-{}", row / 40),
+                    "enabled": row % 3 != 0
+                })
+            })
+            .collect::<Vec<_>>();
+        let license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": { "entitlements": entitlements }
+        }))
+        .unwrap();
+
+        let plain = signer.sign(&license).unwrap();
+        let compressed = signer.with_compression().sign(&license).unwrap();
+        let plain_len = plain["licenseValidation"]["payload"]
+            .as_str()
+            .unwrap()
+            .len();
+        let compressed_len = compressed["licenseValidation"]["payload"]
+            .as_str()
+            .unwrap()
+            .len();
+        assert!(plain_len > 100 * 1024);
+        assert!(compressed_len * 10 < plain_len);
+
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .max_custom_data_size(usize::MAX)
+            .build()
+            .expect("Verifier instantiation must work");
+        let verified_license = verifier
+            .verify(compressed)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license, license);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_payload_over_the_cap() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let protected = jws::encode(br#"{"alg":"RS512","zip":"DEF","crit":["zip"]}"#);
+        let payload = jws::encode(&crate::deflate::compress(&vec![b' '; 8 * 1024 * 1024]));
+        assert!(payload.len() < 100 * 1024);
+        let signature = signer.sign_bytes(format!("{}.{}", protected, payload).as_bytes());
+        let bomb = serde_json::json!({
+            "license": {
+                "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+                "expirationDate": "9999-12-31T23:59:59Z",
+                "customData": {}
+            },
+            "licenseValidation": {
+                "protected": protected,
+                "payload": payload,
+                "signature": jws::encode(&signature),
+            }
+        });
+
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .max_decompressed_size(2 * 1024 * 1024)
+            .build()
+            .expect("Verifier instantiation must work");
+        let result = verifier.verify(bomb);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            crate::verification::LicenseVerificationError::PayloadTooLarge
+        );
+    }
+}
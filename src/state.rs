@@ -0,0 +1,240 @@
+use crate::datetime::DateTime;
+use crate::device::{DeviceKey, DeviceKeyError};
+use crate::store::{LicenseStore, LicenseStoreError};
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::License;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const LOCAL_STATE_TYP: &str = "jls-local-state+jwt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocalStateError {
+    Store(LicenseStoreError),
+    MissingState,
+    InvalidState,
+    VerificationFailure,
+    TamperedState,
+    DeviceKey(DeviceKeyError),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LocalState {
+    #[serde(rename = "firstUse", with = "crate::datetime::rfc3339")]
+    pub first_use: DateTime,
+    #[serde(rename = "activationCount")]
+    pub activation_count: u32,
+    #[serde(
+        rename = "lastOnline",
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::datetime::rfc3339::option"
+    )]
+    pub last_online: Option<DateTime>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct SignedLocalState {
+    state: LocalState,
+    #[serde(rename = "stateValidation")]
+    state_validation: String,
+}
+
+impl LocalState {
+    pub fn new(first_use: DateTime) -> Self {
+        Self {
+            first_use,
+            activation_count: 0,
+            last_online: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    pub fn load(
+        store: &dyn LicenseStore,
+        device_key: &DeviceKey,
+        verifier: &LicenseVerifier,
+    ) -> Result<Self, LocalStateError> {
+        let contents = store.load().map_err(|error| match error {
+            LicenseStoreError::NotFound => LocalStateError::MissingState,
+            error => LocalStateError::Store(error),
+        })?;
+        let signed_state: SignedLocalState =
+            serde_json::from_value(contents).map_err(|_| LocalStateError::InvalidState)?;
+
+        let protected_state: LocalState = device_key
+            .public_key()
+            .verify_token_reported(verifier, &signed_state.state_validation, LOCAL_STATE_TYP)
+            .map_err(|error| match error {
+                LicenseVerificationError::VerificationFailure => {
+                    LocalStateError::VerificationFailure
+                }
+                _ => LocalStateError::InvalidState,
+            })?;
+        if protected_state != signed_state.state {
+            return Err(LocalStateError::TamperedState);
+        }
+
+        Ok(protected_state)
+    }
+
+    pub fn load_for_license(
+        license: &License,
+        store: &dyn LicenseStore,
+        device_key: &DeviceKey,
+        verifier: &LicenseVerifier,
+        now: DateTime,
+    ) -> Result<Self, LocalStateError> {
+        match Self::load(store, device_key, verifier) {
+            Err(LocalStateError::MissingState) if !license.state_required => Ok(Self::new(now)),
+            result => result,
+        }
+    }
+
+    pub fn save(
+        &self,
+        store: &dyn LicenseStore,
+        device_key: &DeviceKey,
+    ) -> Result<(), LocalStateError> {
+        let signed_state = SignedLocalState {
+            state: self.clone(),
+            state_validation: device_key
+                .sign_token(LOCAL_STATE_TYP, self)
+                .map_err(LocalStateError::DeviceKey)?,
+        };
+        let contents =
+            serde_json::to_value(signed_state).expect("Local state is always serializable");
+        store.save(&contents).map_err(LocalStateError::Store)
+    }
+}
+
+pub fn record_online_validation(state: &mut LocalState, now: DateTime) {
+    state.last_online = Some(now);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::datetime;
+    use crate::datetime::Duration;
+    use crate::store::MemoryLicenseStore;
+    use crate::test_keys::{verifier, ISSUER_PUBLIC_KEY_JWK_JSON};
+    use std::sync::Arc;
+
+    fn license(state_required: bool) -> License {
+        let mut license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": {}
+        }))
+        .unwrap();
+        license.state_required = state_required;
+        license
+    }
+
+    #[test]
+    fn local_state_update_cycle() {
+        let store = MemoryLicenseStore::new();
+        let device_key = DeviceKey::generate();
+        let first_use = datetime::now() - Duration::days(3);
+
+        let mut state = LocalState::load_for_license(
+            &license(false),
+            &store,
+            &device_key,
+            &verifier(),
+            first_use,
+        )
+        .expect("A fresh state should be created");
+        state.activation_count += 1;
+        state.last_online = Some(first_use + Duration::days(1));
+        state
+            .extra
+            .insert("channel".to_owned(), serde_json::json!("beta"));
+        state.save(&store, &device_key).expect("Saving should work");
+
+        let reloaded_state = LocalState::load_for_license(
+            &license(true),
+            &store,
+            &device_key,
+            &verifier(),
+            datetime::now(),
+        )
+        .expect("Loading should work");
+        assert_eq!(reloaded_state, state);
+        assert_eq!(reloaded_state.first_use, first_use);
+    }
+
+    #[test]
+    fn local_state_with_modified_field() {
+        let store = MemoryLicenseStore::new();
+        let device_key = DeviceKey::generate();
+        LocalState::new(datetime::now())
+            .save(&store, &device_key)
+            .unwrap();
+
+        let mut contents = store.load().unwrap();
+        contents["state"]["firstUse"] = serde_json::json!("2099-01-01T00:00:00Z");
+        store.save(&contents).unwrap();
+
+        let result = LocalState::load(&store, &device_key, &verifier());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LocalStateError::TamperedState);
+    }
+
+    #[test]
+    fn local_state_from_other_machine() {
+        let store = MemoryLicenseStore::new();
+        let device_key = DeviceKey::generate();
+        let other_device_key = DeviceKey::generate();
+        LocalState::new(datetime::now())
+            .save(&store, &other_device_key)
+            .unwrap();
+
+        let result = LocalState::load(&store, &device_key, &verifier());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LocalStateError::VerificationFailure);
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let opaque_verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let result = LocalState::load(&store, &device_key, &opaque_verifier);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LocalStateError::InvalidState);
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::VerificationFailure]
+        );
+    }
+
+    #[test]
+    fn local_state_deleted_when_required() {
+        let store = MemoryLicenseStore::new();
+        let device_key = DeviceKey::generate();
+
+        let result = LocalState::load_for_license(
+            &license(true),
+            &store,
+            &device_key,
+            &verifier(),
+            datetime::now(),
+        );
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LocalStateError::MissingState);
+    }
+}
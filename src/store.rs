@@ -0,0 +1,137 @@
+#[cfg(feature = "tpm")]
+use crate::tpm::TpmError;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseStoreError {
+    Io(std::io::ErrorKind),
+    NotFound,
+    InvalidContents,
+    #[cfg(feature = "tpm")]
+    Tpm(TpmError),
+}
+
+pub trait LicenseStore {
+    fn load(&self) -> Result<serde_json::Value, LicenseStoreError>;
+    fn save(&self, contents: &serde_json::Value) -> Result<(), LicenseStoreError>;
+}
+
+impl<T: LicenseStore + ?Sized> LicenseStore for &T {
+    fn load(&self) -> Result<serde_json::Value, LicenseStoreError> {
+        (**self).load()
+    }
+
+    fn save(&self, contents: &serde_json::Value) -> Result<(), LicenseStoreError> {
+        (**self).save(contents)
+    }
+}
+
+impl<T: LicenseStore + ?Sized> LicenseStore for Arc<T> {
+    fn load(&self) -> Result<serde_json::Value, LicenseStoreError> {
+        (**self).load()
+    }
+
+    fn save(&self, contents: &serde_json::Value) -> Result<(), LicenseStoreError> {
+        (**self).save(contents)
+    }
+}
+
+pub struct FileLicenseStore {
+    path: PathBuf,
+}
+
+impl FileLicenseStore {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+        }
+    }
+}
+
+impl LicenseStore for FileLicenseStore {
+    fn load(&self) -> Result<serde_json::Value, LicenseStoreError> {
+        let contents = std::fs::read(&self.path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => LicenseStoreError::NotFound,
+            kind => LicenseStoreError::Io(kind),
+        })?;
+        serde_json::from_slice(&contents).map_err(|_| LicenseStoreError::InvalidContents)
+    }
+
+    fn save(&self, contents: &serde_json::Value) -> Result<(), LicenseStoreError> {
+        let contents =
+            serde_json::to_vec_pretty(contents).map_err(|_| LicenseStoreError::InvalidContents)?;
+        let mut temporary_path = self.path.clone().into_os_string();
+        temporary_path.push(".tmp");
+
+        std::fs::write(&temporary_path, contents)
+            .and_then(|_| std::fs::rename(&temporary_path, &self.path))
+            .map_err(|e| LicenseStoreError::Io(e.kind()))
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryLicenseStore {
+    contents: Mutex<Option<serde_json::Value>>,
+}
+
+impl MemoryLicenseStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LicenseStore for MemoryLicenseStore {
+    fn load(&self) -> Result<serde_json::Value, LicenseStoreError> {
+        self.contents
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or(LicenseStoreError::NotFound)
+    }
+
+    fn save(&self, contents: &serde_json::Value) -> Result<(), LicenseStoreError> {
+        *self.contents.lock().unwrap() = Some(contents.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_license_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileLicenseStore::new(&dir.path().join("license.json"));
+        let contents = serde_json::json!({ "license": { "id": "some-id" } });
+
+        store.save(&contents).expect("Saving should work");
+
+        assert_eq!(store.load(), Ok(contents));
+    }
+
+    #[test]
+    fn file_license_store_with_corrupted_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("license.json");
+        std::fs::write(&path, b"{\"license\": {\"id\"").unwrap();
+
+        let result = FileLicenseStore::new(&path).load();
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseStoreError::InvalidContents);
+    }
+
+    #[test]
+    fn file_license_store_without_file() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let result = FileLicenseStore::new(&dir.path().join("license.json")).load();
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseStoreError::NotFound);
+    }
+}
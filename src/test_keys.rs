@@ -0,0 +1,254 @@
+#[cfg(feature = "sign")]
+use crate::signing::LicenseSigner;
+#[cfg(feature = "std")]
+use crate::verification::LicenseVerifier;
+#[cfg(feature = "std")]
+use crate::License;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    pub(crate) static ref ISSUER_PRIVATE_KEY_JWK_JSON: serde_json::Value = serde_json::json!({
+        "alg": "RS512",
+        "kty": "RSA",
+        "n": "q13dfHR5a5k6VY9Yg39GOUnuXTdPg9gI3_CxqIT8cxnFn2kxlp4qUuxTKSl5rz03XgQ-3E4-2NFv_JunPBD-Rg\
+            MyTaQtAQCk4mQzKmIp9uY11GbxL9gm3BxgymIHGwlUoOf0qwlk4dX0uUw_JEbo7vcoA-Hd82b05eb7xkN9jyX-\
+            SOnUjg2wb3wySNYqotRKODYSlDXXH-aQwD5Y0_kmLw9TPQnwM-z4eyDsXAl2t9enHc-UMAGMUInRxIeEKQQcPp\
+            YhdXIC3-6b9YhSmX-Q-vd5C-Fb1o41AXjguc8LYGTqlcJsj_YryOXlK1768bB4SPw7B7slCCGwxkodsMX7IJ_q\
+            JBzCORLKrXqpFkLmtg8F-dXc7PDZdCfcLBiBhrpSaF__smJPaUUQErs9CGhz7E9fonNmkUArnqfJjhAMrYdwn2\
+            FJcWPckammUJdH-MGejMEweCtzq9DgaJ27SdQwH4or9OrrBptectLQL9k76LuLTVnERK8ftwBk8ktmIrKyEHSS\
+            hVdlrDTzFFxQkMwFWdI1eB1swQs87PqCNxLnoUbXb8ipD6kjS3jXptMrroJa-qTlR5RFnGaNgd2MtZIqvqZ8CN\
+            Bp9DJAzuSgxIhwl5PyYTK-UselRp9fgPNsaPP66FRqRSrkM5DQ3_fUllAZyTlV1_emG2d2Blk__37uPOc",
+        "e": "AQAB",
+        "d": "BV47y63VS7qKGjn1WzZmfLZlIXvdpl9PcCrioEkkVafHSf6qK6gw9jhqRCzds1CSBmb34A7DRNihyhYWAK-nb3\
+            ZAjn7FXL98JdzBcqMsCb3TZwJG_B-BPfkQhl3YcplApmCj6vJOWYOi7xbi3IxTpM8sOzldILyJpIwH_W3E9PH3\
+            ZDO2qh1JIEWH-d-W_o5BzReEOqEErPSl2BaZbzU6nyyJ1vGdkBXtALzftaj8O7bqQ62vUldZdU9VJa6gnN6eSP\
+            Y8JbL2BgQA-4mwpaF330y5qfQb3GoWYfxifrNEyIqB-fMULDdjHYI7VtWVu613O3Q35QNzHfH6YsKK6XrkqoTf\
+            EDfUQtTCl66hwsc8gBVl1CiIEvXREG6cDfiMqRR_qEQC2jMJRxxh3fv6ZTfUsQCQg90N_BCwq1VjAJRVsHd8nY\
+            aPCNe5DsuxDNQ05IeRfuZ_oq1UXaqyLCXcAPuVRIusdPU2_QPuK3Qvj2ET2MqfDmJzNyM1hsQzOVUYRquLMQLa\
+            iqNeWbr_BpfhNwE3Vpy4Td_y8ofS9Fw0pHxgh7xCRAOT5U-zxwvluFegZsien0iVVE4JWSqRXdvTQxS8oXuQza\
+            u74Zn_YA3Q8kIl7Yk6OoU1igsZgogSwWgtPxhvdRHuKkIonAqImvdyR9vTiWpKAh5p1dlXxnW_-UxL_0U",
+        "p": "2QF45-LLQNkUcmvCvX7nIwK7X6qhoHku6UJZz6IMAu8tgOkzLGeJqvPMcgs7JFYudj_BhrCEasxxahKTnoTwJX\
+            fG_rmZSCVq8ey17y7OzDOcH3QMeeMDRDvOWUxOp47VgVGFf4HH7l0dG-2lfm8MiQR90sUnjAF3r2pO7LuFybkd\
+            Ey4XoWyAGw_1FTOoJR2yc3LvpDTaDUUOqYVD5DdmTrYBr7GOxO6_AhfcW2owg6r9x3oLP0FeqtukjKiMILr3uL\
+            GbQj8dUV5xRGfoyyVuTgENyTNSbFDT3ZNwJl4pQnvtDdbPuTp5gMrOFIAujqiwi6aFkqHziS8zgrpjIA-VhQ",
+        "q": "yijwfB3CCVfWjCOAQl1k_Uel72rBLTyqwD2KLB0cp7VbyPpHg2YVPFciW9BCQKGRbvEtutia8_JAChw8ufI6Tt\
+            vrKLuhVq0nZrR6t2JwCL4lacy79Jlm76nfr770x6kf06yRgMePR3ax3W8cK_ZeB_JmdX9iKrk060FrWuXJlERg\
+            Yn-YM2u7uqOIxj9HCGyAoABwR4FY3W3BxPO2ed1mbwBJ68yPE1ljz0ox4q2IUq7oBQBOiRjjDgA6JN9KinvtSs\
+            oO1UIBd-L_RxKZsf_lv8XqGSh8wlkB4q1CFFqo0xc6OA7JEjV1n7vyUFUybKGnweexJpne7TI17mokfN2uew",
+        "dp": "T3EyifGE5yCRU7IPgL_Mbgy5-7PDp7O1cL9RkamJLCnTRrYYfdEJnQPTfIyQAiVqrO96MYy9bTua6XITiJyyLY\
+            VD3EylyLuqAxbdI8rG-gqKxRj-2EA7GeerCxXcVYr8rnLhpGQmhSShW3AMfdS5ig-S4G48pAyFfDo22oNr3wVd\
+            l3_wNrA51a5p0nEuxSeyBIG3KS4QBXuaeoTG7e06Duaih2dBFOcyFdoWWYDuccVPUo9jPqz3syTlgHjTk8femE\
+            asx_vDB97DKH_BwVzanMPWACpBqlWMujkXfXRLNoOXrNFPCkhV4shb2TVz1dX7i6RSkfOn7DjTarBBSaNq4Q",
+        "dq": "ctLeBSFB3NyvV9myN_-kJNhVP697GZ6jvZ02pefDJt6zy2ucwt1wol07frrDZsJeQI1sU_xDxtuJ1DRFqbgTtk\
+            bjQreP5BNllchdC1w2FkEqLvXCrCOVe_sYulHT5rQBB9G0s1il4Qlh7Uv2zGGa9xiPaZ14vJuFmzoFKBPscvKD\
+            bG7cnBhNAK1yOmgmiHpYdIjLPVISj3KP5LYopv9B9zpKwkvl2C-lqU-TFhVKoOwHjQ-ni-c-z-0BGYBabl4FYb\
+            1yv15GRzn_lPbTpW6bqFlZpgCBHEJB9-ZA6h60VXGT99MuEdGbhabS8x3xDhKjnldjH3G2VTN3vns7TlD7kQ",
+        "qi": "glAyx_t1T13Rft7TeNhSJB3RyyVEll_o0KhkAI8CC9VJUq8HDROw79PsQGr4Z777hZVbFvdX4m9zJj_-TYbXmo\
+            z73Boazkj5kHsNtGr64BKPMYuLKMUaAMD5uoR_qjdEVzI6Mg11G6Ju-fZd2KmN2BZ-ytC1daENS8u0bY_RaKVA\
+            neeOdx8Ump3NSsrCDaLKa9Iblk43yX1wxnDX77ttPLcaX-LA6cZ5lArqHjLCOvA4UzZHx5Fr9o5cFq4llbg1uY\
+            F_vd7DvB_d7hHIIFZDOSE8Wa8mcKWDiytAT_0eAlfQsfbE9X8Ea0ERW8OWvRI91ptE6OUZhGrDqiFDgqk3pw",
+    });
+    pub(crate) static ref ISSUER_PUBLIC_KEY_JWK_JSON: serde_json::Value = serde_json::json!({
+        "alg": "RS512",
+        "kty": "RSA",
+        "n": "q13dfHR5a5k6VY9Yg39GOUnuXTdPg9gI3_CxqIT8cxnFn2kxlp4qUuxTKSl5rz03XgQ-3E4-2NFv_JunPBD-Rg\
+            MyTaQtAQCk4mQzKmIp9uY11GbxL9gm3BxgymIHGwlUoOf0qwlk4dX0uUw_JEbo7vcoA-Hd82b05eb7xkN9jyX-\
+            SOnUjg2wb3wySNYqotRKODYSlDXXH-aQwD5Y0_kmLw9TPQnwM-z4eyDsXAl2t9enHc-UMAGMUInRxIeEKQQcPp\
+            YhdXIC3-6b9YhSmX-Q-vd5C-Fb1o41AXjguc8LYGTqlcJsj_YryOXlK1768bB4SPw7B7slCCGwxkodsMX7IJ_q\
+            JBzCORLKrXqpFkLmtg8F-dXc7PDZdCfcLBiBhrpSaF__smJPaUUQErs9CGhz7E9fonNmkUArnqfJjhAMrYdwn2\
+            FJcWPckammUJdH-MGejMEweCtzq9DgaJ27SdQwH4or9OrrBptectLQL9k76LuLTVnERK8ftwBk8ktmIrKyEHSS\
+            hVdlrDTzFFxQkMwFWdI1eB1swQs87PqCNxLnoUbXb8ipD6kjS3jXptMrroJa-qTlR5RFnGaNgd2MtZIqvqZ8CN\
+            Bp9DJAzuSgxIhwl5PyYTK-UselRp9fgPNsaPP66FRqRSrkM5DQ3_fUllAZyTlV1_emG2d2Blk__37uPOc",
+        "e": "AQAB",
+    });
+    pub(crate) static ref OTHER_PRIVATE_KEY_JWK_JSON: serde_json::Value = serde_json::json!({
+        "alg": "RS512",
+        "kty": "RSA",
+        "n": "tsysrci-e2vWM0Ru9B8oLF08szKrTwLL76iMm1NZ4xY_ArTLNIIdShc0YtxWCvz1R0HueVGFBJ50ZFj_Td4STP\
+            ZbdrupxfBHXB8opCuRGHtC3TYwbSC7YOQavVd1s071tO0wbho6QIHTIOWrD-4AvR1wt9IVlPSdixRBhN8ritI2\
+            5fc5wvPtHYZqnBKrJhN4oYij77TiogFIZssxKmCoKB_AM22qyDyrWqj3LT6tBVz-lMpkFSRpqWBdxBE8QOj-yn\
+            NaKQ-fZYjkNtXYOxrjqCVMAt-3qK8NSHY7CyRCqN_MniYXyrr6tMnKiVv2he8ACqgQcZ1ljWW8aeAnmDk3svVe\
+            _e0Cq28zm8Rd1AhWKcHtr0_RTfD3hCKPff-dDlYYNqkMJqzYYaV_diP9qQtYIB4jlPr-MiLg9uy1T6CW9oIzpC\
+            fRsG3AnM4qbApunG0iV4z07sn4t_YKDqU6YWF6YN4rc55hXF-UW-fDujAcxygq0eZ-uyJiqDIPqBOFx-aUm2U-\
+            YNhdSgCqCNerRU7_yenreftXnePpGW6lOUpGiUMu1eO_i-eOzsrJMIH9Pok0oLcT9821ky7zDaOoKZPm681ik0\
+            LiA4XBMLLaHT4gaJmx9fDR4ZHaTKRW-RnO3MizeG0bhxkfEReVJCDN-bCmk5jR-TsBul7NEfgjzlDzXh0",
+        "e": "AQAB",
+        "d": "B8taM-PFdCObjnPlIOgg2_loG0JLvyf2SaRVGydhQ7dLswIXUF3EQXKUA4DJHhlTR7lLjDqPXSx4zTGP8Jee40\
+            9gLtkeyqeYPK5AcErs73oMARIshkQDxq8bO8Zk_fhd2NvXOY6ekWhnt_VLB2LFfyAoUaeLJN7kRnfz45DqJ_Zs\
+            LFSPuYdnQspxNRvvq8U0mtClytSv6CqoAL1lEzKWtkqaSlHAsIkztj8noPMqnfoCietyUTDMXOeUv_A13T-suO\
+            BVGxSaiOdexxQfBEV9KQrZaZ2f6g5NbEZMS16TBWUw3JpxCm4mvqWEYGR-h7nyisHH8mGSIQa86K0hNb1U1Fbp\
+            lISN-_OWjCc1LlZkGMlpgIRlrYObn8E_Eq9vriQ0nug2h4U7bDE3O0MjZY0OyMG2H7I6Z8_LnhmHilj9K6NdkF\
+            lLvxtI4o5Yh2IOrBSWzV6EUL5kYtfAUVWxxepiQB0CpE74yNr4UtvrdnHogVacoGz1T5BldmN5UHcmagQamqX0\
+            RL_Ou8rM-EPwg5DXPTe4PLh-X4FOk7u8KdwoT8IaRSKBbKab7zBpXwsPnwTKi3kIgUb-eZ3vjItJoStGYY2a5I\
+            dT4p7LFcaOW00pnjeZJACw1V63FHPUSmcQ_h0_Wh3Bk0ny19ErVF2eGGGpjZLj8oRFWZzqnWz2OuFJW3k",
+        "p": "-O8ZXy6kTDCW5StIP05_Aaw9XtmPEL7Sjb3r26hshNd4-S8Wf6f3o4RTs8ItseD9MMiNSfS8UmLArESrRcvocT\
+            gSn2vh7LAGZoP1fijyez6Vqws66EIkDL3yiHOV4Ml6MI0UHJngV5-E6L5ncjBn34k-nKU8u4XvqtlYE2LvQYnJ\
+            Gcy7SWIWyT2GiVFdLipPCNIgSoLzPAITC7_js021evJeJWiHmv5q2-1OpOWVm3N2oQZd1nX_1K7jxC6whmAzHb\
+            SpNoWvF0qjh_q-v5FYKKu1aNFej3miNG5cEq0sU-DAgJxe44aDkgEDeMUtX3XHktu3ppIhx6gWmj93uAYtFQ",
+        "q": "u_0A31jHlxFs-t0s7EC7bloExdbTvqQTGxFak89avsL2sx0pe6rAqMgSJrMjqpor_UycF6mrW6okOKu2RQTCJd\
+            n_CU8Stqh8ZYz5ZDXFnjqT_oJdPJJwQw_Go1QMpZZnHb4iCmis0Es24CYcCfUk8IUFDeI1xfW6PIfIipJeJL63\
+            GJmEY2YWAySuA0-vGSoAVIKrq1c6YJ8ReSTXv19BocWsCTI2gbDU43h-7_T4Vz8ovEDUWF0aOFzPECCHqnt2G-\
+            pVu324IFh_I1VBXDg6T5lue2ujTjDErUQS2GthsckuMwUSxSdnYzIZrvo9WXbrRJV8KwqwdCDKNdYAFZR-6Q",
+        "dp": "dkn3vnkFbYbxTTjB_gMo6vyTtQygpQ5oMx56g0iHOKDDRXWQtVx1mmf49kRhPlNxPMswCGKZUnTGpZWD5Yi9jX\
+            9dqg-zrar_f5tY_ngqsVHOARc3CMLVKpDgPOLf548V1xhS5DxilICiwY4ozeQel-Q5smn6cAl3F7Jr3dp3Yqc8\
+            vlvA62AG_92SnpHhO1yzbbebzIxpVKyPrFektcFJaYDeJ9yCA669zH_5jPXjn5jAWEgUMyKjTMdGSCT_kPsJjy\
+            YX-maQCvtWtfj6x0EjqT_e1k4XJwpff5apfd54X-s-zl5_4JI81xZQNgVMSD5JdYmevTJXhu5ivy1FkFau-Q",
+        "dq": "pPvFwT7cZDPx7jUgkPO6y32LhMomqcC719yiHukzngnjk4z7dEKGtVppu7cqjTajdrm-oHHTJy6qfWYOIIKtla\
+            S91MCmDtLN4h4YXh9nIZgNfKeTBm8KwYBd98fzFXxrYUoV_Aaj7GamcZBguUiBs-k6Z1XplaQ0X51Xnm4Y26zZ\
+            amiJLOdSWE3fvp43cSFQitrnqQdz_4p7PlPcWWNTaZPfiW6dMbMtE7oTxaq8LW4L5TQMaMTFF0Q0odW62x9hp9\
+            xAg70xVIn2gt727uWWH3fr47uy5hOc-OeulmufoPWMa1B_ibIsZX151vxdzV-12UpMYEYQWMrSTO9UwZvCyQ",
+        "qi": "6NRg1lKFaP149eNhE5ujRwO5g7ZHEak8zvVx_RG-KlzbT72D_uLI9gTic7C2lqo-d3MWOdw4CPkEolByrzBcYU\
+            hwdEdB_3I8UeYADlAAcKCq_w0TFr3DHTGEzi9IMU55G_kDBy4i7KkFhfyz7QtdUR8tkcbCliKRQRlZxJUbqVkx\
+            7pQpc3DYW92LmTWxzmO-DaApDg9tdv3_fCDE1G39ML3x4nWLZ5rZXWKN7UPSSIUuOIeqbu5s9ShrMife9kORkR\
+            dje5raK4oEqH1yKrhSG_e9GrAWcT5mQeGaRwjqe978QHegu2VgRRyd51GN3b1iYGSrGpHQ8kuSoDqG92-Keg",
+    });
+    pub(crate) static ref OTHER_PUBLIC_KEY_JWK_JSON: serde_json::Value = serde_json::json!({
+        "alg": "RS512",
+        "kty": "RSA",
+        "n": "tsysrci-e2vWM0Ru9B8oLF08szKrTwLL76iMm1NZ4xY_ArTLNIIdShc0YtxWCvz1R0HueVGFBJ50ZFj_Td4STP\
+            ZbdrupxfBHXB8opCuRGHtC3TYwbSC7YOQavVd1s071tO0wbho6QIHTIOWrD-4AvR1wt9IVlPSdixRBhN8ritI2\
+            5fc5wvPtHYZqnBKrJhN4oYij77TiogFIZssxKmCoKB_AM22qyDyrWqj3LT6tBVz-lMpkFSRpqWBdxBE8QOj-yn\
+            NaKQ-fZYjkNtXYOxrjqCVMAt-3qK8NSHY7CyRCqN_MniYXyrr6tMnKiVv2he8ACqgQcZ1ljWW8aeAnmDk3svVe\
+            _e0Cq28zm8Rd1AhWKcHtr0_RTfD3hCKPff-dDlYYNqkMJqzYYaV_diP9qQtYIB4jlPr-MiLg9uy1T6CW9oIzpC\
+            fRsG3AnM4qbApunG0iV4z07sn4t_YKDqU6YWF6YN4rc55hXF-UW-fDujAcxygq0eZ-uyJiqDIPqBOFx-aUm2U-\
+            YNhdSgCqCNerRU7_yenreftXnePpGW6lOUpGiUMu1eO_i-eOzsrJMIH9Pok0oLcT9821ky7zDaOoKZPm681ik0\
+            LiA4XBMLLaHT4gaJmx9fDR4ZHaTKRW-RnO3MizeG0bhxkfEReVJCDN-bCmk5jR-TsBul7NEfgjzlDzXh0",
+        "e": "AQAB",
+    });
+    pub(crate) static ref THIRD_PRIVATE_KEY_JWK_JSON: serde_json::Value = serde_json::json!({
+        "alg": "RS512",
+        "kty": "RSA",
+        "n": "4QkrAo4iZ_x-dcRVMbYPCU694i_1yMiiZNEwrxx0hvVJFjq1Up_fS0rqO_eisZYXIAXtIz9zp0AUj5pnbvYnO4\
+            2D8gBBv0PqwnZVqXaWnp7MAvkfBORnpxvxhtOBVJlCdP-Gd2_UKlyLQvlbki9G3xv51U-Z7sId7XxdGoq8Ur2T\
+            n7LaQgcrfrFYRa3hdsym2URwAZqD4V7qoMpglIVR4l0_8M3IvdOqkYKmdvlFSqCJncygvYD4o5zaoGbeSNDMTb\
+            _Wd06gfVq4VrHjUD6mmNnwgLZD9z3bpt9gSaeHaxfv6ohLfa5hk3xUIYzR-PX0YaP70cFWizikQPHykfuq_XsR\
+            VCRGczufSN-nZ_WYQ9QoIHInU8HWUzEfo2xSju8n9LCQBs5wXta4_yQvG2GoolcRvy-QHo5_mVjE-AIzPU9fqa\
+            Ph4VbThpsf8F3a01_3FZzPYNNmIyHuf5WtDtOAglCUmb9BRjNUOZx7P4rMebq9buCvmLMGcN1fF09GB7Cj58f4\
+            enx5xrGnxoWNS5Oq0n5BSe37vyZWErV57J-u8hvyNyww-fbNa5Tq-NT64qKovtxaTcT9wpTI1R8ynwv_3CGxEq\
+            vdfWMJpG-0G8rJE476-TFiTZDJrjOCz8yvWLzpbUL_yV60L1wutNZGK19MDndPs8REKazNBrNkhA4jFmk",
+        "e": "AQAB",
+        "d": "BbHgM0PajgwfPSa3SzdCwScLeh5hl2-wYGHE3CGZt1Kcw2G_9fPSyEqAi_zz0o-sTSSpQCOabyiZfJrZND8pdg\
+            HaTXUBNPBoj_xDpbK5FBGLsyWPyeVn1BxKa_GNS9FHE8PlqL8dPOczpQG_df8ZSYshMus9R4QFEt37UzEgm-1t\
+            P3TcviAFU0L0q2q_kmySalwng3rgDIOE54XHlOqczGA0vojOpDMnlWAbi1Y6kS1n3iTwCmupnyZYiAeiZ3BVQ0\
+            BwEnvcZt-1W4hL6ttzBto5EmDKbobTuLDddG-3Zxfne-VwF6GimYNjc6fnJmg_A3uNY10DrilEStp0m4olZRGh\
+            iqjfM8D21w5DbBXHOOb_j5YqbqqIwmrIMDqnigeeF43pTMiDsJTV9KSklJhI4Xeick4Cfxdcys2NB5zTb-5Ens\
+            acWP75Lug63O_Ctg53uTJywfgygCH-kviVI1AnCJQmRYXxXmJ3f76pk0Q28Q8m-a-Gfz_EwyICpQf0q6uzMaHq\
+            mbqlml9MPcEnSvw413BTSoCi_0Bscf44bN4OVIjnAM0ZI6-d-JDtCtEaILUEZLy5RhFTr0-b6g8nN_PW5TL-rm\
+            0GKADh8oTe6UPXB9koUz7mQbo2UIRCeqdaTVGTMibbcG6-VPwK3C9RYMd-JaL6KXafhFqqqYd3j3CczwE",
+        "p": "9uw6oucmjRsQEpgZ4jHlSD5cxoE_G51lhA8LN63CjZXrrfX6F3Nve9pJCKRqurrtOqN4-BUrj6Fj3flmDIi1AJ\
+            bQc1nNx7Ub6_QgQVcACMVlcXQl43HmWU-lRtB1aggu7ldij2iTPnAUBpCWHQniuVKdcfde6Dp2HoX8lhUHcDJC\
+            gZsZVi0zMinWySlMI6iVGd_-AUN0dmoJB-uNoxQRIqS_1zpchV_HydCSKJWEvKlepzOId5701lb2pE3VGEsTgR\
+            WeZAQkQnMbj9IyU8vXT2LKwarTvgFst1lppoc5WwRRt53GpT6naslgMeZT1mQ4daH4VRZtaub2UgRz_p_5wQ",
+        "q": "6U72ZxNp-9WkS1OhjTd-iptWlS_eoTefuD1HbKI5Gdpm27n4rVhMlQ5850HzlhuDLKbHMC3VJj8yQyzxmw0gZO\
+            1FDIwmfs56hOm5BBqVLz_w0XNmUYMLFOr0rcYoEdG9UjWMW8cNR8xro3Na-JJPraxxzwKBhuDVMeC9Fz2PvvW5\
+            K3iGFGtxyVV8rfNzW1oLM1Yh361FwpGZDKb6MeeUdFGaM2Zm_i1SdqdKeyozWdOTlxa_9h65elbA6Q-vAgH0to\
+            tWEoGXorfQSty4VzkseZsNStM9jVC_qOz7XEP1G5VY-FgqDp97EtaEFEXlI1m7IR8J9Sm5wmXXP6JzSPi2qQ",
+        "dp": "6qmoNCbvKUzd4n3wo1n958F0lCZCnnDgCDfv1jNpYPtopsPMsLrA6XiSzgbLc7iwOXkgPsyHwJGFgnBBfonxU\
+            qUBZ46iFThWV9CDUvVmj-9hMAhM9bHKU3hwvzLfUyyBxXsArP-aZ81GCtMgBmrdsHgGL0gDRzpABVQar1s-3W6\
+            2a8vFsXN1YygF-tknhf_x60xHblX3-Y1eFcvTqHcqrbzaoKXTReG30mFIdg9avZ1M2iQMfnpvfr_Y2F8rtFGtM\
+            JEmHTEecJRKTk1aDO79KXHe9VzurHVYJ4tu75c1d_1L1--22FYJ719_7wxRAr_uNEUA54RP6jejQmD_B65rQQ",
+        "dq": "DQPiqw1iNDJO4ATiVkZqQME5q0joWoYH2vdQLLdACDku08MkJEdwvj_4p83-ELmoL61-HynvYhrUavru339z9\
+            y1lVLXcz-Yk35VKRPhWqjQe8xBE_Pcafyg8ThVn1takgRmLovMPwpmcx5l_0a4X09By6OFy6FtSiV3ILqu4gSa\
+            kFdC7ahg0WM70DaAvJ0F2rn53lUrNQXVt3E7UVkRc-8AxofxscnGE0Cm09fn8s14iNaOHQeuk3TZJvLarRKlnR\
+            WewfYfNNb6bWBHIFtWRrZcAvtPZ8zA2AO53d0YzPj6un-wLLJWo__wHkjnz9mzjC433uD2Dp1yXDW26kK-PQQ",
+        "qi": "j-Hch7AVdEsl3FqMLcrhgd0MTcjvVYZMCbi7bauSVp_tUaqhL2etJeN0ZqGlBKceBKQzbXjqJ0tZrG1dnwPUR\
+            aHPetFyeXTF9X-0kCKjKo-r6LhSF9vMYJhqOYqkxw0cOJqXHTfPdxh8aSBUCNS7aq4gXSjs7QCHfCm70wdlwgt\
+            FS__NdxbYqj_5nYq6xWa_VAURvfXwqDoQM2yr_uQTnRr8JvS7KkmfbqRqNsmynpqjIMZvHoERLq8_69-0Dd0jI\
+            Ka1Bj82KJJ-ZOs7aUzsBRx-wjxmDG_VDxbF4V8AFkGweyX57tLnzbs3CXhUBrc8_MXcN0JQsNzhnAKqoZv_aQ",
+    });
+    pub(crate) static ref THIRD_PUBLIC_KEY_JWK_JSON: serde_json::Value = serde_json::json!({
+        "alg": "RS512",
+        "kty": "RSA",
+        "n": "4QkrAo4iZ_x-dcRVMbYPCU694i_1yMiiZNEwrxx0hvVJFjq1Up_fS0rqO_eisZYXIAXtIz9zp0AUj5pnbvYnO4\
+            2D8gBBv0PqwnZVqXaWnp7MAvkfBORnpxvxhtOBVJlCdP-Gd2_UKlyLQvlbki9G3xv51U-Z7sId7XxdGoq8Ur2T\
+            n7LaQgcrfrFYRa3hdsym2URwAZqD4V7qoMpglIVR4l0_8M3IvdOqkYKmdvlFSqCJncygvYD4o5zaoGbeSNDMTb\
+            _Wd06gfVq4VrHjUD6mmNnwgLZD9z3bpt9gSaeHaxfv6ohLfa5hk3xUIYzR-PX0YaP70cFWizikQPHykfuq_XsR\
+            VCRGczufSN-nZ_WYQ9QoIHInU8HWUzEfo2xSju8n9LCQBs5wXta4_yQvG2GoolcRvy-QHo5_mVjE-AIzPU9fqa\
+            Ph4VbThpsf8F3a01_3FZzPYNNmIyHuf5WtDtOAglCUmb9BRjNUOZx7P4rMebq9buCvmLMGcN1fF09GB7Cj58f4\
+            enx5xrGnxoWNS5Oq0n5BSe37vyZWErV57J-u8hvyNyww-fbNa5Tq-NT64qKovtxaTcT9wpTI1R8ynwv_3CGxEq\
+            vdfWMJpG-0G8rJE476-TFiTZDJrjOCz8yvWLzpbUL_yV60L1wutNZGK19MDndPs8REKazNBrNkhA4jFmk",
+        "e": "AQAB",
+    });
+}
+
+// The license most tests sign with the issuer key
+#[cfg(feature = "std")]
+pub(crate) fn license() -> License {
+    license_with(serde_json::json!({}))
+}
+
+// `license()` with the given top-level members added or replaced
+#[cfg(feature = "std")]
+pub(crate) fn license_with(members: serde_json::Value) -> License {
+    let mut license = serde_json::json!({
+        "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+        "expirationDate": "2024-10-01T00:00:00Z",
+        "customData": {
+            "owner": "John Doe"
+        }
+    });
+    for (name, value) in members
+        .as_object()
+        .expect("License members come as an object")
+    {
+        license[name] = value.clone();
+    }
+    serde_json::from_value(license).unwrap()
+}
+
+#[cfg(feature = "sign")]
+pub(crate) fn signer() -> LicenseSigner {
+    LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).expect("Signer instantiation must work")
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn verifier() -> LicenseVerifier {
+    LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+        .expect("Verifier instantiation must work")
+}
+
+// Only the tests mixing key types use these, and they need the `ecdsa` backend
+#[cfg(feature = "ecdsa")]
+lazy_static! {
+    pub(crate) static ref P256_PRIVATE_KEY_JWK_JSON: serde_json::Value = serde_json::json!({
+        "alg": "ES256",
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "nMvhV80GZZ0Zp68bBypsehjBSy_9JeETdAjZNI4kD_Y",
+        "y": "nA85dIJZA_EYgJ_AFrPG2GDtZZ6gQ6daNVE9QOmoxcY",
+        "d": "HtkBTOmsmRA1CDbhoxFVH2mqXMUYGb9Rvz3n3QxewIk",
+    });
+    pub(crate) static ref P256_PUBLIC_KEY_JWK_JSON: serde_json::Value = serde_json::json!({
+        "alg": "ES256",
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "nMvhV80GZZ0Zp68bBypsehjBSy_9JeETdAjZNI4kD_Y",
+        "y": "nA85dIJZA_EYgJ_AFrPG2GDtZZ6gQ6daNVE9QOmoxcY",
+    });
+    pub(crate) static ref ED25519_PRIVATE_KEY_JWK_JSON: serde_json::Value = serde_json::json!({
+        "alg": "EdDSA",
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "x": "jbMmqB5zhpSIfzOn9xLA8-KS3rmHBd54AKjEV8vF_pw",
+        "d": "hbLUZ8gf6GUgac1_BnJDtFsUlEOxFuhntdv1ruzmFvQ",
+    });
+    pub(crate) static ref ED25519_PUBLIC_KEY_JWK_JSON: serde_json::Value = serde_json::json!({
+        "alg": "EdDSA",
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "x": "jbMmqB5zhpSIfzOn9xLA8-KS3rmHBd54AKjEV8vF_pw",
+    });
+}
+
+// Signs like a TPM-backed device key, which tests cannot create without a TPM
+#[cfg(all(feature = "ecdsa", feature = "std"))]
+pub(crate) fn p256_device_token<T: serde::Serialize>(typ: &str, claims: &T) -> String {
+    use crate::jws;
+
+    let d = jws::decode(P256_PRIVATE_KEY_JWK_JSON["d"].as_str().unwrap()).unwrap();
+    let signing_key = p256::ecdsa::SigningKey::from_slice(&d).unwrap();
+    let signing_input = jws::signing_input(&jws::Header::new("ES256", typ), claims);
+    let signature: p256::ecdsa::Signature =
+        p256::ecdsa::signature::Signer::sign(&signing_key, signing_input.as_bytes());
+    jws::compact(signing_input, &signature.to_bytes())
+}
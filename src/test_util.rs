@@ -0,0 +1,204 @@
+// Helpers for exercising licensing code in tests. Nothing here is fit for production: keys are
+// throwaway and the tampering helpers exist only to build documents that must fail verification.
+use crate::builder::LicenseBuilder;
+use crate::clock::Clock;
+use crate::datetime::{self, DateTime, Duration};
+use crate::jws;
+#[cfg(feature = "sign")]
+use crate::signing::LicenseSigner;
+#[cfg(feature = "sign")]
+use crate::verification::LicenseVerifier;
+use crate::License;
+#[cfg(feature = "sign")]
+use rand::rngs::OsRng;
+#[cfg(feature = "sign")]
+use rsa::traits::{PrivateKeyParts, PublicKeyParts};
+#[cfg(feature = "sign")]
+use rsa::{BigUint, RsaPrivateKey};
+use std::sync::Mutex;
+
+#[cfg(feature = "sign")]
+const EPHEMERAL_KEY_BITS: usize = 4096;
+
+#[cfg(feature = "sign")]
+pub fn ephemeral_signer_and_verifier() -> (LicenseSigner, LicenseVerifier) {
+    let private_key =
+        RsaPrivateKey::new(&mut OsRng, EPHEMERAL_KEY_BITS).expect("RSA key generation must work");
+    let mut jwk = serde_json::json!({
+        "alg": "RS512",
+        "kty": "RSA",
+        "n": encode(private_key.n()),
+        "e": encode(private_key.e()),
+    });
+    let verifier =
+        LicenseVerifier::new(jwk.clone()).expect("Ephemeral verifier instantiation must work");
+
+    // jose-jwk only imports private keys that carry the CRT parameters
+    let primes = private_key.primes();
+    let (Some(dp), Some(dq), Some(qi)) = (
+        private_key.dp(),
+        private_key.dq(),
+        private_key.crt_coefficient(),
+    ) else {
+        panic!("Generated keys are always precomputed")
+    };
+    for (member, value) in [
+        ("d", private_key.d()),
+        ("p", &primes[0]),
+        ("q", &primes[1]),
+        ("dp", dp),
+        ("dq", dq),
+        ("qi", &qi),
+    ] {
+        jwk[member] = serde_json::json!(encode(value));
+    }
+    let signer = LicenseSigner::new(jwk).expect("Ephemeral signer instantiation must work");
+    (signer, verifier)
+}
+
+#[cfg(feature = "sign")]
+fn encode(value: &BigUint) -> String {
+    jws::encode(&value.to_bytes_be())
+}
+
+// A clock that only moves when told to, for verifiers whose checks depend on the time
+pub struct FixedClock(Mutex<DateTime>);
+
+impl FixedClock {
+    pub fn new(now: DateTime) -> Self {
+        Self(Mutex::new(now))
+    }
+
+    // RFC 3339, e.g. "2024-09-01T00:00:00Z"
+    pub fn at(now: &str) -> Self {
+        Self::new(datetime::parse(now).expect("Fixed clocks need an RFC 3339 time"))
+    }
+
+    pub fn set(&self, now: DateTime) {
+        *self.0.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.0.lock().unwrap();
+        *now += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime {
+        *self.0.lock().unwrap()
+    }
+}
+
+pub fn test_license(expires_in: Duration) -> License {
+    LicenseBuilder::new()
+        .validity(expires_in)
+        .custom_field("owner", serde_json::json!("Test Licensee"))
+        .build()
+}
+
+pub struct Tamper {
+    verifiable_license: serde_json::Value,
+}
+
+impl Tamper {
+    pub fn new(verifiable_license: serde_json::Value) -> Self {
+        Self { verifiable_license }
+    }
+
+    pub fn flip_signature_bit(mut self) -> Self {
+        let signature = &mut self.verifiable_license["licenseValidation"]["signature"];
+        let mut signature_bytes = signature
+            .as_str()
+            .and_then(jws::decode)
+            .expect("The license must carry a flattened JWS signature");
+        if let Some(last) = signature_bytes.last_mut() {
+            *last ^= 1;
+        }
+        *signature = serde_json::json!(jws::encode(&signature_bytes));
+        self
+    }
+
+    // `path` is a JSON pointer into the outer license, e.g. "/customData/owner"
+    pub fn modify_outer_field(mut self, path: &str, value: serde_json::Value) -> Self {
+        let (parent, field) = path
+            .rsplit_once('/')
+            .expect("The path must be a JSON pointer");
+        let parent = self
+            .verifiable_license
+            .pointer_mut(&format!("/license{}", parent))
+            .expect("The parent of the modified field must exist");
+        match parent {
+            serde_json::Value::Array(items) => {
+                let index: usize = field.parse().expect("Array fields need an index");
+                items[index] = value;
+            }
+            _ => {
+                parent[field] = value;
+            }
+        }
+        self
+    }
+
+    pub fn swap_payload(mut self, other: &serde_json::Value) -> Self {
+        self.verifiable_license["licenseValidation"]["payload"] =
+            other["licenseValidation"]["payload"].clone();
+        self
+    }
+
+    pub fn into_value(self) -> serde_json::Value {
+        self.verifiable_license
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::verification::LicenseVerificationError;
+
+    #[test]
+    fn tampered_documents_fail_verification() {
+        let (signer, verifier) = ephemeral_signer_and_verifier();
+        let verifiable_license = signer.sign(&test_license(Duration::days(30))).unwrap();
+        let other_license = signer.sign(&test_license(Duration::days(60))).unwrap();
+        verifier
+            .verify(verifiable_license.clone())
+            .expect("Verification should succeed");
+
+        let cases = [
+            (
+                Tamper::new(verifiable_license.clone()).flip_signature_bit(),
+                LicenseVerificationError::VerificationFailure,
+            ),
+            (
+                Tamper::new(verifiable_license.clone())
+                    .modify_outer_field("/customData/owner", serde_json::json!("Mallory")),
+                LicenseVerificationError::TamperedLicense,
+            ),
+            (
+                Tamper::new(verifiable_license.clone()).swap_payload(&other_license),
+                LicenseVerificationError::TamperedLicense,
+            ),
+        ];
+        for (tamper, expected) in cases {
+            let result = verifier.verify(tamper.into_value());
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, expected);
+        }
+    }
+
+    #[test]
+    fn ephemeral_keys_are_independent() {
+        let (signer, _) = ephemeral_signer_and_verifier();
+        let (_, other_verifier) = ephemeral_signer_and_verifier();
+        let verifiable_license = signer.sign(&test_license(Duration::days(30))).unwrap();
+
+        let result = other_verifier.verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::VerificationFailure);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::datetime::{self, DateTime};
+use crate::License;
+use time::OffsetDateTime;
+
+// Identities when `time` is the only backend. chrono dates outside years -9999 to 9999 saturate to
+// time's range and leap seconds to the second before.
+pub fn to_offset_date_time(date_time: DateTime) -> OffsetDateTime {
+    let seconds = datetime::unix_seconds(date_time);
+    let nanos = datetime::subsec_nanos(date_time).min(999_999_999);
+    OffsetDateTime::from_unix_timestamp(seconds)
+        .and_then(|d| d.replace_nanosecond(nanos))
+        .unwrap_or(if seconds < 0 {
+            time::PrimitiveDateTime::MIN.assume_utc()
+        } else {
+            time::PrimitiveDateTime::MAX.assume_utc()
+        })
+}
+
+pub fn from_offset_date_time(date_time: OffsetDateTime) -> DateTime {
+    datetime::from_unix(date_time.unix_timestamp(), date_time.nanosecond())
+        .expect("time timestamps are representable by chrono")
+}
+
+impl License {
+    pub fn expiration_offset_date_time(&self) -> OffsetDateTime {
+        to_offset_date_time(self.expiration_date)
+    }
+
+    pub fn is_expired_at_offset(&self, now: OffsetDateTime) -> bool {
+        from_offset_date_time(now) >= self.expiration_date
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license() -> License {
+        serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "2024-10-01T00:00:00.250Z",
+            "customData": {
+                "owner": "John Doe"
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn expiration_date_as_offset_date_time() {
+        let expiration_date = license().expiration_offset_date_time();
+
+        assert_eq!(
+            expiration_date,
+            OffsetDateTime::from_unix_timestamp(1727740800)
+                .and_then(|d| d.replace_millisecond(250))
+                .unwrap()
+        );
+        assert_eq!(
+            from_offset_date_time(expiration_date),
+            license().expiration_date
+        );
+    }
+
+    #[test]
+    fn expiration_check_with_offset_date_time() {
+        let expiration_date = license().expiration_offset_date_time();
+
+        assert!(!license().is_expired_at_offset(expiration_date - time::Duration::SECOND));
+        assert!(license().is_expired_at_offset(expiration_date));
+    }
+}
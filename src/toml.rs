@@ -0,0 +1,254 @@
+use crate::jws;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::{License, VerifiableLicense, VerifiedLicense};
+
+impl VerifiableLicense {
+    pub fn from_toml_str(toml: &str) -> Result<Self, LicenseVerificationError> {
+        let table: ::toml::Table = toml
+            .parse()
+            .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
+        Self::from_toml_table(table)
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, LicenseVerificationError> {
+        let verifiable_license =
+            serde_json::to_value(self).expect("Verifiable licenses are serializable");
+        let ::toml::Value::Table(table) = json_to_toml(verifiable_license)
+            .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?
+        else {
+            return Err(LicenseVerificationError::InvalidVerifiableLicense);
+        };
+        ::toml::to_string(&table).map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)
+    }
+
+    fn from_toml_table(table: ::toml::Table) -> Result<Self, LicenseVerificationError> {
+        serde_json::from_value(toml_license_json(table))
+            .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)
+    }
+}
+
+// Both entry points verify the converted document as is: going through `VerifiableLicense` would
+// drop unsigned members of the outer license before the tamper check sees them
+impl LicenseVerifier {
+    pub fn verify_toml_str(&self, toml: &str) -> Result<License, LicenseVerificationError> {
+        let verifiable_license_json = toml
+            .parse()
+            .map(toml_license_json)
+            .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense);
+        self.verify_decoded(verifiable_license_json)
+            .map(VerifiedLicense::into_license)
+    }
+
+    pub fn verify_toml_table(
+        &self,
+        document: &str,
+        table_path: &str,
+    ) -> Result<License, LicenseVerificationError> {
+        let verifiable_license_json = document
+            .parse::<::toml::Table>()
+            .ok()
+            .and_then(|document| {
+                table_path
+                    .split('.')
+                    .try_fold(&document, |table, key| table.get(key)?.as_table())
+                    .cloned()
+            })
+            .map(toml_license_json)
+            .ok_or(LicenseVerificationError::InvalidVerifiableLicense);
+        self.verify_decoded(verifiable_license_json)
+            .map(VerifiedLicense::into_license)
+    }
+}
+
+fn toml_license_json(table: ::toml::Table) -> serde_json::Value {
+    let mut verifiable_license = toml_to_json(::toml::Value::Table(table));
+    restore_signed_nulls(&mut verifiable_license);
+    verifiable_license
+}
+
+// TOML datetimes are mapped to their RFC 3339 string form, which is how JSON licenses carry them.
+fn toml_to_json(value: ::toml::Value) -> serde_json::Value {
+    match value {
+        ::toml::Value::String(string) => serde_json::Value::String(string),
+        ::toml::Value::Integer(integer) => serde_json::Value::from(integer),
+        ::toml::Value::Float(float) => serde_json::Value::from(float),
+        ::toml::Value::Boolean(boolean) => serde_json::Value::Bool(boolean),
+        ::toml::Value::Datetime(datetime) => serde_json::Value::String(datetime.to_string()),
+        ::toml::Value::Array(array) => {
+            serde_json::Value::Array(array.into_iter().map(toml_to_json).collect())
+        }
+        ::toml::Value::Table(table) => serde_json::Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+// TOML has no null: null object members are omitted and restored from the signed payload on
+// read, while nulls inside arrays cannot be represented at all.
+fn json_to_toml(value: serde_json::Value) -> Option<::toml::Value> {
+    Some(match value {
+        serde_json::Value::Null => return None,
+        serde_json::Value::Bool(boolean) => ::toml::Value::Boolean(boolean),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(integer) => ::toml::Value::Integer(integer),
+            None => ::toml::Value::Float(number.as_f64()?),
+        },
+        serde_json::Value::String(string) => ::toml::Value::String(string),
+        serde_json::Value::Array(array) => ::toml::Value::Array(
+            array
+                .into_iter()
+                .map(json_to_toml)
+                .collect::<Option<Vec<_>>>()?,
+        ),
+        serde_json::Value::Object(object) => ::toml::Value::Table(
+            object
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| Some((key, json_to_toml(value)?)))
+                .collect::<Option<::toml::Table>>()?,
+        ),
+    })
+}
+
+fn restore_signed_nulls(verifiable_license: &mut serde_json::Value) {
+    let signed_license: Option<serde_json::Value> = verifiable_license
+        .pointer("/licenseValidation/payload")
+        .and_then(|v| v.as_str())
+        .and_then(jws::decode)
+        .and_then(|payload| serde_json::from_slice(&payload).ok());
+    if let (Some(license), Some(signed_license)) =
+        (verifiable_license.get_mut("license"), signed_license)
+    {
+        restore_nulls(license, &signed_license);
+    }
+}
+
+fn restore_nulls(value: &mut serde_json::Value, signed_value: &serde_json::Value) {
+    let (Some(object), Some(signed_object)) = (value.as_object_mut(), signed_value.as_object())
+    else {
+        return;
+    };
+    for (key, signed_value) in signed_object {
+        match object.get_mut(key) {
+            Some(value) => restore_nulls(value, signed_value),
+            None if signed_value.is_null() => {
+                object.insert(key.clone(), serde_json::Value::Null);
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::test_keys::{license_with, signer, verifier, ISSUER_PUBLIC_KEY_JWK_JSON};
+
+    fn license() -> License {
+        license_with(serde_json::json!({
+            "customData": {
+                "owner": "John Doe",
+                "renewal": "2025-01-01T00:00:00Z",
+                "reseller": null,
+                "limits": { "nodes": 4 }
+            }
+        }))
+    }
+
+    fn verifiable_license() -> VerifiableLicense {
+        let verifiable_license = signer().sign(&license()).unwrap();
+        serde_json::from_value(verifiable_license).unwrap()
+    }
+
+    #[test]
+    fn standalone_toml_license() {
+        let toml = verifiable_license()
+            .to_toml_string()
+            .expect("Serializing should work");
+        assert!(!toml.contains("reseller"));
+
+        let verified_license = verifier()
+            .verify_toml_str(&toml)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license, license());
+    }
+
+    #[test]
+    fn toml_license_with_native_datetimes() {
+        let toml = verifiable_license()
+            .to_toml_string()
+            .unwrap()
+            .replace("\"2024-10-01T00:00:00Z\"", "2024-10-01T00:00:00Z")
+            .replace("\"2025-01-01T00:00:00Z\"", "2025-01-01T00:00:00Z");
+        assert!(toml.contains("= 2025-01-01T00:00:00Z"));
+
+        let verified_license = verifier()
+            .verify_toml_str(&toml)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license, license());
+    }
+
+    #[test]
+    fn toml_license_nested_in_config_document() {
+        let license_toml = verifiable_license().to_toml_string().unwrap();
+        let license_table: ::toml::Table = license_toml.parse().unwrap();
+        let mut document: ::toml::Table =
+            "[server]\nport = 8443\n\n[deployment]\nregion = \"eu\"\n"
+                .parse()
+                .unwrap();
+        document["deployment"]
+            .as_table_mut()
+            .unwrap()
+            .insert("license".to_owned(), ::toml::Value::Table(license_table));
+        let document = ::toml::to_string(&document).unwrap();
+
+        let verified_license = verifier()
+            .verify_toml_table(&document, "deployment.license")
+            .expect("Verification should succeed");
+        assert_eq!(verified_license, license());
+
+        let result = verifier().verify_toml_table(&document, "server.license");
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::InvalidVerifiableLicense);
+    }
+
+    #[test]
+    fn toml_license_with_unsigned_outer_field() {
+        let toml = verifiable_license().to_toml_string().unwrap().replacen(
+            "[license]\n",
+            "[license]\ntier = \"enterprise\"\n",
+            1,
+        );
+        assert!(toml.contains("tier = \"enterprise\""));
+
+        let result = verifier().verify_toml_str(&toml);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::TamperedLicense);
+    }
+
+    #[test]
+    fn toml_license_errors_in_opaque_mode() {
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .build()
+            .expect("Verifier instantiation must work");
+
+        for result in [
+            verifier.verify_toml_str("license = ["),
+            verifier.verify_toml_table("[server]\nport = 8443\n", "server.license"),
+        ] {
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::LicenseInvalid);
+        }
+    }
+}
@@ -0,0 +1,682 @@
+use crate::at_rest;
+use crate::jws;
+use crate::store::{LicenseStore, LicenseStoreError};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tss_esapi::attributes::ObjectAttributesBuilder;
+use tss_esapi::constants::tss::{TPM2_RH_NULL, TPM2_ST_HASHCHECK};
+use tss_esapi::constants::{SessionType, Tss2ResponseCodeKind};
+use tss_esapi::handles::{KeyHandle, ObjectHandle, SessionHandle};
+use tss_esapi::interface_types::algorithm::{HashingAlgorithm, PublicAlgorithm};
+use tss_esapi::interface_types::ecc::EccCurve;
+use tss_esapi::interface_types::key_bits::RsaKeyBits;
+use tss_esapi::interface_types::resource_handles::Hierarchy;
+use tss_esapi::interface_types::session_handles::{AuthSession, PolicySession};
+use tss_esapi::structures::{
+    Digest, EccScheme, HashScheme, HashcheckTicket, KeyedHashScheme, PcrSelectionList, PcrSlot,
+    Private, Public, PublicBuilder, PublicKeyedHashParameters, RsaExponent, SensitiveData,
+    Signature, SignatureScheme, SymmetricDefinition, SymmetricDefinitionObject,
+};
+use tss_esapi::tcti_ldr::{DeviceConfig, TctiNameConf};
+use tss_esapi::traits::{Marshall, UnMarshall};
+use tss_esapi::tss2_esys::TPMT_TK_HASHCHECK;
+use tss_esapi::utils::{
+    create_restricted_decryption_rsa_public, create_unrestricted_signing_ecc_public,
+};
+use tss_esapi::Context;
+use zeroize::Zeroizing;
+
+// Resource manager first: it lets several processes share the TPM
+const TPM_DEVICES: [&str; 2] = ["/dev/tpmrm0", "/dev/tpm0"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TpmError {
+    NotPresent,
+    PcrMismatch,
+    InvalidBlob,
+    Backend(String),
+}
+
+// Seals data to the local TPM. The returned blob is only usable by the TPM that sealed it and,
+// when PCR indices are given, only while those PCRs hold the values they had at sealing time.
+pub trait TpmSealer: Send + Sync {
+    fn seal(&self, data: &[u8], pcrs: &[u32]) -> Result<Vec<u8>, TpmError>;
+    fn unseal(&self, sealed: &[u8]) -> Result<Vec<u8>, TpmError>;
+}
+
+pub fn detect_tpm() -> Result<PathBuf, TpmError> {
+    detect_tpm_in(&TPM_DEVICES.map(Path::new))
+}
+
+fn detect_tpm_in(devices: &[&Path]) -> Result<PathBuf, TpmError> {
+    devices
+        .iter()
+        .find(|device| device.exists())
+        .map(|device| device.to_path_buf())
+        .ok_or(TpmError::NotPresent)
+}
+
+// Talks to the TPM through tss-esapi. Each operation opens its own ESAPI context and flushes the
+// transient objects it loaded, so the TPM is not held between calls and `Tpm` can be shared.
+pub struct Tpm {
+    tcti: TctiNameConf,
+}
+
+impl Tpm {
+    // The `TPM2TOOLS_TCTI`/`TCTI` environment variables select another TPM, e.g. the swtpm
+    // simulator; otherwise the local device is used and its absence is `NotPresent`
+    pub fn open() -> Result<Self, TpmError> {
+        let tcti = match TctiNameConf::from_environment_variable() {
+            Ok(tcti) => tcti,
+            Err(_) => {
+                let device = detect_tpm()?;
+                DeviceConfig::from_str(&device.to_string_lossy())
+                    .map(TctiNameConf::Device)
+                    .map_err(backend_error)?
+            }
+        };
+        let tpm = Self { tcti };
+        tpm.context()?;
+        Ok(tpm)
+    }
+
+    pub fn generate_signing_key(&self) -> Result<TpmSigningKey, TpmError> {
+        let public = create_unrestricted_signing_ecc_public(
+            EccScheme::EcDsa(HashScheme::new(HashingAlgorithm::Sha256)),
+            EccCurve::NistP256,
+        )
+        .map_err(backend_error)?;
+        let created = self.with_primary_key(|context, primary_key| {
+            context
+                .execute_with_nullauth_session(|context| {
+                    context.create(primary_key, public, None, None, None, None)
+                })
+                .map_err(backend_error)
+        })?;
+        TpmSigningKey::new(self.tcti.clone(), created.out_public, created.out_private)
+    }
+
+    // `wrapped_key` is what `TpmSigningKey::wrapped_key` returned on this same TPM
+    pub fn load_signing_key(
+        &self,
+        wrapped_key: &serde_json::Value,
+    ) -> Result<TpmSigningKey, TpmError> {
+        let wrapped_key: WrappedKey = wrapped_key
+            .get("tpmKey")
+            .cloned()
+            .and_then(|wrapped_key| serde_json::from_value(wrapped_key).ok())
+            .ok_or(TpmError::InvalidBlob)?;
+        let public = jws::decode(&wrapped_key.public)
+            .and_then(|public| Public::unmarshall(&public).ok())
+            .ok_or(TpmError::InvalidBlob)?;
+        let private = jws::decode(&wrapped_key.private)
+            .and_then(|private| Private::try_from(private).ok())
+            .ok_or(TpmError::InvalidBlob)?;
+        TpmSigningKey::new(self.tcti.clone(), public, private)
+    }
+
+    fn context(&self) -> Result<Context, TpmError> {
+        context(&self.tcti)
+    }
+
+    fn with_primary_key<T>(
+        &self,
+        operation: impl FnOnce(&mut Context, KeyHandle) -> Result<T, TpmError>,
+    ) -> Result<T, TpmError> {
+        with_primary_key(&self.tcti, operation)
+    }
+}
+
+// The TPM only seals a few dozen bytes, so it seals a random key and the data itself is encrypted
+// with it like `at_rest` does
+impl TpmSealer for Tpm {
+    fn seal(&self, data: &[u8], pcrs: &[u32]) -> Result<Vec<u8>, TpmError> {
+        let mut secret = Zeroizing::new(vec![0u8; 32]);
+        OsRng.fill_bytes(&mut secret);
+        let sensitive_data = SensitiveData::try_from(secret.to_vec()).map_err(backend_error)?;
+
+        let created = self.with_primary_key(|context, primary_key| {
+            let auth_policy = if pcrs.is_empty() {
+                Digest::default()
+            } else {
+                let trial_session = start_pcr_policy(context, SessionType::Trial, pcrs)?;
+                let auth_policy = context.policy_get_digest(trial_session);
+                flush_session(context, trial_session.into());
+                auth_policy.map_err(backend_error)?
+            };
+            let object_attributes = ObjectAttributesBuilder::new()
+                .with_fixed_tpm(true)
+                .with_fixed_parent(true)
+                .with_no_da(true)
+                .with_user_with_auth(pcrs.is_empty())
+                .build()
+                .map_err(backend_error)?;
+            let public = PublicBuilder::new()
+                .with_public_algorithm(PublicAlgorithm::KeyedHash)
+                .with_name_hashing_algorithm(HashingAlgorithm::Sha256)
+                .with_object_attributes(object_attributes)
+                .with_auth_policy(auth_policy)
+                .with_keyed_hash_parameters(PublicKeyedHashParameters::new(KeyedHashScheme::Null))
+                .with_keyed_hash_unique_identifier(Digest::default())
+                .build()
+                .map_err(backend_error)?;
+            context
+                .execute_with_nullauth_session(|context| {
+                    context.create(primary_key, public, None, Some(sensitive_data), None, None)
+                })
+                .map_err(backend_error)
+        })?;
+
+        let sealed_blob = SealedBlob {
+            public: jws::encode(&created.out_public.marshall().map_err(backend_error)?),
+            private: jws::encode(created.out_private.value()),
+            pcrs: pcrs.to_vec(),
+            ciphertext: jws::encode(&at_rest::seal(data, &secret)),
+        };
+        Ok(serde_json::to_vec(&sealed_blob).expect("Sealed blobs are serializable"))
+    }
+
+    fn unseal(&self, sealed: &[u8]) -> Result<Vec<u8>, TpmError> {
+        let sealed_blob: SealedBlob =
+            serde_json::from_slice(sealed).map_err(|_| TpmError::InvalidBlob)?;
+        let public = jws::decode(&sealed_blob.public)
+            .and_then(|public| Public::unmarshall(&public).ok())
+            .ok_or(TpmError::InvalidBlob)?;
+        let private = jws::decode(&sealed_blob.private)
+            .and_then(|private| Private::try_from(private).ok())
+            .ok_or(TpmError::InvalidBlob)?;
+        let ciphertext = jws::decode(&sealed_blob.ciphertext).ok_or(TpmError::InvalidBlob)?;
+
+        let secret = self.with_primary_key(|context, primary_key| {
+            let sealed_object = context
+                .execute_with_nullauth_session(|context| context.load(primary_key, private, public))
+                .map_err(backend_error)?;
+            let secret = if sealed_blob.pcrs.is_empty() {
+                context
+                    .execute_with_nullauth_session(|context| context.unseal(sealed_object.into()))
+            } else {
+                match start_pcr_policy(context, SessionType::Policy, &sealed_blob.pcrs) {
+                    Ok(policy_session) => {
+                        let secret = context
+                            .execute_with_session(Some(policy_session.into()), |context| {
+                                context.unseal(sealed_object.into())
+                            });
+                        flush_session(context, policy_session.into());
+                        secret
+                    }
+                    Err(error) => {
+                        flush(context, sealed_object.into());
+                        return Err(error);
+                    }
+                }
+            };
+            flush(context, sealed_object.into());
+            secret.map_err(backend_error)
+        })?;
+
+        let secret = Zeroizing::new(secret.value().to_vec());
+        at_rest::unseal(&ciphertext, &secret).map_err(|_| TpmError::InvalidBlob)
+    }
+}
+
+// An ECDSA P-256 key created under the storage primary key. Only its TPM-wrapped private part
+// leaves the TPM, which cannot be loaded by any other TPM.
+pub struct TpmSigningKey {
+    tcti: TctiNameConf,
+    public: Public,
+    private: Private,
+    verifying_key: p256::ecdsa::VerifyingKey,
+}
+
+impl TpmSigningKey {
+    fn new(tcti: TctiNameConf, public: Public, private: Private) -> Result<Self, TpmError> {
+        let Public::Ecc { unique, .. } = &public else {
+            return Err(TpmError::InvalidBlob);
+        };
+        let point = p256::EncodedPoint::from_affine_coordinates(
+            &p256::FieldBytes::from(field_element(unique.x().value())?),
+            &p256::FieldBytes::from(field_element(unique.y().value())?),
+            false,
+        );
+        let verifying_key = p256::ecdsa::VerifyingKey::from_encoded_point(&point)
+            .map_err(|_| TpmError::InvalidBlob)?;
+        Ok(Self {
+            tcti,
+            public,
+            private,
+            verifying_key,
+        })
+    }
+
+    pub fn verifying_key(&self) -> &p256::ecdsa::VerifyingKey {
+        &self.verifying_key
+    }
+
+    pub fn wrapped_key(&self) -> serde_json::Value {
+        let wrapped_key = WrappedKey {
+            public: jws::encode(
+                &self
+                    .public
+                    .marshall()
+                    .expect("A public area read from the TPM can be marshalled"),
+            ),
+            private: jws::encode(self.private.value()),
+        };
+        serde_json::json!({ "tpmKey": wrapped_key })
+    }
+
+    // The digest is computed here and the TPM only signs it, returning the raw `r || s` of ES256
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, TpmError> {
+        let digest = Digest::try_from(Sha256::digest(data).to_vec()).map_err(backend_error)?;
+        // The key is not restricted, so the TPM does not need proof that it computed the digest
+        let validation = HashcheckTicket::try_from(TPMT_TK_HASHCHECK {
+            tag: TPM2_ST_HASHCHECK,
+            hierarchy: TPM2_RH_NULL,
+            digest: Default::default(),
+        })
+        .map_err(backend_error)?;
+
+        let signature = with_primary_key(&self.tcti, |context, primary_key| {
+            let signing_key = context
+                .execute_with_nullauth_session(|context| {
+                    context.load(primary_key, self.private.clone(), self.public.clone())
+                })
+                .map_err(backend_error)?;
+            let signature = context.execute_with_nullauth_session(|context| {
+                context.sign(signing_key, digest, SignatureScheme::Null, validation)
+            });
+            flush(context, signing_key.into());
+            signature.map_err(backend_error)
+        })?;
+
+        let Signature::EcDsa(signature) = signature else {
+            return Err(TpmError::Backend("unexpected signature scheme".to_owned()));
+        };
+        let signature = p256::ecdsa::Signature::from_scalars(
+            field_element(signature.signature_r().value())?,
+            field_element(signature.signature_s().value())?,
+        )
+        .map_err(|_| TpmError::Backend("invalid ECDSA signature".to_owned()))?;
+        Ok(signature.to_bytes().to_vec())
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct SealedBlob {
+    public: String,
+    private: String,
+    pcrs: Vec<u32>,
+    ciphertext: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct WrappedKey {
+    public: String,
+    private: String,
+}
+
+fn context(tcti: &TctiNameConf) -> Result<Context, TpmError> {
+    Context::new(tcti.clone()).map_err(backend_error)
+}
+
+// The storage primary key is derived from the owner seed, so recreating it from the same template
+// gives back the parent every blob was created under
+fn with_primary_key<T>(
+    tcti: &TctiNameConf,
+    operation: impl FnOnce(&mut Context, KeyHandle) -> Result<T, TpmError>,
+) -> Result<T, TpmError> {
+    let mut context = context(tcti)?;
+    let public = create_restricted_decryption_rsa_public(
+        SymmetricDefinitionObject::AES_128_CFB,
+        RsaKeyBits::Rsa2048,
+        RsaExponent::default(),
+    )
+    .map_err(backend_error)?;
+    let primary_key = context
+        .execute_with_nullauth_session(|context| {
+            context.create_primary(Hierarchy::Owner, public, None, None, None, None)
+        })
+        .map_err(backend_error)?
+        .key_handle;
+    let result = operation(&mut context, primary_key);
+    flush(&mut context, primary_key.into());
+    result
+}
+
+fn start_pcr_policy(
+    context: &mut Context,
+    session_type: SessionType,
+    pcrs: &[u32],
+) -> Result<PolicySession, TpmError> {
+    let pcr_selection = pcr_selection(pcrs)?;
+    let session = context
+        .start_auth_session(
+            None,
+            None,
+            None,
+            session_type,
+            SymmetricDefinition::AES_128_CFB,
+            HashingAlgorithm::Sha256,
+        )
+        .map_err(backend_error)?
+        .ok_or_else(|| TpmError::Backend("no policy session was started".to_owned()))?;
+    let policy_session = PolicySession::try_from(session).map_err(backend_error)?;
+    // An empty digest makes the TPM use the current values of the selected PCRs
+    if let Err(error) = context.policy_pcr(policy_session, Digest::default(), pcr_selection) {
+        flush_session(context, session);
+        return Err(backend_error(error));
+    }
+    Ok(policy_session)
+}
+
+fn pcr_selection(pcrs: &[u32]) -> Result<PcrSelectionList, TpmError> {
+    let pcr_slots = pcrs
+        .iter()
+        .map(|pcr| {
+            1u32.checked_shl(*pcr)
+                .and_then(|slot| PcrSlot::try_from(slot).ok())
+                .ok_or_else(|| TpmError::Backend(format!("invalid PCR index {}", pcr)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    PcrSelectionList::builder()
+        .with_selection(HashingAlgorithm::Sha256, &pcr_slots)
+        .build()
+        .map_err(backend_error)
+}
+
+// Flushing is best effort: the handles are transient and the context is closed right after
+fn flush(context: &mut Context, handle: ObjectHandle) {
+    let _ = context.flush_context(handle);
+}
+
+fn flush_session(context: &mut Context, session: AuthSession) {
+    flush(context, SessionHandle::from(session).into());
+}
+
+// The TPM drops leading zero bytes of ECC parameters
+fn field_element(value: &[u8]) -> Result<[u8; 32], TpmError> {
+    let padding = 32usize
+        .checked_sub(value.len())
+        .ok_or(TpmError::InvalidBlob)?;
+    let mut field_element = [0u8; 32];
+    field_element[padding..].copy_from_slice(value);
+    Ok(field_element)
+}
+
+fn backend_error(error: tss_esapi::Error) -> TpmError {
+    match error {
+        tss_esapi::Error::TssError(response_code)
+            if response_code.kind() == Some(Tss2ResponseCodeKind::PolicyFail) =>
+        {
+            TpmError::PcrMismatch
+        }
+        error => TpmError::Backend(error.to_string()),
+    }
+}
+
+// Only the sealed blob reaches `inner`, so copying the disk to another machine does not move the
+// license.
+pub struct TpmSealedStore<S: LicenseStore, T: TpmSealer> {
+    inner: S,
+    sealer: T,
+    pcrs: Vec<u32>,
+}
+
+impl<S: LicenseStore, T: TpmSealer> TpmSealedStore<S, T> {
+    pub fn new(inner: S, sealer: T) -> Self {
+        Self {
+            inner,
+            sealer,
+            pcrs: vec![],
+        }
+    }
+
+    pub fn bind_to_pcrs(mut self, pcrs: &[u32]) -> Self {
+        self.pcrs = pcrs.to_vec();
+        self
+    }
+}
+
+impl<S: LicenseStore, T: TpmSealer> LicenseStore for TpmSealedStore<S, T> {
+    fn load(&self) -> Result<serde_json::Value, LicenseStoreError> {
+        let contents = self.inner.load()?;
+        let sealed = contents
+            .get("tpmSealed")
+            .and_then(|sealed| sealed.as_str())
+            .and_then(jws::decode)
+            .ok_or(LicenseStoreError::InvalidContents)?;
+        let plaintext = self
+            .sealer
+            .unseal(&sealed)
+            .map_err(LicenseStoreError::Tpm)?;
+        serde_json::from_slice(&plaintext).map_err(|_| LicenseStoreError::InvalidContents)
+    }
+
+    fn save(&self, contents: &serde_json::Value) -> Result<(), LicenseStoreError> {
+        let plaintext =
+            serde_json::to_vec(contents).map_err(|_| LicenseStoreError::InvalidContents)?;
+        let sealed = self
+            .sealer
+            .seal(&plaintext, &self.pcrs)
+            .map_err(LicenseStoreError::Tpm)?;
+        self.inner
+            .save(&serde_json::json!({ "tpmSealed": jws::encode(&sealed) }))
+    }
+}
+
+pub enum TpmStoreSelection<S: LicenseStore, T: TpmSealer> {
+    Sealed(TpmSealedStore<S, T>),
+    Unsealed(S),
+}
+
+impl<S: LicenseStore, T: TpmSealer> TpmStoreSelection<S, T> {
+    // Falls back to storing into `inner` unsealed only when there is no TPM: any other failure to
+    // open it is returned so a misconfigured machine does not silently lose the protection.
+    pub fn select(sealer: Result<T, TpmError>, inner: S, pcrs: &[u32]) -> Result<Self, TpmError> {
+        match sealer {
+            Ok(sealer) => Ok(Self::Sealed(
+                TpmSealedStore::new(inner, sealer).bind_to_pcrs(pcrs),
+            )),
+            Err(TpmError::NotPresent) => Ok(Self::Unsealed(inner)),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn is_sealed(&self) -> bool {
+        matches!(self, Self::Sealed(_))
+    }
+}
+
+impl<S: LicenseStore, T: TpmSealer> LicenseStore for TpmStoreSelection<S, T> {
+    fn load(&self) -> Result<serde_json::Value, LicenseStoreError> {
+        match self {
+            Self::Sealed(store) => store.load(),
+            Self::Unsealed(store) => store.load(),
+        }
+    }
+
+    fn save(&self, contents: &serde_json::Value) -> Result<(), LicenseStoreError> {
+        match self {
+            Self::Sealed(store) => store.save(contents),
+            Self::Unsealed(store) => store.save(contents),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::DeviceKey;
+    use crate::store::MemoryLicenseStore;
+    use std::sync::{Arc, Mutex};
+
+    // Stands in for a TPM: blobs are bound to the machine and to the PCR values at sealing time
+    struct SimulatedTpm {
+        machine: u8,
+        pcr_values: Mutex<Vec<u8>>,
+    }
+
+    impl SimulatedTpm {
+        fn new(machine: u8) -> Self {
+            Self {
+                machine,
+                pcr_values: Mutex::new(vec![0; 24]),
+            }
+        }
+    }
+
+    impl TpmSealer for &SimulatedTpm {
+        fn seal(&self, data: &[u8], pcrs: &[u32]) -> Result<Vec<u8>, TpmError> {
+            let pcr_values = self.pcr_values.lock().unwrap();
+            let policy = pcrs
+                .iter()
+                .map(|pcr| pcr_values[*pcr as usize])
+                .collect::<Vec<_>>();
+            let pcrs = pcrs.iter().map(|pcr| *pcr as u8).collect::<Vec<_>>();
+            let data = data
+                .iter()
+                .map(|byte| byte ^ self.machine)
+                .collect::<Vec<_>>();
+            Ok(serde_json::to_vec(&(self.machine, pcrs, policy, data)).unwrap())
+        }
+
+        fn unseal(&self, sealed: &[u8]) -> Result<Vec<u8>, TpmError> {
+            let (machine, pcrs, policy, data): (u8, Vec<u8>, Vec<u8>, Vec<u8>) =
+                serde_json::from_slice(sealed).unwrap();
+            if machine != self.machine {
+                return Err(TpmError::Backend("TPM_RC_INTEGRITY".to_owned()));
+            }
+            let pcr_values = self.pcr_values.lock().unwrap();
+            if pcrs.iter().map(|pcr| pcr_values[*pcr as usize]).ne(policy) {
+                return Err(TpmError::PcrMismatch);
+            }
+            Ok(data.iter().map(|byte| byte ^ self.machine).collect())
+        }
+    }
+
+    fn license() -> serde_json::Value {
+        serde_json::json!({
+            "license": { "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8", "customData": {} },
+            "licenseValidation": {}
+        })
+    }
+
+    #[test]
+    fn sealed_license_does_not_move_with_the_disk() {
+        let tpm = SimulatedTpm::new(1);
+        let other_machine_tpm = SimulatedTpm::new(2);
+        let disk = Arc::new(MemoryLicenseStore::new());
+        let store = TpmSealedStore::new(disk.clone(), &tpm);
+
+        store.save(&license()).expect("Sealing should work");
+        assert_eq!(store.load(), Ok(license()));
+        assert!(disk.load().unwrap().get("license").is_none());
+
+        let copied_store = TpmSealedStore::new(disk.clone(), &other_machine_tpm);
+        let Err(error) = copied_store.load() else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseStoreError::Tpm(TpmError::Backend("TPM_RC_INTEGRITY".to_owned()))
+        );
+    }
+
+    #[test]
+    fn pcr_bound_license_requires_the_same_boot_state() {
+        let tpm = SimulatedTpm::new(1);
+        let store = TpmSealedStore::new(MemoryLicenseStore::new(), &tpm).bind_to_pcrs(&[0, 7]);
+        store.save(&license()).unwrap();
+
+        tpm.pcr_values.lock().unwrap()[4] = 0xAA;
+        assert_eq!(store.load(), Ok(license()));
+
+        tpm.pcr_values.lock().unwrap()[7] = 0xAA;
+        let Err(error) = store.load() else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseStoreError::Tpm(TpmError::PcrMismatch));
+    }
+
+    #[test]
+    fn missing_tpm_falls_back_to_the_inner_store() {
+        let tpm = SimulatedTpm::new(1);
+
+        let selection = TpmStoreSelection::select(
+            Err::<&SimulatedTpm, _>(TpmError::NotPresent),
+            MemoryLicenseStore::new(),
+            &[7],
+        )
+        .expect("A missing TPM should fall back");
+        assert!(!selection.is_sealed());
+        selection.save(&license()).unwrap();
+        assert_eq!(selection.load(), Ok(license()));
+
+        let selection =
+            TpmStoreSelection::select(Ok(&tpm), MemoryLicenseStore::new(), &[7]).unwrap();
+        assert!(selection.is_sealed());
+
+        let result = TpmStoreSelection::select(
+            Err::<&SimulatedTpm, _>(TpmError::Backend("TPM_RC_LOCKOUT".to_owned())),
+            MemoryLicenseStore::new(),
+            &[7],
+        );
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, TpmError::Backend("TPM_RC_LOCKOUT".to_owned()));
+    }
+
+    // Run with `cargo test --features tpm -- --ignored` against swtpm, e.g. with
+    // `TCTI=swtpm:port=2321`
+    #[test]
+    #[ignore = "needs a TPM"]
+    fn tpm_seals_licenses_and_keeps_device_keys() {
+        let tpm = Tpm::open().expect("A TPM should be available");
+        let key_store = MemoryLicenseStore::new();
+        let device_key = DeviceKey::load_or_generate_in_tpm(&tpm, &key_store).unwrap();
+        assert_eq!(device_key.public_key().alg(), "ES256");
+        assert!(key_store.load().unwrap()["tpmKey"].is_object());
+        let reloaded_key = DeviceKey::load_or_generate_in_tpm(&tpm, &key_store).unwrap();
+        assert_eq!(reloaded_key.thumbprint(), device_key.thumbprint());
+
+        let claims = serde_json::json!({ "sequence": 1 });
+        let token = device_key.sign_token("test+jwt", &claims).unwrap();
+        assert_eq!(
+            device_key
+                .public_key()
+                .verify_token::<serde_json::Value>(&token, "test+jwt"),
+            Ok(claims)
+        );
+
+        let store = TpmSealedStore::new(MemoryLicenseStore::new(), tpm).bind_to_pcrs(&[0, 7]);
+        store.save(&license()).expect("Sealing should work");
+        assert_eq!(store.load(), Ok(license()));
+    }
+
+    #[test]
+    fn tpm_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let resource_manager = dir.path().join("tpmrm0");
+        let device = dir.path().join("tpm0");
+
+        assert_eq!(
+            detect_tpm_in(&[&resource_manager, &device]),
+            Err(TpmError::NotPresent)
+        );
+        std::fs::write(&device, b"").unwrap();
+        assert_eq!(
+            detect_tpm_in(&[&resource_manager, &device]),
+            Ok(device.clone())
+        );
+        std::fs::write(&resource_manager, b"").unwrap();
+        assert_eq!(
+            detect_tpm_in(&[&resource_manager, &device]),
+            Ok(resource_manager)
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use crate::datetime::DateTime;
+#[cfg(feature = "std")]
+use crate::verification::LicenseVerifier;
+use crate::verification::{LicenseVerifierError, PublicKey};
+use alloc::borrow::ToOwned;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde_derive::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "std")]
+pub(crate) const TRUST_BUNDLE_TYP: &str = "jls-trust-bundle+json";
+const SUPPORTED_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrustBundleError {
+    #[cfg(feature = "std")]
+    Io(std::io::ErrorKind),
+    InvalidBundle,
+    UnsupportedVersion(u64),
+    InvalidEntry {
+        issuer: String,
+        reason: TrustBundleEntryError,
+    },
+    MissingSignature,
+    InvalidSignature,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrustBundleEntryError {
+    Malformed,
+    InvalidKey(LicenseVerifierError),
+    NoSupportedAlgorithm,
+    NoKeys,
+    UnpinnedKey(String),
+    DuplicateKeyId(String),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct IssuerEntry {
+    name: String,
+    keys: Vec<serde_json::Value>,
+    algorithms: Vec<String>,
+    #[serde(
+        rename = "notBefore",
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::datetime::rfc3339::option"
+    )]
+    not_before: Option<DateTime>,
+    #[serde(
+        rename = "notAfter",
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::datetime::rfc3339::option"
+    )]
+    not_after: Option<DateTime>,
+    #[serde(
+        rename = "pinnedThumbprints",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    pinned_thumbprints: Vec<String>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct SignedBundleFile {
+    #[serde(rename = "signedBundle")]
+    signed_bundle: String,
+}
+
+#[derive(Clone)]
+pub(crate) struct BundleKey {
+    pub issuer: String,
+    pub public_key: PublicKey,
+    not_before: Option<DateTime>,
+    not_after: Option<DateTime>,
+}
+
+impl BundleKey {
+    pub fn is_valid_at(&self, now: DateTime) -> bool {
+        self.not_before.is_none_or(|not_before| now >= not_before)
+            && self.not_after.is_none_or(|not_after| now < not_after)
+    }
+}
+
+pub struct TrustBundle {
+    keys: BTreeMap<String, BundleKey>,
+}
+
+impl TrustBundle {
+    #[cfg(feature = "std")]
+    pub fn load(path: &Path) -> Result<Self, TrustBundleError> {
+        Self::from_json(&read_json(path)?)
+    }
+
+    // The root verifier holds the key the bundle is signed with; its audit sink receives the cause
+    // of a rejected signature
+    #[cfg(feature = "std")]
+    pub fn load_signed(
+        path: &Path,
+        root_verifier: &LicenseVerifier,
+    ) -> Result<Self, TrustBundleError> {
+        let signed_bundle_file: SignedBundleFile = serde_json::from_value(read_json(path)?)
+            .map_err(|_| TrustBundleError::MissingSignature)?;
+        let bundle: serde_json::Value = root_verifier
+            .reported(
+                root_verifier.verify_token(&signed_bundle_file.signed_bundle, TRUST_BUNDLE_TYP),
+            )
+            .map_err(|_| TrustBundleError::InvalidSignature)?;
+        Self::from_json(&bundle)
+    }
+
+    pub fn from_json(bundle: &serde_json::Value) -> Result<Self, TrustBundleError> {
+        let version = bundle
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .ok_or(TrustBundleError::InvalidBundle)?;
+        if version != SUPPORTED_VERSION {
+            return Err(TrustBundleError::UnsupportedVersion(version));
+        }
+        let issuers = bundle
+            .get("issuers")
+            .and_then(|v| v.as_array())
+            .ok_or(TrustBundleError::InvalidBundle)?;
+
+        let mut keys = BTreeMap::new();
+        for (index, issuer) in issuers.iter().enumerate() {
+            let name = issuer
+                .get("name")
+                .and_then(|v| v.as_str())
+                .map_or_else(|| format!("#{}", index), |name| name.to_owned());
+            let invalid_entry = |reason| TrustBundleError::InvalidEntry {
+                issuer: name.clone(),
+                reason,
+            };
+
+            let issuer: IssuerEntry = serde_json::from_value(issuer.clone())
+                .map_err(|_| invalid_entry(TrustBundleEntryError::Malformed))?;
+            if !issuer
+                .algorithms
+                .iter()
+                .any(|alg| PublicKey::is_supported_alg(alg))
+            {
+                return Err(invalid_entry(TrustBundleEntryError::NoSupportedAlgorithm));
+            }
+            if issuer.keys.is_empty() {
+                return Err(invalid_entry(TrustBundleEntryError::NoKeys));
+            }
+
+            for jwk in &issuer.keys {
+                let kid = jwk
+                    .get("kid")
+                    .and_then(|v| v.as_str())
+                    .ok_or(invalid_entry(TrustBundleEntryError::InvalidKey(
+                        LicenseVerifierError::KeyWithoutId,
+                    )))?;
+                let public_key = PublicKey::from_jwk(jwk.clone())
+                    .map_err(|error| invalid_entry(TrustBundleEntryError::InvalidKey(error)))?;
+                if !issuer.pinned_thumbprints.is_empty()
+                    && !issuer.pinned_thumbprints.contains(&public_key.thumbprint())
+                {
+                    return Err(invalid_entry(TrustBundleEntryError::UnpinnedKey(
+                        kid.to_owned(),
+                    )));
+                }
+
+                let bundle_key = BundleKey {
+                    issuer: issuer.name.clone(),
+                    public_key,
+                    not_before: issuer.not_before,
+                    not_after: issuer.not_after,
+                };
+                if keys.insert(kid.to_owned(), bundle_key).is_some() {
+                    return Err(invalid_entry(TrustBundleEntryError::DuplicateKeyId(
+                        kid.to_owned(),
+                    )));
+                }
+            }
+        }
+
+        // A verifier over an empty bundle would reject everything, which is never what was meant
+        if keys.is_empty() {
+            return Err(TrustBundleError::InvalidBundle);
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn issuers(&self) -> Vec<&str> {
+        let issuers = self
+            .keys
+            .values()
+            .map(|key| key.issuer.as_str())
+            .collect::<BTreeSet<_>>();
+        issuers.into_iter().collect()
+    }
+
+    pub(crate) fn into_keys(self) -> BTreeMap<String, BundleKey> {
+        self.keys
+    }
+}
+
+#[cfg(feature = "std")]
+fn read_json(path: &Path) -> Result<serde_json::Value, TrustBundleError> {
+    let contents = std::fs::read(path).map_err(|e| TrustBundleError::Io(e.kind()))?;
+    serde_json::from_slice(&contents).map_err(|_| TrustBundleError::InvalidBundle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "sign")]
+    use crate::audit::MemoryAuditSink;
+    #[cfg(feature = "sign")]
+    use crate::signing::LicenseSigner;
+    #[cfg(feature = "sign")]
+    use crate::test_keys::{
+        ISSUER_PRIVATE_KEY_JWK_JSON, OTHER_PRIVATE_KEY_JWK_JSON, THIRD_PRIVATE_KEY_JWK_JSON,
+        THIRD_PUBLIC_KEY_JWK_JSON,
+    };
+    use crate::test_keys::{ISSUER_PUBLIC_KEY_JWK_JSON, OTHER_PUBLIC_KEY_JWK_JSON};
+    #[cfg(feature = "sign")]
+    use crate::verification::LicenseVerificationError;
+    #[cfg(feature = "sign")]
+    use crate::License;
+    #[cfg(feature = "sign")]
+    use std::sync::Arc;
+
+    fn with_kid(jwk: &serde_json::Value, kid: &str) -> serde_json::Value {
+        let mut jwk = jwk.clone();
+        jwk["kid"] = serde_json::json!(kid);
+        jwk
+    }
+
+    fn bundle() -> serde_json::Value {
+        serde_json::json!({
+            "version": 1,
+            "issuers": [
+                {
+                    "name": "licensing-eu",
+                    "keys": [with_kid(&ISSUER_PUBLIC_KEY_JWK_JSON, "eu-2024")],
+                    "algorithms": ["RS512"],
+                    "notBefore": "2024-01-01T00:00:00Z"
+                },
+                {
+                    "name": "licensing-us",
+                    "keys": [with_kid(&OTHER_PUBLIC_KEY_JWK_JSON, "us-2024")],
+                    "algorithms": ["RS512"]
+                }
+            ]
+        })
+    }
+
+    #[cfg(feature = "sign")]
+    fn license() -> License {
+        serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": {}
+        }))
+        .unwrap()
+    }
+
+    #[cfg(feature = "sign")]
+    fn sign(private_key: &serde_json::Value, kid: &str) -> serde_json::Value {
+        LicenseSigner::new(private_key.clone())
+            .unwrap()
+            .with_key_id(kid)
+            .sign(&license())
+            .unwrap()
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn bundle_with_two_issuers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trust-bundle.json");
+        std::fs::write(&path, bundle().to_string()).unwrap();
+
+        let bundle = TrustBundle::load(&path).expect("Bundle should load");
+        assert_eq!(bundle.issuers(), vec!["licensing-eu", "licensing-us"]);
+        let verifier = LicenseVerifier::from_trust_bundle(bundle);
+        assert!(verifier.algorithm().is_some());
+
+        for (private_key, kid) in [
+            (&*ISSUER_PRIVATE_KEY_JWK_JSON, "eu-2024"),
+            (&*OTHER_PRIVATE_KEY_JWK_JSON, "us-2024"),
+        ] {
+            let verified_license = verifier
+                .verify(sign(private_key, kid))
+                .expect("Verification should succeed");
+            assert_eq!(verified_license, license());
+        }
+
+        let result = verifier.verify(sign(&THIRD_PRIVATE_KEY_JWK_JSON, "eu-2024"));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::VerificationFailure);
+
+        let result = verifier.verify(sign(&ISSUER_PRIVATE_KEY_JWK_JSON, "eu-2023"));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::UnknownKeyId("eu-2023".to_owned())
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn signed_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trust-bundle.json");
+        let signed_bundle = LicenseSigner::new(THIRD_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign_token(TRUST_BUNDLE_TYP, &bundle());
+        std::fs::write(
+            &path,
+            serde_json::json!({ "signedBundle": signed_bundle }).to_string(),
+        )
+        .unwrap();
+
+        let root_verifier = LicenseVerifier::new(THIRD_PUBLIC_KEY_JWK_JSON.clone()).unwrap();
+        assert!(TrustBundle::load_signed(&path, &root_verifier).is_ok());
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let other_root_verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let result = TrustBundle::load_signed(&path, &other_root_verifier);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, TrustBundleError::InvalidSignature);
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::VerificationFailure]
+        );
+    }
+
+    #[test]
+    fn bundle_with_unknown_version() {
+        let mut bundle = bundle();
+        bundle["version"] = serde_json::json!(2);
+
+        let result = TrustBundle::from_json(&bundle);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, TrustBundleError::UnsupportedVersion(2));
+    }
+
+    #[test]
+    fn bundle_with_malformed_entries() {
+        let mut bundle = bundle();
+        bundle["issuers"][1]["algorithms"] = serde_json::json!(["ES512"]);
+        let result = TrustBundle::from_json(&bundle);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            TrustBundleError::InvalidEntry {
+                issuer: "licensing-us".to_owned(),
+                reason: TrustBundleEntryError::NoSupportedAlgorithm,
+            }
+        );
+
+        let mut bundle = self::bundle();
+        bundle["issuers"][0]["pinnedThumbprints"] = serde_json::json!(["not-the-key"]);
+        let result = TrustBundle::from_json(&bundle);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            TrustBundleError::InvalidEntry {
+                issuer: "licensing-eu".to_owned(),
+                reason: TrustBundleEntryError::UnpinnedKey("eu-2024".to_owned()),
+            }
+        );
+    }
+
+    #[test]
+    fn bundle_without_keys() {
+        let mut bundle = self::bundle();
+        bundle["issuers"][1]["keys"] = serde_json::json!([]);
+        let result = TrustBundle::from_json(&bundle);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            TrustBundleError::InvalidEntry {
+                issuer: "licensing-us".to_owned(),
+                reason: TrustBundleEntryError::NoKeys,
+            }
+        );
+
+        let result = TrustBundle::from_json(&serde_json::json!({ "version": 1, "issuers": [] }));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, TrustBundleError::InvalidBundle);
+    }
+}
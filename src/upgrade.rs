@@ -0,0 +1,153 @@
+use crate::datetime::Duration;
+use crate::VerifiedLicense;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpgradeError {
+    NotAnUpgrade,
+    MissingIssuanceDate,
+    MissingPredecessor,
+    StalePredecessor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpgradePolicy {
+    max_predecessor_lapse: Option<Duration>,
+}
+
+impl UpgradePolicy {
+    pub fn new() -> Self {
+        Self {
+            max_predecessor_lapse: Some(Duration::days(30)),
+        }
+    }
+
+    pub fn max_predecessor_lapse(mut self, max_predecessor_lapse: Duration) -> Self {
+        self.max_predecessor_lapse = Some(max_predecessor_lapse);
+        self
+    }
+
+    pub fn any_predecessor_lapse(mut self) -> Self {
+        self.max_predecessor_lapse = None;
+        self
+    }
+}
+
+impl Default for UpgradePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn verify_upgrade(
+    new: &VerifiedLicense,
+    old_candidates: &[VerifiedLicense],
+) -> Result<(), UpgradeError> {
+    verify_upgrade_with_policy(new, old_candidates, &UpgradePolicy::default())
+}
+
+pub fn verify_upgrade_with_policy(
+    new: &VerifiedLicense,
+    old_candidates: &[VerifiedLicense],
+    policy: &UpgradePolicy,
+) -> Result<(), UpgradeError> {
+    let predecessor_id = new
+        .upgrades_from
+        .as_ref()
+        .ok_or(UpgradeError::NotAnUpgrade)?;
+    let predecessor = old_candidates
+        .iter()
+        .find(|old| &old.id == predecessor_id)
+        .ok_or(UpgradeError::MissingPredecessor)?;
+
+    if let Some(max_predecessor_lapse) = policy.max_predecessor_lapse {
+        let issued_at = new.issued_at.ok_or(UpgradeError::MissingIssuanceDate)?;
+        if issued_at - predecessor.expiration_date > max_predecessor_lapse {
+            return Err(UpgradeError::StalePredecessor);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime;
+    use crate::License;
+    use uuid::Uuid;
+
+    fn verified(license: serde_json::Value) -> VerifiedLicense {
+        let license: License = serde_json::from_value(license).unwrap();
+        VerifiedLicense::new(license, "RS512", Vec::new(), datetime::now())
+    }
+
+    fn base_license(expiration_date: &str) -> VerifiedLicense {
+        verified(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": expiration_date,
+            "customData": {
+                "version": 1
+            }
+        }))
+    }
+
+    fn upgrade_license(issued_at: &str) -> VerifiedLicense {
+        verified(serde_json::json!({
+            "id": "7d1b1c8e-3f52-4a8e-9c55-3f0e2b0b8a61",
+            "issuedAt": issued_at,
+            "expirationDate": "2026-01-01T00:00:00Z",
+            "customData": {
+                "version": 2
+            },
+            "upgradesFrom": "0b5b88f5-a264-4f90-8406-50b01d9515c8"
+        }))
+    }
+
+    #[test]
+    fn upgrade_with_valid_chain() {
+        let other_license = verified(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "expirationDate": "2024-12-01T00:00:00Z",
+            "customData": {}
+        }));
+        let candidates = [other_license, base_license("2024-12-15T00:00:00Z")];
+
+        let result = verify_upgrade(&upgrade_license("2025-01-01T00:00:00Z"), &candidates);
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn upgrade_without_base_license() {
+        let other_license = verified(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "expirationDate": "2024-12-01T00:00:00Z",
+            "customData": {}
+        }));
+
+        let result = verify_upgrade(&upgrade_license("2025-01-01T00:00:00Z"), &[other_license]);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, UpgradeError::MissingPredecessor);
+    }
+
+    #[test]
+    fn upgrade_with_too_old_base_license() {
+        let candidates = [base_license("2024-06-01T00:00:00Z")];
+        let new = upgrade_license("2025-01-01T00:00:00Z");
+
+        let result = verify_upgrade(&new, &candidates);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, UpgradeError::StalePredecessor);
+
+        let issued_at = datetime::parse("2025-01-01T00:00:00Z").unwrap();
+        let lapse = issued_at - candidates[0].expiration_date;
+        let policy = UpgradePolicy::new().max_predecessor_lapse(lapse);
+        assert_eq!(
+            verify_upgrade_with_policy(&new, &candidates, &policy),
+            Ok(())
+        );
+    }
+}
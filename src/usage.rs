@@ -0,0 +1,236 @@
+use crate::datetime::DateTime;
+use crate::device::{DeviceKey, DeviceKeyError, DevicePublicKey};
+use crate::id::LicenseId;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::License;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const USAGE_REPORT_TYP: &str = "jls-usage-report+jwt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UsageReportError {
+    LicenseWithoutDeviceKey,
+    InvalidBoundDeviceKey,
+    InvalidUsageReport,
+    VerificationFailure,
+    LicenseMismatch,
+    DeviceKeyMismatch,
+    InvalidPeriod,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct UsageReport {
+    #[serde(rename = "licenseId")]
+    pub license_id: LicenseId,
+    #[serde(rename = "periodStart", with = "crate::datetime::rfc3339")]
+    pub period_start: DateTime,
+    #[serde(rename = "periodEnd", with = "crate::datetime::rfc3339")]
+    pub period_end: DateTime,
+    pub counters: BTreeMap<String, u64>,
+    #[serde(rename = "deviceKeyThumbprint")]
+    pub device_key_thumbprint: String,
+}
+
+impl UsageReport {
+    pub fn new(
+        license: &License,
+        period_start: DateTime,
+        period_end: DateTime,
+        device_key: &DeviceKey,
+    ) -> Self {
+        Self {
+            license_id: license.id.clone(),
+            period_start,
+            period_end,
+            counters: BTreeMap::new(),
+            device_key_thumbprint: device_key.thumbprint(),
+        }
+    }
+
+    pub fn counter(mut self, name: &str, value: u64) -> Self {
+        self.counters.insert(name.to_owned(), value);
+        self
+    }
+
+    pub fn sign(&self, device_key: &DeviceKey) -> Result<String, DeviceKeyError> {
+        device_key.sign_token(USAGE_REPORT_TYP, self)
+    }
+}
+
+pub fn verify_usage_report(
+    verifier: &LicenseVerifier,
+    usage_report: &str,
+    license: &License,
+) -> Result<UsageReport, UsageReportError> {
+    let bound_device_key = license
+        .device_key
+        .as_ref()
+        .ok_or(UsageReportError::LicenseWithoutDeviceKey)?;
+    let bound_device_key = DevicePublicKey::from_jwk(bound_device_key)
+        .map_err(|_| UsageReportError::InvalidBoundDeviceKey)?;
+
+    let report: UsageReport = bound_device_key
+        .verify_token_reported(verifier, usage_report, USAGE_REPORT_TYP)
+        .map_err(|error| match error {
+            LicenseVerificationError::VerificationFailure => UsageReportError::VerificationFailure,
+            _ => UsageReportError::InvalidUsageReport,
+        })?;
+
+    if report.license_id != license.id {
+        return Err(UsageReportError::LicenseMismatch);
+    }
+    if report.device_key_thumbprint != bound_device_key.thumbprint() {
+        return Err(UsageReportError::DeviceKeyMismatch);
+    }
+    if report.period_start >= report.period_end {
+        return Err(UsageReportError::InvalidPeriod);
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::datetime;
+    use crate::datetime::Duration;
+    use crate::test_keys::{verifier, ISSUER_PUBLIC_KEY_JWK_JSON};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn opaque_verifier(audit_sink: Arc<MemoryAuditSink>) -> LicenseVerifier {
+        LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink)
+            .build()
+            .unwrap()
+    }
+
+    fn license_bound_to(device_key: Option<&DeviceKey>) -> License {
+        License {
+            id: Uuid::parse_str("0b5b88f5-a264-4f90-8406-50b01d9515c8")
+                .unwrap()
+                .into(),
+            product: None,
+            kind: None,
+            issuer: None,
+            issued_at: None,
+            expiration_date: datetime::now() + Duration::days(365),
+            custom_data: serde_json::json!({ "owner": "John Doe" }),
+            device_key: device_key.map(|key| key.public_jwk()),
+            features: Vec::new(),
+            max_seats: None,
+            delegated_key: None,
+            activation_secret: None,
+            allowed_hosts: Vec::new(),
+            allowed_networks: Vec::new(),
+            upgrades_from: None,
+            state_required: false,
+            offline_validity_days: None,
+            maintenance_until: None,
+            watermark: None,
+        }
+    }
+
+    #[test]
+    fn usage_report_round_trip() {
+        let device_key = DeviceKey::generate();
+        let license = license_bound_to(Some(&device_key));
+        let period_end = datetime::now();
+        let period_start = period_end - Duration::days(30);
+
+        let usage_report = UsageReport::new(&license, period_start, period_end, &device_key)
+            .counter("renders", 1250)
+            .counter("exports", 42);
+        let signed_report = usage_report.sign(&device_key).unwrap();
+
+        let verified_report = verify_usage_report(&verifier(), &signed_report, &license)
+            .expect("Verification should succeed");
+        assert_eq!(verified_report, usage_report);
+    }
+
+    #[test]
+    fn usage_report_signed_by_other_device() {
+        let device_key = DeviceKey::generate();
+        let other_device_key = DeviceKey::generate();
+        let license = license_bound_to(Some(&device_key));
+        let period_end = datetime::now();
+
+        let signed_report = UsageReport::new(
+            &license,
+            period_end - Duration::days(30),
+            period_end,
+            &other_device_key,
+        )
+        .sign(&other_device_key)
+        .unwrap();
+
+        let result = verify_usage_report(&verifier(), &signed_report, &license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, UsageReportError::VerificationFailure);
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let result = verify_usage_report(
+            &opaque_verifier(audit_sink.clone()),
+            &signed_report,
+            &license,
+        );
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, UsageReportError::InvalidUsageReport);
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::VerificationFailure]
+        );
+    }
+
+    #[test]
+    fn usage_report_with_inverted_period() {
+        let device_key = DeviceKey::generate();
+        let license = license_bound_to(Some(&device_key));
+        let period_start = datetime::now();
+
+        let signed_report = UsageReport::new(
+            &license,
+            period_start,
+            period_start - Duration::days(30),
+            &device_key,
+        )
+        .sign(&device_key)
+        .unwrap();
+
+        let result = verify_usage_report(&verifier(), &signed_report, &license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, UsageReportError::InvalidPeriod);
+    }
+
+    #[test]
+    fn usage_report_for_license_without_device_key() {
+        let device_key = DeviceKey::generate();
+        let license = license_bound_to(None);
+        let period_end = datetime::now();
+
+        let signed_report = UsageReport::new(
+            &license,
+            period_end - Duration::days(30),
+            period_end,
+            &device_key,
+        )
+        .sign(&device_key)
+        .unwrap();
+
+        let result = verify_usage_report(&verifier(), &signed_report, &license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, UsageReportError::LicenseWithoutDeviceKey);
+    }
+}
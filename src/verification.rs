@@ -1,106 +1,1484 @@
-use crate::{License, VerifiableLicense};
-use jose_jwk::crypto::KeyInfo;
-use jose_jwk::jose_jwa::{Algorithm, Signing};
-use jose_jwk::{Jwk, Key};
-use jose_jws::Jws;
-use rsa::pkcs1v15::{Signature, VerifyingKey};
-use rsa::sha2::Sha512;
-use rsa::signature::Verifier;
-use rsa::RsaPublicKey;
+use crate::audit::AuditSink;
+use crate::certificate::{KeyCertificate, KEY_CERTIFICATE_TYP};
+#[cfg(feature = "std")]
+use crate::clock::{Clock, SystemClock};
+use crate::datetime::{self, DateTime};
+use crate::id::LicenseId;
+use crate::jwa::Algorithm;
+use crate::jws;
+#[cfg(feature = "std")]
+use crate::jwt::JwtClaims;
+#[cfg(feature = "std")]
+use crate::policy::ValidationPolicy;
+use crate::trust_bundle::{BundleKey, TrustBundle};
+use crate::{License, Verifiable, VerifiedLicense};
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::de::DeserializeOwned;
+#[cfg(feature = "std")]
+use serde::Serialize;
+
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(feature = "std")]
+mod embedded;
+mod public_key;
+#[cfg(feature = "async")]
+pub use asynchronous::{AsyncKeyStore, AsyncRevocationProvider, BoxFuture};
+pub use public_key::PublicKey;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LicenseVerificationError {
     InvalidVerifiableLicense,
     TamperedLicense,
     VerificationFailure,
+    ExpiredLease,
+    MissingIssuerCertificate,
+    BrokenCertificateChain,
+    IssuerCertificateExpired,
+    ProductOutOfScope,
+    InsufficientSignatures,
+    ReceiptLicenseMismatch,
+    LicenseIdMismatch {
+        expected: LicenseId,
+        got: LicenseId,
+    },
+    HostNotLicensed,
+    AddressNotLicensed,
+    InvalidClaimFormat(String),
+    LicenseExpired,
+    ClockSuspicious {
+        now: DateTime,
+        issued_at: DateTime,
+    },
+    IssuerOutsideValidityWindow,
+    MissingKeyId,
+    UnknownKeyId(String),
+    TypMismatch {
+        expected: String,
+        got: Option<String>,
+    },
+    UnexpectedValidationMember(String),
+    ExpirationOutOfRange,
+    CustomDataTooComplex,
+    CustomDataTooLarge,
+    OfflineWindowExceeded {
+        last_online: Option<DateTime>,
+    },
+    MissingLicenseId,
+    IssuerMismatch {
+        expected: String,
+        got: Option<String>,
+    },
+    NotYetValid {
+        not_before: DateTime,
+    },
+    SignatureLengthMismatch {
+        expected: usize,
+        got: usize,
+    },
+    LicenseInvalid,
+    UnsupportedCriticalHeader(String),
+    UnsupportedCompression(String),
+    PayloadTooLarge,
+    LicenseRevoked,
+    SeatOutOfRange(u32),
+}
+
+impl LicenseVerificationError {
+    // Codes are part of the wire format of services built on the crate: never rename one, and
+    // give every new variant its own
+    pub fn code(&self) -> &'static str {
+        match self {
+            LicenseVerificationError::InvalidVerifiableLicense => "invalid_verifiable_license",
+            LicenseVerificationError::TamperedLicense => "tampered_license",
+            LicenseVerificationError::VerificationFailure => "verification_failure",
+            LicenseVerificationError::ExpiredLease => "expired_lease",
+            LicenseVerificationError::MissingIssuerCertificate => "missing_issuer_certificate",
+            LicenseVerificationError::BrokenCertificateChain => "broken_certificate_chain",
+            LicenseVerificationError::IssuerCertificateExpired => "issuer_certificate_expired",
+            LicenseVerificationError::ProductOutOfScope => "product_out_of_scope",
+            LicenseVerificationError::InsufficientSignatures => "insufficient_signatures",
+            LicenseVerificationError::ReceiptLicenseMismatch => "receipt_license_mismatch",
+            LicenseVerificationError::LicenseIdMismatch { .. } => "license_id_mismatch",
+            LicenseVerificationError::HostNotLicensed => "host_not_licensed",
+            LicenseVerificationError::AddressNotLicensed => "address_not_licensed",
+            LicenseVerificationError::InvalidClaimFormat(_) => "invalid_claim_format",
+            LicenseVerificationError::LicenseExpired => "license_expired",
+            LicenseVerificationError::ClockSuspicious { .. } => "clock_suspicious",
+            LicenseVerificationError::IssuerOutsideValidityWindow => {
+                "issuer_outside_validity_window"
+            }
+            LicenseVerificationError::MissingKeyId => "missing_key_id",
+            LicenseVerificationError::UnknownKeyId(_) => "unknown_key_id",
+            LicenseVerificationError::TypMismatch { .. } => "typ_mismatch",
+            LicenseVerificationError::UnexpectedValidationMember(_) => {
+                "unexpected_validation_member"
+            }
+            LicenseVerificationError::ExpirationOutOfRange => "expiration_out_of_range",
+            LicenseVerificationError::CustomDataTooComplex => "custom_data_too_complex",
+            LicenseVerificationError::CustomDataTooLarge => "custom_data_too_large",
+            LicenseVerificationError::OfflineWindowExceeded { .. } => "offline_window_exceeded",
+            LicenseVerificationError::MissingLicenseId => "missing_license_id",
+            LicenseVerificationError::IssuerMismatch { .. } => "issuer_mismatch",
+            LicenseVerificationError::NotYetValid { .. } => "not_yet_valid",
+            LicenseVerificationError::SignatureLengthMismatch { .. } => "signature_length_mismatch",
+            LicenseVerificationError::LicenseInvalid => "license_invalid",
+            LicenseVerificationError::UnsupportedCriticalHeader(_) => "unsupported_critical_header",
+            LicenseVerificationError::UnsupportedCompression(_) => "unsupported_compression",
+            LicenseVerificationError::PayloadTooLarge => "payload_too_large",
+            LicenseVerificationError::LicenseRevoked => "license_revoked",
+            LicenseVerificationError::SeatOutOfRange(_) => "seat_out_of_range",
+        }
+    }
+}
+
+impl fmt::Display for LicenseVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseVerificationError::InvalidVerifiableLicense => {
+                f.write_str("malformed verifiable license")
+            }
+            LicenseVerificationError::TamperedLicense => {
+                f.write_str("license does not match its signed payload")
+            }
+            LicenseVerificationError::VerificationFailure => {
+                f.write_str("signature verification failed")
+            }
+            LicenseVerificationError::ExpiredLease => f.write_str("lease expired"),
+            LicenseVerificationError::MissingIssuerCertificate => {
+                f.write_str("missing issuer certificate")
+            }
+            LicenseVerificationError::BrokenCertificateChain => {
+                f.write_str("issuer certificate not signed by the root")
+            }
+            LicenseVerificationError::IssuerCertificateExpired => {
+                f.write_str("issuer certificate outside its validity period")
+            }
+            LicenseVerificationError::ProductOutOfScope => {
+                f.write_str("product outside the scope of the issuer certificate")
+            }
+            LicenseVerificationError::InsufficientSignatures => {
+                f.write_str("not enough valid signatures")
+            }
+            LicenseVerificationError::ReceiptLicenseMismatch => {
+                f.write_str("receipt issued for another license")
+            }
+            LicenseVerificationError::LicenseIdMismatch { expected, got } => {
+                write!(f, "expected license {}, got {}", expected, got)
+            }
+            LicenseVerificationError::HostNotLicensed => f.write_str("host not licensed"),
+            LicenseVerificationError::AddressNotLicensed => f.write_str("address not licensed"),
+            LicenseVerificationError::InvalidClaimFormat(claim) => {
+                write!(f, "invalid `{}` claim", claim)
+            }
+            LicenseVerificationError::LicenseExpired => f.write_str("license expired"),
+            LicenseVerificationError::ClockSuspicious { now, issued_at } => write!(
+                f,
+                "clock at {} is before the issuance at {}",
+                now, issued_at
+            ),
+            LicenseVerificationError::IssuerOutsideValidityWindow => {
+                f.write_str("issuer key outside its validity window")
+            }
+            LicenseVerificationError::MissingKeyId => f.write_str("missing key id"),
+            LicenseVerificationError::UnknownKeyId(kid) => write!(f, "unknown key id `{}`", kid),
+            LicenseVerificationError::TypMismatch { expected, got } => {
+                write!(f, "expected typ `{}`, got {:?}", expected, got)
+            }
+            LicenseVerificationError::UnexpectedValidationMember(member) => write!(
+                f,
+                "unexpected `{}` member in the license validation",
+                member
+            ),
+            LicenseVerificationError::ExpirationOutOfRange => {
+                f.write_str("expiration date out of range")
+            }
+            LicenseVerificationError::CustomDataTooComplex => {
+                f.write_str("custom data nested too deeply")
+            }
+            LicenseVerificationError::CustomDataTooLarge => f.write_str("custom data too large"),
+            LicenseVerificationError::OfflineWindowExceeded {
+                last_online: Some(last_online),
+            } => write!(f, "offline since {} for too long", last_online),
+            LicenseVerificationError::OfflineWindowExceeded { last_online: None } => {
+                f.write_str("never validated online")
+            }
+            LicenseVerificationError::MissingLicenseId => f.write_str("missing license id"),
+            LicenseVerificationError::IssuerMismatch { expected, got } => {
+                write!(f, "expected issuer `{}`, got {:?}", expected, got)
+            }
+            LicenseVerificationError::NotYetValid { not_before } => {
+                write!(f, "license not valid before {}", not_before)
+            }
+            LicenseVerificationError::SignatureLengthMismatch { expected, got } => write!(
+                f,
+                "expected a {}-byte signature, got {} bytes",
+                expected, got
+            ),
+            LicenseVerificationError::LicenseInvalid => f.write_str("invalid license"),
+            LicenseVerificationError::UnsupportedCriticalHeader(name) => {
+                write!(f, "unsupported critical header `{}`", name)
+            }
+            LicenseVerificationError::UnsupportedCompression(zip) => {
+                write!(f, "unsupported compression `{}`", zip)
+            }
+            LicenseVerificationError::PayloadTooLarge => f.write_str("payload too large"),
+            LicenseVerificationError::LicenseRevoked => f.write_str("license revoked"),
+            LicenseVerificationError::SeatOutOfRange(seat) => {
+                write!(f, "seat {} outside the licensed seats", seat)
+            }
+        }
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for LicenseVerificationError {}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum LicenseVerifierError {
     KeyIsNotJwk,
     KeyTypeNotSupported,
+    KeyWithoutId,
+    InvalidSignatureThreshold,
+    MissingKey,
+    ConflictingKeys,
+    AlgorithmNotAllowed,
+    PinnedKeyMismatch,
+}
+
+impl fmt::Display for LicenseVerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LicenseVerifierError::KeyIsNotJwk => "key is not a JWK",
+            LicenseVerifierError::KeyTypeNotSupported => "key type not supported",
+            LicenseVerifierError::KeyWithoutId => "key without id",
+            LicenseVerifierError::InvalidSignatureThreshold => "invalid signature threshold",
+            LicenseVerifierError::MissingKey => "no key configured",
+            LicenseVerifierError::ConflictingKeys => "more than one key configured",
+            LicenseVerifierError::AlgorithmNotAllowed => "key algorithm is not allowed",
+            LicenseVerifierError::PinnedKeyMismatch => "key does not match the pinned thumbprint",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LicenseVerifierError {}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyWithKeyError {
+    Key(LicenseVerifierError),
+    Verification(LicenseVerificationError),
+}
+
+#[cfg(feature = "std")]
+impl From<LicenseVerifierError> for VerifyWithKeyError {
+    fn from(error: LicenseVerifierError) -> Self {
+        VerifyWithKeyError::Key(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<LicenseVerificationError> for VerifyWithKeyError {
+    fn from(error: LicenseVerificationError) -> Self {
+        VerifyWithKeyError::Verification(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for VerifyWithKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyWithKeyError::Key(error) => write!(f, "invalid key: {}", error),
+            VerifyWithKeyError::Verification(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyWithKeyError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Base64Policy {
+    // RFC 7515 §2: unpadded base64url without whitespace
+    #[default]
+    Strict,
+    // Strips `=` padding and embedded whitespace before decoding
+    Lenient,
+}
+
+impl Base64Policy {
+    pub(crate) fn decode(self, data: &str) -> Option<Vec<u8>> {
+        match self {
+            Base64Policy::Strict => jws::decode(data),
+            Base64Policy::Lenient => jws::decode(
+                &data
+                    .chars()
+                    .filter(|c| *c != '=' && !c.is_ascii_whitespace())
+                    .collect::<String>(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CustomDataLimits {
+    max_depth: usize,
+    max_size: usize,
+}
+
+impl Default for CustomDataLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 32,
+            max_size: 64 * 1024,
+        }
+    }
+}
+
+impl CustomDataLimits {
+    fn check(&self, license: &serde_json::Value) -> Result<(), LicenseVerificationError> {
+        let Some(custom_data) = license.get("customData") else {
+            return Ok(());
+        };
+
+        // Walked iteratively so hostile nesting cannot exhaust the stack
+        let mut pending = vec![(custom_data, 0)];
+        while let Some((value, depth)) = pending.pop() {
+            let children: Box<dyn Iterator<Item = &serde_json::Value>> = match value {
+                serde_json::Value::Array(items) => Box::new(items.iter()),
+                serde_json::Value::Object(members) => Box::new(members.values()),
+                _ => continue,
+            };
+            if depth >= self.max_depth {
+                return Err(LicenseVerificationError::CustomDataTooComplex);
+            }
+            pending.extend(children.map(|child| (child, depth + 1)));
+        }
+
+        #[cfg(feature = "std")]
+        let within_limit = serde_json::to_writer(
+            SizeLimit {
+                remaining: self.max_size,
+            },
+            custom_data,
+        )
+        .is_ok();
+        // Without `std::io` writers the size is measured on a full serialization
+        #[cfg(not(feature = "std"))]
+        let within_limit = serde_json::to_vec(custom_data)
+            .is_ok_and(|serialized| serialized.len() <= self.max_size);
+        if !within_limit {
+            return Err(LicenseVerificationError::CustomDataTooLarge);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+struct SizeLimit {
+    remaining: usize,
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for SizeLimit {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.remaining = self
+            .remaining
+            .checked_sub(buf.len())
+            .ok_or(std::io::ErrorKind::FileTooLarge)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+enum TrustModel {
+    Issuer(PublicKey),
+    Root(PublicKey),
+    Quorum {
+        required: usize,
+        keys: BTreeMap<String, PublicKey>,
+    },
+    Bundle(BTreeMap<String, BundleKey>),
+}
+
+// Consulted on every verification once the signature checks out
+pub trait RevocationProvider: Send + Sync {
+    fn is_revoked(&self, license_id: &LicenseId) -> bool;
+}
+
+#[cfg(feature = "std")]
+pub trait LicenseVerification: Send + Sync {
+    fn verify(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<License, LicenseVerificationError>;
+
+    // Required: a `VerifiedLicense` can only come from the crate's own verifiers, so implementations
+    // outside it delegate to one
+    fn verify_license(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<VerifiedLicense, LicenseVerificationError>;
+
+    // Reports a failure of a check made outside the verifier, such as a device-signed token, the
+    // way the verifier reports its own
+    fn report_failure(&self, error: LicenseVerificationError) -> LicenseVerificationError {
+        error
+    }
+
+    // The time licenses are checked against, so callers keeping their own timestamps agree with
+    // the verifier's clock
+    fn now(&self) -> DateTime {
+        datetime::now()
+    }
+}
+
+impl TrustModel {
+    fn public_keys(&self) -> Vec<&PublicKey> {
+        match self {
+            TrustModel::Issuer(public_key) | TrustModel::Root(public_key) => vec![public_key],
+            TrustModel::Quorum { keys, .. } => keys.values().collect(),
+            TrustModel::Bundle(keys) => keys.values().map(|key| &key.public_key).collect(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct VerifierSettings {
+    require_kid: bool,
+    required_typ: Option<String>,
+    strict_structure: bool,
+    base64_policy: Base64Policy,
+    custom_data_limits: CustomDataLimits,
+    #[cfg(feature = "std")]
+    clock: Arc<dyn Clock>,
+    #[cfg(feature = "std")]
+    policy: Option<ValidationPolicy>,
+    expected_issuer: Option<String>,
+    revocation_provider: Option<Arc<dyn RevocationProvider>>,
+    opaque_errors: bool,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    #[cfg(feature = "compression")]
+    max_decompressed_size: usize,
+    #[cfg(feature = "async")]
+    key_store: Option<Arc<dyn AsyncKeyStore>>,
+    #[cfg(feature = "async")]
+    async_revocation_provider: Option<Arc<dyn AsyncRevocationProvider>>,
+    #[cfg(feature = "async")]
+    offload_signature_check: bool,
+    #[cfg(feature = "std")]
+    embedded_cache: embedded::EmbeddedCache,
+}
+
+impl VerifierSettings {
+    fn check_header(&self, header: &jws::Header) -> Result<(), LicenseVerificationError> {
+        if self.require_kid && header.kid.is_none() {
+            return Err(LicenseVerificationError::MissingKeyId);
+        }
+        if let Some(expected) = &self.required_typ {
+            let matches = header
+                .typ
+                .as_deref()
+                .is_some_and(|typ| normalize_typ(typ) == normalize_typ(expected));
+            if !matches {
+                return Err(LicenseVerificationError::TypMismatch {
+                    expected: expected.clone(),
+                    got: header.typ.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for VerifierSettings {
+    fn default() -> Self {
+        Self {
+            require_kid: false,
+            required_typ: None,
+            strict_structure: false,
+            base64_policy: Base64Policy::Strict,
+            custom_data_limits: CustomDataLimits::default(),
+            #[cfg(feature = "std")]
+            clock: Arc::new(SystemClock),
+            #[cfg(feature = "std")]
+            policy: None,
+            expected_issuer: None,
+            revocation_provider: None,
+            opaque_errors: false,
+            audit_sink: None,
+            #[cfg(feature = "compression")]
+            max_decompressed_size: 1024 * 1024,
+            #[cfg(feature = "async")]
+            key_store: None,
+            #[cfg(feature = "async")]
+            async_revocation_provider: None,
+            #[cfg(feature = "async")]
+            offload_signature_check: false,
+            #[cfg(feature = "std")]
+            embedded_cache: embedded::EmbeddedCache::default(),
+        }
+    }
 }
 
 pub struct LicenseVerifier {
-    rsa_public_key: RsaPublicKey,
+    trust_model: TrustModel,
+    key_id: Option<String>,
+    settings: VerifierSettings,
+}
+
+// Where the verifier gets its keys from; setting more than one is a conflicting configuration
+enum TrustSource {
+    Issuer(serde_json::Value),
+    Root(serde_json::Value),
+    Quorum {
+        required: usize,
+        keys: serde_json::Value,
+    },
+    Bundle(TrustBundle),
+    Resolved(TrustModel, Option<String>),
+}
+
+impl TrustSource {
+    fn resolve(self) -> Result<(TrustModel, Option<String>), LicenseVerifierError> {
+        match self {
+            TrustSource::Issuer(public_key) => {
+                let key_id = key_id_of(&public_key);
+                Ok((TrustModel::Issuer(PublicKey::from_jwk(public_key)?), key_id))
+            }
+            TrustSource::Root(root_public_key) => {
+                let key_id = key_id_of(&root_public_key);
+                Ok((
+                    TrustModel::Root(PublicKey::from_jwk(root_public_key)?),
+                    key_id,
+                ))
+            }
+            TrustSource::Quorum { required, keys } => {
+                let key_set = keys
+                    .get("keys")
+                    .and_then(|v| v.as_array())
+                    .ok_or(LicenseVerifierError::KeyIsNotJwk)?;
+
+                let mut keys = BTreeMap::new();
+                for key in key_set {
+                    let kid = key
+                        .get("kid")
+                        .and_then(|v| v.as_str())
+                        .ok_or(LicenseVerifierError::KeyWithoutId)?;
+                    keys.insert(kid.to_owned(), PublicKey::from_jwk(key.clone())?);
+                }
+                if required == 0 || required > keys.len() {
+                    return Err(LicenseVerifierError::InvalidSignatureThreshold);
+                }
+                Ok((TrustModel::Quorum { required, keys }, None))
+            }
+            TrustSource::Bundle(bundle) => Ok((TrustModel::Bundle(bundle.into_keys()), None)),
+            TrustSource::Resolved(trust_model, key_id) => Ok((trust_model, key_id)),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LicenseVerifierBuilder {
+    trust_sources: Vec<TrustSource>,
+    allowed_algorithms: Option<Vec<Algorithm>>,
+    pinned_thumbprint: Option<String>,
+    settings: VerifierSettings,
+}
+
+impl LicenseVerifierBuilder {
+    pub fn new(public_key: serde_json::Value) -> Result<Self, LicenseVerifierError> {
+        LicenseVerifier::new(public_key).map(Self::from)
+    }
+
+    pub fn key_jwk(mut self, public_key: serde_json::Value) -> Self {
+        self.trust_sources.push(TrustSource::Issuer(public_key));
+        self
+    }
+
+    // Licenses are signed by issuers whose keys are certified by this root
+    pub fn root_jwk(mut self, root_public_key: serde_json::Value) -> Self {
+        self.trust_sources.push(TrustSource::Root(root_public_key));
+        self
+    }
+
+    // `required` distinct keys of the JWK set, each identified by its `kid`, must sign the license
+    pub fn require_signatures(mut self, required: usize, keys: serde_json::Value) -> Self {
+        self.trust_sources
+            .push(TrustSource::Quorum { required, keys });
+        self
+    }
+
+    pub fn trust_bundle(mut self, bundle: TrustBundle) -> Self {
+        self.trust_sources.push(TrustSource::Bundle(bundle));
+        self
+    }
+
+    pub fn allowed_algorithms(mut self, algorithms: &[Algorithm]) -> Self {
+        self.allowed_algorithms = Some(algorithms.to_vec());
+        self
+    }
+
+    pub fn pin_thumbprint(mut self, thumbprint: &str) -> Self {
+        self.pinned_thumbprint = Some(thumbprint.to_owned());
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.settings.clock = clock;
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub fn policy(mut self, policy: ValidationPolicy) -> Self {
+        self.settings.policy = Some(policy);
+        self
+    }
+
+    pub fn require_kid(mut self, require_kid: bool) -> Self {
+        self.settings.require_kid = require_kid;
+        self
+    }
+
+    pub fn expected_issuer(mut self, issuer: &str) -> Self {
+        self.settings.expected_issuer = Some(issuer.to_owned());
+        self
+    }
+
+    pub fn revocation_provider(mut self, revocation_provider: Arc<dyn RevocationProvider>) -> Self {
+        self.settings.revocation_provider = Some(revocation_provider);
+        self
+    }
+
+    // Every failure surfaces as `LicenseInvalid`; the audit sink still receives the real cause
+    pub fn opaque_errors(mut self, opaque_errors: bool) -> Self {
+        self.settings.opaque_errors = opaque_errors;
+        self
+    }
+
+    pub fn audit_sink(mut self, audit_sink: Arc<dyn AuditSink>) -> Self {
+        self.settings.audit_sink = Some(audit_sink);
+        self
+    }
+
+    pub fn required_typ(mut self, typ: &str) -> Self {
+        self.settings.required_typ = Some(typ.to_owned());
+        self
+    }
+
+    pub fn strict_structure(mut self, strict_structure: bool) -> Self {
+        self.settings.strict_structure = strict_structure;
+        self
+    }
+
+    pub fn base64_policy(mut self, base64_policy: Base64Policy) -> Self {
+        self.settings.base64_policy = base64_policy;
+        self
+    }
+
+    pub fn max_custom_data_depth(mut self, max_depth: usize) -> Self {
+        self.settings.custom_data_limits.max_depth = max_depth;
+        self
+    }
+
+    pub fn max_custom_data_size(mut self, max_size: usize) -> Self {
+        self.settings.custom_data_limits.max_size = max_size;
+        self
+    }
+
+    // Limits the inflated size of `"zip": "DEF"` payloads
+    #[cfg(feature = "compression")]
+    pub fn max_decompressed_size(mut self, max_size: usize) -> Self {
+        self.settings.max_decompressed_size = max_size;
+        self
+    }
+
+    // `verify_async` resolves the signing key from the store instead of the configured trust
+    #[cfg(feature = "async")]
+    pub fn async_key_store(mut self, key_store: Arc<dyn AsyncKeyStore>) -> Self {
+        self.settings.key_store = Some(key_store);
+        self
+    }
+
+    #[cfg(feature = "async")]
+    pub fn async_revocation_provider(
+        mut self,
+        revocation_provider: Arc<dyn AsyncRevocationProvider>,
+    ) -> Self {
+        self.settings.async_revocation_provider = Some(revocation_provider);
+        self
+    }
+
+    // Runs the signature check of `verify_async` on a dedicated thread instead of inline
+    #[cfg(feature = "async")]
+    pub fn offload_signature_check(mut self, offload_signature_check: bool) -> Self {
+        self.settings.offload_signature_check = offload_signature_check;
+        self
+    }
+
+    pub fn build(self) -> Result<LicenseVerifier, LicenseVerifierError> {
+        if self.trust_sources.len() > 1 {
+            return Err(LicenseVerifierError::ConflictingKeys);
+        }
+        let (trust_model, key_id) = self
+            .trust_sources
+            .into_iter()
+            .next()
+            .ok_or(LicenseVerifierError::MissingKey)?
+            .resolve()?;
+        if trust_model.public_keys().is_empty() {
+            return Err(LicenseVerifierError::MissingKey);
+        }
+
+        if let Some(allowed_algorithms) = &self.allowed_algorithms {
+            let allowed = trust_model
+                .public_keys()
+                .into_iter()
+                .all(|public_key| allowed_algorithms.contains(&public_key.algorithm()));
+            if !allowed {
+                return Err(LicenseVerifierError::AlgorithmNotAllowed);
+            }
+        }
+        if let Some(pinned_thumbprint) = &self.pinned_thumbprint {
+            let pinned = trust_model
+                .public_keys()
+                .into_iter()
+                .any(|public_key| &public_key.thumbprint() == pinned_thumbprint);
+            if !pinned {
+                return Err(LicenseVerifierError::PinnedKeyMismatch);
+            }
+        }
+
+        Ok(LicenseVerifier {
+            trust_model,
+            key_id,
+            settings: self.settings,
+        })
+    }
+}
+
+impl From<LicenseVerifier> for LicenseVerifierBuilder {
+    fn from(verifier: LicenseVerifier) -> Self {
+        Self {
+            trust_sources: vec![TrustSource::Resolved(verifier.trust_model, verifier.key_id)],
+            settings: verifier.settings,
+            ..Self::default()
+        }
+    }
 }
 
 impl LicenseVerifier {
+    pub fn builder() -> LicenseVerifierBuilder {
+        LicenseVerifierBuilder::default()
+    }
+
     pub fn new(public_key: serde_json::Value) -> Result<Self, LicenseVerifierError> {
-        let parsed_public_key: Jwk =
-            serde_json::from_value(public_key).map_err(|_| LicenseVerifierError::KeyIsNotJwk)?;
+        Self::builder().key_jwk(public_key).build()
+    }
+
+    // Shorthands for the default build over each trust model; anything else is configured on the
+    // builder
+    pub fn with_root(root_public_key: serde_json::Value) -> Result<Self, LicenseVerifierError> {
+        Self::builder().root_jwk(root_public_key).build()
+    }
+
+    pub fn require_signatures(
+        required: usize,
+        keys: serde_json::Value,
+    ) -> Result<Self, LicenseVerifierError> {
+        Self::builder().require_signatures(required, keys).build()
+    }
+
+    pub fn from_trust_bundle(bundle: TrustBundle) -> Self {
+        Self::builder()
+            .trust_bundle(bundle)
+            .build()
+            .expect("A trust bundle alone is a complete configuration")
+    }
+
+    // The algorithm of the first configured key; `supported_algorithms` lists those of every key
+    pub fn algorithm(&self) -> Option<Algorithm> {
+        self.trust_model
+            .public_keys()
+            .first()
+            .map(|public_key| public_key.algorithm())
+    }
+
+    pub fn supported_algorithms(&self) -> Vec<Algorithm> {
+        let mut algorithms = Vec::new();
+        for public_key in self.trust_model.public_keys() {
+            if !algorithms.contains(&public_key.algorithm()) {
+                algorithms.push(public_key.algorithm());
+            }
+        }
+        algorithms
+    }
+
+    // Quorum and bundle verifiers hold several keys and export them as a JWK set
+    pub fn public_jwk(&self) -> serde_json::Value {
+        match &self.trust_model {
+            TrustModel::Issuer(public_key) | TrustModel::Root(public_key) => {
+                let kid = self
+                    .key_id
+                    .clone()
+                    .unwrap_or_else(|| public_key.thumbprint());
+                public_key.to_jwk(&kid)
+            }
+            TrustModel::Quorum { keys, .. } => serde_json::json!({
+                "keys": keys
+                    .iter()
+                    .map(|(kid, public_key)| public_key.to_jwk(kid))
+                    .collect::<Vec<_>>(),
+            }),
+            TrustModel::Bundle(keys) => serde_json::json!({
+                "keys": keys
+                    .iter()
+                    .map(|(kid, bundle_key)| bundle_key.public_key.to_jwk(kid))
+                    .collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    // Expiry is only enforced by a configured policy; without one the expiration date is left
+    // to the caller, exactly as in `verify_at`
+    #[cfg(feature = "std")]
+    pub fn verify(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<License, LicenseVerificationError> {
+        self.verify_license(verifiable_license_json)
+            .map(VerifiedLicense::into_license)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn verify_license(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        self.reported(self.verify_license_detailed(verifiable_license_json))
+    }
+
+    // Entry points reading other formats convert the document themselves; a conversion failure is
+    // reported like any other verification error
+    #[cfg(any(feature = "yaml", feature = "toml"))]
+    pub(crate) fn verify_decoded(
+        &self,
+        verifiable_license_json: Result<serde_json::Value, LicenseVerificationError>,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        self.reported(verifiable_license_json.and_then(|json| self.verify_license_detailed(json)))
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn verify_license_detailed(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        let now = self.now();
+        let (license, validating_keys) = self.verify_signed(verifiable_license_json, now)?;
+        self.validated(license, &validating_keys, now)
+    }
+
+    // Every time check runs against `now` instead of a clock. Expiry is only enforced by a
+    // configured policy; without one the expiration date is left to the caller, as in `verify`
+    pub fn verify_at(
+        &self,
+        verifiable_license_json: serde_json::Value,
+        now: DateTime,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        self.reported(self.verify_at_detailed(verifiable_license_json, now))
+    }
+
+    fn verify_at_detailed(
+        &self,
+        verifiable_license_json: serde_json::Value,
+        now: DateTime,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        let (license, validating_keys) = self.verify_signed(verifiable_license_json, now)?;
+        self.validated(license, &validating_keys, now)
+    }
+
+    // Compatibility mode for plain JWTs using registered claim names instead of license fields
+    #[cfg(feature = "std")]
+    pub fn verify_jwt_claims(
+        &self,
+        token: &str,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        self.reported(self.verify_jwt_claims_detailed(token))
+    }
+
+    #[cfg(feature = "std")]
+    fn verify_jwt_claims_detailed(
+        &self,
+        token: &str,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        let now = self.now();
+        let compact =
+            jws::parse_compact(token).ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+        check_critical(&compact.header)?;
+        let public_key = self.signing_key(compact.header.kid.as_deref(), now)?;
+        public_key.verify(
+            &compact.header.alg,
+            compact.signing_input.as_bytes(),
+            &compact.signature,
+        )?;
+
+        let claims: serde_json::Value = serde_json::from_slice(&compact.payload)
+            .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
+        self.settings.custom_data_limits.check(&claims)?;
+        let serde_json::Value::Object(claims) = claims else {
+            return Err(LicenseVerificationError::InvalidVerifiableLicense);
+        };
+        let jwt_claims = JwtClaims::from_claims(claims)?;
+
+        if let Some(not_before) = jwt_claims.not_before.filter(|not_before| now < *not_before) {
+            return Err(LicenseVerificationError::NotYetValid { not_before });
+        }
+        self.validated(jwt_claims.license, std::slice::from_ref(public_key), now)
+    }
+
+    pub(crate) fn validated(
+        &self,
+        license: License,
+        validating_keys: &[PublicKey],
+        now: DateTime,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        if let Some(expected) = &self.settings.expected_issuer {
+            if license.issuer.as_ref() != Some(expected) {
+                return Err(LicenseVerificationError::IssuerMismatch {
+                    expected: expected.clone(),
+                    got: license.issuer,
+                });
+            }
+        }
+        if let Some(revocation_provider) = &self.settings.revocation_provider {
+            if revocation_provider.is_revoked(&license.id) {
+                return Err(LicenseVerificationError::LicenseRevoked);
+            }
+        }
+        let alg = validating_keys
+            .first()
+            .expect("A verified license has at least one validating key")
+            .alg();
+        let key_thumbprints = validating_keys.iter().map(PublicKey::thumbprint).collect();
+        let verified_license = VerifiedLicense::new(license, alg, key_thumbprints, now);
+        #[cfg(feature = "std")]
+        if let Some(policy) = &self.settings.policy {
+            policy.validate_at(&verified_license, now)?;
+        }
+        Ok(verified_license)
+    }
+
+    // Same checks as a configured policy, grace period and clock suspicion included, at the time of
+    // verification
+    #[cfg(feature = "std")]
+    pub fn verify_with_policy(
+        &self,
+        verifiable_license_json: serde_json::Value,
+        policy: &ValidationPolicy,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        self.reported(
+            self.verify_license_detailed(verifiable_license_json)
+                .and_then(|verified_license| self.validated_with(verified_license, policy)),
+        )
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn validated_with(
+        &self,
+        verified_license: VerifiedLicense,
+        policy: &ValidationPolicy,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        policy.validate_at(&verified_license, verified_license.verified_at())?;
+        Ok(verified_license)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn now(&self) -> DateTime {
+        self.settings.clock.now()
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn base64_policy(&self) -> Base64Policy {
+        self.settings.base64_policy
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn untampered_license(
+        &self,
+        outer_license: &serde_json::Value,
+        payload: &[u8],
+    ) -> Result<License, LicenseVerificationError> {
+        untampered(outer_license, payload, self.settings.custom_data_limits)
+    }
+
+    // Applied once at every public entry point so opaque mode cannot leak a detailed cause
+    pub(crate) fn reported<T>(
+        &self,
+        result: Result<T, LicenseVerificationError>,
+    ) -> Result<T, LicenseVerificationError> {
+        result.map_err(|error| {
+            if let Some(audit_sink) = &self.settings.audit_sink {
+                audit_sink.verification_failed(&error);
+            }
+            if self.settings.opaque_errors {
+                LicenseVerificationError::LicenseInvalid
+            } else {
+                error
+            }
+        })
+    }
+
+    pub(crate) fn inflated(
+        &self,
+        header: &jws::Header,
+        payload: Vec<u8>,
+    ) -> Result<Vec<u8>, LicenseVerificationError> {
+        match header.zip.as_deref() {
+            None => Ok(payload),
+            #[cfg(feature = "compression")]
+            Some(jws::DEFLATE) => crate::deflate::decompress(
+                &payload,
+                self.settings.max_decompressed_size,
+            )
+            .map_err(|error| match error {
+                crate::deflate::InflateError::TooLarge => LicenseVerificationError::PayloadTooLarge,
+                crate::deflate::InflateError::Invalid => {
+                    LicenseVerificationError::InvalidVerifiableLicense
+                }
+            }),
+            Some(zip) => Err(LicenseVerificationError::UnsupportedCompression(
+                zip.to_owned(),
+            )),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn verify_into<T: Serialize + DeserializeOwned + PartialEq>(
+        &self,
+        verifiable_json: serde_json::Value,
+    ) -> Result<T, LicenseVerificationError> {
+        self.reported(
+            self.verify_signed_payload(verifiable_json, self.now())
+                .map(|(payload, _, _)| payload),
+        )
+    }
+
+    pub(crate) fn verify_signed(
+        &self,
+        verifiable_license_json: serde_json::Value,
+        now: DateTime,
+    ) -> Result<(License, Vec<PublicKey>), LicenseVerificationError> {
+        let expiration_out_of_range = verifiable_license_json
+            .get("license")
+            .and_then(|license| license.get("expirationDate"))
+            .and_then(|v| v.as_str())
+            .is_some_and(is_out_of_range);
+        if expiration_out_of_range {
+            return Err(LicenseVerificationError::ExpirationOutOfRange);
+        }
+
+        let (license, validating_keys, issuer_certificate) =
+            self.verify_signed_payload::<License>(verifiable_license_json, now)?;
+        if let Some(issuer_certificate) = issuer_certificate {
+            let in_scope = license
+                .product
+                .as_ref()
+                .is_some_and(|product| issuer_certificate.products.contains(product));
+            if !in_scope {
+                return Err(LicenseVerificationError::ProductOutOfScope);
+            }
+        }
+        Ok((license, validating_keys))
+    }
 
-        if !parsed_public_key.is_supported(&Algorithm::from(Signing::Rs512)) {
-            return Err(LicenseVerifierError::KeyTypeNotSupported);
+    // Under a root, the certificate of the signing issuer is returned so callers can check the
+    // payload against its product scope
+    fn verify_signed_payload<T: DeserializeOwned + PartialEq>(
+        &self,
+        verifiable_json: serde_json::Value,
+        now: DateTime,
+    ) -> Result<(T, Vec<PublicKey>, Option<KeyCertificate>), LicenseVerificationError> {
+        if let Some(outer_payload) = verifiable_json.get("license") {
+            self.settings.custom_data_limits.check(outer_payload)?;
+        }
+        let outer_payload = verifiable_json.get("license").cloned().unwrap_or_default();
+        let verifiable: Verifiable<T> = serde_json::from_value(verifiable_json)
+            .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
+        let license_validation_obj = verifiable
+            .license_validation
+            .as_object()
+            .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+        let is_quorum = matches!(self.trust_model, TrustModel::Quorum { .. });
+        if self.settings.strict_structure {
+            let expected_members: &[&str] = if is_quorum {
+                &["payload", "signatures"]
+            } else {
+                &["protected", "payload", "signature"]
+            };
+            if let Some(member) = license_validation_obj
+                .keys()
+                .find(|member| !expected_members.contains(&member.as_str()))
+            {
+                return Err(LicenseVerificationError::UnexpectedValidationMember(
+                    member.clone(),
+                ));
+            }
+        }
+        if let TrustModel::Quorum { required, keys } = &self.trust_model {
+            let (payload, validating_keys) = verify_signature_quorum(
+                &verifiable.license_validation,
+                &outer_payload,
+                *required,
+                keys,
+                self,
+            )?;
+            return Ok((payload, validating_keys, None));
+        }
+
+        let protected_to_verify = license_validation_obj
+            .get("protected")
+            .and_then(|v| v.as_str())
+            .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+        let payload_to_verify = license_validation_obj
+            .get("payload")
+            .and_then(|v| v.as_str())
+            .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+        let data_to_verify = format!("{}.{}", protected_to_verify, payload_to_verify);
+        let signature = license_validation_obj
+            .get("signature")
+            .and_then(|v| v.as_str())
+            .and_then(|signature| self.settings.base64_policy.decode(signature))
+            .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+        let header: jws::Header = self
+            .settings
+            .base64_policy
+            .decode(protected_to_verify)
+            .and_then(|header| serde_json::from_slice(&header).ok())
+            .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+        check_critical(&header)?;
+        self.settings.check_header(&header)?;
+
+        let payload = self
+            .settings
+            .base64_policy
+            .decode(payload_to_verify)
+            .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+        let payload = self.inflated(&header, payload)?;
+        let protected_payload =
+            untampered(&outer_payload, &payload, self.settings.custom_data_limits)?;
+
+        if !matches!(self.trust_model, TrustModel::Root(_)) {
+            let public_key = self.signing_key(header.kid.as_deref(), now)?;
+            public_key.verify(&header.alg, data_to_verify.as_bytes(), &signature)?;
+            return Ok((protected_payload, vec![public_key.clone()], None));
+        }
+
+        let issuer_certificate = verifiable
+            .issuer_certificate
+            .as_deref()
+            .ok_or(LicenseVerificationError::MissingIssuerCertificate)?;
+        let issuer_certificate: KeyCertificate = self
+            .verify_token_at(issuer_certificate, KEY_CERTIFICATE_TYP, now)
+            .map_err(|_| LicenseVerificationError::BrokenCertificateChain)?;
+        if now < issuer_certificate.not_before || now >= issuer_certificate.not_after {
+            return Err(LicenseVerificationError::IssuerCertificateExpired);
+        }
+
+        let issuer_key = PublicKey::from_jwk(issuer_certificate.issuer_key.clone())
+            .map_err(|_| LicenseVerificationError::BrokenCertificateChain)?;
+        issuer_key.verify(&header.alg, data_to_verify.as_bytes(), &signature)?;
+
+        Ok((
+            protected_payload,
+            vec![issuer_key],
+            Some(issuer_certificate),
+        ))
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn verify_token<T: serde::de::DeserializeOwned>(
+        &self,
+        token: &str,
+        typ: &str,
+    ) -> Result<T, LicenseVerificationError> {
+        self.verify_token_at(token, typ, self.now())
+    }
+
+    fn verify_token_at<T: serde::de::DeserializeOwned>(
+        &self,
+        token: &str,
+        typ: &str,
+        now: DateTime,
+    ) -> Result<T, LicenseVerificationError> {
+        let compact =
+            jws::parse_compact(token).ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+        if compact.header.typ.as_deref() != Some(typ) {
+            return Err(LicenseVerificationError::InvalidVerifiableLicense);
         }
+        check_critical(&compact.header)?;
 
-        let rsa_key;
-        if let Key::Rsa(jwk_rsa_key) = parsed_public_key.key {
-            rsa_key = RsaPublicKey::try_from(&jwk_rsa_key)
-                .map_err(|_| LicenseVerifierError::KeyTypeNotSupported)?;
-        } else {
-            return Err(LicenseVerifierError::KeyTypeNotSupported);
-        }
+        self.signing_key(compact.header.kid.as_deref(), now)?
+            .verify(
+                &compact.header.alg,
+                compact.signing_input.as_bytes(),
+                &compact.signature,
+            )?;
 
-        Ok(Self {
-            rsa_public_key: rsa_key,
-        })
+        serde_json::from_slice(&compact.payload)
+            .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)
     }
 
-    pub fn verify(
+    fn signing_key(
         &self,
-        verifiable_license_json: serde_json::Value,
-    ) -> Result<License, LicenseVerificationError> {
-        let verifiable_license: VerifiableLicense = serde_json::from_value(verifiable_license_json)
-            .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
+        kid: Option<&str>,
+        now: DateTime,
+    ) -> Result<&PublicKey, LicenseVerificationError> {
+        match &self.trust_model {
+            TrustModel::Issuer(public_key) | TrustModel::Root(public_key) => Ok(public_key),
+            TrustModel::Quorum { keys, .. } => {
+                let kid = kid.ok_or(LicenseVerificationError::MissingKeyId)?;
+                keys.get(kid)
+                    .ok_or_else(|| LicenseVerificationError::UnknownKeyId(kid.to_owned()))
+            }
+            TrustModel::Bundle(keys) => {
+                let kid = kid.ok_or(LicenseVerificationError::MissingKeyId)?;
+                let bundle_key = keys
+                    .get(kid)
+                    .ok_or_else(|| LicenseVerificationError::UnknownKeyId(kid.to_owned()))?;
+                if !bundle_key.is_valid_at(now) {
+                    return Err(LicenseVerificationError::IssuerOutsideValidityWindow);
+                }
+                Ok(&bundle_key.public_key)
+            }
+        }
+    }
+}
 
-        let license_validation_obj = verifiable_license
-            .license_validation
-            .as_object()
-            .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
-        let protected_to_verify = license_validation_obj
+fn check_critical(header: &jws::Header) -> Result<(), LicenseVerificationError> {
+    match header.unsupported_critical() {
+        Some(name) => Err(LicenseVerificationError::UnsupportedCriticalHeader(
+            name.to_owned(),
+        )),
+        None => Ok(()),
+    }
+}
+
+// Every signature must pass the header checks of the verifier, even one that does not count towards
+// the quorum
+fn verify_signature_quorum<T: DeserializeOwned + PartialEq>(
+    license_validation: &serde_json::Value,
+    outer_payload: &serde_json::Value,
+    required: usize,
+    keys: &BTreeMap<String, PublicKey>,
+    verifier: &LicenseVerifier,
+) -> Result<(T, Vec<PublicKey>), LicenseVerificationError> {
+    let settings = &verifier.settings;
+    let base64_policy = settings.base64_policy;
+    let license_validation_obj = license_validation
+        .as_object()
+        .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+    let payload_to_verify = license_validation_obj
+        .get("payload")
+        .and_then(|v| v.as_str())
+        .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+    let signatures = license_validation_obj
+        .get("signatures")
+        .and_then(|v| v.as_array())
+        .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+
+    let mut entries = Vec::with_capacity(signatures.len());
+    for entry in signatures {
+        let protected_to_verify = entry
             .get("protected")
             .and_then(|v| v.as_str())
             .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
-        let payload_to_verify = license_validation_obj
-            .get("payload")
+        let signature = entry
+            .get("signature")
             .and_then(|v| v.as_str())
+            .and_then(|signature| base64_policy.decode(signature))
             .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
-        let data_to_verify = format!("{}.{}", protected_to_verify, payload_to_verify);
+        let header: jws::Header = base64_policy
+            .decode(protected_to_verify)
+            .and_then(|header| serde_json::from_slice(&header).ok())
+            .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+        settings.check_header(&header)?;
+        check_critical(&header)?;
+        entries.push((protected_to_verify, signature, header));
+    }
+
+    // All signatures cover the same payload, so they must agree on how it is compressed
+    let payload = base64_policy
+        .decode(payload_to_verify)
+        .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+    let payload = match entries.split_first() {
+        Some(((_, _, header), others)) => {
+            if others.iter().any(|(_, _, other)| other.zip != header.zip) {
+                return Err(LicenseVerificationError::InvalidVerifiableLicense);
+            }
+            verifier.inflated(header, payload)?
+        }
+        None => payload,
+    };
+    let protected_payload = untampered(outer_payload, &payload, settings.custom_data_limits)?;
 
-        let Jws::Flattened(license_validation) =
-            serde_json::from_value(verifiable_license.license_validation.clone())
-                .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?
+    // Signatures under a key outside the quorum do not count towards it
+    let mut signed_by = BTreeMap::new();
+    for (protected_to_verify, signature, header) in &entries {
+        let Some((kid, public_key)) = header.kid.as_ref().and_then(|kid| keys.get_key_value(kid))
         else {
-            return Err(LicenseVerificationError::InvalidVerifiableLicense);
+            continue;
         };
+        let data_to_verify = format!("{}.{}", protected_to_verify, payload_to_verify);
+        if public_key
+            .verify(&header.alg, data_to_verify.as_bytes(), signature)
+            .is_ok()
+        {
+            signed_by.insert(kid, public_key.clone());
+        }
+    }
 
-        let payload_slice = license_validation
-            .payload
-            .as_deref()
-            .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
-        let protected_license: License = serde_json::from_slice(payload_slice)
-            .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
+    if signed_by.len() < required {
+        return Err(LicenseVerificationError::InsufficientSignatures);
+    }
+
+    Ok((protected_payload, signed_by.into_values().collect()))
+}
+
+// The outer object is also compared as raw JSON so members `T` does not model cannot be altered,
+// added or dropped without invalidating the signature
+fn untampered<T: DeserializeOwned + PartialEq>(
+    outer: &serde_json::Value,
+    payload: &[u8],
+    custom_data_limits: CustomDataLimits,
+) -> Result<T, LicenseVerificationError> {
+    let protected: serde_json::Value = serde_json::from_slice(payload)
+        .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
+    custom_data_limits.check(&protected)?;
+    let protected_payload: T = serde_json::from_value(protected.clone())
+        .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
+    let outer_payload = T::deserialize(outer).ok();
+    if outer_payload.as_ref() != Some(&protected_payload)
+        || normalize_dates(protected) != normalize_dates(outer.clone())
+    {
+        return Err(LicenseVerificationError::TamperedLicense);
+    }
+    Ok(protected_payload)
+}
+
+// Well-formed RFC 3339 dates whose year, or offset-adjusted instant, the date backend cannot
+// represent
+fn is_out_of_range(date: &str) -> bool {
+    datetime::parse(date) == Err(datetime::ParseError::OutOfRange)
+}
 
-        if protected_license != verifiable_license.license {
-            return Err(LicenseVerificationError::TamperedLicense);
+// Equivalent RFC 3339 representations of the same instant compare equal
+fn normalize_dates(mut license: serde_json::Value) -> serde_json::Value {
+    for field in ["issuedAt", "expirationDate", "maintenanceUntil"] {
+        normalize_date(license.get_mut(field));
+    }
+    if let Some(features) = license.get_mut("features").and_then(|v| v.as_array_mut()) {
+        for feature in features {
+            normalize_date(feature.get_mut("expires"));
         }
+    }
+    license
+}
+
+fn normalize_date(value: Option<&mut serde_json::Value>) {
+    let Some(value) = value else {
+        return;
+    };
+    if let Some(date) = value.as_str().and_then(|v| datetime::parse(v).ok()) {
+        *value = serde_json::json!(datetime::to_rfc3339(date));
+    }
+}
+
+// RFC 7515 §4.1.9: media types compare case-insensitively and may omit "application/"
+fn normalize_typ(typ: &str) -> String {
+    let typ = typ.to_ascii_lowercase();
+    match typ.strip_prefix("application/") {
+        Some(subtype) if !subtype.contains('/') => subtype.to_owned(),
+        _ => typ,
+    }
+}
+
+// One-shot verification with a default verifier, for scripts and tests that check a single document
+#[cfg(feature = "std")]
+pub fn verify_with_key(
+    public_key_jwk: &serde_json::Value,
+    verifiable_license: &serde_json::Value,
+) -> Result<License, VerifyWithKeyError> {
+    let verifier = LicenseVerifier::new(public_key_jwk.clone())?;
+    Ok(verifier.verify(verifiable_license.clone())?)
+}
+
+#[cfg(feature = "std")]
+impl LicenseVerification for LicenseVerifier {
+    fn verify(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<License, LicenseVerificationError> {
+        LicenseVerifier::verify(self, verifiable_license_json)
+    }
+
+    fn verify_license(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        LicenseVerifier::verify_license(self, verifiable_license_json)
+    }
 
-        let rsa_signature =
-            Signature::try_from(license_validation.signature.signature.iter().as_ref())
-                .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
-        let verifying_key = VerifyingKey::<Sha512>::new(self.rsa_public_key.clone());
-        verifying_key
-            .verify(data_to_verify.as_bytes(), &rsa_signature)
-            .map_err(|_| LicenseVerificationError::VerificationFailure)?;
+    fn report_failure(&self, error: LicenseVerificationError) -> LicenseVerificationError {
+        match self.reported::<()>(Err(error)) {
+            Ok(()) => unreachable!("An error stays an error"),
+            Err(error) => error,
+        }
+    }
 
-        Ok(protected_license)
+    fn now(&self) -> DateTime {
+        LicenseVerifier::now(self)
     }
 }
 
+fn key_id_of(public_key: &serde_json::Value) -> Option<String> {
+    public_key
+        .get("kid")
+        .and_then(|v| v.as_str())
+        .map(ToOwned::to_owned)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::datetime::Duration;
+    use crate::jwa::Signing;
+    #[cfg(feature = "sign")]
+    use crate::signing::{sign_with_quorum, LicenseSigner};
+    use crate::test_keys::ISSUER_PUBLIC_KEY_JWK_JSON;
+    #[cfg(feature = "sign")]
+    use crate::test_keys::{
+        ISSUER_PRIVATE_KEY_JWK_JSON, OTHER_PRIVATE_KEY_JWK_JSON, OTHER_PUBLIC_KEY_JWK_JSON,
+        THIRD_PRIVATE_KEY_JWK_JSON, THIRD_PUBLIC_KEY_JWK_JSON,
+    };
+    use crate::test_util::{FixedClock, Tamper};
+    use crate::VerifiableLicense;
+    #[cfg(feature = "sign")]
+    use serde_derive::{Deserialize, Serialize};
 
     use lazy_static::lazy_static;
 
@@ -164,6 +1542,171 @@ mod tests {
         assert_eq!(verified_license, expected_license);
     }
 
+    #[test]
+    fn verify_at_explicit_timestamp() {
+        let verifier = LicenseVerifier::new(PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let now = datetime::parse("2024-06-01T00:00:00Z").unwrap();
+
+        let verified_license = verifier
+            .verify_at(VALID_VERIFIABLE_LICENSE.clone(), now)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license.verified_at(), now);
+
+        verifier
+            .verify_at(
+                VALID_VERIFIABLE_LICENSE.clone(),
+                datetime::parse("2024-10-01T00:00:00Z").unwrap(),
+            )
+            .expect("Expiry is left to the caller without a policy");
+    }
+
+    #[test]
+    fn expired_license_through_verify_and_verify_at() {
+        let expired_at = "2024-10-01T00:00:00Z";
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(PUBLIC_KEY_JWK_JSON.clone())
+            .clock(Arc::new(FixedClock::at(expired_at)))
+            .build()
+            .expect("Verifier instantiation must work");
+        let verified_license = verifier
+            .verify_license(VALID_VERIFIABLE_LICENSE.clone())
+            .expect("Expiry is left to the caller without a policy");
+        assert!(verified_license.verified_at() >= verified_license.expiration_date);
+        verifier
+            .verify_at(
+                VALID_VERIFIABLE_LICENSE.clone(),
+                datetime::parse(expired_at).unwrap(),
+            )
+            .expect("Expiry is left to the caller without a policy");
+
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(PUBLIC_KEY_JWK_JSON.clone())
+            .clock(Arc::new(FixedClock::at(expired_at)))
+            .policy(ValidationPolicy::new())
+            .build()
+            .expect("Verifier instantiation must work");
+        let results = [
+            verifier
+                .verify(VALID_VERIFIABLE_LICENSE.clone())
+                .map(|_| ()),
+            verifier
+                .verify_at(
+                    VALID_VERIFIABLE_LICENSE.clone(),
+                    datetime::parse(expired_at).unwrap(),
+                )
+                .map(|_| ()),
+        ];
+        for result in results {
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::LicenseExpired);
+            assert_eq!(error.to_string(), "license expired");
+        }
+    }
+
+    #[test]
+    fn verify_at_defers_to_policy() {
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(PUBLIC_KEY_JWK_JSON.clone())
+            .policy(ValidationPolicy::new().grace_period(Duration::days(7)))
+            .build()
+            .expect("Verifier instantiation must work");
+
+        verifier
+            .verify_at(
+                VALID_VERIFIABLE_LICENSE.clone(),
+                datetime::parse("2024-10-03T00:00:00Z").unwrap(),
+            )
+            .expect("Verification should succeed within the grace period");
+    }
+
+    #[test]
+    fn license_fixture_through_generic_envelope() {
+        let verifier = LicenseVerifier::new(PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+
+        let license: License = verifier
+            .verify_into(VALID_VERIFIABLE_LICENSE.clone())
+            .expect("Verification should succeed");
+        assert_eq!(
+            license,
+            serde_json::from_value::<License>(EXPECTED_LICENSE.clone()).unwrap()
+        );
+
+        let verifiable_license: VerifiableLicense =
+            serde_json::from_value(VALID_VERIFIABLE_LICENSE.clone()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&verifiable_license).unwrap(),
+            *VALID_VERIFIABLE_LICENSE
+        );
+        assert_eq!(
+            jws::encode(&serde_json::to_vec(&license).unwrap()),
+            VALID_VERIFIABLE_LICENSE["licenseValidation"]["payload"]
+                .as_str()
+                .unwrap()
+                .replace(' ', "")
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+    struct RevocationList {
+        issuer: String,
+        revoked: Vec<String>,
+        #[serde(rename = "issuedAt", with = "crate::datetime::rfc3339")]
+        issued_at: DateTime,
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn custom_payload_round_trip() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let revocation_list = RevocationList {
+            issuer: "https://licensing.acme.example".to_owned(),
+            revoked: vec!["ACME-2023-00481".to_owned(), "ACME-2023-00507".to_owned()],
+            issued_at: datetime::parse("2024-03-01T00:00:00Z").unwrap(),
+        };
+        let verifiable = signer.sign_payload(&revocation_list).unwrap();
+
+        let verified: RevocationList = verifier
+            .verify_into(verifiable.clone())
+            .expect("Verification should succeed");
+        assert_eq!(verified, revocation_list);
+
+        let mut unrevoked = verifiable.clone();
+        unrevoked["license"]["revoked"] = serde_json::json!(["ACME-2023-00507"]);
+        let mut extended = verifiable.clone();
+        extended["license"]["note"] = serde_json::json!("Unsigned member");
+        for tampered in [unrevoked, extended] {
+            let result = verifier.verify_into::<RevocationList>(tampered);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::TamperedLicense);
+        }
+
+        let result = verifier.verify_into::<License>(verifiable.clone());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::InvalidVerifiableLicense);
+
+        let kid_verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .require_kid(true)
+            .build()
+            .expect("Verifier instantiation must work");
+        let result = kid_verifier.verify_into::<RevocationList>(verifiable);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::MissingKeyId);
+    }
+
     #[test]
     fn verifier_with_non_jwk_key() {
         let non_jwk_key = serde_json::json!({
@@ -179,16 +1722,29 @@ mod tests {
     }
 
     #[test]
-    fn verifier_with_non_rsa_key() {
-        let non_rsa_key = serde_json::json!({
-            "alg": "ES256",
+    fn verifier_with_unsupported_key_type() {
+        let p384_key = serde_json::json!({
+            "alg": "ES384",
             "kty": "EC",
-            "crv": "P-256",
-            "x": "6G267OCXrqG-Kr5RuHmUOO7OoRMItapzzG3z0I4pnEU",
-            "y": "i3vOYB9DU-pbCS_vD0ob9X6jvWX2W-TZxF-tJ4sc710"
+            "crv": "P-384",
+            "x": "6G267OCXrqG-Kr5RuHmUOO7OoRMItapzzG3z0I4pnEUi3vOYB9DU-pbCS_vD0ob9",
+            "y": "i3vOYB9DU-pbCS_vD0ob9X6jvWX2W-TZxF-tJ4sc7106G267OCXrqG-Kr5RuHmUO"
         });
 
-        let result = LicenseVerifier::new(non_rsa_key);
+        let result = LicenseVerifier::new(p384_key);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerifierError::KeyTypeNotSupported);
+    }
+
+    #[cfg(feature = "ecdsa")]
+    #[test]
+    fn verifier_with_key_not_on_its_curve() {
+        let mut p256_key = crate::test_keys::P256_PUBLIC_KEY_JWK_JSON.clone();
+        p256_key["y"] = p256_key["x"].clone();
+
+        let result = LicenseVerifier::new(p256_key);
         let Err(error) = result else {
             panic!("An error was expected")
         };
@@ -299,7 +1855,455 @@ mod tests {
         let Err(error) = result else {
             panic!("An error was expected")
         };
-        assert_eq!(error, LicenseVerificationError::TamperedLicense);
+        assert_eq!(error, LicenseVerificationError::TamperedLicense);
+    }
+
+    #[test]
+    fn license_verification_with_extra_outer_field() {
+        let verifiable_license = Tamper::new(VALID_VERIFIABLE_LICENSE.clone())
+            .modify_outer_field("/tier", serde_json::json!("enterprise"))
+            .into_value();
+
+        let verifier = LicenseVerifier::new(PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let result = verifier.verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::TamperedLicense);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_verification_with_dropped_outer_field() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let mut license = EXPECTED_LICENSE.clone();
+        license["tier"] = serde_json::json!("enterprise");
+        let protected = jws::encode(br#"{"alg":"RS512"}"#);
+        let payload = jws::encode(&serde_json::to_vec(&license).unwrap());
+        let signature = signer.sign_bytes(format!("{}.{}", protected, payload).as_bytes());
+        let mut verifiable_license = serde_json::json!({
+            "license": license,
+            "licenseValidation": {
+                "protected": protected,
+                "payload": payload,
+                "signature": jws::encode(&signature),
+            }
+        });
+
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        verifier
+            .verify(verifiable_license.clone())
+            .expect("Verification should succeed");
+
+        verifiable_license["license"]
+            .as_object_mut()
+            .unwrap()
+            .remove("tier");
+        let result = verifier.verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::TamperedLicense);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_verification_with_unsupported_critical_header() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let payload = jws::encode(&serde_json::to_vec(&*EXPECTED_LICENSE).unwrap());
+        let mut cases = vec![(
+            serde_json::json!({ "alg": "RS512", "crit": ["urn:acme:audience"] }),
+            LicenseVerificationError::UnsupportedCriticalHeader("urn:acme:audience".to_owned()),
+        )];
+        if !cfg!(feature = "compression") {
+            cases.push((
+                serde_json::json!({ "alg": "RS512", "zip": "DEF", "crit": ["zip"] }),
+                LicenseVerificationError::UnsupportedCriticalHeader("zip".to_owned()),
+            ));
+        }
+
+        for (header, expected) in cases {
+            let protected = jws::encode(&serde_json::to_vec(&header).unwrap());
+            let signature = signer.sign_bytes(format!("{}.{}", protected, payload).as_bytes());
+            let result = verifier.verify(serde_json::json!({
+                "license": *EXPECTED_LICENSE,
+                "licenseValidation": {
+                    "protected": protected,
+                    "payload": payload,
+                    "signature": jws::encode(&signature),
+                }
+            }));
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, expected);
+        }
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_verification_with_equivalent_date_representations() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let sign_with_dates = |signed: &str, outer: &str| {
+            let mut license = EXPECTED_LICENSE.clone();
+            license["expirationDate"] = serde_json::json!(signed);
+            let protected = jws::encode(br#"{"alg":"RS512"}"#);
+            let payload = jws::encode(&serde_json::to_vec(&license).unwrap());
+            let signature = signer.sign_bytes(format!("{}.{}", protected, payload).as_bytes());
+            license["expirationDate"] = serde_json::json!(outer);
+            serde_json::json!({
+                "license": license,
+                "licenseValidation": {
+                    "protected": protected,
+                    "payload": payload,
+                    "signature": jws::encode(&signature),
+                }
+            })
+        };
+
+        let expiration_date = datetime::parse("2024-10-01T00:00:00Z").unwrap();
+        for (signed, outer) in [
+            ("2024-10-01T02:00:00+02:00", "2024-10-01T00:00:00Z"),
+            ("2024-10-01T00:00:00Z", "2024-09-30T19:00:00-05:00"),
+            ("2024-10-01T00:00:00.000Z", "2024-10-01T00:00:00z"),
+            ("2024-10-01t00:00:00z", "2024-10-01T00:00:00.000000+00:00"),
+        ] {
+            let license = verifier
+                .verify(sign_with_dates(signed, outer))
+                .expect("Verification should succeed");
+            assert_eq!(license.expiration_date, expiration_date);
+            assert_eq!(
+                serde_json::to_value(&license).unwrap()["expirationDate"],
+                "2024-10-01T00:00:00Z"
+            );
+        }
+
+        let result = verifier.verify(sign_with_dates(
+            "2024-10-01T02:00:00+02:00",
+            "2024-10-01T02:00:00Z",
+        ));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::TamperedLicense);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_verification_with_extreme_expiration_dates() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+
+        let max_date = datetime::max();
+        for expiration_date in [datetime::parse("9999-12-31T23:59:59Z").unwrap(), max_date] {
+            let mut license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+            license.expiration_date = expiration_date;
+            let verifiable_license = signer.sign(&license).unwrap();
+
+            let license = verifier
+                .verify(verifiable_license)
+                .expect("Verification should succeed");
+            assert_eq!(license.expiration_date, expiration_date);
+            ValidationPolicy::new()
+                .validate_at(&license, datetime::now())
+                .expect("Validation should succeed");
+        }
+
+        for expiration_date in [
+            "+262143-01-01T00:00:00Z",
+            "-262144-01-01T00:00:00Z",
+            "+262142-12-31T23:59:59-01:00",
+        ] {
+            let mut verifiable_license = VALID_VERIFIABLE_LICENSE.clone();
+            verifiable_license["license"]["expirationDate"] = serde_json::json!(expiration_date);
+            let result = verifier.verify(verifiable_license);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::ExpirationOutOfRange);
+        }
+
+        let mut verifiable_license = VALID_VERIFIABLE_LICENSE.clone();
+        verifiable_license["license"]["expirationDate"] = serde_json::json!("2024-13-01T00:00:00Z");
+        let result = verifier.verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::InvalidVerifiableLicense);
+
+        // The expiration date is mandatory: perpetual licenses carry an explicit far-future date
+        let mut verifiable_license = VALID_VERIFIABLE_LICENSE.clone();
+        verifiable_license["license"]
+            .as_object_mut()
+            .unwrap()
+            .remove("expirationDate");
+        let result = verifier.verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::InvalidVerifiableLicense);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_verification_with_opaque_license_id() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let mut license_json = EXPECTED_LICENSE.clone();
+        license_json["id"] = serde_json::json!("ACME-2023-00481");
+        let license: License = serde_json::from_value(license_json.clone()).unwrap();
+        assert_eq!(license.id.as_str(), Some("ACME-2023-00481"));
+
+        let verifiable_license = signer.sign(&license).unwrap();
+        assert_eq!(verifiable_license["license"], license_json);
+        let verified_license = verifier
+            .verify(verifiable_license.clone())
+            .expect("Verification should succeed");
+        assert_eq!(verified_license.id, LicenseId::from("ACME-2023-00481"));
+
+        let mut tampered_license = verifiable_license;
+        tampered_license["license"]["id"] = serde_json::json!("ACME-2023-00482");
+        let result = verifier.verify(tampered_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::TamperedLicense);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_verification_with_complex_custom_data() {
+        let verifier = LicenseVerifier::new(PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        verifier
+            .verify(VALID_VERIFIABLE_LICENSE.clone())
+            .expect("Verification should succeed");
+
+        let mut custom_data = serde_json::json!("leaf");
+        for _ in 0..10_000 {
+            custom_data = serde_json::Value::Array(vec![custom_data]);
+        }
+        let mut verifiable_license = VALID_VERIFIABLE_LICENSE.clone();
+        verifiable_license["license"]["customData"] = custom_data;
+        let result = verifier.verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::CustomDataTooComplex);
+
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let mut license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        license.custom_data = serde_json::json!({ "owner": "John Doe" });
+        for _ in 0..40 {
+            license.custom_data = serde_json::json!({ "nested": license.custom_data });
+        }
+        let verifiable_license = signer.sign(&license).unwrap();
+
+        let result = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .unwrap()
+            .verify(verifiable_license.clone());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::CustomDataTooComplex);
+
+        LicenseVerifierBuilder::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .unwrap()
+            .max_custom_data_depth(64)
+            .build()
+            .expect("Verifier instantiation must work")
+            .verify(verifiable_license)
+            .expect("Verification should succeed");
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_verification_with_oversized_custom_data() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let mut license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        license.custom_data = serde_json::json!({ "blob": "x".repeat(100 * 1024) });
+        let verifiable_license = signer.sign(&license).unwrap();
+
+        let result = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .unwrap()
+            .verify(verifiable_license.clone());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::CustomDataTooLarge);
+
+        LicenseVerifierBuilder::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .unwrap()
+            .max_custom_data_size(1024 * 1024)
+            .build()
+            .expect("Verifier instantiation must work")
+            .verify(verifiable_license)
+            .expect("Verification should succeed");
+    }
+
+    #[test]
+    fn verifier_public_jwk_round_trip() {
+        let verifier = LicenseVerifier::new(PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        assert_eq!(verifier.algorithm(), Some(Algorithm::from(Signing::Rs512)));
+        assert_eq!(
+            verifier.supported_algorithms(),
+            vec![Algorithm::from(Signing::Rs512)]
+        );
+
+        let public_jwk = verifier.public_jwk();
+        assert_eq!(public_jwk["n"], PUBLIC_KEY_JWK_JSON["n"]);
+        assert_eq!(public_jwk["e"], PUBLIC_KEY_JWK_JSON["e"]);
+        assert_eq!(
+            public_jwk["kid"],
+            PublicKey::from_jwk(PUBLIC_KEY_JWK_JSON.clone())
+                .unwrap()
+                .thumbprint()
+        );
+
+        let reconstructed_verifier =
+            LicenseVerifier::new(public_jwk.clone()).expect("Verifier instantiation must work");
+        assert_eq!(reconstructed_verifier.public_jwk(), public_jwk);
+        let license = reconstructed_verifier
+            .verify(VALID_VERIFIABLE_LICENSE.clone())
+            .expect("Verification should succeed");
+        assert_eq!(
+            license,
+            serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap()
+        );
+
+        let mut named_key = PUBLIC_KEY_JWK_JSON.clone();
+        named_key["kid"] = serde_json::json!("issuer-2024");
+        let verifier = LicenseVerifier::new(named_key).unwrap();
+        assert_eq!(verifier.public_jwk()["kid"], "issuer-2024");
+    }
+
+    #[test]
+    fn fully_configured_verifier_builder() {
+        let thumbprint = PublicKey::from_jwk(PUBLIC_KEY_JWK_JSON.clone())
+            .unwrap()
+            .thumbprint();
+        let configured_builder = |now: &str| {
+            LicenseVerifier::builder()
+                .key_jwk(PUBLIC_KEY_JWK_JSON.clone())
+                .allowed_algorithms(&[
+                    Algorithm::from(Signing::Rs256),
+                    Algorithm::from(Signing::Rs512),
+                ])
+                .pin_thumbprint(&thumbprint)
+                .clock(Arc::new(FixedClock::at(now)))
+                .policy(ValidationPolicy::new())
+                .required_typ("JWT")
+                .strict_structure(true)
+                .base64_policy(Base64Policy::Strict)
+                .max_custom_data_depth(8)
+                .max_custom_data_size(1024)
+        };
+
+        let verifier = configured_builder("2024-09-01T00:00:00Z")
+            .build()
+            .expect("Verifier instantiation must work");
+        let verified_license = verifier
+            .verify_license(VALID_VERIFIABLE_LICENSE.clone())
+            .expect("Verification should succeed");
+        assert_eq!(
+            verified_license.verified_at(),
+            datetime::parse("2024-09-01T00:00:00Z").unwrap()
+        );
+        assert_eq!(
+            verified_license.key_thumbprints(),
+            std::slice::from_ref(&thumbprint)
+        );
+
+        let verifier = configured_builder("2024-11-01T00:00:00Z")
+            .build()
+            .expect("Verifier instantiation must work");
+        let result = verifier.verify(VALID_VERIFIABLE_LICENSE.clone());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::LicenseExpired);
+    }
+
+    #[test]
+    fn conflicting_verifier_builder_configuration() {
+        let other_thumbprint = PublicKey::from_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .unwrap()
+            .thumbprint();
+        let cases = [
+            (
+                LicenseVerifier::builder()
+                    .key_jwk(PUBLIC_KEY_JWK_JSON.clone())
+                    .pin_thumbprint(&other_thumbprint),
+                LicenseVerifierError::PinnedKeyMismatch,
+            ),
+            (
+                LicenseVerifier::builder()
+                    .key_jwk(PUBLIC_KEY_JWK_JSON.clone())
+                    .allowed_algorithms(&[Algorithm::from(Signing::Rs256)]),
+                LicenseVerifierError::AlgorithmNotAllowed,
+            ),
+            (
+                LicenseVerifier::builder().require_kid(true),
+                LicenseVerifierError::MissingKey,
+            ),
+            (
+                LicenseVerifierBuilder::new(PUBLIC_KEY_JWK_JSON.clone())
+                    .unwrap()
+                    .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone()),
+                LicenseVerifierError::ConflictingKeys,
+            ),
+            (
+                LicenseVerifier::builder()
+                    .root_jwk(PUBLIC_KEY_JWK_JSON.clone())
+                    .require_signatures(1, serde_json::json!({ "keys": [] })),
+                LicenseVerifierError::ConflictingKeys,
+            ),
+            (
+                LicenseVerifier::builder().key_jwk(serde_json::json!({ "kty": "RSA" })),
+                LicenseVerifierError::KeyIsNotJwk,
+            ),
+        ];
+
+        for (builder, expected) in cases {
+            let Err(error) = builder.build() else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, expected);
+        }
+    }
+
+    #[test]
+    fn shorthand_verifier_matches_default_build() {
+        let shorthand_verifier = LicenseVerifier::new(PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let built_verifier = LicenseVerifier::builder()
+            .key_jwk(PUBLIC_KEY_JWK_JSON.clone())
+            .build()
+            .expect("Verifier instantiation must work");
+
+        assert_eq!(shorthand_verifier.public_jwk(), built_verifier.public_jwk());
+        assert_eq!(
+            shorthand_verifier.verify(VALID_VERIFIABLE_LICENSE.clone()),
+            built_verifier.verify(VALID_VERIFIABLE_LICENSE.clone())
+        );
+        let tampered_license = Tamper::new(VALID_VERIFIABLE_LICENSE.clone())
+            .modify_outer_field("/customData/owner", serde_json::json!("Jane Doe"))
+            .into_value();
+        for verifiable_license in [tampered_license, serde_json::json!({ "license": {} })] {
+            assert_eq!(
+                shorthand_verifier.verify(verifiable_license.clone()),
+                built_verifier.verify(verifiable_license)
+            );
+        }
     }
 
     #[test]
@@ -368,4 +2372,935 @@ mod tests {
         };
         assert_eq!(error, LicenseVerificationError::VerificationFailure);
     }
+
+    #[test]
+    fn license_verification_with_signature_of_wrong_length() {
+        let verifier = LicenseVerifier::new(PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let with_signature = |signature: &[u8]| {
+            let mut verifiable_license = VALID_VERIFIABLE_LICENSE.clone();
+            verifiable_license["licenseValidation"]["signature"] =
+                serde_json::json!(jws::encode(signature));
+            verifiable_license
+        };
+
+        let cases = [
+            (
+                with_signature(&[0x5a; 10]),
+                LicenseVerificationError::SignatureLengthMismatch {
+                    expected: 512,
+                    got: 10,
+                },
+            ),
+            (
+                with_signature(&vec![0x5a; 10 * 1024 * 1024]),
+                LicenseVerificationError::SignatureLengthMismatch {
+                    expected: 512,
+                    got: 10 * 1024 * 1024,
+                },
+            ),
+            (
+                with_signature(&[0x5a; 512]),
+                LicenseVerificationError::VerificationFailure,
+            ),
+        ];
+        for (verifiable_license, expected) in cases {
+            let result = verifier.verify(verifiable_license);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, expected);
+        }
+    }
+
+    #[cfg(feature = "sign")]
+    fn quorum_signer(private_key: &serde_json::Value, key_id: &str) -> LicenseSigner {
+        LicenseSigner::new(private_key.clone())
+            .unwrap()
+            .with_key_id(key_id)
+    }
+
+    #[cfg(feature = "sign")]
+    fn quorum_verifier() -> LicenseVerifier {
+        let with_kid = |key: &serde_json::Value, key_id: &str| {
+            let mut key = key.clone();
+            key["kid"] = serde_json::json!(key_id);
+            key
+        };
+        let key_set = serde_json::json!({
+            "keys": [
+                with_kid(&ISSUER_PUBLIC_KEY_JWK_JSON, "signer-1"),
+                with_kid(&OTHER_PUBLIC_KEY_JWK_JSON, "signer-2"),
+                with_kid(&THIRD_PUBLIC_KEY_JWK_JSON, "signer-3"),
+            ]
+        });
+        LicenseVerifier::require_signatures(2, key_set).expect("Verifier instantiation must work")
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_verification_with_two_of_three_signatures() {
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let first_signer = quorum_signer(&ISSUER_PRIVATE_KEY_JWK_JSON, "signer-1");
+        let third_signer = quorum_signer(&THIRD_PRIVATE_KEY_JWK_JSON, "signer-3");
+        let verifiable_license = sign_with_quorum(&license, &[&first_signer, &third_signer])
+            .expect("Signing should work");
+
+        let verified_license = quorum_verifier()
+            .verify(verifiable_license)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license, license);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_verification_with_one_of_three_signatures() {
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let second_signer = quorum_signer(&OTHER_PRIVATE_KEY_JWK_JSON, "signer-2");
+        let verifiable_license = sign_with_quorum(&license, &[&second_signer]).unwrap();
+
+        let result = quorum_verifier().verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::InsufficientSignatures);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_verification_with_duplicate_signatures() {
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let second_signer = quorum_signer(&OTHER_PRIVATE_KEY_JWK_JSON, "signer-2");
+        let verifiable_license =
+            sign_with_quorum(&license, &[&second_signer, &second_signer]).unwrap();
+
+        let result = quorum_verifier().verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::InsufficientSignatures);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn license_verification_with_signature_under_wrong_key_id() {
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let second_signer = quorum_signer(&OTHER_PRIVATE_KEY_JWK_JSON, "signer-2");
+        let impersonating_signer = quorum_signer(&OTHER_PRIVATE_KEY_JWK_JSON, "signer-1");
+        let verifiable_license =
+            sign_with_quorum(&license, &[&second_signer, &impersonating_signer]).unwrap();
+
+        let result = quorum_verifier().verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::InsufficientSignatures);
+    }
+
+    // Signs `payload` under each header and assembles the quorum license around it
+    #[cfg(feature = "sign")]
+    fn quorum_license_with_headers(
+        payload: &[u8],
+        signed: &[(&LicenseSigner, serde_json::Value)],
+    ) -> serde_json::Value {
+        let payload = jws::encode(payload);
+        let signatures = signed
+            .iter()
+            .map(|(signer, header)| {
+                let protected = jws::encode(&serde_json::to_vec(header).unwrap());
+                let signature = signer.sign_bytes(format!("{}.{}", protected, payload).as_bytes());
+                serde_json::json!({
+                    "protected": protected,
+                    "signature": jws::encode(&signature),
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({
+            "license": *EXPECTED_LICENSE,
+            "licenseValidation": {
+                "payload": payload,
+                "signatures": signatures,
+            }
+        })
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn quorum_verification_with_unsupported_critical_header() {
+        let first_signer = quorum_signer(&ISSUER_PRIVATE_KEY_JWK_JSON, "signer-1");
+        let second_signer = quorum_signer(&OTHER_PRIVATE_KEY_JWK_JSON, "signer-2");
+        let third_signer = quorum_signer(&THIRD_PRIVATE_KEY_JWK_JSON, "signer-3");
+        let payload = serde_json::to_vec(&*EXPECTED_LICENSE).unwrap();
+        let verifiable_license = quorum_license_with_headers(
+            &payload,
+            &[
+                (
+                    &first_signer,
+                    serde_json::json!({ "alg": "RS512", "kid": "signer-1" }),
+                ),
+                (
+                    &third_signer,
+                    serde_json::json!({ "alg": "RS512", "kid": "signer-3" }),
+                ),
+                (
+                    &second_signer,
+                    serde_json::json!({
+                        "alg": "RS512",
+                        "kid": "signer-2",
+                        "crit": ["urn:acme:audience"],
+                    }),
+                ),
+            ],
+        );
+
+        let result = quorum_verifier().verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::UnsupportedCriticalHeader("urn:acme:audience".to_owned())
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn quorum_verification_with_compressed_payload() {
+        let first_signer = quorum_signer(&ISSUER_PRIVATE_KEY_JWK_JSON, "signer-1");
+        let third_signer = quorum_signer(&THIRD_PRIVATE_KEY_JWK_JSON, "signer-3");
+        let headers = |zip: &str| {
+            [
+                serde_json::json!({ "alg": "RS512", "kid": "signer-1", "zip": zip }),
+                serde_json::json!({ "alg": "RS512", "kid": "signer-3", "zip": zip }),
+            ]
+        };
+        let payload = serde_json::to_vec(&*EXPECTED_LICENSE).unwrap();
+
+        #[cfg(feature = "compression")]
+        {
+            let [first_header, third_header] = headers(jws::DEFLATE);
+            let verifiable_license = quorum_license_with_headers(
+                &crate::deflate::compress(&payload),
+                &[(&first_signer, first_header), (&third_signer, third_header)],
+            );
+            let license = quorum_verifier()
+                .verify(verifiable_license)
+                .expect("Verification should succeed");
+            assert_eq!(serde_json::to_value(license).unwrap(), *EXPECTED_LICENSE);
+        }
+
+        let [first_header, third_header] = headers("LZW");
+        let verifiable_license = quorum_license_with_headers(
+            &payload,
+            &[(&first_signer, first_header), (&third_signer, third_header)],
+        );
+        let result = quorum_verifier().verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::UnsupportedCompression("LZW".to_owned())
+        );
+
+        let [first_header, _] = headers("DEF");
+        let verifiable_license = quorum_license_with_headers(
+            &payload,
+            &[
+                (&first_signer, first_header),
+                (
+                    &third_signer,
+                    serde_json::json!({ "alg": "RS512", "kid": "signer-3" }),
+                ),
+            ],
+        );
+        let result = quorum_verifier().verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::InvalidVerifiableLicense);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn strict_kid_quorum_verification() {
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let first_signer = quorum_signer(&ISSUER_PRIVATE_KEY_JWK_JSON, "signer-1");
+        let third_signer = quorum_signer(&THIRD_PRIVATE_KEY_JWK_JSON, "signer-3");
+        let signer_without_kid = LicenseSigner::new(OTHER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let verifiable_license = sign_with_quorum(
+            &license,
+            &[&first_signer, &third_signer, &signer_without_kid],
+        )
+        .unwrap();
+
+        quorum_verifier()
+            .verify(verifiable_license.clone())
+            .expect("Verification should succeed");
+
+        let strict_verifier = LicenseVerifierBuilder::from(quorum_verifier())
+            .require_kid(true)
+            .build()
+            .unwrap();
+        let result = strict_verifier.verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::MissingKeyId);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn verified_license_carries_verification_metadata() {
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let verifiable_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license)
+            .unwrap();
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone()).unwrap();
+
+        let before_verification = datetime::now();
+        let verified_license = verifier
+            .verify_license(verifiable_license)
+            .expect("Verification should succeed");
+
+        assert_eq!(*verified_license, license);
+        assert_eq!(verified_license.id, license.id);
+        assert_eq!(verified_license.alg(), "RS512");
+        assert_eq!(
+            verified_license.key_thumbprints(),
+            ["3WJ1HIGkV9lhDSJfzWKtdvpKMQ2a4Zw7SeNsgbmyRgw"]
+        );
+        assert!(verified_license.verified_at() >= before_verification);
+    }
+
+    #[cfg(all(feature = "sign", feature = "ecdsa"))]
+    #[test]
+    fn license_verification_with_each_key_type() {
+        use crate::test_keys::{
+            ED25519_PRIVATE_KEY_JWK_JSON, ED25519_PUBLIC_KEY_JWK_JSON, P256_PRIVATE_KEY_JWK_JSON,
+            P256_PUBLIC_KEY_JWK_JSON,
+        };
+
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        for (private_key, public_key, signing, signature_len) in [
+            (
+                &*ISSUER_PRIVATE_KEY_JWK_JSON,
+                &*ISSUER_PUBLIC_KEY_JWK_JSON,
+                Signing::Rs512,
+                512,
+            ),
+            (
+                &*P256_PRIVATE_KEY_JWK_JSON,
+                &*P256_PUBLIC_KEY_JWK_JSON,
+                Signing::Es256,
+                64,
+            ),
+            (
+                &*ED25519_PRIVATE_KEY_JWK_JSON,
+                &*ED25519_PUBLIC_KEY_JWK_JSON,
+                Signing::EdDsa,
+                64,
+            ),
+        ] {
+            let verifiable_license = LicenseSigner::new(private_key.clone())
+                .unwrap()
+                .sign(&license)
+                .unwrap();
+            let verifier = LicenseVerifier::new(public_key.clone()).unwrap();
+            let thumbprint = PublicKey::from_jwk(public_key.clone())
+                .unwrap()
+                .thumbprint();
+
+            assert_eq!(verifier.algorithm(), Some(Algorithm::from(signing)));
+            let verified_license = verifier
+                .verify_license(verifiable_license.clone())
+                .expect("Verification should succeed");
+            assert_eq!(*verified_license, license);
+            assert_eq!(verified_license.alg(), public_key["alg"]);
+            assert_eq!(
+                verified_license.key_thumbprints(),
+                std::slice::from_ref(&thumbprint)
+            );
+
+            let exported_verifier = LicenseVerifier::new(verifier.public_jwk()).unwrap();
+            assert_eq!(verifier.public_jwk()["kid"], serde_json::json!(thumbprint));
+            assert!(exported_verifier.verify(verifiable_license.clone()).is_ok());
+
+            let mut truncated_license = verifiable_license.clone();
+            let signature = truncated_license["licenseValidation"]["signature"]
+                .as_str()
+                .unwrap()
+                .to_owned();
+            truncated_license["licenseValidation"]["signature"] =
+                serde_json::json!(jws::encode(&jws::decode(&signature).unwrap()[1..]));
+            let Err(error) = verifier.verify(truncated_license) else {
+                panic!("An error was expected")
+            };
+            assert_eq!(
+                error,
+                LicenseVerificationError::SignatureLengthMismatch {
+                    expected: signature_len,
+                    got: signature_len - 1,
+                }
+            );
+
+            let tampered_license = Tamper::new(verifiable_license)
+                .flip_signature_bit()
+                .into_value();
+            let Err(error) = verifier.verify(tampered_license) else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::VerificationFailure);
+        }
+    }
+
+    // A signature only counts under the algorithm of the key checking it, whatever the header says
+    #[cfg(all(feature = "sign", feature = "ecdsa"))]
+    #[test]
+    fn license_verification_with_signature_of_another_key_type() {
+        use crate::test_keys::{ED25519_PRIVATE_KEY_JWK_JSON, P256_PUBLIC_KEY_JWK_JSON};
+
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let verifiable_license = LicenseSigner::new(ED25519_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license)
+            .unwrap();
+
+        for public_key in [&*ISSUER_PUBLIC_KEY_JWK_JSON, &*P256_PUBLIC_KEY_JWK_JSON] {
+            let verifier = LicenseVerifier::new(public_key.clone()).unwrap();
+            let Err(error) = verifier.verify(verifiable_license.clone()) else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::VerificationFailure);
+        }
+    }
+
+    #[cfg(all(feature = "sign", feature = "ecdsa"))]
+    #[test]
+    fn quorum_verification_across_key_types() {
+        use crate::test_keys::{
+            ED25519_PRIVATE_KEY_JWK_JSON, ED25519_PUBLIC_KEY_JWK_JSON, P256_PRIVATE_KEY_JWK_JSON,
+            P256_PUBLIC_KEY_JWK_JSON,
+        };
+
+        let with_kid = |jwk: &serde_json::Value, kid: &str| {
+            let mut jwk = jwk.clone();
+            jwk["kid"] = serde_json::json!(kid);
+            jwk
+        };
+        let verifier = LicenseVerifier::builder()
+            .require_signatures(
+                2,
+                serde_json::json!({
+                    "keys": [
+                        with_kid(&ISSUER_PUBLIC_KEY_JWK_JSON, "rsa"),
+                        with_kid(&P256_PUBLIC_KEY_JWK_JSON, "p256"),
+                        with_kid(&ED25519_PUBLIC_KEY_JWK_JSON, "ed25519"),
+                    ]
+                }),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(
+            verifier.supported_algorithms(),
+            [
+                Algorithm::from(Signing::EdDsa),
+                Algorithm::from(Signing::Es256),
+                Algorithm::from(Signing::Rs512),
+            ]
+        );
+
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let p256_signer = quorum_signer(&P256_PRIVATE_KEY_JWK_JSON, "p256");
+        let ed25519_signer = quorum_signer(&ED25519_PRIVATE_KEY_JWK_JSON, "ed25519");
+        let verifiable_license =
+            sign_with_quorum(&license, &[&p256_signer, &ed25519_signer]).unwrap();
+
+        let verified_license = verifier
+            .verify_license(verifiable_license)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license.key_thumbprints().len(), 2);
+
+        let Err(error) = LicenseVerifier::builder()
+            .require_signatures(
+                1,
+                serde_json::json!({ "keys": [with_kid(&P256_PUBLIC_KEY_JWK_JSON, "p256")] }),
+            )
+            .allowed_algorithms(&[Algorithm::from(Signing::Rs512)])
+            .build()
+        else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerifierError::AlgorithmNotAllowed);
+    }
+
+    #[test]
+    fn verified_license_serializes_with_marker() {
+        let verifier = LicenseVerifier::new(PUBLIC_KEY_JWK_JSON.clone()).unwrap();
+        let verified_license = verifier
+            .verify_license(VALID_VERIFIABLE_LICENSE.clone())
+            .unwrap();
+
+        let serialized_license = serde_json::to_value(&verified_license).unwrap();
+
+        assert_eq!(serialized_license["verified"], serde_json::json!(true));
+        assert_eq!(serialized_license["license"], *EXPECTED_LICENSE);
+        assert_eq!(serialized_license["alg"], serde_json::json!("RS512"));
+        assert!(serde_json::from_value::<License>(serialized_license).is_err());
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn strict_kid_verification() {
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let verifier = |require_kid: bool| {
+            LicenseVerifier::builder()
+                .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+                .require_kid(require_kid)
+                .build()
+                .expect("Verifier instantiation must work")
+        };
+        let with_kid = quorum_signer(&ISSUER_PRIVATE_KEY_JWK_JSON, "issuer-2024")
+            .sign(&license)
+            .unwrap();
+        let without_kid = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license)
+            .unwrap();
+
+        verifier(true)
+            .verify(with_kid)
+            .expect("Verification should succeed");
+        verifier(false)
+            .verify(without_kid.clone())
+            .expect("Verification should succeed");
+
+        let result = verifier(true).verify(without_kid);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::MissingKeyId);
+    }
+
+    #[cfg(feature = "sign")]
+    struct RevokedIds(Vec<LicenseId>);
+
+    #[cfg(feature = "sign")]
+    impl RevocationProvider for RevokedIds {
+        fn is_revoked(&self, license_id: &LicenseId) -> bool {
+            self.0.contains(license_id)
+        }
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn revocation_is_checked_during_verification() {
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let verifiable_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license)
+            .unwrap();
+        let now = datetime::parse("2024-09-01T00:00:00Z").unwrap();
+        let verifier_revoking = |revoked: Vec<LicenseId>| {
+            LicenseVerifierBuilder::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+                .expect("Verifier instantiation must work")
+                .revocation_provider(Arc::new(RevokedIds(revoked)))
+                .build()
+                .expect("Verifier instantiation must work")
+        };
+
+        verifier_revoking(vec![LicenseId::from("ACME-2023-00481")])
+            .verify_at(verifiable_license.clone(), now)
+            .expect("Verification should succeed");
+
+        let result = verifier_revoking(vec![license.id.clone()]).verify_at(verifiable_license, now);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::LicenseRevoked);
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn required_typ_verification() {
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let verifier = LicenseVerifierBuilder::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work")
+            .required_typ("jls-license+jws")
+            .build()
+            .expect("Verifier instantiation must work");
+        let sign_with_typ = |typ: Option<&str>| {
+            let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+            let mut header = serde_json::json!({ "alg": "RS512" });
+            if let Some(typ) = typ {
+                header["typ"] = serde_json::json!(typ);
+            }
+            let protected = jws::encode(header.to_string().as_bytes());
+            let payload = jws::encode(&serde_json::to_vec(&license).unwrap());
+            let signature = signer.sign_bytes(format!("{}.{}", protected, payload).as_bytes());
+            serde_json::json!({
+                "license": license,
+                "licenseValidation": {
+                    "protected": protected,
+                    "payload": payload,
+                    "signature": jws::encode(&signature),
+                }
+            })
+        };
+
+        for typ in [
+            "jls-license+jws",
+            "JLS-License+JWS",
+            "application/jls-license+jws",
+        ] {
+            verifier
+                .verify(sign_with_typ(Some(typ)))
+                .expect("Verification should succeed");
+        }
+
+        let result = verifier.verify(sign_with_typ(Some("JWT")));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::TypMismatch {
+                expected: "jls-license+jws".to_owned(),
+                got: Some("JWT".to_owned()),
+            }
+        );
+
+        let result = verifier.verify(sign_with_typ(None));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::TypMismatch {
+                expected: "jls-license+jws".to_owned(),
+                got: None,
+            }
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn required_typ_quorum_verification() {
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let first_signer = quorum_signer(&ISSUER_PRIVATE_KEY_JWK_JSON, "signer-1");
+        let third_signer = quorum_signer(&THIRD_PRIVATE_KEY_JWK_JSON, "signer-3");
+        let verifiable_license =
+            sign_with_quorum(&license, &[&first_signer, &third_signer]).unwrap();
+        let verifier_requiring = |typ: &str| {
+            LicenseVerifierBuilder::from(quorum_verifier())
+                .required_typ(typ)
+                .build()
+                .unwrap()
+        };
+
+        verifier_requiring("jwt")
+            .verify(verifiable_license.clone())
+            .expect("Verification should succeed");
+
+        let result = verifier_requiring("jls-license+jws").verify(verifiable_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::TypMismatch {
+                expected: "jls-license+jws".to_owned(),
+                got: Some("JWT".to_owned()),
+            }
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn strict_structure_verification() {
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let verifiable_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign(&license)
+            .unwrap();
+        let strict_verifier = LicenseVerifierBuilder::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work")
+            .strict_structure(true)
+            .build()
+            .expect("Verifier instantiation must work");
+        let lenient_verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+
+        strict_verifier
+            .verify(verifiable_license.clone())
+            .expect("Verification should succeed");
+
+        for (member, value) in [
+            ("header", serde_json::json!({ "kid": "issuer-2024" })),
+            ("kid", serde_json::json!("issuer-2024")),
+        ] {
+            let mut verifiable_license = verifiable_license.clone();
+            verifiable_license["licenseValidation"][member] = value;
+
+            lenient_verifier
+                .verify(verifiable_license.clone())
+                .expect("Verification should succeed");
+
+            let result = strict_verifier.verify(verifiable_license);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(
+                error,
+                LicenseVerificationError::UnexpectedValidationMember(member.to_owned())
+            );
+        }
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn base64_policy_verification() {
+        use base64ct::{Base64Url, Encoding};
+
+        let license: License = serde_json::from_value(EXPECTED_LICENSE.clone()).unwrap();
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let strict_verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let lenient_verifier = LicenseVerifierBuilder::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work")
+            .base64_policy(Base64Policy::Lenient)
+            .build()
+            .expect("Verifier instantiation must work");
+
+        let standard_license = signer.sign(&license).unwrap();
+        for verifier in [&strict_verifier, &lenient_verifier] {
+            verifier
+                .verify(standard_license.clone())
+                .expect("Verification should succeed");
+        }
+
+        let protected = Base64Url::encode_string(br#"{"alg":"RS512","kid":"k1"}"#);
+        let payload = Base64Url::encode_string(&serde_json::to_vec(&license).unwrap());
+        assert!(protected.ends_with('=') && payload.ends_with('='));
+        let sign_padded = |payload: &str| {
+            let signature = signer.sign_bytes(format!("{}.{}", protected, payload).as_bytes());
+            serde_json::json!({
+                "license": license,
+                "licenseValidation": {
+                    "protected": protected,
+                    "payload": payload,
+                    "signature": Base64Url::encode_string(&signature),
+                }
+            })
+        };
+        let padded_license = sign_padded(&payload);
+        let wrapped_license = sign_padded(&format!("{}\n{}", &payload[..40], &payload[40..]));
+
+        for verifiable_license in [padded_license, wrapped_license] {
+            lenient_verifier
+                .verify(verifiable_license.clone())
+                .expect("Verification should succeed");
+
+            let result = strict_verifier.verify(verifiable_license);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::InvalidVerifiableLicense);
+        }
+    }
+
+    #[cfg(feature = "sign")]
+    lazy_static! {
+        static ref STANDARD_JWT: String = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .unwrap()
+            .sign_token(
+                "JWT",
+                &serde_json::json!({
+                    "jti": "ACME-2023-00481",
+                    "iss": "https://licensing.acme.example",
+                    "sub": "ACME Corp",
+                    "aud": "acme-cad",
+                    "iat": 1704067200,
+                    "nbf": 1704067200,
+                    "exp": 1727740800,
+                    "seats": 25,
+                }),
+            );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn verify_standard_jwt_claims() {
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .clock(Arc::new(FixedClock::at("2024-09-01T00:00:00Z")))
+            .build()
+            .expect("Verifier instantiation must work");
+
+        let verified_license = verifier
+            .verify_jwt_claims(&STANDARD_JWT)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license.id, LicenseId::from("ACME-2023-00481"));
+        assert_eq!(
+            verified_license.issuer.as_deref(),
+            Some("https://licensing.acme.example")
+        );
+        assert_eq!(verified_license.product.as_deref(), Some("acme-cad"));
+        assert_eq!(
+            verified_license.issued_at,
+            Some(datetime::parse("2024-01-01T00:00:00Z").unwrap())
+        );
+        assert_eq!(
+            verified_license.expiration_date,
+            datetime::parse("2024-10-01T00:00:00Z").unwrap()
+        );
+        assert_eq!(
+            verified_license.custom_data,
+            serde_json::json!({ "licensee": "ACME Corp", "seats": 25 })
+        );
+        assert_eq!(
+            verified_license.key_thumbprints(),
+            [PublicKey::from_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+                .unwrap()
+                .thumbprint()]
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn standard_jwt_claims_are_validated() {
+        let verifier_at = |now: &str| {
+            LicenseVerifier::builder()
+                .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+                .clock(Arc::new(FixedClock::at(now)))
+                .policy(ValidationPolicy::new())
+                .build()
+                .expect("Verifier instantiation must work")
+        };
+        let cases = [
+            (
+                verifier_at("2023-12-31T23:59:59Z"),
+                STANDARD_JWT.clone(),
+                LicenseVerificationError::NotYetValid {
+                    not_before: datetime::parse("2024-01-01T00:00:00Z").unwrap(),
+                },
+            ),
+            (
+                verifier_at("2024-11-01T00:00:00Z"),
+                STANDARD_JWT.clone(),
+                LicenseVerificationError::LicenseExpired,
+            ),
+            (
+                LicenseVerifier::new(PUBLIC_KEY_JWK_JSON.clone())
+                    .expect("Verifier instantiation must work"),
+                STANDARD_JWT.clone(),
+                LicenseVerificationError::VerificationFailure,
+            ),
+            (
+                verifier_at("2024-09-01T00:00:00Z"),
+                STANDARD_JWT.replacen('.', ".e30.", 1),
+                LicenseVerificationError::InvalidVerifiableLicense,
+            ),
+        ];
+        for (verifier, token, expected) in cases {
+            let result = verifier.verify_jwt_claims(&token);
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, expected);
+        }
+    }
+
+    #[test]
+    fn opaque_errors_hide_failure_causes() {
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(PUBLIC_KEY_JWK_JSON.clone())
+            .clock(Arc::new(FixedClock::at("2024-11-01T00:00:00Z")))
+            .policy(ValidationPolicy::new())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .expect("Verifier instantiation must work");
+
+        let tampered_license = Tamper::new(VALID_VERIFIABLE_LICENSE.clone())
+            .modify_outer_field("/customData/owner", serde_json::json!("Jane Doe"))
+            .into_value();
+        let results = [
+            verifier.verify(tampered_license),
+            verifier.verify(VALID_VERIFIABLE_LICENSE.clone()),
+            verifier.verify(serde_json::json!({ "license": 1 })),
+            verifier
+                .verify_jwt_claims("not-a-jwt")
+                .map(VerifiedLicense::into_license),
+        ];
+        for result in results {
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::LicenseInvalid);
+        }
+
+        assert_eq!(
+            audit_sink.failures(),
+            [
+                LicenseVerificationError::TamperedLicense,
+                LicenseVerificationError::LicenseExpired,
+                LicenseVerificationError::InvalidVerifiableLicense,
+                LicenseVerificationError::InvalidVerifiableLicense,
+            ]
+        );
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn verify_with_key_success() {
+        let license: License = serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "2124-10-01T00:00:00Z",
+            "customData": {
+                "owner": "John Doe"
+            }
+        }))
+        .unwrap();
+        let verifiable_license = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .expect("Signer instantiation must work")
+            .sign(&license)
+            .unwrap();
+
+        let result = verify_with_key(&ISSUER_PUBLIC_KEY_JWK_JSON, &verifiable_license);
+        assert_eq!(result, Ok(license));
+    }
+
+    #[test]
+    fn verify_with_key_bad_key() {
+        let result = verify_with_key(
+            &serde_json::json!({"kty": "RSA"}),
+            &VALID_VERIFIABLE_LICENSE,
+        );
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            VerifyWithKeyError::Key(LicenseVerifierError::KeyIsNotJwk)
+        );
+    }
+
+    #[test]
+    fn verify_with_key_tampered_license() {
+        let tampered_license = Tamper::new(VALID_VERIFIABLE_LICENSE.clone())
+            .modify_outer_field("/customData/owner", serde_json::json!("Jane Doe"))
+            .into_value();
+
+        let result = verify_with_key(&PUBLIC_KEY_JWK_JSON, &tampered_license);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            VerifyWithKeyError::Verification(LicenseVerificationError::TamperedLicense)
+        );
+    }
 }
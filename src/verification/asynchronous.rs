@@ -0,0 +1,299 @@
+use super::TrustModel;
+use super::{key_id_of, LicenseVerificationError, LicenseVerifier, PublicKey};
+use crate::id::LicenseId;
+use crate::jws;
+use crate::License;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+// Resolves the JWK for the `kid` of a license header, `None` when the store has no such key
+pub trait AsyncKeyStore: Send + Sync {
+    fn key<'a>(&'a self, kid: Option<&'a str>) -> BoxFuture<'a, Option<serde_json::Value>>;
+}
+
+pub trait AsyncRevocationProvider: Send + Sync {
+    fn is_revoked<'a>(&'a self, license_id: &'a LicenseId) -> BoxFuture<'a, bool>;
+}
+
+impl LicenseVerifier {
+    pub async fn verify_async(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<License, LicenseVerificationError> {
+        let result = self.verify_async_detailed(verifiable_license_json).await;
+        self.reported(result)
+    }
+
+    async fn verify_async_detailed(
+        &self,
+        verifiable_license_json: serde_json::Value,
+    ) -> Result<License, LicenseVerificationError> {
+        let key_jwk = match &self.settings.key_store {
+            Some(key_store) => Some(
+                self.stored_key(&**key_store, &verifiable_license_json)
+                    .await?,
+            ),
+            None => None,
+        };
+        // The parsing and signature checks are the synchronous ones, run by a verifier bound to
+        // the resolved key
+        let verified_license = match (key_jwk, self.settings.offload_signature_check) {
+            (None, false) => self.verify_license_detailed(verifiable_license_json)?,
+            (key_jwk, false) => self
+                .bound_to(key_jwk)?
+                .verify_license_detailed(verifiable_license_json)?,
+            (key_jwk, true) => {
+                let verifier = self.bound_to(key_jwk)?;
+                offloaded(move || verifier.verify_license_detailed(verifiable_license_json)).await?
+            }
+        };
+
+        if let Some(revocation_provider) = &self.settings.async_revocation_provider {
+            if revocation_provider.is_revoked(&verified_license.id).await {
+                return Err(LicenseVerificationError::LicenseRevoked);
+            }
+        }
+        Ok(verified_license.into_license())
+    }
+
+    async fn stored_key(
+        &self,
+        key_store: &dyn AsyncKeyStore,
+        verifiable_license_json: &serde_json::Value,
+    ) -> Result<serde_json::Value, LicenseVerificationError> {
+        let header: jws::Header = verifiable_license_json
+            .get("licenseValidation")
+            .and_then(|license_validation| license_validation.get("protected"))
+            .and_then(|v| v.as_str())
+            .and_then(|protected| self.settings.base64_policy.decode(protected))
+            .and_then(|header| serde_json::from_slice(&header).ok())
+            .ok_or(LicenseVerificationError::InvalidVerifiableLicense)?;
+        key_store
+            .key(header.kid.as_deref())
+            .await
+            .ok_or(match header.kid {
+                Some(kid) => LicenseVerificationError::UnknownKeyId(kid),
+                None => LicenseVerificationError::MissingKeyId,
+            })
+    }
+
+    fn bound_to(
+        &self,
+        key_jwk: Option<serde_json::Value>,
+    ) -> Result<LicenseVerifier, LicenseVerificationError> {
+        let (trust_model, key_id) = match key_jwk {
+            Some(key_jwk) => {
+                let key_id = key_id_of(&key_jwk);
+                let public_key = PublicKey::from_jwk(key_jwk)
+                    .map_err(|_| LicenseVerificationError::VerificationFailure)?;
+                (TrustModel::Issuer(public_key), key_id)
+            }
+            None => (self.trust_model.clone(), self.key_id.clone()),
+        };
+        Ok(LicenseVerifier {
+            trust_model,
+            key_id,
+            settings: self.settings.clone(),
+        })
+    }
+}
+
+// Completes once `task` has run on its own thread, keeping the CPU-bound RSA check off the
+// executor without depending on a particular runtime
+struct Offloaded<T> {
+    state: Arc<Mutex<(Option<T>, Option<Waker>)>>,
+}
+
+fn offloaded<T: Send + 'static>(task: impl FnOnce() -> T + Send + 'static) -> Offloaded<T> {
+    let state = Arc::new(Mutex::new((None::<T>, None::<Waker>)));
+    let task_state = Arc::clone(&state);
+    std::thread::spawn(move || {
+        let output = task();
+        let mut state = task_state.lock().unwrap();
+        state.0 = Some(output);
+        if let Some(waker) = state.1.take() {
+            waker.wake();
+        }
+    });
+    Offloaded { state }
+}
+
+impl<T> Future for Offloaded<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.lock().unwrap();
+        match state.0.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::signing::LicenseSigner;
+    use crate::test_keys::{
+        ISSUER_PRIVATE_KEY_JWK_JSON, ISSUER_PUBLIC_KEY_JWK_JSON, OTHER_PUBLIC_KEY_JWK_JSON,
+    };
+    use std::collections::BTreeMap;
+    use std::task::Wake;
+    use std::thread::Thread;
+
+    struct MockKeyStore {
+        keys: BTreeMap<String, serde_json::Value>,
+    }
+
+    impl AsyncKeyStore for MockKeyStore {
+        fn key<'a>(&'a self, kid: Option<&'a str>) -> BoxFuture<'a, Option<serde_json::Value>> {
+            Box::pin(async move { kid.and_then(|kid| self.keys.get(kid)).cloned() })
+        }
+    }
+
+    struct MockRevocationProvider {
+        revoked: Vec<LicenseId>,
+    }
+
+    impl AsyncRevocationProvider for MockRevocationProvider {
+        fn is_revoked<'a>(&'a self, license_id: &'a LicenseId) -> BoxFuture<'a, bool> {
+            Box::pin(async move { self.revoked.contains(license_id) })
+        }
+    }
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            std::thread::park();
+        }
+    }
+
+    fn license() -> License {
+        serde_json::from_value(serde_json::json!({
+            "id": "0b5b88f5-a264-4f90-8406-50b01d9515c8",
+            "expirationDate": "2124-10-01T00:00:00Z",
+            "customData": {
+                "owner": "John Doe"
+            }
+        }))
+        .unwrap()
+    }
+
+    fn verifiable_license(kid: &str) -> serde_json::Value {
+        LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone())
+            .expect("Signer instantiation must work")
+            .with_key_id(kid)
+            .sign(&license())
+            .unwrap()
+    }
+
+    fn key_store() -> Arc<dyn AsyncKeyStore> {
+        Arc::new(MockKeyStore {
+            keys: BTreeMap::from([
+                ("issuer-2024".to_owned(), ISSUER_PUBLIC_KEY_JWK_JSON.clone()),
+                ("other".to_owned(), OTHER_PUBLIC_KEY_JWK_JSON.clone()),
+            ]),
+        })
+    }
+
+    fn revocation_provider(revoked: Vec<LicenseId>) -> Arc<dyn AsyncRevocationProvider> {
+        Arc::new(MockRevocationProvider { revoked })
+    }
+
+    // The configured key is never the right one, so only keys from the store can verify
+    fn verifier(offload_signature_check: bool) -> LicenseVerifier {
+        LicenseVerifier::builder()
+            .key_jwk(OTHER_PUBLIC_KEY_JWK_JSON.clone())
+            .async_key_store(key_store())
+            .async_revocation_provider(revocation_provider(Vec::new()))
+            .offload_signature_check(offload_signature_check)
+            .build()
+            .expect("Verifier instantiation must work")
+    }
+
+    #[test]
+    fn verify_async_success() {
+        for offload_signature_check in [false, true] {
+            let verifier = verifier(offload_signature_check);
+
+            let license = block_on(verifier.verify_async(verifiable_license("issuer-2024")))
+                .expect("Verification should succeed");
+            assert_eq!(license, self::license());
+        }
+    }
+
+    #[test]
+    fn verify_async_key_miss() {
+        let verifier = verifier(false);
+
+        let result = block_on(verifier.verify_async(verifiable_license("issuer-2025")));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(
+            error,
+            LicenseVerificationError::UnknownKeyId("issuer-2025".to_owned())
+        );
+    }
+
+    #[test]
+    fn verify_async_wrong_stored_key() {
+        let verifier = verifier(true);
+
+        let result = block_on(verifier.verify_async(verifiable_license("other")));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::VerificationFailure);
+    }
+
+    #[test]
+    fn verify_async_revoked() {
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .async_revocation_provider(revocation_provider(vec![license().id]))
+            .audit_sink(audit_sink.clone())
+            .build()
+            .expect("Verifier instantiation must work");
+
+        let result = block_on(verifier.verify_async(verifiable_license("issuer-2024")));
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::LicenseRevoked);
+        assert_eq!(
+            audit_sink.failures(),
+            vec![LicenseVerificationError::LicenseRevoked]
+        );
+    }
+
+    #[test]
+    fn verify_async_future_is_send() {
+        fn assert_send<T: Send>(_: T) {}
+
+        let verifier = verifier(true);
+        assert_send(verifier.verify_async(verifiable_license("issuer-2024")));
+    }
+}
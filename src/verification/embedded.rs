@@ -0,0 +1,161 @@
+use super::{LicenseVerificationError, LicenseVerifier};
+use crate::policy::ValidationPolicy;
+use crate::{VerifiableLicense, VerifiedLicense};
+use std::sync::Mutex;
+
+type Outcome = Result<VerifiedLicense, LicenseVerificationError>;
+
+// Outcomes of `verify_embedded`, keyed by the address and length of the embedded bytes and the
+// policy. A verifier built from another one starts over, as it may trust different keys.
+#[derive(Default)]
+pub(super) struct EmbeddedCache(Mutex<Vec<(usize, usize, ValidationPolicy, Outcome)>>);
+
+impl Clone for EmbeddedCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl VerifiableLicense {
+    // Meant for `include_bytes!`: a license baked into the binary at build time
+    pub fn embedded(bytes: &'static [u8]) -> Result<Self, LicenseVerificationError> {
+        serde_json::from_slice(bytes)
+            .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)
+    }
+}
+
+impl LicenseVerifier {
+    // The first outcome sticks for the lifetime of the verifier, so it suits startup checks rather
+    // than long-running processes that must notice the license expiring
+    pub fn verify_embedded(
+        &self,
+        bytes: &'static [u8],
+        policy: &ValidationPolicy,
+    ) -> Result<VerifiedLicense, LicenseVerificationError> {
+        let (address, len) = (bytes.as_ptr() as usize, bytes.len());
+        let mut cache = self.settings.embedded_cache.0.lock().unwrap();
+        let cached = cache
+            .iter()
+            .find(|(cached_address, cached_len, cached_policy, _)| {
+                (*cached_address, *cached_len) == (address, len) && cached_policy == policy
+            });
+        if let Some((_, _, _, outcome)) = cached {
+            return outcome.clone();
+        }
+
+        let outcome = self.reported(
+            serde_json::from_slice(bytes)
+                .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)
+                .and_then(|json| self.verify_license_detailed(json))
+                .and_then(|verified_license| self.validated_with(verified_license, policy)),
+        );
+        cache.push((address, len, policy.clone(), outcome.clone()));
+        outcome
+    }
+
+    // Startup guard: panics with a message telling a broken build apart from a license that does
+    // not cover this release
+    pub fn expect_embedded(
+        &self,
+        bytes: &'static [u8],
+        policy: &ValidationPolicy,
+    ) -> VerifiedLicense {
+        match self.verify_embedded(bytes, policy) {
+            Ok(verified_license) => verified_license,
+            Err(error) => panic!("{}", failure_message(&error)),
+        }
+    }
+}
+
+fn failure_message(error: &LicenseVerificationError) -> String {
+    match error {
+        LicenseVerificationError::InvalidVerifiableLicense => format!(
+            "The embedded license is malformed ({}): the build embedded a file that is not a \
+             verifiable license",
+            error
+        ),
+        _ => format!(
+            "The embedded license is not valid ({}): this release needs a renewed license",
+            error
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::test_util::FixedClock;
+    use std::sync::Arc;
+
+    const ISSUER_PUBLIC_KEY: &[u8] =
+        include_bytes!("../../no-std-check/fixtures/rs512/issuer_public_key.json");
+    const EMBEDDED_LICENSE: &[u8] =
+        include_bytes!("../../no-std-check/fixtures/rs512/verifiable_license.json");
+
+    fn verifier(now: &str, audit_sink: Arc<MemoryAuditSink>) -> LicenseVerifier {
+        LicenseVerifier::builder()
+            .key_jwk(serde_json::from_slice(ISSUER_PUBLIC_KEY).unwrap())
+            .clock(Arc::new(FixedClock::at(now)))
+            .audit_sink(audit_sink)
+            .build()
+            .expect("Verifier instantiation must work")
+    }
+
+    #[test]
+    fn embedded_license_parses() {
+        let verifiable_license =
+            VerifiableLicense::embedded(EMBEDDED_LICENSE).expect("Parsing should succeed");
+        assert_eq!(
+            verifiable_license.license.id.to_string(),
+            "0b5b88f5-a264-4f90-8406-50b01d9515c8"
+        );
+
+        let result = VerifiableLicense::embedded(b"{\"license\": 1}");
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::InvalidVerifiableLicense);
+    }
+
+    #[test]
+    fn verify_embedded_valid() {
+        let verifier = verifier("2024-06-01T00:00:00Z", Arc::new(MemoryAuditSink::new()));
+
+        let verified_license = verifier
+            .expect_embedded(EMBEDDED_LICENSE, &ValidationPolicy::new())
+            .into_license();
+        assert_eq!(verified_license.custom_data["owner"], "John Doe");
+    }
+
+    #[test]
+    fn verify_embedded_expired_is_cached() {
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let verifier = verifier("2024-10-02T00:00:00Z", audit_sink.clone());
+
+        for _ in 0..2 {
+            let result = verifier.verify_embedded(EMBEDDED_LICENSE, &ValidationPolicy::new());
+            let Err(error) = result else {
+                panic!("An error was expected")
+            };
+            assert_eq!(error, LicenseVerificationError::LicenseExpired);
+        }
+        assert_eq!(
+            audit_sink.failures(),
+            vec![LicenseVerificationError::LicenseExpired]
+        );
+    }
+
+    #[test]
+    fn verify_embedded_malformed() {
+        let verifier = verifier("2024-06-01T00:00:00Z", Arc::new(MemoryAuditSink::new()));
+
+        let result = verifier.verify_embedded(b"not a license", &ValidationPolicy::new());
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::InvalidVerifiableLicense);
+        assert!(failure_message(&error).contains("malformed"));
+        assert!(failure_message(&LicenseVerificationError::LicenseExpired).contains("renewed"));
+    }
+}
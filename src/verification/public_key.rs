@@ -0,0 +1,221 @@
+use super::{LicenseVerificationError, LicenseVerifierError};
+use crate::jwa::{Algorithm, Signing};
+#[cfg(feature = "eddsa")]
+use crate::jwk::OkpCurves;
+use crate::jwk::{Jwk, Key, KeyInfo};
+use crate::jws;
+use alloc::string::String;
+#[cfg(all(feature = "ecdsa", not(feature = "rsa")))]
+use p256::ecdsa::signature::Verifier as _;
+#[cfg(feature = "rsa")]
+use rsa::signature::Verifier as _;
+#[cfg(feature = "rsa")]
+use rsa::traits::PublicKeyParts;
+
+// A key of one of the enabled signature backends. Each key type verifies a single algorithm.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PublicKey {
+    #[cfg(feature = "rsa")]
+    Rsa(rsa::RsaPublicKey),
+    #[cfg(feature = "ecdsa")]
+    P256(p256::ecdsa::VerifyingKey),
+    #[cfg(feature = "eddsa")]
+    Ed25519(ed25519_dalek::VerifyingKey),
+}
+
+impl PublicKey {
+    // Keys of a backend that is not compiled in are rejected like any other unsupported key
+    pub fn from_jwk(public_key: serde_json::Value) -> Result<Self, LicenseVerifierError> {
+        let parsed_public_key: Jwk =
+            serde_json::from_value(public_key).map_err(|_| LicenseVerifierError::KeyIsNotJwk)?;
+        let supports = |signing: Signing| parsed_public_key.is_supported(&Algorithm::from(signing));
+
+        match &parsed_public_key.key {
+            #[cfg(feature = "rsa")]
+            Key::Rsa(rsa_key) if supports(Signing::Rs512) => rsa::RsaPublicKey::try_from(rsa_key)
+                .map(Self::Rsa)
+                .map_err(|_| LicenseVerifierError::KeyTypeNotSupported),
+            #[cfg(feature = "ecdsa")]
+            Key::Ec(ec_key) if supports(Signing::Es256) => {
+                if ec_key.x.len() != 32 || ec_key.y.len() != 32 {
+                    return Err(LicenseVerifierError::KeyTypeNotSupported);
+                }
+                let mut point = alloc::vec![0x04];
+                point.extend_from_slice(&ec_key.x[..]);
+                point.extend_from_slice(&ec_key.y[..]);
+                p256::ecdsa::VerifyingKey::from_sec1_bytes(&point)
+                    .map(Self::P256)
+                    .map_err(|_| LicenseVerifierError::KeyTypeNotSupported)
+            }
+            #[cfg(feature = "eddsa")]
+            Key::Okp(okp_key)
+                if supports(Signing::EdDsa) && matches!(okp_key.crv, OkpCurves::Ed25519) =>
+            {
+                let x: &[u8; 32] = okp_key.x[..]
+                    .try_into()
+                    .map_err(|_| LicenseVerifierError::KeyTypeNotSupported)?;
+                ed25519_dalek::VerifyingKey::from_bytes(x)
+                    .map(Self::Ed25519)
+                    .map_err(|_| LicenseVerifierError::KeyTypeNotSupported)
+            }
+            _ => Err(LicenseVerifierError::KeyTypeNotSupported),
+        }
+    }
+
+    // Whether this build has the backend for signatures with the `alg` header value
+    pub fn is_supported_alg(alg: &str) -> bool {
+        match alg {
+            #[cfg(feature = "rsa")]
+            "RS512" => true,
+            #[cfg(feature = "ecdsa")]
+            "ES256" => true,
+            #[cfg(feature = "eddsa")]
+            "EdDSA" => true,
+            _ => false,
+        }
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        Algorithm::from(match self {
+            #[cfg(feature = "rsa")]
+            PublicKey::Rsa(_) => Signing::Rs512,
+            #[cfg(feature = "ecdsa")]
+            PublicKey::P256(_) => Signing::Es256,
+            #[cfg(feature = "eddsa")]
+            PublicKey::Ed25519(_) => Signing::EdDsa,
+        })
+    }
+
+    // The `alg` header value of signatures made with the key
+    pub fn alg(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "rsa")]
+            PublicKey::Rsa(_) => "RS512",
+            #[cfg(feature = "ecdsa")]
+            PublicKey::P256(_) => "ES256",
+            #[cfg(feature = "eddsa")]
+            PublicKey::Ed25519(_) => "EdDSA",
+        }
+    }
+
+    // RFC 7638 thumbprint
+    pub fn thumbprint(&self) -> String {
+        match self {
+            #[cfg(feature = "rsa")]
+            PublicKey::Rsa(public_key) => {
+                let e = jws::encode(&public_key.e().to_bytes_be());
+                let n = jws::encode(&public_key.n().to_bytes_be());
+                jws::thumbprint(&[("e", &e), ("kty", "RSA"), ("n", &n)])
+            }
+            #[cfg(feature = "ecdsa")]
+            PublicKey::P256(public_key) => {
+                let (x, y) = ec_coordinates(public_key);
+                jws::thumbprint(&[("crv", "P-256"), ("kty", "EC"), ("x", &x), ("y", &y)])
+            }
+            #[cfg(feature = "eddsa")]
+            PublicKey::Ed25519(public_key) => {
+                let x = jws::encode(public_key.as_bytes());
+                jws::thumbprint(&[("crv", "Ed25519"), ("kty", "OKP"), ("x", &x)])
+            }
+        }
+    }
+
+    pub fn to_jwk(&self, kid: &str) -> serde_json::Value {
+        match self {
+            #[cfg(feature = "rsa")]
+            PublicKey::Rsa(public_key) => serde_json::json!({
+                "alg": "RS512",
+                "kty": "RSA",
+                "kid": kid,
+                "n": jws::encode(&public_key.n().to_bytes_be()),
+                "e": jws::encode(&public_key.e().to_bytes_be()),
+            }),
+            #[cfg(feature = "ecdsa")]
+            PublicKey::P256(public_key) => {
+                let (x, y) = ec_coordinates(public_key);
+                serde_json::json!({
+                    "alg": "ES256",
+                    "kty": "EC",
+                    "kid": kid,
+                    "crv": "P-256",
+                    "x": x,
+                    "y": y,
+                })
+            }
+            #[cfg(feature = "eddsa")]
+            PublicKey::Ed25519(public_key) => serde_json::json!({
+                "alg": "EdDSA",
+                "kty": "OKP",
+                "kid": kid,
+                "crv": "Ed25519",
+                "x": jws::encode(public_key.as_bytes()),
+            }),
+        }
+    }
+
+    // The header must name the algorithm of the key, so a signature is never checked under one the
+    // issuer did not choose
+    pub(crate) fn verify(
+        &self,
+        alg: &str,
+        data: &[u8],
+        signature: &[u8],
+    ) -> Result<(), LicenseVerificationError> {
+        if alg != self.alg() {
+            return Err(LicenseVerificationError::VerificationFailure);
+        }
+        // PKCS#1 v1.5 signatures are exactly as long as the modulus; ES256 and EdDSA ones are the
+        // fixed-size concatenation of two scalars
+        let expected = self.signature_len();
+        if signature.len() != expected {
+            return Err(LicenseVerificationError::SignatureLengthMismatch {
+                expected,
+                got: signature.len(),
+            });
+        }
+        let verified = match self {
+            #[cfg(feature = "rsa")]
+            PublicKey::Rsa(public_key) => {
+                let rsa_signature = rsa::pkcs1v15::Signature::try_from(signature)
+                    .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
+                rsa::pkcs1v15::VerifyingKey::<rsa::sha2::Sha512>::new(public_key.clone())
+                    .verify(data, &rsa_signature)
+                    .is_ok()
+            }
+            #[cfg(feature = "ecdsa")]
+            PublicKey::P256(public_key) => p256::ecdsa::Signature::from_slice(signature)
+                .is_ok_and(|ec_signature| public_key.verify(data, &ec_signature).is_ok()),
+            #[cfg(feature = "eddsa")]
+            PublicKey::Ed25519(public_key) => ed25519_dalek::Signature::from_slice(signature)
+                .is_ok_and(|ed_signature| public_key.verify_strict(data, &ed_signature).is_ok()),
+        };
+        if !verified {
+            return Err(LicenseVerificationError::VerificationFailure);
+        }
+        Ok(())
+    }
+
+    fn signature_len(&self) -> usize {
+        match self {
+            #[cfg(feature = "rsa")]
+            PublicKey::Rsa(public_key) => public_key.size(),
+            #[cfg(feature = "ecdsa")]
+            PublicKey::P256(_) => 64,
+            #[cfg(feature = "eddsa")]
+            PublicKey::Ed25519(_) => ed25519_dalek::SIGNATURE_LENGTH,
+        }
+    }
+}
+
+#[cfg(feature = "ecdsa")]
+fn ec_coordinates(public_key: &p256::ecdsa::VerifyingKey) -> (String, String) {
+    let point = public_key.to_encoded_point(false);
+    let x = point
+        .x()
+        .expect("Uncompressed points carry both coordinates");
+    let y = point
+        .y()
+        .expect("Uncompressed points carry both coordinates");
+    (jws::encode(x), jws::encode(y))
+}
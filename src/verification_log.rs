@@ -0,0 +1,504 @@
+use crate::audit::AuditSink;
+use crate::datetime::{self, DateTime};
+use crate::device::{DeviceKey, DeviceKeyError, DevicePublicKey};
+use crate::id::LicenseId;
+use crate::jws;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const LOG_ENTRY_TYP: &str = "jls-log-entry+jwt";
+const LOG_HEAD_TYP: &str = "jls-log-head+jwt";
+const HEAD_FILE: &str = "head.jwt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerificationLogError {
+    Io(std::io::ErrorKind),
+    InvalidExport,
+    ChainBroken { at_index: u64 },
+    DeviceKey(DeviceKeyError),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum LogEvent {
+    VerificationSucceeded {
+        #[serde(rename = "licenseId")]
+        license_id: LicenseId,
+    },
+    VerificationFailed {
+        cause: String,
+    },
+    // Closing record of a rotated segment, appended by the log itself
+    SegmentSealed {
+        #[serde(rename = "nextSegment")]
+        next_segment: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogSummary {
+    pub entries: u64,
+    pub segments: u32,
+    pub head_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct LogEntry {
+    index: u64,
+    segment: u32,
+    #[serde(with = "crate::datetime::rfc3339")]
+    at: DateTime,
+    #[serde(rename = "previousHash")]
+    previous_hash: Option<String>,
+    event: LogEvent,
+}
+
+// Signed after every append: entries can only be removed from the tail by also rolling back the
+// head, which requires the device key.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct LogHead {
+    entries: u64,
+    #[serde(rename = "headHash")]
+    head_hash: Option<String>,
+}
+
+#[derive(Debug)]
+struct ChainPosition {
+    entries: u64,
+    segment: u32,
+    segment_entries: usize,
+    sealed: bool,
+    head_hash: Option<String>,
+}
+
+// Append-only log of verification events kept in `dir`. Each entry is signed with the device key
+// and carries the hash of the previous entry, so edits and truncations break the chain. Segments
+// holding `segment_len` events are sealed with a closing record and a new segment is started.
+pub struct VerificationLog {
+    dir: PathBuf,
+    device_key: DeviceKey,
+    segment_len: usize,
+    position: Mutex<ChainPosition>,
+}
+
+impl VerificationLog {
+    pub fn open(
+        dir: &Path,
+        device_key: DeviceKey,
+        segment_len: usize,
+    ) -> Result<Self, VerificationLogError> {
+        std::fs::create_dir_all(dir).map_err(|e| VerificationLogError::Io(e.kind()))?;
+        let export = read_export(dir)?;
+        let position = verify_segments(
+            &export.segments,
+            &export.head,
+            &device_key.public_key(),
+            None,
+        )?;
+        Ok(Self {
+            dir: dir.to_owned(),
+            device_key,
+            segment_len: segment_len.max(1),
+            position: Mutex::new(position),
+        })
+    }
+
+    pub fn append(&self, event: LogEvent) -> Result<(), VerificationLogError> {
+        let mut position = self.position.lock().unwrap();
+        if !position.sealed && position.segment_entries >= self.segment_len {
+            let next_segment = position.segment + 1;
+            self.write_entry(&mut position, LogEvent::SegmentSealed { next_segment })?;
+            position.sealed = true;
+        }
+        // Also resumes a log whose last segment was sealed right before the process stopped
+        if position.sealed {
+            position.segment += 1;
+            position.segment_entries = 0;
+            position.sealed = false;
+        }
+        self.write_entry(&mut position, event)?;
+        position.segment_entries += 1;
+        Ok(())
+    }
+
+    // The lock is released before verifying: the verifier may report failures back to this log
+    pub fn verify_chain(
+        &self,
+        verifier: &LicenseVerifier,
+    ) -> Result<LogSummary, VerificationLogError> {
+        let export = {
+            let _position = self.position.lock().unwrap();
+            read_export(&self.dir)?
+        };
+        verify_segments(
+            &export.segments,
+            &export.head,
+            &self.device_key.public_key(),
+            Some(verifier),
+        )
+        .map(LogSummary::from)
+    }
+
+    // Every segment and the signed head, to be uploaded to support along with the device public key
+    pub fn export(&self) -> Result<serde_json::Value, VerificationLogError> {
+        let _position = self.position.lock().unwrap();
+        let export = read_export(&self.dir)?;
+        Ok(serde_json::json!({
+            "deviceKey": self.device_key.public_jwk(),
+            "segments": export.segments,
+            "head": export.head,
+        }))
+    }
+
+    fn write_entry(
+        &self,
+        position: &mut ChainPosition,
+        event: LogEvent,
+    ) -> Result<(), VerificationLogError> {
+        let entry = LogEntry {
+            index: position.entries,
+            segment: position.segment,
+            at: datetime::now(),
+            previous_hash: position.head_hash.clone(),
+            event,
+        };
+        let token = self
+            .device_key
+            .sign_token(LOG_ENTRY_TYP, &entry)
+            .map_err(VerificationLogError::DeviceKey)?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(segment_path(&self.dir, position.segment))
+            .and_then(|mut file| writeln!(file, "{}", token))
+            .map_err(|e| VerificationLogError::Io(e.kind()))?;
+
+        position.entries += 1;
+        position.head_hash = Some(entry_hash(&token));
+        let head = LogHead {
+            entries: position.entries,
+            head_hash: position.head_hash.clone(),
+        };
+        let mut temporary_path = self.dir.join(HEAD_FILE).into_os_string();
+        temporary_path.push(".tmp");
+        let signed_head = self
+            .device_key
+            .sign_token(LOG_HEAD_TYP, &head)
+            .map_err(VerificationLogError::DeviceKey)?;
+        std::fs::write(&temporary_path, signed_head)
+            .and_then(|_| std::fs::rename(&temporary_path, self.dir.join(HEAD_FILE)))
+            .map_err(|e| VerificationLogError::Io(e.kind()))
+    }
+}
+
+impl AuditSink for VerificationLog {
+    // Logging is best effort: a failing disk must not turn into a verification outcome
+    fn verification_failed(&self, error: &LicenseVerificationError) {
+        let _ = self.append(LogEvent::VerificationFailed {
+            cause: format!("{:?}", error),
+        });
+    }
+}
+
+pub fn verify_export(
+    export: &serde_json::Value,
+    device_key: &DevicePublicKey,
+    verifier: &LicenseVerifier,
+) -> Result<LogSummary, VerificationLogError> {
+    let export: LogExport =
+        serde_json::from_value(export.clone()).map_err(|_| VerificationLogError::InvalidExport)?;
+    verify_segments(&export.segments, &export.head, device_key, Some(verifier))
+        .map(LogSummary::from)
+}
+
+#[derive(Debug, Deserialize)]
+struct LogExport {
+    segments: Vec<Vec<String>>,
+    head: Option<String>,
+}
+
+fn read_export(dir: &Path) -> Result<LogExport, VerificationLogError> {
+    let read = |path: PathBuf| match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(VerificationLogError::Io(e.kind())),
+    };
+    let mut segments = vec![];
+    while let Some(contents) = read(segment_path(dir, segments.len() as u32))? {
+        segments.push(contents.lines().map(str::to_owned).collect());
+    }
+    let head = read(dir.join(HEAD_FILE))?;
+    Ok(LogExport { segments, head })
+}
+
+// Opening a log checks its own chain without a verifier; explicit verifications report the cause of
+// a rejected token through theirs
+fn verify_log_token<T: serde::de::DeserializeOwned>(
+    device_key: &DevicePublicKey,
+    verifier: Option<&LicenseVerifier>,
+    token: &str,
+    typ: &str,
+) -> Option<T> {
+    match verifier {
+        Some(verifier) => device_key.verify_token_reported(verifier, token, typ).ok(),
+        None => device_key.verify_token(token, typ).ok(),
+    }
+}
+
+fn verify_segments(
+    segments: &[Vec<String>],
+    head: &Option<String>,
+    device_key: &DevicePublicKey,
+    verifier: Option<&LicenseVerifier>,
+) -> Result<ChainPosition, VerificationLogError> {
+    let mut position = ChainPosition {
+        entries: 0,
+        segment: 0,
+        segment_entries: 0,
+        sealed: false,
+        head_hash: None,
+    };
+    for (segment, tokens) in segments.iter().enumerate() {
+        let segment = segment as u32;
+        let last_segment = segment as usize + 1 == segments.len();
+        position.segment = segment;
+        position.segment_entries = 0;
+        position.sealed = false;
+        for (offset, token) in tokens.iter().enumerate() {
+            let broken = VerificationLogError::ChainBroken {
+                at_index: position.entries,
+            };
+            let entry: LogEntry = verify_log_token(device_key, verifier, token, LOG_ENTRY_TYP)
+                .ok_or_else(|| broken.clone())?;
+            let sealed = matches!(entry.event, LogEvent::SegmentSealed { next_segment } if next_segment == segment + 1);
+            let last_entry = offset + 1 == tokens.len();
+            if entry.index != position.entries
+                || entry.segment != segment
+                || entry.previous_hash != position.head_hash
+                || matches!(entry.event, LogEvent::SegmentSealed { .. }) && (!sealed || !last_entry)
+            {
+                return Err(broken);
+            }
+            position.entries += 1;
+            position.head_hash = Some(entry_hash(token));
+            position.sealed = sealed;
+            if !sealed {
+                position.segment_entries += 1;
+            }
+        }
+        if !last_segment && !position.sealed {
+            // A rotated segment lost its closing record
+            return Err(VerificationLogError::ChainBroken {
+                at_index: position.entries,
+            });
+        }
+    }
+
+    let head: Option<LogHead> = match head {
+        None => None,
+        Some(token) => Some(
+            verify_log_token(device_key, verifier, token.trim_end(), LOG_HEAD_TYP).ok_or(
+                VerificationLogError::ChainBroken {
+                    at_index: position.entries,
+                },
+            )?,
+        ),
+    };
+    let (head_entries, head_hash) = head.map_or((0, None), |head| (head.entries, head.head_hash));
+    if head_entries != position.entries || head_hash != position.head_hash {
+        // Points at the first entry that is missing, or at the first one the head does not cover
+        return Err(VerificationLogError::ChainBroken {
+            at_index: head_entries.min(position.entries),
+        });
+    }
+    Ok(position)
+}
+
+impl From<ChainPosition> for LogSummary {
+    fn from(position: ChainPosition) -> Self {
+        Self {
+            entries: position.entries,
+            segments: if position.entries == 0 {
+                0
+            } else {
+                position.segment + 1
+            },
+            head_hash: position.head_hash,
+        }
+    }
+}
+
+fn segment_path(dir: &Path, segment: u32) -> PathBuf {
+    dir.join(format!("segment-{:06}.log", segment))
+}
+
+fn entry_hash(token: &str) -> String {
+    jws::encode(&Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::MemoryAuditSink;
+    use crate::store::MemoryLicenseStore;
+    use crate::test_keys::{verifier, ISSUER_PUBLIC_KEY_JWK_JSON};
+    use std::sync::Arc;
+
+    fn device_key(store: &MemoryLicenseStore) -> DeviceKey {
+        DeviceKey::load_or_generate(store).unwrap()
+    }
+
+    fn license_id() -> LicenseId {
+        LicenseId::from("ACME-2023-00481".to_owned())
+    }
+
+    fn log_with_events(dir: &Path, store: &MemoryLicenseStore, events: usize) -> VerificationLog {
+        let log = VerificationLog::open(dir, device_key(store), 3).unwrap();
+        for _ in 0..events {
+            log.append(LogEvent::VerificationSucceeded {
+                license_id: license_id(),
+            })
+            .unwrap();
+        }
+        log
+    }
+
+    #[test]
+    fn log_rotates_and_verifies() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryLicenseStore::new();
+        let log = log_with_events(dir.path(), &store, 7);
+        log.verification_failed(&LicenseVerificationError::LicenseExpired);
+
+        // 8 events in segments of 3, plus the closing records of the first two segments
+        let summary = log
+            .verify_chain(&verifier())
+            .expect("The chain should be intact");
+        assert_eq!(summary.entries, 10);
+        assert_eq!(summary.segments, 3);
+
+        drop(log);
+        let reopened_log = VerificationLog::open(dir.path(), device_key(&store), 3).unwrap();
+        reopened_log
+            .append(LogEvent::VerificationSucceeded {
+                license_id: license_id(),
+            })
+            .unwrap();
+        assert_eq!(reopened_log.verify_chain(&verifier()).unwrap().entries, 11);
+
+        let export = reopened_log.export().unwrap();
+        let public_key = DevicePublicKey::from_jwk(&export["deviceKey"]).unwrap();
+        assert_eq!(
+            verify_export(&export, &public_key, &verifier()),
+            reopened_log.verify_chain(&verifier())
+        );
+        assert!(export["segments"][2][2]
+            .as_str()
+            .is_some_and(|token| crate::jws::parse_compact(token).is_some()));
+    }
+
+    #[test]
+    fn edited_entry_breaks_the_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryLicenseStore::new();
+        let log = log_with_events(dir.path(), &store, 5);
+        let other_log_dir = tempfile::tempdir().unwrap();
+        let other_log = log_with_events(other_log_dir.path(), &store, 5);
+
+        // Replaces the second entry with a validly signed entry from another log
+        let segment = segment_path(dir.path(), 0);
+        let mut lines = std::fs::read_to_string(&segment)
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        let other_lines = std::fs::read_to_string(segment_path(other_log_dir.path(), 0)).unwrap();
+        lines[1] = other_lines.lines().nth(1).unwrap().to_owned();
+        std::fs::write(&segment, lines.join("\n") + "\n").unwrap();
+
+        let Err(error) = log.verify_chain(&verifier()) else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, VerificationLogError::ChainBroken { at_index: 1 });
+        assert!(other_log.verify_chain(&verifier()).is_ok());
+
+        // Garbles the payload of the first entry
+        let payload_start = lines[0].find('.').unwrap() + 1;
+        lines[0].insert(payload_start + 4, 'x');
+        std::fs::write(&segment, lines.join("\n") + "\n").unwrap();
+        let Err(error) = log.verify_chain(&verifier()) else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, VerificationLogError::ChainBroken { at_index: 0 });
+    }
+
+    #[test]
+    fn forged_entry_in_opaque_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryLicenseStore::new();
+        let log = log_with_events(dir.path(), &store, 2);
+        let other_log_dir = tempfile::tempdir().unwrap();
+        log_with_events(other_log_dir.path(), &MemoryLicenseStore::new(), 2);
+
+        // Replaces the first entry with one signed by another device
+        let segment = segment_path(dir.path(), 0);
+        let mut lines = std::fs::read_to_string(&segment)
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        let other_lines = std::fs::read_to_string(segment_path(other_log_dir.path(), 0)).unwrap();
+        lines[0] = other_lines.lines().next().unwrap().to_owned();
+        std::fs::write(&segment, lines.join("\n") + "\n").unwrap();
+
+        let audit_sink = Arc::new(MemoryAuditSink::new());
+        let opaque_verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .audit_sink(audit_sink.clone())
+            .build()
+            .unwrap();
+        let Err(error) = log.verify_chain(&opaque_verifier) else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, VerificationLogError::ChainBroken { at_index: 0 });
+        assert_eq!(
+            audit_sink.failures(),
+            [LicenseVerificationError::VerificationFailure]
+        );
+    }
+
+    #[test]
+    fn truncated_log_breaks_the_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryLicenseStore::new();
+        let log = log_with_events(dir.path(), &store, 5);
+
+        // Drops the last entry of the current segment
+        let segment = segment_path(dir.path(), 1);
+        let contents = std::fs::read_to_string(&segment).unwrap();
+        let truncated = contents.lines().next().unwrap().to_owned() + "\n";
+        std::fs::write(&segment, truncated).unwrap();
+        let Err(error) = log.verify_chain(&verifier()) else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, VerificationLogError::ChainBroken { at_index: 5 });
+
+        // Drops the whole current segment
+        std::fs::remove_file(&segment).unwrap();
+        let Err(error) = log.verify_chain(&verifier()) else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, VerificationLogError::ChainBroken { at_index: 4 });
+
+        let Err(error) = VerificationLog::open(dir.path(), device_key(&store), 3) else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, VerificationLogError::ChainBroken { at_index: 4 });
+    }
+}
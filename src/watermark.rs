@@ -0,0 +1,194 @@
+use crate::id::LicenseId;
+use crate::jws;
+#[cfg(feature = "sign")]
+use crate::signing::{LicenseSigner, LicenseSigningError};
+use crate::License;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+const TAG_LEN: usize = 16;
+
+// `{account tag}.{license binding}`. The account tag only depends on the account, so every renewal
+// issued to the same account carries it and a leaked copy is identified without knowing which
+// license it came from. The binding ties the watermark to the license id so it cannot be
+// transplanted into another license.
+pub fn watermark(license_id: &LicenseId, account_id: &str, vendor_secret: &[u8]) -> String {
+    format!(
+        "{}.{}",
+        jws::encode(
+            &account_tag(account_id, vendor_secret)
+                .finalize()
+                .into_bytes()[..TAG_LEN]
+        ),
+        jws::encode(
+            &license_binding(license_id, account_id, vendor_secret)
+                .finalize()
+                .into_bytes()[..TAG_LEN]
+        )
+    )
+}
+
+#[cfg(feature = "sign")]
+impl LicenseSigner {
+    pub fn sign_watermarked(
+        &self,
+        license: &License,
+        account_id: &str,
+        vendor_secret: &[u8],
+    ) -> Result<serde_json::Value, LicenseSigningError> {
+        let mut license = license.clone();
+        license.watermark = Some(watermark(&license.id, account_id, vendor_secret));
+        self.sign(&license)
+    }
+}
+
+// Tests every candidate without stopping early, so timing does not reveal which one matched
+pub fn identify_watermark<A: AsRef<str>>(
+    watermark: &str,
+    candidate_account_ids: impl IntoIterator<Item = A>,
+    vendor_secret: &[u8],
+) -> Option<A> {
+    let tag = watermark
+        .split_once('.')
+        .and_then(|(tag, _)| jws::decode(tag))
+        .filter(|tag| tag.len() == TAG_LEN)?;
+    let mut leaker = None;
+    for account_id in candidate_account_ids {
+        let matches = account_tag(account_id.as_ref(), vendor_secret)
+            .verify_truncated_left(&tag)
+            .is_ok();
+        if matches && leaker.is_none() {
+            leaker = Some(account_id);
+        }
+    }
+    leaker
+}
+
+impl License {
+    pub fn watermark_matches(&self, account_id: &str, vendor_secret: &[u8]) -> bool {
+        let Some((tag, binding)) = self
+            .watermark
+            .as_deref()
+            .and_then(|watermark| watermark.split_once('.'))
+            .and_then(|(tag, binding)| Some((jws::decode(tag)?, jws::decode(binding)?)))
+        else {
+            return false;
+        };
+        tag.len() == TAG_LEN
+            && binding.len() == TAG_LEN
+            && account_tag(account_id, vendor_secret)
+                .verify_truncated_left(&tag)
+                .is_ok()
+            && license_binding(&self.id, account_id, vendor_secret)
+                .verify_truncated_left(&binding)
+                .is_ok()
+    }
+}
+
+fn account_tag(account_id: &str, vendor_secret: &[u8]) -> Hmac<Sha256> {
+    let mut mac = hmac(vendor_secret);
+    mac.update(b"jls-watermark-account\0");
+    mac.update(account_id.as_bytes());
+    mac
+}
+
+// Inputs are length-prefixed so that no (id, account) pair can collide with another one
+fn license_binding(license_id: &LicenseId, account_id: &str, vendor_secret: &[u8]) -> Hmac<Sha256> {
+    let id = serde_json::to_vec(license_id).expect("License ids are always serializable");
+    let mut mac = hmac(vendor_secret);
+    mac.update(b"jls-watermark-license\0");
+    mac.update(&(id.len() as u64).to_be_bytes());
+    mac.update(&id);
+    mac.update(account_id.as_bytes());
+    mac
+}
+
+fn hmac(vendor_secret: &[u8]) -> Hmac<Sha256> {
+    <Hmac<Sha256> as Mac>::new_from_slice(vendor_secret).expect("HMAC accepts keys of any length")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "sign")]
+    use crate::test_keys::{ISSUER_PRIVATE_KEY_JWK_JSON, ISSUER_PUBLIC_KEY_JWK_JSON};
+    #[cfg(feature = "sign")]
+    use crate::verification::LicenseVerifier;
+
+    const VENDOR_SECRET: &[u8] = b"vendor watermark secret";
+    const ACCOUNTS: [&str; 4] = ["acct-1001", "acct-1002", "acct-1003", "acct-1004"];
+
+    fn license(id: &str) -> License {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "expirationDate": "9999-12-31T23:59:59Z",
+            "customData": { "owner": "John Doe" }
+        }))
+        .unwrap()
+    }
+
+    #[cfg(feature = "sign")]
+    #[test]
+    fn identify_leaked_license() {
+        let signer = LicenseSigner::new(ISSUER_PRIVATE_KEY_JWK_JSON.clone()).unwrap();
+        let verifier = LicenseVerifier::new(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .expect("Verifier instantiation must work");
+        let issued = ACCOUNTS
+            .iter()
+            .enumerate()
+            .map(|(index, account_id)| {
+                let license = license(&format!("ACME-2023-0048{}", index));
+                signer
+                    .sign_watermarked(&license, account_id, VENDOR_SECRET)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        // The leaker stripped the owner, which only breaks the signature
+        let mut leaked = issued[2].clone();
+        leaked["license"]["customData"] = serde_json::json!({});
+        let watermark = leaked["license"]["watermark"].as_str().unwrap();
+
+        assert_eq!(
+            identify_watermark(watermark, ACCOUNTS, VENDOR_SECRET),
+            Some("acct-1003")
+        );
+        assert_eq!(
+            identify_watermark(watermark, ["acct-1001", "acct-1004"], VENDOR_SECRET),
+            None
+        );
+        assert_eq!(
+            identify_watermark(watermark, ACCOUNTS, b"another secret"),
+            None
+        );
+        assert!(verifier.verify(issued[2].clone()).is_ok());
+    }
+
+    #[test]
+    fn watermark_is_stable_across_renewals() {
+        let license_id = LicenseId::from("ACME-2023-00481".to_owned());
+        let renewed_license_id = LicenseId::from("ACME-2024-00481".to_owned());
+
+        let first = watermark(&license_id, "acct-1001", VENDOR_SECRET);
+        assert_eq!(first, watermark(&license_id, "acct-1001", VENDOR_SECRET));
+
+        let renewed = watermark(&renewed_license_id, "acct-1001", VENDOR_SECRET);
+        assert_ne!(renewed, first);
+        assert_eq!(
+            identify_watermark(&renewed, ACCOUNTS, VENDOR_SECRET),
+            Some("acct-1001")
+        );
+    }
+
+    #[test]
+    fn transplanted_watermark_does_not_match() {
+        let mut license = license("ACME-2023-00481");
+        license.watermark = Some(watermark(&license.id, "acct-1001", VENDOR_SECRET));
+        assert!(license.watermark_matches("acct-1001", VENDOR_SECRET));
+        assert!(!license.watermark_matches("acct-1002", VENDOR_SECRET));
+
+        let mut other_license = self::license("ACME-2023-00482");
+        other_license.watermark = license.watermark.clone();
+        assert!(!other_license.watermark_matches("acct-1001", VENDOR_SECRET));
+    }
+}
@@ -0,0 +1,231 @@
+use crate::jws;
+use crate::verification::{LicenseVerificationError, LicenseVerifier};
+use crate::{License, VerifiableLicense, VerifiedLicense};
+
+const ENCODED_VALIDATION_FIELDS: [&str; 3] = ["protected", "payload", "signature"];
+
+impl VerifiableLicense {
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, LicenseVerificationError> {
+        serde_json::from_value(yaml_license_json(yaml)?)
+            .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)
+    }
+
+    pub fn to_yaml_string(&self) -> String {
+        serde_yaml::to_string(self).expect("Verifiable licenses are serializable")
+    }
+}
+
+impl LicenseVerifier {
+    // The normalized document is verified as is: going through `VerifiableLicense` would drop
+    // unsigned members of the outer license before the tamper check sees them
+    pub fn verify_yaml_str(&self, yaml: &str) -> Result<License, LicenseVerificationError> {
+        self.verify_decoded(yaml_license_json(yaml))
+            .map(VerifiedLicense::into_license)
+    }
+}
+
+fn yaml_license_json(yaml: &str) -> Result<serde_json::Value, LicenseVerificationError> {
+    let mut verifiable_license: serde_json::Value = serde_yaml::from_str(yaml)
+        .map_err(|_| LicenseVerificationError::InvalidVerifiableLicense)?;
+    normalize_yaml_license(&mut verifiable_license);
+    Ok(verifiable_license)
+}
+
+fn normalize_yaml_license(verifiable_license: &mut serde_json::Value) {
+    let Some(license_validation) = verifiable_license.get_mut("licenseValidation") else {
+        return;
+    };
+    strip_whitespace(license_validation);
+    if let Some(signatures) = license_validation
+        .get_mut("signatures")
+        .and_then(|v| v.as_array_mut())
+    {
+        signatures.iter_mut().for_each(strip_whitespace);
+    }
+
+    let signed_license: Option<serde_json::Value> = license_validation
+        .get("payload")
+        .and_then(|v| v.as_str())
+        .and_then(jws::decode)
+        .and_then(|payload| serde_json::from_slice(&payload).ok());
+    if let (Some(license), Some(signed_license)) =
+        (verifiable_license.get_mut("license"), signed_license)
+    {
+        align_scalars(license, &signed_license);
+    }
+}
+
+// Block scalars fold long base64url strings into space or newline separated chunks.
+fn strip_whitespace(value: &mut serde_json::Value) {
+    for field in ENCODED_VALIDATION_FIELDS {
+        if let Some(serde_json::Value::String(encoded)) = value.get_mut(field) {
+            encoded.retain(|c| !c.is_whitespace());
+        }
+    }
+}
+
+// Plain YAML scalars may carry YAML 1.1 spellings (`yes`, `off`, unquoted numbers) of the
+// JSON values that were signed; they are only rewritten when they denote the signed value.
+fn align_scalars(value: &mut serde_json::Value, signed_value: &serde_json::Value) {
+    match (value, signed_value) {
+        (serde_json::Value::Object(object), serde_json::Value::Object(signed_object)) => {
+            for (key, value) in object.iter_mut() {
+                if let Some(signed_value) = signed_object.get(key) {
+                    align_scalars(value, signed_value);
+                }
+            }
+        }
+        (serde_json::Value::Array(array), serde_json::Value::Array(signed_array)) => {
+            for (value, signed_value) in array.iter_mut().zip(signed_array) {
+                align_scalars(value, signed_value);
+            }
+        }
+        (value @ serde_json::Value::String(_), serde_json::Value::Bool(signed_bool))
+            if value.as_str().and_then(yaml_bool) == Some(*signed_bool) =>
+        {
+            *value = serde_json::Value::Bool(*signed_bool);
+        }
+        (value @ serde_json::Value::String(_), serde_json::Value::Number(signed_number))
+            if value.as_str() == Some(signed_number.to_string().as_str()) =>
+        {
+            *value = serde_json::Value::Number(signed_number.clone());
+        }
+        (value @ serde_json::Value::Bool(_), serde_json::Value::String(signed_string))
+            if yaml_bool(signed_string) == value.as_bool() =>
+        {
+            *value = serde_json::Value::String(signed_string.clone());
+        }
+        _ => {}
+    }
+}
+
+fn yaml_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "y" | "yes" | "true" | "on" => Some(true),
+        "n" | "no" | "false" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use super::*;
+    use crate::test_keys::{license_with, signer, verifier, ISSUER_PUBLIC_KEY_JWK_JSON};
+
+    fn license() -> License {
+        license_with(serde_json::json!({
+            "customData": {
+                "owner": "John Doe",
+                "support": true,
+                "answer": "no",
+                "renewal": "2025-01-01"
+            }
+        }))
+    }
+
+    fn verifiable_license() -> VerifiableLicense {
+        let verifiable_license = signer().sign(&license()).unwrap();
+        serde_json::from_value(verifiable_license).unwrap()
+    }
+
+    fn validation_field(verifiable_license: &VerifiableLicense, field: &str) -> String {
+        verifiable_license.license_validation[field]
+            .as_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn yaml_license_round_trip() {
+        let yaml = verifiable_license().to_yaml_string();
+
+        let verified_license = verifier()
+            .verify_yaml_str(&yaml)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license, license());
+    }
+
+    #[test]
+    fn yaml_license_with_block_scalars_and_yaml_scalars() {
+        let verifiable_license = verifiable_license();
+        let signature = validation_field(&verifiable_license, "signature");
+        let folded_signature = signature
+            .as_bytes()
+            .chunks(76)
+            .map(|chunk| format!("    {}", std::str::from_utf8(chunk).unwrap()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let payload = validation_field(&verifiable_license, "payload");
+        let literal_payload = payload
+            .as_bytes()
+            .chunks(64)
+            .map(|chunk| format!("    {}", std::str::from_utf8(chunk).unwrap()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let yaml = format!(
+            "license:\n  \
+               id: 0b5b88f5-a264-4f90-8406-50b01d9515c8\n  \
+               expirationDate: 2024-10-01T00:00:00Z\n  \
+               customData:\n    \
+                 owner: John Doe\n    \
+                 support: yes\n    \
+                 answer: no\n    \
+                 renewal: 2025-01-01\n\
+             licenseValidation:\n  \
+               protected: {}\n  \
+               payload: |\n{}\n  \
+               signature: >-\n{}\n",
+            validation_field(&verifiable_license, "protected"),
+            literal_payload,
+            folded_signature
+        );
+
+        let verified_license = verifier()
+            .verify_yaml_str(&yaml)
+            .expect("Verification should succeed");
+        assert_eq!(verified_license, license());
+    }
+
+    #[test]
+    fn yaml_license_with_tampered_custom_data() {
+        let yaml = verifiable_license()
+            .to_yaml_string()
+            .replace("John Doe", "Jane Doe");
+
+        let result = verifier().verify_yaml_str(&yaml);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::TamperedLicense);
+    }
+
+    #[test]
+    fn yaml_license_with_unsigned_outer_field() {
+        let yaml = verifiable_license().to_yaml_string().replacen(
+            "license:\n",
+            "license:\n  tier: enterprise\n",
+            1,
+        );
+
+        let result = verifier().verify_yaml_str(&yaml);
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::TamperedLicense);
+    }
+
+    #[test]
+    fn yaml_license_errors_in_opaque_mode() {
+        let verifier = LicenseVerifier::builder()
+            .key_jwk(ISSUER_PUBLIC_KEY_JWK_JSON.clone())
+            .opaque_errors(true)
+            .build()
+            .expect("Verifier instantiation must work");
+
+        let result = verifier.verify_yaml_str("license: [unterminated");
+        let Err(error) = result else {
+            panic!("An error was expected")
+        };
+        assert_eq!(error, LicenseVerificationError::LicenseInvalid);
+    }
+}